@@ -0,0 +1,94 @@
+use serenity::{
+    framework::standard::{macros::command, Args, CommandResult},
+    model::prelude::Message,
+    prelude::Context,
+};
+
+use crate::{
+    models::anime::{provider_by_name, PROVIDERS},
+    save_settings, InoriChannelUtils, MessageCreator, Settings,
+};
+
+#[command]
+#[aliases("animeprov")]
+#[description(
+    "Set the provider used for anime, manga, character and actor lookups, if no provider is specified then the \
+     current one will be shown\n**Providers**\njikan - MyAnimeList through jikan\nanilist - AniList"
+)]
+#[usage("[provider]")]
+#[example("anilist")]
+#[max_args(1)]
+#[sub_commands(fallback)]
+async fn animeprovider(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    if args.is_empty() {
+        let content = {
+            let data = ctx.data.read().await;
+            let settings = data.get::<Settings>().expect("Expected Setting in TypeMap.").lock().await;
+
+            format!(
+                "Provider is currently set to `{}`, fallback is {}",
+                settings.anime.provider,
+                if settings.anime.fallback { "enabled" } else { "disabled" }
+            )
+        };
+
+        return msg
+            .channel_id
+            .send_tmp(ctx, |m: &mut MessageCreator| m.info().title("Anime Provider").content(content))
+            .await;
+    }
+
+    let provider = match provider_by_name(args.current().unwrap()) {
+        Some(provider) => provider.name(),
+        None => {
+            return msg
+                .channel_id
+                .send_tmp(ctx, |m: &mut MessageCreator| {
+                    m.error().title("Anime Provider").content(format!(
+                        "Invalid provider specified.\n**Valid providers**\n{}",
+                        PROVIDERS.iter().map(|p| format!("`{}`", p)).collect::<Vec<String>>().join("\n")
+                    ))
+                })
+                .await;
+        },
+    };
+
+    let data = ctx.data.write().await;
+    let mut settings = data.get::<Settings>().expect("Expected Setting in TypeMap.").lock().await;
+
+    settings.anime.provider = provider.to_string();
+    save_settings(&settings);
+
+    drop(settings);
+    drop(data);
+
+    msg.channel_id
+        .send_tmp(ctx, |m: &mut MessageCreator| {
+            m.success()
+                .title("Anime Provider")
+                .content(format!("Provider set to `{}`", provider))
+        })
+        .await
+}
+
+#[command]
+#[description("Toggles falling back to the other provider when the current one errors")]
+async fn fallback(ctx: &Context, msg: &Message) -> CommandResult {
+    let data = ctx.data.write().await;
+    let mut settings = data.get::<Settings>().expect("Expected Setting in TypeMap.").lock().await;
+    settings.anime.fallback = !settings.anime.fallback;
+    save_settings(&settings);
+
+    let content = if settings.anime.fallback {
+        "Other providers will be tried when the current one errors"
+    } else {
+        "Only the current provider will be used"
+    };
+
+    drop(settings);
+    drop(data);
+
+    msg.channel_id
+        .send_tmp(ctx, |m: &mut MessageCreator| m.success().title("Anime Provider").content(content))
+        .await
+}
@@ -0,0 +1,290 @@
+use serenity::{
+    framework::standard::{macros::command, Args, CommandResult},
+    model::prelude::Message,
+    prelude::Context,
+};
+
+use super::highlight_guild::*;
+use crate::{
+    models::highlight::{compile_keyword, HighlightState},
+    parse_arg, save_settings,
+    utils::chat::{get_channel, is_channel},
+    InoriChannelUtils, MessageCreator, Settings,
+};
+
+#[command]
+#[aliases("hl", "highlights")]
+#[description(
+    "Get notified when a message in a guild contains one of your keywords. Keywords match whole words, wrap a \
+     keyword in `/` to use a regex instead. Highlights are forwarded to a channel or logged to the console"
+)]
+#[usage("<subcommand>")]
+#[example("add inori")]
+#[example("add /\\bl3af(me)?\\b/")]
+#[example("channel #highlights")]
+#[example("guild add rust")]
+#[min_args(1)]
+#[sub_commands(toggle, mentions, channel, cooldown, add, remove, list, guild)]
+async fn highlight(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    msg.channel_id
+        .send_tmp(ctx, |m: &mut MessageCreator| {
+            m.error()
+                .title("Highlight")
+                .content(format!("Unknown subcommand: {}", args.current().unwrap()))
+        })
+        .await
+}
+
+#[command]
+#[aliases("t")]
+#[description("Toggles highlights")]
+async fn toggle(ctx: &Context, msg: &Message) -> CommandResult {
+    let data = ctx.data.write().await;
+    let mut settings = data.get::<Settings>().expect("Expected Setting in TypeMap.").lock().await;
+    settings.highlight.enabled = !settings.highlight.enabled;
+    save_settings(&settings);
+
+    let content = if settings.highlight.enabled {
+        "Enabled"
+    } else {
+        "Disabled"
+    };
+
+    drop(settings);
+    drop(data);
+
+    msg.channel_id
+        .send_tmp(ctx, |m: &mut MessageCreator| m.success().title("Highlight").content(content))
+        .await
+}
+
+#[command]
+#[aliases("mention", "pings")]
+#[description("Toggles highlighting messages that mention you")]
+async fn mentions(ctx: &Context, msg: &Message) -> CommandResult {
+    let data = ctx.data.write().await;
+    let mut settings = data.get::<Settings>().expect("Expected Setting in TypeMap.").lock().await;
+    settings.highlight.mentions = !settings.highlight.mentions;
+    save_settings(&settings);
+
+    let content = if settings.highlight.mentions {
+        "Mentions will now be highlighted"
+    } else {
+        "Mentions will no longer be highlighted"
+    };
+
+    drop(settings);
+    drop(data);
+
+    msg.channel_id
+        .send_tmp(ctx, |m: &mut MessageCreator| m.success().title("Highlight").content(content))
+        .await
+}
+
+#[command]
+#[aliases("c", "log")]
+#[description(
+    "Set the channel highlights are forwarded to, if no channel is specified then the current channel will be used. \
+     Use `console` to log highlights instead"
+)]
+#[usage("[channel|console]")]
+#[example("#highlights")]
+#[example("console")]
+#[max_args(1)]
+async fn channel(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    let arg = args.rest();
+    let channel_id = if arg.is_empty() {
+        msg.channel_id.0
+    } else if arg.eq_ignore_ascii_case("console") {
+        0
+    } else if is_channel(arg) {
+        get_channel(arg).parse::<u64>().unwrap_or_default()
+    } else {
+        return msg
+            .channel_id
+            .send_tmp(ctx, |m: &mut MessageCreator| {
+                m.error().title("Highlight").content("Unable to parse channel")
+            })
+            .await;
+    };
+
+    let data = ctx.data.write().await;
+    let mut settings = data.get::<Settings>().expect("Expected Setting in TypeMap.").lock().await;
+    settings.highlight.channel = channel_id;
+    save_settings(&settings);
+
+    drop(settings);
+    drop(data);
+
+    let content = if channel_id == 0 {
+        "Highlights will be logged to the console".to_string()
+    } else {
+        format!("Highlights will be sent to <#{}>", channel_id)
+    };
+
+    msg.channel_id
+        .send_tmp(ctx, |m: &mut MessageCreator| m.success().title("Highlight").content(content))
+        .await
+}
+
+#[command]
+#[aliases("cd")]
+#[description(
+    "Set how many seconds to wait before highlighting the same channel again. Talking in a channel also starts the \
+     cooldown"
+)]
+#[usage("<seconds>")]
+#[example("300")]
+#[num_args(1)]
+async fn cooldown(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let cooldown = parse_arg!(ctx, msg, args, "seconds", u64);
+
+    let data = ctx.data.write().await;
+    let mut settings = data.get::<Settings>().expect("Expected Setting in TypeMap.").lock().await;
+    settings.highlight.cooldown = cooldown;
+    save_settings(&settings);
+
+    drop(settings);
+    drop(data);
+
+    msg.channel_id
+        .send_tmp(ctx, |m: &mut MessageCreator| {
+            m.success()
+                .title("Highlight")
+                .content(format!("Cooldown set to {} seconds", cooldown))
+        })
+        .await
+}
+
+#[command]
+#[aliases("a")]
+#[description("Add a keyword which is highlighted in every guild")]
+#[usage("<keyword>")]
+#[example("inori")]
+#[example("/\\bl3af(me)?\\b/")]
+#[min_args(1)]
+async fn add(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    let keyword = args.rest().to_string();
+
+    if let Err(why) = compile_keyword(&keyword) {
+        return msg
+            .channel_id
+            .send_tmp(ctx, |m: &mut MessageCreator| m.error().title("Highlight").content(why))
+            .await;
+    }
+
+    let data = ctx.data.write().await;
+    let mut settings = data.get::<Settings>().expect("Expected Setting in TypeMap.").lock().await;
+
+    let res = if settings.highlight.keywords.contains(&keyword) {
+        Err(format!("`{}` is already highlighted", keyword))
+    } else {
+        settings.highlight.keywords.push(keyword.clone());
+        save_settings(&settings);
+
+        Ok(format!("Added `{}`", keyword))
+    };
+
+    drop(settings);
+    drop(data);
+
+    match res {
+        Ok(content) => {
+            msg.channel_id
+                .send_tmp(ctx, |m: &mut MessageCreator| m.success().title("Highlight").content(content))
+                .await
+        },
+        Err(content) => {
+            msg.channel_id
+                .send_tmp(ctx, |m: &mut MessageCreator| m.info().title("Highlight").content(content))
+                .await
+        },
+    }
+}
+
+#[command]
+#[aliases("delete", "rem", "del", "d", "r")]
+#[description("Remove a keyword which is highlighted in every guild")]
+#[usage("<keyword>")]
+#[example("inori")]
+#[min_args(1)]
+async fn remove(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    let keyword = args.rest().to_string();
+
+    let data = ctx.data.write().await;
+    let mut settings = data.get::<Settings>().expect("Expected Setting in TypeMap.").lock().await;
+
+    let res = if settings.highlight.keywords.contains(&keyword) {
+        settings.highlight.keywords.retain(|k| *k != keyword);
+        save_settings(&settings);
+        data.get::<HighlightState>()
+            .expect("Expected HighlightState in TypeMap.")
+            .lock()
+            .await
+            .prune(&settings.highlight);
+
+        Ok(format!("Removed `{}`", keyword))
+    } else {
+        Err(format!("`{}` isn't highlighted", keyword))
+    };
+
+    drop(settings);
+    drop(data);
+
+    match res {
+        Ok(content) => {
+            msg.channel_id
+                .send_tmp(ctx, |m: &mut MessageCreator| m.success().title("Highlight").content(content))
+                .await
+        },
+        Err(content) => {
+            msg.channel_id
+                .send_tmp(ctx, |m: &mut MessageCreator| m.info().title("Highlight").content(content))
+                .await
+        },
+    }
+}
+
+#[command]
+#[aliases("l", "ls")]
+#[description("List highlighted keywords")]
+async fn list(ctx: &Context, msg: &Message) -> CommandResult {
+    let content = {
+        let data = ctx.data.read().await;
+        let settings = data.get::<Settings>().expect("Expected Setting in TypeMap.").lock().await;
+        let config = &settings.highlight;
+
+        let format_keywords = |keywords: &[String]| {
+            if keywords.is_empty() {
+                "None".to_string()
+            } else {
+                format!("`{}`", keywords.join("`, `"))
+            }
+        };
+
+        let mut content = format!(
+            "**Enabled**: {}\n**Mentions**: {}\n**Cooldown**: {}s\n**Channel**: {}\n**Global**: {}",
+            config.enabled,
+            config.mentions,
+            config.cooldown,
+            if config.channel == 0 {
+                "Console".to_string()
+            } else {
+                format!("<#{}>", config.channel)
+            },
+            format_keywords(&config.keywords)
+        );
+
+        if let Some(guild_id) = msg.guild_id {
+            if let Some(keywords) = config.guilds.get(&guild_id.0.to_string()) {
+                content = format!("{}\n**This Guild**: {}", content, format_keywords(keywords));
+            }
+        }
+
+        content
+    };
+
+    msg.channel_id
+        .send_tmp(ctx, |m: &mut MessageCreator| m.title("Highlight").content(content))
+        .await
+}
@@ -0,0 +1,152 @@
+use serenity::{
+    framework::standard::{macros::command, Args, CommandResult},
+    model::prelude::Message,
+    prelude::Context,
+};
+
+use crate::{
+    models::highlight::{compile_keyword, HighlightState},
+    save_settings, InoriChannelUtils, MessageCreator, Settings,
+};
+
+#[command]
+#[aliases("g", "server")]
+#[description("Add/remove keywords which are only highlighted in the current guild")]
+#[only_in("guilds")]
+#[min_args(1)]
+#[sub_commands(add, remove, list)]
+async fn guild(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    msg.channel_id
+        .send_tmp(ctx, |m: &mut MessageCreator| {
+            m.error()
+                .title("Highlight")
+                .content(format!("Unknown subcommand: {}", args.current().unwrap()))
+        })
+        .await
+}
+
+#[command]
+#[aliases("a")]
+#[description("Add a keyword which is highlighted in the current guild")]
+#[usage("<keyword>")]
+#[example("rust")]
+#[only_in("guilds")]
+#[min_args(1)]
+async fn add(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    let keyword = args.rest().to_string();
+    let guild_id = msg.guild_id.unwrap().0.to_string();
+
+    if let Err(why) = compile_keyword(&keyword) {
+        return msg
+            .channel_id
+            .send_tmp(ctx, |m: &mut MessageCreator| m.error().title("Highlight").content(why))
+            .await;
+    }
+
+    let data = ctx.data.write().await;
+    let mut settings = data.get::<Settings>().expect("Expected Setting in TypeMap.").lock().await;
+    let keywords = settings.highlight.guilds.entry(guild_id).or_insert_with(Vec::new);
+
+    let res = if keywords.contains(&keyword) {
+        Err(format!("`{}` is already highlighted in this guild", keyword))
+    } else {
+        keywords.push(keyword.clone());
+        save_settings(&settings);
+
+        Ok(format!("Added `{}` for this guild", keyword))
+    };
+
+    drop(settings);
+    drop(data);
+
+    match res {
+        Ok(content) => {
+            msg.channel_id
+                .send_tmp(ctx, |m: &mut MessageCreator| m.success().title("Highlight").content(content))
+                .await
+        },
+        Err(content) => {
+            msg.channel_id
+                .send_tmp(ctx, |m: &mut MessageCreator| m.info().title("Highlight").content(content))
+                .await
+        },
+    }
+}
+
+#[command]
+#[aliases("delete", "rem", "del", "d", "r")]
+#[description("Remove a keyword which is highlighted in the current guild")]
+#[usage("<keyword>")]
+#[example("rust")]
+#[only_in("guilds")]
+#[min_args(1)]
+async fn remove(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    let keyword = args.rest().to_string();
+    let guild_id = msg.guild_id.unwrap().0.to_string();
+
+    let data = ctx.data.write().await;
+    let mut settings = data.get::<Settings>().expect("Expected Setting in TypeMap.").lock().await;
+
+    let removed = match settings.highlight.guilds.get_mut(&guild_id) {
+        Some(keywords) if keywords.contains(&keyword) => {
+            keywords.retain(|k| *k != keyword);
+
+            if keywords.is_empty() {
+                settings.highlight.guilds.remove(&guild_id);
+            }
+
+            true
+        },
+        _ => false,
+    };
+
+    if removed {
+        save_settings(&settings);
+        data.get::<HighlightState>()
+            .expect("Expected HighlightState in TypeMap.")
+            .lock()
+            .await
+            .prune(&settings.highlight);
+    }
+
+    drop(settings);
+    drop(data);
+
+    if removed {
+        msg.channel_id
+            .send_tmp(ctx, |m: &mut MessageCreator| {
+                m.success()
+                    .title("Highlight")
+                    .content(format!("Removed `{}` for this guild", keyword))
+            })
+            .await
+    } else {
+        msg.channel_id
+            .send_tmp(ctx, |m: &mut MessageCreator| {
+                m.info()
+                    .title("Highlight")
+                    .content(format!("`{}` isn't highlighted in this guild", keyword))
+            })
+            .await
+    }
+}
+
+#[command]
+#[aliases("l", "ls")]
+#[description("List keywords which are highlighted in the current guild")]
+#[only_in("guilds")]
+async fn list(ctx: &Context, msg: &Message) -> CommandResult {
+    let content = {
+        let data = ctx.data.read().await;
+        let settings = data.get::<Settings>().expect("Expected Setting in TypeMap.").lock().await;
+
+        match settings.highlight.guilds.get(&msg.guild_id.unwrap().0.to_string()) {
+            Some(keywords) if !keywords.is_empty() => format!("`{}`", keywords.join("`, `")),
+            _ => "No keywords for this guild".to_string(),
+        }
+    };
+
+    msg.channel_id
+        .send_tmp(ctx, |m: &mut MessageCreator| m.title("Highlight").content(content))
+        .await
+}
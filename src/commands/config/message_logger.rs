@@ -0,0 +1,221 @@
+use serenity::{
+    framework::standard::{macros::command, Args, CommandResult},
+    model::prelude::Message,
+    prelude::Context,
+};
+
+use crate::{
+    models::{message_log::MessageLogContainer, settings::MAX_LOG_AGE},
+    parse_arg, save_settings, InoriChannelUtils, MessageCreator, Settings,
+};
+
+#[command]
+#[aliases("logger", "msglogger")]
+#[description(
+    "Keep a local cache of messages in DMs and chosen guilds so deleted and edited messages can be viewed with \
+     `snipe` and `editsnipe`. Your own messages are never logged"
+)]
+#[usage("<subcommand>")]
+#[example("enable")]
+#[example("guilds 800041653318451232")]
+#[example("limit 5000 24")]
+#[min_args(1)]
+#[sub_commands(toggle, enable, disable, dms, guilds, limit)]
+async fn messagelogger(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    msg.channel_id
+        .send_tmp(ctx, |m: &mut MessageCreator| {
+            m.error()
+                .title("Message Logger")
+                .content(format!("Unknown subcommand: {}", args.current().unwrap()))
+        })
+        .await
+}
+
+async fn set_enabled(ctx: &Context, enabled: Option<bool>) -> bool {
+    let data = ctx.data.read().await;
+    let enabled = {
+        let mut settings = data.get::<Settings>().expect("Expected Setting in TypeMap.").lock().await;
+        settings.message_logger.enabled = enabled.unwrap_or(!settings.message_logger.enabled);
+        save_settings(&settings);
+
+        settings.message_logger.enabled
+    };
+
+    if !enabled {
+        if let Some(log) = data.get::<MessageLogContainer>() {
+            log.lock().await.clear();
+        }
+    }
+
+    enabled
+}
+
+#[command]
+#[aliases("t")]
+#[description("Toggles the message logger")]
+async fn toggle(ctx: &Context, msg: &Message) -> CommandResult {
+    let content = if set_enabled(ctx, None).await {
+        "Enabled"
+    } else {
+        "Disabled"
+    };
+
+    msg.channel_id
+        .send_tmp(ctx, |m: &mut MessageCreator| m.success().title("Message Logger").content(content))
+        .await
+}
+
+#[command]
+#[description("Enables the message logger")]
+async fn enable(ctx: &Context, msg: &Message) -> CommandResult {
+    set_enabled(ctx, Some(true)).await;
+
+    msg.channel_id
+        .send_tmp(ctx, |m: &mut MessageCreator| m.success().title("Message Logger").content("Enabled"))
+        .await
+}
+
+#[command]
+#[description("Disables the message logger and clears logged messages")]
+async fn disable(ctx: &Context, msg: &Message) -> CommandResult {
+    set_enabled(ctx, Some(false)).await;
+
+    msg.channel_id
+        .send_tmp(ctx, |m: &mut MessageCreator| m.success().title("Message Logger").content("Disabled"))
+        .await
+}
+
+#[command]
+#[aliases("dm")]
+#[description("Toggles logging messages in DMs")]
+async fn dms(ctx: &Context, msg: &Message) -> CommandResult {
+    let data = ctx.data.write().await;
+    let mut settings = data.get::<Settings>().expect("Expected Setting in TypeMap.").lock().await;
+    settings.message_logger.dms = !settings.message_logger.dms;
+    save_settings(&settings);
+
+    let content = if settings.message_logger.dms {
+        "DMs will now be logged"
+    } else {
+        "DMs will no longer be logged"
+    };
+
+    drop(settings);
+    drop(data);
+
+    msg.channel_id
+        .send_tmp(ctx, |m: &mut MessageCreator| m.success().title("Message Logger").content(content))
+        .await
+}
+
+#[command]
+#[aliases("guild", "g")]
+#[description("Toggles logging messages in a guild, if no guild id is specified then the current guild will be used")]
+#[usage("[guild id]")]
+#[example("800041653318451232")]
+#[max_args(1)]
+async fn guilds(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let guild_id = if args.is_empty() {
+        if let Some(guild) = msg.guild_id {
+            guild.0
+        } else {
+            return msg
+                .channel_id
+                .send_tmp(ctx, |m: &mut MessageCreator| {
+                    m.error().title("Message Logger").content("Unable to get current guild id")
+                })
+                .await;
+        }
+    } else {
+        parse_arg!(ctx, msg, args, "guild id", u64)
+    };
+
+    let data = ctx.data.write().await;
+    let mut settings = data.get::<Settings>().expect("Expected Setting in TypeMap.").lock().await;
+
+    let content = if settings.message_logger.guilds.contains(&guild_id) {
+        settings.message_logger.guilds.retain(|guild| *guild != guild_id);
+
+        "Guild will no longer be logged"
+    } else {
+        settings.message_logger.guilds.push(guild_id);
+
+        "Guild will now be logged"
+    };
+    save_settings(&settings);
+
+    drop(settings);
+    drop(data);
+
+    msg.channel_id
+        .send_tmp(ctx, |m: &mut MessageCreator| m.success().title("Message Logger").content(content))
+        .await
+}
+
+#[command]
+#[aliases("l")]
+#[description("Get or set how many messages are kept and for how many hours")]
+#[usage("[messages] [hours]")]
+#[example("5000")]
+#[example("10000 48")]
+#[max_args(2)]
+async fn limit(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    if args.is_empty() {
+        let content = {
+            let data = ctx.data.read().await;
+            let settings = data.get::<Settings>().expect("Expected Setting in TypeMap.").lock().await;
+            let logged = match data.get::<MessageLogContainer>() {
+                Some(log) => log.lock().await.message_count(),
+                None => 0,
+            };
+
+            format!(
+                "Keeping up to {} messages for {} hours\n{} messages currently logged",
+                settings.message_logger.max_messages, settings.message_logger.max_age, logged
+            )
+        };
+
+        return msg
+            .channel_id
+            .send_tmp(ctx, |m: &mut MessageCreator| m.info().title("Message Logger").content(content))
+            .await;
+    }
+
+    let max_messages = parse_arg!(ctx, msg, args, "messages", usize);
+    let max_age = if args.is_empty() {
+        None
+    } else {
+        Some(parse_arg!(ctx, msg, args, "hours", u64))
+    };
+
+    if matches!(max_age, Some(max_age) if max_age == 0 || max_age > MAX_LOG_AGE) {
+        return msg
+            .channel_id
+            .send_tmp(ctx, |m: &mut MessageCreator| {
+                m.error()
+                    .title("Message Logger")
+                    .content(format!("Hours must be between 1 and {}", MAX_LOG_AGE))
+            })
+            .await;
+    }
+
+    let data = ctx.data.write().await;
+    let mut settings = data.get::<Settings>().expect("Expected Setting in TypeMap.").lock().await;
+    settings.message_logger.max_messages = max_messages;
+    if let Some(max_age) = max_age {
+        settings.message_logger.max_age = max_age;
+    }
+    save_settings(&settings);
+
+    let content = format!(
+        "Keeping up to {} messages for {} hours",
+        settings.message_logger.max_messages, settings.message_logger.max_age
+    );
+
+    drop(settings);
+    drop(data);
+
+    msg.channel_id
+        .send_tmp(ctx, |m: &mut MessageCreator| m.success().title("Message Logger").content(content))
+        .await
+}
@@ -1,13 +1,22 @@
+mod anime_provider;
 mod autodelete;
 mod giveaway;
 mod giveaway_blacklist;
 mod giveaway_whitelist;
+mod highlight;
+mod highlight_guild;
+mod message_logger;
 mod slotbot;
 mod slotbot_blacklist;
 mod slotbot_whitelist;
+mod snippets;
+mod theme;
 
+use anime_provider::*;
 use autodelete::*;
 use giveaway::*;
+use highlight::*;
+use message_logger::*;
 use serenity::{
     framework::standard::{
         macros::{command, group},
@@ -17,11 +26,25 @@ use serenity::{
     prelude::*,
 };
 use slotbot::*;
+use snippets::*;
+use theme::*;
 
 use crate::{parse_arg, save_settings, InoriChannelUtils, MessageCreator, Settings};
 
 #[group]
-#[commands(autodelete, embedmode, giveaway, nsfwfilter, prefix, slotbot)]
+#[commands(
+    animeprovider,
+    autodelete,
+    embedmode,
+    giveaway,
+    highlight,
+    messagelogger,
+    nsfwfilter,
+    prefix,
+    slotbot,
+    snippets,
+    theme
+)]
 #[description("**Config**")]
 struct Config;
 
@@ -119,6 +142,7 @@ async fn nsfwfilter(ctx: &Context, msg: &Message, mut args: Args) -> CommandResu
 )]
 #[usage("<mode>")]
 #[example("2")]
+#[sub_commands(channel, guild)]
 async fn embedmode(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
     if args.is_empty() {
         let data = ctx.data.read().await;
@@ -176,3 +200,106 @@ async fn embedmode(ctx: &Context, msg: &Message, mut args: Args) -> CommandResul
             .await
     }
 }
+
+async fn _embed_override(ctx: &Context, msg: &Message, mut args: Args, key: String, scope: &str) -> CommandResult {
+    if args.is_empty() {
+        let data = ctx.data.read().await;
+        let settings = data.get::<Settings>().expect("Expected Setting in TypeMap.").lock().await;
+
+        let mode = match settings.embed_overrides.get(&key) {
+            Some(0) => "Never",
+            Some(1) => "Detect",
+            Some(_) => "Always",
+            None => "not overridden",
+        };
+
+        drop(settings);
+        drop(data);
+
+        return msg
+            .channel_id
+            .send_tmp(ctx, |m: &mut MessageCreator| {
+                m.info()
+                    .title("Embed Mode")
+                    .content(format!("Embed mode for this {} is {}", scope, mode))
+            })
+            .await;
+    }
+
+    if args.current().unwrap_or_default().eq_ignore_ascii_case("reset") {
+        let data = ctx.data.write().await;
+        let mut settings = data.get::<Settings>().expect("Expected Setting in TypeMap.").lock().await;
+
+        settings.embed_overrides.remove(&key);
+        save_settings(&settings);
+
+        drop(settings);
+        drop(data);
+
+        return msg
+            .channel_id
+            .send_tmp(ctx, |m: &mut MessageCreator| {
+                m.success()
+                    .title("Embed Mode")
+                    .content(format!("Removed embed mode override for this {}", scope))
+            })
+            .await;
+    }
+
+    let val = parse_arg!(ctx, msg, args, "mode", u8);
+
+    if val <= 2 {
+        let data = ctx.data.write().await;
+        let mut settings = data.get::<Settings>().expect("Expected Setting in TypeMap.").lock().await;
+
+        settings.embed_overrides.insert(key, val);
+        save_settings(&settings);
+
+        let mode = match val {
+            0 => "Never",
+            1 => "Detect",
+            _ => "Always",
+        };
+
+        drop(settings);
+        drop(data);
+
+        msg.channel_id
+            .send_tmp(ctx, |m: &mut MessageCreator| {
+                m.success()
+                    .title("Embed Mode")
+                    .content(format!("Embed mode for this {} set to `{}`", scope, mode))
+            })
+            .await
+    } else {
+        msg.channel_id
+            .send_tmp(ctx, |m: &mut MessageCreator| {
+                m.error()
+                    .title("Embed Mode")
+                    .content("Invalid mode specified.\n**Valid Modes**\n`0` - Never\n`1` - Detect\n`2` - Always")
+            })
+            .await
+    }
+}
+
+#[command]
+#[description("Override the embed mode for the current channel, `reset` removes the override")]
+#[usage("[mode/reset]")]
+#[example("0")]
+#[example("reset")]
+#[max_args(1)]
+async fn channel(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    _embed_override(ctx, msg, args, msg.channel_id.0.to_string(), "channel").await
+}
+
+#[command]
+#[aliases("server")]
+#[description("Override the embed mode for the current guild, `reset` removes the override")]
+#[usage("[mode/reset]")]
+#[example("0")]
+#[example("reset")]
+#[only_in("guilds")]
+#[max_args(1)]
+async fn guild(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    _embed_override(ctx, msg, args, msg.guild_id.unwrap().0.to_string(), "guild").await
+}
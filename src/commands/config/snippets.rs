@@ -0,0 +1,158 @@
+use serenity::{
+    framework::standard::{macros::command, Args, CommandResult},
+    model::prelude::Message,
+    prelude::Context,
+};
+
+use crate::{save_settings, InoriChannelUtils, MessageCreator, Settings};
+
+#[command]
+#[aliases("snippet", "snip")]
+#[description(
+    "Expand tags inside your own messages by editing them in place.\nWith the default trigger, `{{sig}}` will be \
+     replaced with the content of the tag `sig`"
+)]
+#[usage("<subcommand>")]
+#[example("enable")]
+#[example("trigger ;;")]
+#[example("trigger {{ }}")]
+#[min_args(1)]
+#[sub_commands(enable, disable, toggle, trigger)]
+async fn snippets(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    msg.channel_id
+        .send_tmp(ctx, |m: &mut MessageCreator| {
+            m.error()
+                .title("Snippets")
+                .content(&format!("Unknown subcommand: {}", args.current().unwrap()))
+        })
+        .await
+}
+
+#[command]
+#[aliases("t")]
+#[description("Toggles snippet expansion")]
+async fn toggle(ctx: &Context, msg: &Message) -> CommandResult {
+    let data = ctx.data.write().await;
+    let mut settings = data.get::<Settings>().expect("Expected Setting in TypeMap.").lock().await;
+    settings.snippets.enabled = !settings.snippets.enabled;
+    save_settings(&settings);
+
+    let content = if settings.snippets.enabled {
+        "Enabled"
+    } else {
+        "Disabled"
+    };
+
+    drop(settings);
+    drop(data);
+
+    msg.channel_id
+        .send_tmp(ctx, |m: &mut MessageCreator| m.success().title("Snippets").content(content))
+        .await
+}
+
+#[command]
+#[description("Enables snippet expansion")]
+async fn enable(ctx: &Context, msg: &Message) -> CommandResult {
+    let data = ctx.data.write().await;
+    let mut settings = data.get::<Settings>().expect("Expected Setting in TypeMap.").lock().await;
+
+    if settings.snippets.enabled {
+        drop(settings);
+        drop(data);
+
+        msg.channel_id
+            .send_tmp(ctx, |m: &mut MessageCreator| {
+                m.info().title("Snippets").content("Already enabled")
+            })
+            .await
+    } else {
+        settings.snippets.enabled = true;
+        save_settings(&settings);
+
+        drop(settings);
+        drop(data);
+
+        msg.channel_id
+            .send_tmp(ctx, |m: &mut MessageCreator| m.success().title("Snippets").content("Enabled"))
+            .await
+    }
+}
+
+#[command]
+#[description("Disables snippet expansion")]
+async fn disable(ctx: &Context, msg: &Message) -> CommandResult {
+    let data = ctx.data.write().await;
+    let mut settings = data.get::<Settings>().expect("Expected Setting in TypeMap.").lock().await;
+
+    if settings.snippets.enabled {
+        settings.snippets.enabled = false;
+        save_settings(&settings);
+
+        drop(settings);
+        drop(data);
+
+        msg.channel_id
+            .send_tmp(ctx, |m: &mut MessageCreator| m.success().title("Snippets").content("Disabled"))
+            .await
+    } else {
+        drop(settings);
+        drop(data);
+
+        msg.channel_id
+            .send_tmp(ctx, |m: &mut MessageCreator| {
+                m.info().title("Snippets").content("Already disabled")
+            })
+            .await
+    }
+}
+
+#[command]
+#[description(
+    "Get or set the text which marks a snippet. If no suffix is given then the tag name ends at the first character \
+     that isn't a letter, number, `_` or `-`"
+)]
+#[usage("[prefix] [suffix]")]
+#[example(";;")]
+#[example("{{ }}")]
+#[max_args(2)]
+async fn trigger(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    if args.is_empty() {
+        let data = ctx.data.read().await;
+        let settings = data.get::<Settings>().expect("Expected Setting in TypeMap.").lock().await;
+        let example = format!("{}tag{}", settings.snippets.prefix, settings.snippets.suffix);
+
+        drop(settings);
+        drop(data);
+
+        return msg
+            .channel_id
+            .send_tmp(ctx, |m: &mut MessageCreator| {
+                m.info()
+                    .title("Snippets")
+                    .content(format!("Snippets are currently written as `{}`", example))
+            })
+            .await;
+    }
+
+    let prefix = args.single::<String>().unwrap_or_default();
+    let suffix = args.single::<String>().unwrap_or_default();
+
+    let data = ctx.data.write().await;
+    let mut settings = data.get::<Settings>().expect("Expected Setting in TypeMap.").lock().await;
+
+    settings.snippets.prefix = prefix.clone();
+    settings.snippets.suffix = suffix.clone();
+    save_settings(&settings);
+
+    drop(settings);
+    drop(data);
+
+    msg.channel_id
+        .send_tmp(ctx, |m: &mut MessageCreator| {
+            m.success()
+                .title("Snippets")
+                .content(format!("Snippets will now be written as `{}tag{}`", prefix, suffix))
+        })
+        .await
+}
@@ -0,0 +1,330 @@
+use serenity::{
+    framework::standard::{macros::command, Args, CommandResult},
+    model::prelude::Message,
+    prelude::Context,
+};
+
+use crate::{
+    models::discord::{get_render_options, send_pages},
+    save_settings, InoriChannelUtils, InoriMessageUtils, MessageCreator, Settings,
+};
+
+static MODES: [&str; 6] = ["default", "loading", "success", "info", "warning", "error"];
+
+fn parse_mode(mode: &str) -> Option<u64> {
+    MODES.iter().position(|m| m.eq(&mode)).map(|idx| idx as u64)
+}
+
+#[command]
+#[description(
+    "Manage themes which change the colours, emojis, titles and footers of messages.\nThemes are stored under \
+     `[theme.themes]` in the config"
+)]
+#[usage("<subcommand>")]
+#[example("list")]
+#[example("preview dark")]
+#[example("set dark")]
+#[min_args(1)]
+#[sub_commands(list, preview, set, save, delete, edit)]
+async fn theme(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    msg.channel_id
+        .send_tmp(ctx, |m: &mut MessageCreator| {
+            m.error()
+                .title("Theme")
+                .content(format!("Unknown subcommand: {}", args.current().unwrap()))
+        })
+        .await
+}
+
+#[command]
+#[aliases("l")]
+#[description("List saved themes")]
+async fn list(ctx: &Context, msg: &Message) -> CommandResult {
+    let content = {
+        let data = ctx.data.read().await;
+        let settings = data.get::<Settings>().expect("Expected Setting in TypeMap.").lock().await;
+
+        let mut names = settings.theme.themes.keys().cloned().collect::<Vec<String>>();
+        names.sort();
+
+        names
+            .into_iter()
+            .map(|name| {
+                if name.eq(&settings.theme.current) {
+                    format!("**{}** (Current)", name)
+                } else {
+                    name
+                }
+            })
+            .collect::<Vec<String>>()
+            .join("\n")
+    };
+
+    msg.channel_id
+        .send_tmp(ctx, |m: &mut MessageCreator| m.title("Theme").content(content))
+        .await
+}
+
+#[command]
+#[aliases("p")]
+#[description("Preview a theme, if no theme is specified then the current theme will be previewed")]
+#[usage("[theme]")]
+#[example("dark")]
+#[max_args(1)]
+async fn preview(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    let theme = {
+        let data = ctx.data.read().await;
+        let settings = data.get::<Settings>().expect("Expected Setting in TypeMap.").lock().await;
+
+        if args.is_empty() {
+            Some(settings.theme.current())
+        } else {
+            settings.theme.themes.get(args.rest()).cloned()
+        }
+    };
+
+    let theme = if let Some(theme) = theme {
+        theme
+    } else {
+        return msg
+            .channel_id
+            .send_tmp(ctx, |m: &mut MessageCreator| {
+                m.error().title("Theme").content(format!("Unknown theme: {}", args.rest()))
+            })
+            .await;
+    };
+
+    let mut options = get_render_options(ctx, msg.channel_id).await;
+    options.theme = theme;
+
+    for (idx, mode) in MODES.iter().enumerate() {
+        let mut preview = MessageCreator::default();
+        preview.title("Theme Preview").content(format!("This is a {} message", mode));
+
+        match idx {
+            1 => preview.loading(),
+            2 => preview.success(),
+            3 => preview.info(),
+            4 => preview.warning(),
+            5 => preview.error(),
+            _ => &mut preview,
+        };
+
+        for msg in send_pages(ctx, msg.channel_id, &preview, &options).await? {
+            msg.autodelete(ctx).await?;
+        }
+    }
+
+    Ok(())
+}
+
+#[command]
+#[aliases("use", "switch")]
+#[description("Switch to a saved theme")]
+#[usage("<theme>")]
+#[example("dark")]
+#[num_args(1)]
+async fn set(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    let name = args.rest().to_string();
+    let data = ctx.data.write().await;
+    let mut settings = data.get::<Settings>().expect("Expected Setting in TypeMap.").lock().await;
+
+    if !settings.theme.themes.contains_key(&name) {
+        drop(settings);
+        drop(data);
+
+        return msg
+            .channel_id
+            .send_tmp(ctx, |m: &mut MessageCreator| {
+                m.error().title("Theme").content(format!("Unknown theme: {}", name))
+            })
+            .await;
+    }
+
+    settings.theme.current = name.clone();
+    save_settings(&settings);
+
+    drop(settings);
+    drop(data);
+
+    msg.channel_id
+        .send_tmp(ctx, |m: &mut MessageCreator| {
+            m.success().title("Theme").content(format!("Switched to theme `{}`", name))
+        })
+        .await
+}
+
+#[command]
+#[aliases("copy", "new")]
+#[description("Save a copy of the current theme under a new name")]
+#[usage("<name>")]
+#[example("dark")]
+#[num_args(1)]
+async fn save(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    let name = args.rest().to_string();
+    let data = ctx.data.write().await;
+    let mut settings = data.get::<Settings>().expect("Expected Setting in TypeMap.").lock().await;
+
+    if settings.theme.themes.contains_key(&name) {
+        drop(settings);
+        drop(data);
+
+        return msg
+            .channel_id
+            .send_tmp(ctx, |m: &mut MessageCreator| {
+                m.error()
+                    .title("Theme")
+                    .content(format!("Theme with name '{}' already exists", name))
+            })
+            .await;
+    }
+
+    let theme = settings.theme.current();
+    settings.theme.themes.insert(name.clone(), theme);
+    save_settings(&settings);
+
+    drop(settings);
+    drop(data);
+
+    msg.channel_id
+        .send_tmp(ctx, |m: &mut MessageCreator| {
+            m.success().title("Theme").content(format!("Saved theme `{}`", name))
+        })
+        .await
+}
+
+#[command]
+#[aliases("remove", "del", "rem", "d", "r")]
+#[description("Delete a saved theme")]
+#[usage("<theme>")]
+#[example("dark")]
+#[num_args(1)]
+async fn delete(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    let name = args.rest().to_string();
+    let data = ctx.data.write().await;
+    let mut settings = data.get::<Settings>().expect("Expected Setting in TypeMap.").lock().await;
+
+    let content = if name.eq(&settings.theme.current) {
+        Err("Can't delete the theme currently in use".to_string())
+    } else if settings.theme.themes.remove(&name).is_none() {
+        Err(format!("Unknown theme: {}", name))
+    } else {
+        save_settings(&settings);
+
+        Ok(format!("Deleted theme `{}`", name))
+    };
+
+    drop(settings);
+    drop(data);
+
+    match content {
+        Ok(content) => {
+            msg.channel_id
+                .send_tmp(ctx, |m: &mut MessageCreator| m.success().title("Theme").content(content))
+                .await
+        },
+        Err(content) => {
+            msg.channel_id
+                .send_tmp(ctx, |m: &mut MessageCreator| m.error().title("Theme").content(content))
+                .await
+        },
+    }
+}
+
+#[command]
+#[aliases("e")]
+#[description(
+    "Edit a saved theme\n**Keys**\n`title` - Title format, `{}` is replaced with the title\n`footer` - Footer text \
+     when none is set\n`<mode>.colour` - Embed colour in hex\n`<mode>.emoji` - Emoji shown before content\n\
+     `<mode>.prefix` - Text shown before content when there is no emoji\n**Modes**\n`default`, `loading`, \
+     `success`, `info`, `warning`, `error`"
+)]
+#[usage("<theme> <key> [value]")]
+#[example("dark title **{}**")]
+#[example("dark success.colour #43B581")]
+#[example("dark error.emoji ❌")]
+#[min_args(2)]
+async fn edit(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let name = args.single::<String>().unwrap();
+    let key = args.single::<String>().unwrap().to_lowercase();
+    let value = args.rest().to_string();
+
+    let data = ctx.data.write().await;
+    let mut settings = data.get::<Settings>().expect("Expected Setting in TypeMap.").lock().await;
+
+    let res = if let Some(theme) = settings.theme.themes.get_mut(&name) {
+        match key.split_once('.') {
+            None if key.eq("title") => {
+                theme.title_format = value.clone();
+
+                Ok(())
+            },
+            None if key.eq("footer") => {
+                theme.footer_text = value.clone();
+
+                Ok(())
+            },
+            Some((mode, field)) => match parse_mode(mode) {
+                Some(mode) => {
+                    let theme_mode = theme.mode_mut(mode);
+
+                    match field {
+                        "colour" | "color" => {
+                            match u32::from_str_radix(value.trim_start_matches('#'), 16) {
+                                // Embed colours are only RGB
+                                Ok(colour) if colour > 0xFFFFFF => {
+                                    Err(format!("Colours can't be above #FFFFFF: {}", value))
+                                },
+                                Ok(colour) => {
+                                    theme_mode.colour = colour;
+
+                                    Ok(())
+                                },
+                                Err(_) => Err(format!("Invalid hex colour: {}", value)),
+                            }
+                        },
+                        "emoji" => {
+                            theme_mode.emoji = value.clone();
+
+                            Ok(())
+                        },
+                        "prefix" => {
+                            theme_mode.prefix = value.clone();
+
+                            Ok(())
+                        },
+                        _ => Err(format!("Unknown key: {}", key)),
+                    }
+                },
+                None => Err(format!("Unknown mode: {}", mode)),
+            },
+            _ => Err(format!("Unknown key: {}", key)),
+        }
+    } else {
+        Err(format!("Unknown theme: {}", name))
+    };
+
+    if res.is_ok() {
+        save_settings(&settings);
+    }
+
+    drop(settings);
+    drop(data);
+
+    match res {
+        Ok(_) => {
+            msg.channel_id
+                .send_tmp(ctx, |m: &mut MessageCreator| {
+                    m.success()
+                        .title("Theme")
+                        .content(format!("Updated `{}` for theme `{}`", key, name))
+                })
+                .await
+        },
+        Err(why) => {
+            msg.channel_id
+                .send_tmp(ctx, |m: &mut MessageCreator| m.error().title("Theme").content(why))
+                .await
+        },
+    }
+}
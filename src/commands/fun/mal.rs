@@ -3,22 +3,31 @@ use serenity::{
     model::prelude::Message,
     prelude::Context,
 };
-use urlencoding::encode;
 
-use crate::models::{
-    commands::{
-        MALAnimeSearchResult, MALCharacterSearchResult, MALMangaSearchResult, MALPersonSearchResult, MALSearchResponse,
+use crate::{
+    models::{
+        anime::{
+            lookup_id, AnimeProvider, Character, CharacterRole, FallbackProvider, Link, Media, MediaKind, Person,
+            Relation,
+        },
+        discord::{InoriChannelUtils, InoriMessageUtils, MessageCreator},
     },
-    discord::{InoriChannelUtils, MessageCreator},
+    utils::{chat::truncate, jikan::Jikan},
 };
 
 #[command]
 #[aliases("mal")]
-#[description("Search MyAnimeList for your favorite anime, manga, character or actor")]
+#[description(
+    "Search MyAnimeList for your favorite anime, manga, character or actor. Pass a MAL ID instead of a name to see \
+     the full details. Lookups go through the provider set with `animeprovider`"
+)]
 #[usage("<subcommand>")]
 #[example("character Inori Yuzuriha")]
 #[example("anime Kimi no na wa")]
-#[sub_commands(anime, manga, character, actor)]
+#[example("anime 5114")]
+#[example("seasonal 2021 spring")]
+#[example("top manga favorite")]
+#[sub_commands(anime, manga, character, actor, seasonal, top, random)]
 #[min_args(1)]
 async fn myanimelist(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
     msg.channel_id
@@ -30,292 +39,637 @@ async fn myanimelist(ctx: &Context, msg: &Message, args: Args) -> CommandResult
         .await
 }
 
-static BASE_URL: &str = "https://api.jikan.moe/v3/";
+static SEASONS: [&str; 4] = ["winter", "spring", "summer", "fall"];
+static ANIME_FILTERS: [&str; 4] = ["airing", "upcoming", "bypopularity", "favorite"];
+static MANGA_FILTERS: [&str; 4] = ["publishing", "upcoming", "bypopularity", "favorite"];
+
+async fn send_pages(
+    ctx: &Context,
+    msg: &Message,
+    mut loading: Message,
+    pages: Result<Vec<MessageCreator<'static>>, String>,
+    empty: String,
+) -> CommandResult {
+    match pages {
+        Ok(pages) if !pages.is_empty() => {
+            loading.delete(&ctx.http).await?;
+
+            msg.channel_id.send_paginator_noret(ctx, pages).await
+        },
+        Ok(_) => {
+            loading
+                .update_tmp(ctx, |m: &mut MessageCreator| m.warning().title("MyAnimeList").content(empty))
+                .await
+        },
+        Err(why) => {
+            loading
+                .update_tmp(ctx, |m: &mut MessageCreator| m.error().title("MyAnimeList").content(why))
+                .await
+        },
+    }
+}
 
-#[command]
-#[description("Search for anime and manga voice actors")]
-#[usage("<name>")]
-#[example("Yoshitsugu Matsuoka")]
-async fn actor(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
-    let params = args.rest();
+async fn check_query(ctx: &Context, msg: &Message, query: &str) -> Option<CommandResult> {
+    if query.parse::<u64>().is_ok() || query.chars().count() >= 3 {
+        return None;
+    }
 
-    if params.len() < 3 {
-        return msg
-            .channel_id
+    Some(
+        msg.channel_id
             .send_tmp(ctx, |m: &mut MessageCreator| {
                 m.error()
                     .title("MyAnimeList")
                     .content("Search queries must be 3 characters or longer")
             })
-            .await;
+            .await,
+    )
+}
+
+fn or_unknown<T: ToString>(value: &Option<T>) -> String {
+    value.as_ref().map_or_else(|| "Unknown".to_string(), |value| value.to_string())
+}
+
+// MAL synopses end with a "[Written by MAL Rewrite]" credit which only takes
+// up space
+fn clean_text(content: &Option<String>, length: usize) -> Option<String> {
+    let content = content.as_ref()?;
+    let content = match content.rfind("[Written by") {
+        Some(idx) => &content[..idx],
+        None => content,
+    };
+    let content = content.trim();
+
+    if content.is_empty() {
+        None
+    } else {
+        Some(truncate(content, length))
     }
+}
 
-    let new_msg = msg
-        .channel_id
-        .send_loading(ctx, "MyAnimeList", "Loading actor information")
-        .await
-        .unwrap();
+fn format_links(links: &[Link], separator: &str) -> Option<String> {
+    if links.is_empty() {
+        None
+    } else {
+        Some(links.iter().map(Link::markdown).collect::<Vec<String>>().join(separator))
+    }
+}
 
-    let res = reqwest::get(&format!("{}search/person?q={}", BASE_URL, encode(&params)))
-        .await?
-        .text()
-        .await?;
+fn header(name: &str, url: &str, alt: Option<&String>, description: Option<String>) -> String {
+    let mut content = format!("**{}**", Link::new(name, url).markdown());
 
-    let res: MALSearchResponse<MALPersonSearchResult> = serde_json::from_str(&res).unwrap();
+    if let Some(alt) = alt.filter(|alt| *alt != name && !alt.is_empty()) {
+        content = format!("{}\n*{}*", content, alt);
+    }
 
-    if res.results.is_empty() {
-        new_msg.delete(&ctx.http).await?;
+    if let Some(description) = description {
+        content = format!("{}\n\n{}", content, description);
+    }
 
-        return msg
-            .channel_id
-            .send_tmp(ctx, |m: &mut MessageCreator| {
-                m.warning()
-                    .title("MyAnimeList")
-                    .content(&format!("No results found for query: `{}`", params))
-            })
-            .await;
-    } else {
-        let mut msgs = Vec::new();
+    content
+}
+
+fn page(source: &str, content: String, image: &Option<String>) -> MessageCreator<'static> {
+    let mut page = MessageCreator::default();
+    page.title(source).content(content);
 
-        for result in res.results {
-            let mut msg = MessageCreator::default();
+    if let Some(url) = image {
+        page.thumbnail(url);
+    }
 
-            msg.title("MyAnimeList")
-                .content(format!("[{}]({})", result.name, result.url))
-                .thumbnail(&result.image_url);
+    page
+}
 
-            if !result.alternate_names.is_empty() {
-                msg.field("Alternative Names", result.alternate_names.join("\n"), true);
-            }
+fn subpage(media: &Media, title: &str) -> MessageCreator<'static> {
+    page(
+        media.source,
+        format!("**{}**\n{}", Link::new(&media.title, &media.url).markdown(), title),
+        &media.image,
+    )
+}
+
+fn relations_page(media: &Media, relations: &[Relation]) -> Option<MessageCreator<'static>> {
+    if relations.is_empty() {
+        return None;
+    }
 
-            msg.field("MAL ID", result.mal_id, true);
-            msgs.push(msg);
+    let mut page = subpage(media, "Related entries");
+
+    for relation in relations {
+        if let Some(entries) = format_links(&relation.entries, "\n") {
+            page.field(&relation.relation, entries, false);
         }
+    }
 
-        new_msg.delete(&ctx.http).await?;
+    Some(page)
+}
 
-        return msg.channel_id.send_paginator_noret(ctx, msg, msgs).await;
+fn characters_page(media: &Media, characters: &[CharacterRole]) -> Option<MessageCreator<'static>> {
+    if characters.is_empty() {
+        return None;
     }
+
+    let lines = characters
+        .iter()
+        .take(25)
+        .map(|role| match &role.actor {
+            Some(actor) => format!("{} ({}) - {}", role.character.markdown(), role.role, actor.markdown()),
+            None => format!("{} ({})", role.character.markdown(), role.role),
+        })
+        .collect::<Vec<String>>();
+
+    let mut page = subpage(media, "Characters");
+    page.field("Cast", lines.join("\n"), false);
+
+    Some(page)
 }
 
-#[command]
-#[description("Search for character")]
-#[usage("<name>")]
-#[example("Zero Two")]
-async fn character(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
-    let params = args.rest();
+fn list_pages(title: &str, lines: Vec<String>) -> Vec<MessageCreator<'static>> {
+    lines
+        .chunks(10)
+        .map(|chunk| {
+            let mut page = MessageCreator::default();
+            page.title("MyAnimeList")
+                .content(format!("**{}**\n\n{}", title, chunk.join("\n")));
 
-    if params.len() < 3 {
-        return msg
-            .channel_id
-            .send_tmp(ctx, |m: &mut MessageCreator| {
-                m.error()
-                    .title("MyAnimeList")
-                    .content("Search queries must be 3 characters or longer")
-            })
-            .await;
+            page
+        })
+        .collect()
+}
+
+fn media_overview(media: &Media) -> MessageCreator<'static> {
+    let mut page = page(
+        media.source,
+        header(
+            &media.title,
+            &media.url,
+            media.title_alt.as_ref(),
+            clean_text(&media.synopsis, 1000),
+        ),
+        &media.image,
+    );
+
+    page.field("Type", or_unknown(&media.format), true);
+
+    match media.kind {
+        MediaKind::Anime => page.field("Episodes", or_unknown(&media.episodes), true),
+        MediaKind::Manga => {
+            page.field("Volumes", or_unknown(&media.volumes), true)
+                .field("Chapters", or_unknown(&media.chapters), true)
+        },
+    };
+
+    page.field("Status", or_unknown(&media.status), true)
+        .field("Dates", or_unknown(&media.dates), true)
+        .field("Score", or_unknown(&media.score), true)
+        .field("Rank", media.rank.map_or("N/A".to_string(), |rank| format!("#{}", rank)), true)
+        .field("Members", or_unknown(&media.members), true);
+
+    if let Some(rating) = &media.rating {
+        page.field("Rated", rating, true);
     }
 
-    let new_msg = msg
-        .channel_id
-        .send_loading(ctx, "MyAnimeList", "Loading character information")
-        .await
-        .unwrap();
+    page.field("MAL ID", media.mal_id.map_or("N/A".to_string(), |id| id.to_string()), true);
 
-    let res = reqwest::get(&format!("{}search/character?q={}", BASE_URL, encode(&params)))
-        .await?
-        .text()
-        .await?;
+    if let Some(genres) = format_links(&media.genres, ", ") {
+        page.field("Genres", genres, false);
+    }
 
-    let res: MALSearchResponse<MALCharacterSearchResult> = serde_json::from_str(&res).unwrap();
+    if let Some(creators) = format_links(&media.creators, ", ") {
+        page.field("Creators", creators, false);
+    }
 
-    if res.results.is_empty() {
-        new_msg.delete(&ctx.http).await?;
+    page
+}
 
-        return msg
-            .channel_id
-            .send_tmp(ctx, |m: &mut MessageCreator| {
-                m.warning()
-                    .title("MyAnimeList")
-                    .content(&format!("No results found for query: `{}`", params))
-            })
-            .await;
-    } else {
-        let mut msgs = Vec::new();
+fn media_pages(media: &Media) -> Vec<MessageCreator<'static>> {
+    let mut pages = vec![media_overview(media)];
 
-        for result in res.results {
-            let mut msg = MessageCreator::default();
+    let mut info = Vec::new();
+    if let Some(themes) = format_links(&media.themes, ", ") {
+        info.push(("Themes", themes));
+    }
 
-            msg.title("MyAnimeList")
-                .thumbnail(&result.image_url)
-                .content(format!("[{}]({})", result.name, result.url));
+    if let Some(publishers) = format_links(&media.publishers, ", ") {
+        info.push(("Serialization", publishers));
+    }
 
-            if !result.alternate_names.is_empty() {
-                msg.field("Alternative Names", result.alternate_names.join("\n"), true);
-            }
+    if let Some(season) = &media.season {
+        info.push(("Season", season.clone()));
+    }
 
-            if !result.anime.is_empty() {
-                let anime_list = result
-                    .anime
-                    .iter()
-                    .map(|e| format!("[{}]({}) ({})", e.name, e.url, e.mal_id))
-                    .collect::<Vec<String>>();
+    if let Some(streaming) = format_links(&media.streaming, "\n") {
+        info.push(("Streaming", streaming));
+    }
 
-                msg.field("Anime", anime_list.join("\n"), true);
-            }
+    if !info.is_empty() {
+        let mut page = subpage(media, "Information");
+
+        for (name, value) in info {
+            page.field(name, value, false);
+        }
 
-            if !result.manga.is_empty() {
-                let manga_list = result
-                    .manga
-                    .iter()
-                    .map(|e| format!("[{}]({}) ({})", e.name, e.url, e.mal_id))
-                    .collect::<Vec<String>>();
+        pages.push(page);
+    }
 
-                msg.field("Manga", manga_list.join("\n"), true);
-            }
+    pages.extend(relations_page(media, &media.relations));
+    pages.extend(characters_page(media, &media.characters));
 
-            msg.field("MAL ID", result.mal_id, true);
-            msgs.push(msg);
+    pages
+}
+
+fn character_overview(character: &Character) -> MessageCreator<'static> {
+    let mut page = page(
+        character.source,
+        header(
+            &character.name,
+            &character.url,
+            character.name_native.as_ref(),
+            clean_text(&character.about, 1000),
+        ),
+        &character.image,
+    );
+
+    if !character.nicknames.is_empty() {
+        page.field("Nicknames", character.nicknames.join("\n"), true);
+    }
+
+    page.field("Favorites", or_unknown(&character.favorites), true);
+    page.field("ID", format!("`{}`", lookup_id(character.source, character.id)), true);
+
+    page
+}
+
+fn character_pages(character: &Character) -> Vec<MessageCreator<'static>> {
+    let mut pages = vec![character_overview(character)];
+
+    let mut appearances = page(
+        character.source,
+        format!("**{}**\nAppearances", Link::new(&character.name, &character.url).markdown()),
+        &character.image,
+    );
+
+    let sections = [
+        ("Anime", &character.anime),
+        ("Manga", &character.manga),
+        ("Voice Actors", &character.actors),
+    ];
+
+    for (name, entries) in sections.iter() {
+        let lines = entries
+            .iter()
+            .take(15)
+            .map(|entry| format!("{} ({})", entry.media.markdown(), entry.role))
+            .collect::<Vec<String>>();
+
+        if !lines.is_empty() {
+            appearances.field(name, lines.join("\n"), false);
         }
+    }
+
+    if sections.iter().any(|(_, entries)| !entries.is_empty()) {
+        pages.push(appearances);
+    }
+
+    pages
+}
 
-        new_msg.delete(&ctx.http).await?;
+fn person_overview(person: &Person) -> MessageCreator<'static> {
+    let mut page = page(
+        person.source,
+        header(&person.name, &person.url, None, clean_text(&person.about, 1000)),
+        &person.image,
+    );
 
-        return msg.channel_id.send_paginator_noret(ctx, msg, msgs).await;
+    if !person.alternate_names.is_empty() {
+        page.field("Alternative Names", person.alternate_names.join("\n"), true);
     }
+
+    if let Some(birthday) = &person.birthday {
+        page.field("Birthday", birthday, true);
+    }
+
+    page.field("Favorites", or_unknown(&person.favorites), true);
+    page.field("ID", format!("`{}`", lookup_id(person.source, person.id)), true);
+
+    page
 }
 
-#[command]
-#[description("Search for manga")]
-#[usage("<name>")]
-#[example("One Piece")]
-async fn manga(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
-    let params = args.rest();
+fn person_pages(person: &Person) -> Vec<MessageCreator<'static>> {
+    let mut pages = vec![person_overview(person)];
+
+    let roles = person
+        .roles
+        .iter()
+        .take(30)
+        .map(|role| format!("{} in {} ({})", role.character.markdown(), role.media.markdown(), role.role))
+        .collect::<Vec<String>>();
+
+    if !roles.is_empty() {
+        let mut page = page(
+            person.source,
+            format!("**{}**\nVoice Roles", Link::new(&person.name, &person.url).markdown()),
+            &person.image,
+        );
+        page.field("Roles", roles.join("\n"), false);
+
+        pages.push(page);
+    }
 
-    if params.len() < 3 {
-        return msg
-            .channel_id
-            .send_tmp(ctx, |m: &mut MessageCreator| {
-                m.error()
-                    .title("MyAnimeList")
-                    .content("Search queries must be 3 characters or longer")
-            })
-            .await;
+    pages
+}
+
+async fn lookup_media(ctx: &Context, msg: &Message, args: Args, kind: MediaKind) -> CommandResult {
+    let query = args.rest();
+
+    if let Some(res) = check_query(ctx, msg, query).await {
+        return res;
     }
 
-    let new_msg = msg
+    let loading = msg
         .channel_id
-        .send_loading(ctx, "MyAnimeList", "Loading manga information")
-        .await
-        .unwrap();
-
-    let res = reqwest::get(&format!("{}search/manga?q={}", BASE_URL, encode(&params)))
-        .await?
-        .text()
+        .send_loading(ctx, "MyAnimeList", &format!("Loading {} information", kind.name()))
         .await?;
 
-    let res: MALSearchResponse<MALMangaSearchResult> = serde_json::from_str(&res).unwrap();
+    let provider = FallbackProvider::from_ctx(ctx).await;
+    let pages = match query.parse::<u64>() {
+        Ok(id) => provider.media(kind, id).await.map(|media| media_pages(&media)),
+        Err(_) => provider
+            .search_media(kind, query)
+            .await
+            .map(|results| results.iter().map(media_overview).collect()),
+    };
 
-    if res.results.is_empty() {
-        new_msg.delete(&ctx.http).await?;
+    send_pages(ctx, msg, loading, pages, format!("No results found for query: `{}`", query)).await
+}
 
-        return msg
-            .channel_id
-            .send_tmp(ctx, |m: &mut MessageCreator| {
-                m.warning()
-                    .title("MyAnimeList")
-                    .content(&format!("No results found for query: `{}`", params))
-            })
-            .await;
-    } else {
-        let mut msgs = Vec::new();
-
-        for result in res.results {
-            let mut msg = MessageCreator::default();
-
-            msg.title("MyAnimeList")
-                .thumbnail(&result.image_url)
-                .content(format!("**[{}]({})**\n{}", result.title, result.url, result.synopsis))
-                .field("Volumes", result.chapters, true)
-                .field("Chapters", result.chapters, true)
-                .field("Score", result.score, true)
-                .field("Members", result.members, true)
-                .field("Start Date", &result.start_date, true)
-                .field("End Date", &result.end_date, true)
-                .field("MAL ID", result.mal_id, true);
-
-            msgs.push(msg);
-        }
+#[command]
+#[aliases("person", "people", "va")]
+#[description("Search for anime and manga voice actors")]
+#[usage("<name|ID>")]
+#[example("Yoshitsugu Matsuoka")]
+#[min_args(1)]
+async fn actor(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    let query = args.rest();
 
-        new_msg.delete(&ctx.http).await?;
+    if let Some(res) = check_query(ctx, msg, query).await {
+        return res;
+    }
+
+    let loading = msg
+        .channel_id
+        .send_loading(ctx, "MyAnimeList", "Loading actor information")
+        .await?;
+
+    let provider = FallbackProvider::from_ctx(ctx).await;
+    let pages = match provider.parse_id(query) {
+        Some((provider, id)) => provider.person(id).await.map(|person| person_pages(&person)),
+        None => provider
+            .search_people(query)
+            .await
+            .map(|results| results.iter().map(person_overview).collect()),
+    };
+
+    send_pages(ctx, msg, loading, pages, format!("No results found for query: `{}`", query)).await
+}
+
+#[command]
+#[description("Search for characters")]
+#[usage("<name|ID>")]
+#[example("Zero Two")]
+#[min_args(1)]
+async fn character(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    let query = args.rest();
 
-        return msg.channel_id.send_paginator_noret(ctx, msg, msgs).await;
+    if let Some(res) = check_query(ctx, msg, query).await {
+        return res;
     }
+
+    let loading = msg
+        .channel_id
+        .send_loading(ctx, "MyAnimeList", "Loading character information")
+        .await?;
+
+    let provider = FallbackProvider::from_ctx(ctx).await;
+    let pages = match provider.parse_id(query) {
+        Some((provider, id)) => provider.character(id).await.map(|character| character_pages(&character)),
+        None => provider
+            .search_characters(query)
+            .await
+            .map(|results| results.iter().map(character_overview).collect()),
+    };
+
+    send_pages(ctx, msg, loading, pages, format!("No results found for query: `{}`", query)).await
+}
+
+#[command]
+#[description("Search for manga")]
+#[usage("<name|MAL ID>")]
+#[example("One Piece")]
+#[min_args(1)]
+async fn manga(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    lookup_media(ctx, msg, args, MediaKind::Manga).await
 }
 
 #[command]
 #[description("Search for anime")]
-#[usage("<name>")]
+#[usage("<name|MAL ID>")]
 #[example("Shingeki no Kyojin")]
+#[example("16498")]
+#[min_args(1)]
 async fn anime(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
-    let params = args.rest();
+    lookup_media(ctx, msg, args, MediaKind::Anime).await
+}
 
-    if params.len() < 3 {
-        return msg
-            .channel_id
-            .send_tmp(ctx, |m: &mut MessageCreator| {
-                m.error()
-                    .title("MyAnimeList")
-                    .content("Search queries must be 3 characters or longer")
-            })
-            .await;
+#[command]
+#[aliases("season")]
+#[description("List the anime airing this season or in a specific season")]
+#[usage("[year season]")]
+#[example("")]
+#[example("2021 spring")]
+#[max_args(2)]
+async fn seasonal(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    let mut year = None;
+    let mut season = None;
+
+    for arg in args.raw() {
+        match arg.parse::<u64>() {
+            Ok(value) => year = Some(value),
+            Err(_) => season = SEASONS.iter().find(|s| s.eq_ignore_ascii_case(arg)).copied(),
+        }
     }
 
-    let new_msg = msg
+    let (season, title) = match (year, season) {
+        (Some(year), Some(season)) => (
+            Some((year, season)),
+            format!("{} {}", season[..1].to_uppercase() + &season[1..], year),
+        ),
+        (None, None) if args.is_empty() => (None, "This Season".to_string()),
+        _ => {
+            return msg
+                .channel_id
+                .send_tmp(ctx, |m: &mut MessageCreator| {
+                    m.error()
+                        .title("MyAnimeList")
+                        .content(format!("Expected a year and one of: {}", SEASONS.join(", ")))
+                })
+                .await;
+        },
+    };
+
+    let loading = msg
         .channel_id
-        .send_loading(ctx, "MyAnimeList", "Loading anime information")
-        .await
-        .unwrap();
+        .send_loading(ctx, "MyAnimeList", "Loading seasonal anime")
+        .await?;
+
+    let pages = Jikan.seasonal(season).await.map(|results| {
+        let lines = results
+            .iter()
+            .map(|anime| {
+                format!(
+                    "{} ({}, {} eps, score {}) `{}`",
+                    Link::new(&anime.title, &anime.url).markdown(),
+                    or_unknown(&anime.format),
+                    anime.episodes.map_or("?".to_string(), |episodes| episodes.to_string()),
+                    anime.score.map_or("N/A".to_string(), |score| score.to_string()),
+                    or_unknown(&anime.mal_id)
+                )
+            })
+            .collect();
 
-    let res = reqwest::get(&format!("{}search/anime?q={}", BASE_URL, encode(&params)))
-        .await?
-        .text()
+        list_pages(&title, lines)
+    });
+
+    send_pages(ctx, msg, loading, pages, "No anime found for this season".to_string()).await
+}
+
+#[command]
+#[aliases("best", "ranking")]
+#[description(
+    "List the top ranked anime or manga. Anime can be filtered by airing, upcoming, bypopularity or favorite and \
+     manga by publishing, upcoming, bypopularity or favorite"
+)]
+#[usage("[anime|manga] [filter]")]
+#[example("")]
+#[example("anime airing")]
+#[example("manga favorite")]
+#[max_args(2)]
+async fn top(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let kind = match args.current() {
+        Some(kind) if kind.eq_ignore_ascii_case("manga") => {
+            args.advance();
+            MediaKind::Manga
+        },
+        Some(kind) if kind.eq_ignore_ascii_case("anime") => {
+            args.advance();
+            MediaKind::Anime
+        },
+        _ => MediaKind::Anime,
+    };
+
+    let filters = match kind {
+        MediaKind::Anime => &ANIME_FILTERS,
+        MediaKind::Manga => &MANGA_FILTERS,
+    };
+
+    let filter = match args.current() {
+        Some(arg) => match filters.iter().find(|f| f.eq_ignore_ascii_case(arg)) {
+            Some(filter) => Some(*filter),
+            None => {
+                return msg
+                    .channel_id
+                    .send_tmp(ctx, |m: &mut MessageCreator| {
+                        m.error()
+                            .title("MyAnimeList")
+                            .content(format!("Unknown filter, expected one of: {}", filters.join(", ")))
+                    })
+                    .await;
+            },
+        },
+        None => None,
+    };
+
+    let loading = msg
+        .channel_id
+        .send_loading(ctx, "MyAnimeList", &format!("Loading top {}", kind.name()))
         .await?;
 
-    let res: MALSearchResponse<MALAnimeSearchResult> = serde_json::from_str(&res).unwrap();
+    let title = match filter {
+        Some(filter) => format!("Top {} ({})", kind.name(), filter),
+        None => format!("Top {}", kind.name()),
+    };
+
+    let pages = Jikan.top(kind, filter).await.map(|results| {
+        let lines = results
+            .iter()
+            .enumerate()
+            .map(|(idx, media)| {
+                format!(
+                    "**{}.** {} (score {}) `{}`",
+                    media.rank.unwrap_or(idx as u64 + 1),
+                    Link::new(&media.title, &media.url).markdown(),
+                    media.score.map_or("N/A".to_string(), |score| score.to_string()),
+                    or_unknown(&media.mal_id)
+                )
+            })
+            .collect();
+
+        list_pages(&title, lines)
+    });
 
-    if res.results.is_empty() {
-        new_msg.delete(&ctx.http).await?;
+    send_pages(ctx, msg, loading, pages, format!("No {} found", kind.name())).await
+}
 
+#[command]
+#[aliases("rand")]
+#[description("Show a random anime, manga or character")]
+#[usage("[anime|manga|character]")]
+#[example("")]
+#[example("manga")]
+#[max_args(1)]
+async fn random(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    let kind = args.rest().to_lowercase();
+
+    if !["", "anime", "manga", "character", "characters"].contains(&kind.as_str()) {
         return msg
             .channel_id
             .send_tmp(ctx, |m: &mut MessageCreator| {
-                m.warning()
+                m.error()
                     .title("MyAnimeList")
-                    .content(&format!("No results found for query: `{}`", params))
+                    .content("Expected one of: anime, manga, character")
             })
             .await;
-    } else {
-        let mut msgs = Vec::new();
-
-        for result in res.results {
-            let mut msg = MessageCreator::default();
-
-            msg.title("MyAnimeList")
-                .thumbnail(&result.image_url)
-                .content(format!("**[{}]({})**\n{}", result.title, result.url, result.synopsis))
-                .field("Episodes", result.episodes, true)
-                .field("Score", result.score, true)
-                .field("Members", result.members, true)
-                .field("Start Date", &result.start_date, true)
-                .field("End Date", &result.end_date, true)
-                .field("Rated", &result.rated, true)
-                .field("MAL ID", result.mal_id, true);
-
-            msgs.push(msg);
-        }
+    }
+
+    let loading = msg
+        .channel_id
+        .send_loading(ctx, "MyAnimeList", "Picking something random")
+        .await?;
 
-        new_msg.delete(&ctx.http).await?;
+    // Only jikan can pick something random, anime and manga details are then
+    // looked up through the configured provider
+    let provider = FallbackProvider::from_ctx(ctx).await;
+    let pages = match kind.as_str() {
+        "character" | "characters" => match Jikan.random_character().await {
+            Ok(character) => Jikan.character(character.id).await.map(|character| character_pages(&character)),
+            Err(why) => Err(why),
+        },
+        _ => {
+            let kind = if kind == "manga" {
+                MediaKind::Manga
+            } else {
+                MediaKind::Anime
+            };
+
+            match Jikan.random_media(kind).await {
+                Ok(Media {
+                    mal_id: Some(mal_id),
+                    ..
+                }) => provider.media(kind, mal_id).await.map(|media| media_pages(&media)),
+                Ok(_) => Err("Nothing was found".to_string()),
+                Err(why) => Err(why),
+            }
+        },
+    };
 
-        return msg.channel_id.send_paginator_noret(ctx, msg, msgs).await;
-    }
+    send_pages(ctx, msg, loading, pages, "Nothing was found".to_string()).await
 }
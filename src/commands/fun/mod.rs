@@ -1,6 +1,7 @@
 mod mal;
 mod pfpswitcher;
 mod quote;
+mod watch;
 
 use mal::*;
 use pfpswitcher::*;
@@ -13,6 +14,7 @@ use serenity::{
     model::{channel::Message, id::ChannelId, user::User},
     prelude::Context,
 };
+use watch::*;
 
 use crate::{InoriChannelUtils, MessageCreator};
 
@@ -25,7 +27,8 @@ use crate::{InoriChannelUtils, MessageCreator};
     pfpswitcher,
     quote,
     sexuality,
-    urbandictionary
+    urbandictionary,
+    watch
 )]
 #[description("**Fun**")]
 struct Fun;
@@ -179,7 +182,7 @@ async fn urbandictionary(ctx: &Context, msg: &Message, args: Args) -> CommandRes
             msgs.push(msg);
         }
 
-        msg.channel_id.send_paginator_noret(ctx, msg, msgs).await
+        msg.channel_id.send_paginator_noret(ctx, msgs).await
     };
 }
 
@@ -0,0 +1,399 @@
+use serenity::{
+    framework::standard::{macros::command, Args, CommandResult},
+    model::prelude::Message,
+    prelude::Context,
+};
+
+use crate::{
+    models::{
+        anime::{AnimeProvider, FallbackProvider, Media, MediaKind},
+        discord::{InoriChannelUtils, InoriMessageUtils, MessageCreator},
+        watchlist::{parse_progress, WatchEntry, WatchStatus, Watchlist},
+    },
+    save_settings,
+    utils::chat::{get_channel, is_channel},
+    Settings,
+};
+
+#[command]
+#[aliases("watchlist", "wl")]
+#[description(
+    "Keep track of the anime and manga you're watching. Titles are looked up on MyAnimeList when added, after that \
+     entries can be referred to by part of their title or their MAL ID. New episodes of shows you're watching are \
+     sent to a channel or logged to the console"
+)]
+#[usage("<subcommand>")]
+#[example("add Spy x Family")]
+#[example("add manga Chainsaw Man")]
+#[example("progress spy x family +1")]
+#[example("list watching")]
+#[min_args(1)]
+#[sub_commands(add, remove, list, progress, status, score, notify, channel)]
+async fn watch(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    msg.channel_id
+        .send_tmp(ctx, |m: &mut MessageCreator| {
+            m.error()
+                .title("Watchlist")
+                .content(format!("Unknown subcommand: {}", args.current().unwrap()))
+        })
+        .await
+}
+
+async fn reply(ctx: &Context, msg: &Message, res: Result<String, String>) -> CommandResult {
+    msg.channel_id
+        .send_tmp(ctx, |m: &mut MessageCreator| {
+            match res {
+                Ok(content) => m.success().content(content),
+                Err(why) => m.error().content(why),
+            }
+            .title("Watchlist")
+        })
+        .await
+}
+
+// Splits "<title> <value>" on the last space since titles can contain spaces
+fn split_last(args: &Args) -> Option<(&str, &str)> {
+    let rest = args.rest().trim();
+    let idx = rest.rfind(char::is_whitespace)?;
+
+    Some((rest[..idx].trim(), rest[idx..].trim()))
+}
+
+async fn update_entry<F>(ctx: &Context, query: &str, update: F) -> Result<String, String>
+where
+    F: FnOnce(&mut WatchEntry) -> Result<String, String>, {
+    let data = ctx.data.read().await;
+    let mut watchlist = data.get::<Watchlist>().expect("Expected Watchlist in TypeMap.").lock().await;
+
+    let idx = watchlist.find(query)?;
+    let content = update(&mut watchlist.entries[idx])?;
+    watchlist.save();
+
+    Ok(content)
+}
+
+async fn resolve(ctx: &Context, kind: MediaKind, query: &str) -> Result<Media, String> {
+    let provider = FallbackProvider::from_ctx(ctx).await;
+
+    if let Ok(mal_id) = query.parse::<u64>() {
+        return provider.media(kind, mal_id).await;
+    }
+
+    provider
+        .search_media(kind, query)
+        .await?
+        .into_iter()
+        .find(|media| media.mal_id.is_some())
+        .ok_or_else(|| format!("No results found for query: `{}`", query))
+}
+
+#[command]
+#[aliases("a")]
+#[description("Add an anime or manga to your watchlist, anime is assumed if the type is left out")]
+#[usage("[anime|manga] <title|MAL ID>")]
+#[example("Spy x Family")]
+#[example("manga 2")]
+#[min_args(1)]
+async fn add(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let kind = match args.current().map(|arg| arg.to_lowercase()) {
+        Some(arg) if arg == "manga" => MediaKind::Manga,
+        _ => MediaKind::Anime,
+    };
+
+    if args.current().map_or(false, |arg| arg.eq_ignore_ascii_case(kind.name())) {
+        args.advance();
+    }
+
+    let query = args.rest().trim().to_string();
+    if query.is_empty() {
+        return reply(ctx, msg, Err(format!("Specify the {} to add", kind.name()))).await;
+    }
+
+    let mut loading = msg
+        .channel_id
+        .send_loading(ctx, "Watchlist", &format!("Looking up {}", kind.name()))
+        .await?;
+
+    let res = match resolve(ctx, kind, &query).await {
+        Ok(media) => {
+            let data = ctx.data.read().await;
+            let mut watchlist = data.get::<Watchlist>().expect("Expected Watchlist in TypeMap.").lock().await;
+            let mal_id = media.mal_id.unwrap_or_default();
+
+            if watchlist
+                .entries
+                .iter()
+                .any(|entry| entry.mal_id == mal_id && entry.kind == kind)
+            {
+                Err(format!("**{}** is already on your watchlist", media.title))
+            } else {
+                watchlist.entries.push(WatchEntry {
+                    mal_id,
+                    kind,
+                    title: media.title.clone(),
+                    url: media.url.clone(),
+                    total: match kind {
+                        MediaKind::Anime => media.episodes,
+                        MediaKind::Manga => media.chapters,
+                    },
+                    status: WatchStatus::Watching,
+                    progress: 0,
+                    score: None,
+                    aired: None,
+                });
+                watchlist.save();
+
+                Ok(format!("Added [{}]({}) to your watchlist", media.title, media.url))
+            }
+        },
+        Err(why) => Err(why),
+    };
+
+    loading
+        .update_tmp(ctx, |m: &mut MessageCreator| {
+            match res {
+                Ok(content) => m.success().content(content),
+                Err(why) => m.error().content(why),
+            }
+            .title("Watchlist")
+        })
+        .await
+}
+
+#[command]
+#[aliases("rm", "delete")]
+#[description("Remove an entry from your watchlist")]
+#[usage("<title|[anime|manga] MAL ID>")]
+#[example("spy x family")]
+#[min_args(1)]
+async fn remove(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    let res = {
+        let data = ctx.data.read().await;
+        let mut watchlist = data.get::<Watchlist>().expect("Expected Watchlist in TypeMap.").lock().await;
+
+        watchlist.find(args.rest().trim()).map(|idx| {
+            let entry = watchlist.entries.remove(idx);
+            watchlist.save();
+
+            format!("Removed **{}** from your watchlist", entry.title)
+        })
+    };
+
+    reply(ctx, msg, res).await
+}
+
+#[command]
+#[aliases("l", "ls")]
+#[description("List your watchlist, optionally only showing entries with a status")]
+#[usage("[watching|completed|onhold|dropped|planned]")]
+#[example("watching")]
+#[max_args(1)]
+async fn list(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    let filter = if args.is_empty() {
+        None
+    } else {
+        match WatchStatus::parse(args.rest()) {
+            Some(status) => Some(status),
+            None => return reply(ctx, msg, Err(format!("Unknown status: {}", args.rest()))).await,
+        }
+    };
+
+    let lines = {
+        let data = ctx.data.read().await;
+        let watchlist = data.get::<Watchlist>().expect("Expected Watchlist in TypeMap.").lock().await;
+
+        WatchStatus::ALL
+            .iter()
+            .filter(|status| filter.map_or(true, |filter| filter == **status))
+            .flat_map(|status| {
+                let entries = watchlist
+                    .entries
+                    .iter()
+                    .filter(|entry| entry.status == *status)
+                    .map(|entry| {
+                        format!(
+                            "`{}` [{}]({}){}{}",
+                            entry.progress_text(),
+                            entry.title,
+                            entry.url,
+                            if entry.kind == MediaKind::Manga { " (Manga)" } else { "" },
+                            entry.score.map_or(String::new(), |score| format!(" - {}/10", score))
+                        )
+                    })
+                    .collect::<Vec<String>>();
+
+                if entries.is_empty() {
+                    entries
+                } else {
+                    std::iter::once(format!("**{}**", status.name())).chain(entries).collect()
+                }
+            })
+            .collect::<Vec<String>>()
+    };
+
+    if lines.is_empty() {
+        return msg
+            .channel_id
+            .send_tmp(ctx, |m: &mut MessageCreator| {
+                m.warning().title("Watchlist").content("Nothing to show")
+            })
+            .await;
+    }
+
+    let pages = lines
+        .chunks(15)
+        .map(|chunk| {
+            let mut page = MessageCreator::default();
+            page.title("Watchlist").content(chunk.join("\n"));
+
+            page
+        })
+        .collect::<Vec<MessageCreator>>();
+
+    msg.channel_id.send_paginator_noret(ctx, pages).await
+}
+
+#[command]
+#[aliases("p", "ep")]
+#[description("Update how far into an entry you are, `+N` and `-N` are relative to your current progress")]
+#[usage("<title|[anime|manga] MAL ID> <+N|-N|N>")]
+#[example("spy x family +1")]
+#[example("5114 12")]
+#[min_args(2)]
+async fn progress(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    let (query, amount) = match split_last(&args) {
+        Some(split) => split,
+        None => return reply(ctx, msg, Err("Specify a title and progress".to_string())).await,
+    };
+
+    let res = update_entry(ctx, query, |entry| {
+        let progress = parse_progress(amount, entry.progress).ok_or_else(|| format!("Invalid progress: {}", amount))?;
+        entry.set_progress(progress);
+
+        Ok(format!(
+            "**{}** is now at {} ({})",
+            entry.title,
+            entry.progress_text(),
+            entry.status.name()
+        ))
+    })
+    .await;
+
+    reply(ctx, msg, res).await
+}
+
+#[command]
+#[aliases("s")]
+#[description("Set the status of an entry")]
+#[usage("<title|[anime|manga] MAL ID> <watching|completed|onhold|dropped|planned>")]
+#[example("spy x family completed")]
+#[min_args(2)]
+async fn status(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    let (query, status) = match split_last(&args) {
+        Some(split) => split,
+        None => return reply(ctx, msg, Err("Specify a title and status".to_string())).await,
+    };
+
+    let res = update_entry(ctx, query, |entry| {
+        entry.status = WatchStatus::parse(status).ok_or_else(|| format!("Unknown status: {}", status))?;
+
+        if entry.status == WatchStatus::Completed {
+            if let Some(total) = entry.total {
+                entry.progress = total;
+            }
+        }
+
+        Ok(format!("**{}** is now {}", entry.title, entry.status.name()))
+    })
+    .await;
+
+    reply(ctx, msg, res).await
+}
+
+#[command]
+#[description("Score an entry out of 10, a score of 0 removes it")]
+#[usage("<title|[anime|manga] MAL ID> <score>")]
+#[example("spy x family 9")]
+#[min_args(2)]
+async fn score(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    let (query, score) = match split_last(&args) {
+        Some(split) => split,
+        None => return reply(ctx, msg, Err("Specify a title and score".to_string())).await,
+    };
+
+    let res = update_entry(ctx, query, |entry| {
+        entry.score = match score.parse::<u8>() {
+            Ok(0) => None,
+            Ok(score) if score <= 10 => Some(score),
+            _ => return Err("Scores must be between 0 and 10".to_string()),
+        };
+
+        Ok(match entry.score {
+            Some(score) => format!("Scored **{}** {}/10", entry.title, score),
+            None => format!("Removed the score from **{}**", entry.title),
+        })
+    })
+    .await;
+
+    reply(ctx, msg, res).await
+}
+
+#[command]
+#[aliases("notifications")]
+#[description("Toggles new episode notifications")]
+async fn notify(ctx: &Context, msg: &Message) -> CommandResult {
+    let data = ctx.data.write().await;
+    let mut settings = data.get::<Settings>().expect("Expected Setting in TypeMap.").lock().await;
+    settings.watchlist.notify = !settings.watchlist.notify;
+    save_settings(&settings);
+
+    let content = if settings.watchlist.notify {
+        "You will now be notified about new episodes"
+    } else {
+        "You will no longer be notified about new episodes"
+    };
+
+    drop(settings);
+    drop(data);
+
+    reply(ctx, msg, Ok(content.to_string())).await
+}
+
+#[command]
+#[aliases("c", "log")]
+#[description(
+    "Set the channel new episodes are announced in, if no channel is specified then the current channel will be \
+     used. A DM channel's ID works too, use `console` to log them instead"
+)]
+#[usage("[channel|console]")]
+#[example("#anime")]
+#[example("console")]
+#[max_args(1)]
+async fn channel(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    let arg = args.rest();
+    let channel_id = if arg.is_empty() {
+        msg.channel_id.0
+    } else if arg.eq_ignore_ascii_case("console") {
+        0
+    } else if is_channel(arg) {
+        get_channel(arg).parse::<u64>().unwrap_or_default()
+    } else {
+        return reply(ctx, msg, Err("Unable to parse channel".to_string())).await;
+    };
+
+    let data = ctx.data.write().await;
+    let mut settings = data.get::<Settings>().expect("Expected Setting in TypeMap.").lock().await;
+    settings.watchlist.channel = channel_id;
+    save_settings(&settings);
+
+    drop(settings);
+    drop(data);
+
+    let content = if channel_id == 0 {
+        "New episodes will be logged to the console".to_string()
+    } else {
+        format!("New episodes will be announced in <#{}>", channel_id)
+    };
+
+    reply(ctx, msg, Ok(content)).await
+}
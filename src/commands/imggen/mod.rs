@@ -1,17 +1,22 @@
+use std::borrow::Cow;
+
 use serenity::{
     framework::standard::{
         macros::{command, group},
         Args, CommandResult,
     },
-    model::channel::Message,
+    http::AttachmentType,
+    model::{channel::Message, user::User},
     prelude::*,
 };
-use urlencoding::encode;
 
 use crate::{
-    models::commands::NekoBotResponse,
-    utils::{chat::is_mention, discord::get_avatar},
-    InoriChannelUtils, InoriMessageUtils, MessageCreator,
+    utils::{
+        chat::is_mention,
+        discord::get_avatar,
+        imggen::{generate, Rendered, Template},
+    },
+    InoriChannelUtils, InoriMessageUtils, MessageCreator, Settings,
 };
 
 #[group]
@@ -19,13 +24,53 @@ use crate::{
 #[description("**Image Gen**")]
 struct ImageGen;
 
-async fn neko_bot(ctx: &Context, msg: &Message, url: &str, title: &str) -> CommandResult {
-    let mut msg = msg.channel_id.send_loading(ctx, title, "Generating image").await.unwrap();
-    let res = reqwest::get(url).await.unwrap().text().await.unwrap();
-    let res = serde_json::from_str::<NekoBotResponse>(&res).expect("Couldn't parse response.");
+// Images are rendered by nekobot, see `[imggen]` in the config to render
+// them locally instead
+async fn image_gen(ctx: &Context, msg: &Message, template: Template, title: &str) -> CommandResult {
+    let config = {
+        let data = ctx.data.read().await;
+        let settings = data.get::<Settings>().expect("Expected Setting in TypeMap.").lock().await;
+
+        settings.imggen.clone()
+    };
+
+    let mut new_msg = msg.channel_id.send_loading(ctx, title, "Generating image").await?;
+
+    match generate(&template, &config).await {
+        Ok(Rendered::File(data)) => {
+            // Attachments can't be added by editing so the image is sent on
+            // its own
+            new_msg.delete(&ctx.http).await?;
+
+            let file_name = template.file_name();
+            msg.channel_id
+                .send_noret(ctx, |m: &mut MessageCreator| {
+                    m.title(title).attachment(file_name, AttachmentType::Bytes {
+                        data:     Cow::Owned(data),
+                        filename: file_name.to_string(),
+                    })
+                })
+                .await
+        },
+        Ok(Rendered::Url(url)) => {
+            new_msg
+                .update_noret(ctx, |m: &mut MessageCreator| m.title(title).image(url))
+                .await
+        },
+        Err(why) => {
+            new_msg
+                .update_tmp(ctx, |m: &mut MessageCreator| m.error().title(title).content(why))
+                .await
+        },
+    }
+}
 
-    msg.update_noret(ctx, |m: &mut MessageCreator| m.title(title).image(res.message))
-        .await
+fn mentions_or_author(msg: &Message) -> Vec<&User> {
+    if msg.mentions.is_empty() {
+        vec![&msg.author]
+    } else {
+        msg.mentions.iter().collect()
+    }
 }
 
 #[command]
@@ -34,29 +79,17 @@ async fn neko_bot(ctx: &Context, msg: &Message, url: &str, title: &str) -> Comma
 #[example("Stop being such an idiot")]
 #[min_args(1)]
 async fn clyde(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
-    neko_bot(
-        ctx,
-        msg,
-        &format!("https://nekobot.xyz/api/imagegen?type=clyde&text={}", encode(args.rest())),
-        "Clyde",
-    )
-    .await
+    image_gen(ctx, msg, Template::Clyde(args.rest().to_string()), "Clyde").await
 }
 
 #[command]
 #[aliases("kanna")]
-#[description = "Generate an image of Kanna holdinga specified message"]
+#[description = "Generate an image of Kanna holding a specified message"]
 #[usage("<message>")]
 #[example("Thighs are life")]
 #[min_args(1)]
 async fn kannagen(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
-    neko_bot(
-        ctx,
-        msg,
-        &format!("https://nekobot.xyz/api/imagegen?type=kannagen&text={}", encode(args.rest())),
-        "Kanna Gen",
-    )
-    .await
+    image_gen(ctx, msg, Template::Kanna(args.rest().to_string()), "Kanna Gen").await
 }
 
 #[command]
@@ -86,18 +119,13 @@ async fn phcomment(ctx: &Context, msg: &Message, mut args: Args) -> CommandResul
         message = format!("{} {}", first_arg, message);
     }
 
-    neko_bot(
-        ctx,
-        msg,
-        &format!(
-            "https://nekobot.xyz/api/imagegen?type=phcomment&text={}&image={}&username={}",
-            encode(&message),
-            encode(&get_avatar(user)),
-            encode(&user.name)
-        ),
-        "PornHub Comment",
-    )
-    .await
+    let template = Template::PhComment {
+        text:     message,
+        username: user.name.clone(),
+        avatar:   get_avatar(user),
+    };
+
+    image_gen(ctx, msg, template, "PornHub Comment").await
 }
 
 #[command]
@@ -107,13 +135,7 @@ async fn phcomment(ctx: &Context, msg: &Message, mut args: Args) -> CommandResul
 #[example("Catgirls are all that matter")]
 #[min_args(1)]
 async fn trumptweet(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
-    neko_bot(
-        ctx,
-        msg,
-        &format!("https://nekobot.xyz/api/imagegen?type=trumptweet&text={}", encode(args.rest())),
-        "Trump Tweet",
-    )
-    .await
+    image_gen(ctx, msg, Template::TrumpTweet(args.rest().to_string()), "Trump Tweet").await
 }
 
 #[command]
@@ -123,16 +145,7 @@ async fn trumptweet(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
 #[example("SelfBots shouldn't be against TOS")]
 #[min_args(1)]
 async fn changemymind(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
-    neko_bot(
-        ctx,
-        msg,
-        &format!(
-            "https://nekobot.xyz/api/imagegen?type=changemymind&text={}",
-            encode(args.rest())
-        ),
-        "Change My Mind",
-    )
-    .await
+    image_gen(ctx, msg, Template::ChangeMyMind(args.rest().to_string()), "Change My Mind").await
 }
 
 #[command]
@@ -140,30 +153,8 @@ async fn changemymind(ctx: &Context, msg: &Message, args: Args) -> CommandResult
 #[usage("[@user]")]
 #[example("@L3af#0001")]
 async fn lolice(ctx: &Context, msg: &Message) -> CommandResult {
-    if msg.mentions.is_empty() {
-        let _ = neko_bot(
-            ctx,
-            msg,
-            &format!(
-                "https://nekobot.xyz/api/imagegen?type=lolice&url={}",
-                encode(&get_avatar(&msg.author))
-            ),
-            "Lolice",
-        )
-        .await;
-    } else {
-        for mention in &msg.mentions {
-            let _ = neko_bot(
-                ctx,
-                msg,
-                &format!(
-                    "https://nekobot.xyz/api/imagegen?type=lolice&url={}",
-                    encode(&get_avatar(&mention))
-                ),
-                "Lolice",
-            )
-            .await;
-        }
+    for user in mentions_or_author(msg) {
+        image_gen(ctx, msg, Template::Lolice(get_avatar(user)), "Lolice").await?;
     }
 
     Ok(())
@@ -174,30 +165,13 @@ async fn lolice(ctx: &Context, msg: &Message) -> CommandResult {
 #[usage("<@user>")]
 #[example("@L3af#0001")]
 async fn cutie(ctx: &Context, msg: &Message) -> CommandResult {
-    if msg.mentions.is_empty() {
-        let _ = neko_bot(
-            ctx,
-            msg,
-            &format!(
-                "https://nekobot.xyz/api/imagegen?type=captcha&url={}&username=a%20cute%20in%20them",
-                encode(&get_avatar(&msg.author))
-            ),
-            "Cutie",
-        )
-        .await;
-    } else {
-        for mention in &msg.mentions {
-            let _ = neko_bot(
-                ctx,
-                msg,
-                &format!(
-                    "https://nekobot.xyz/api/imagegen?type=captcha&url={}&username=a%20cutie%20in%20them",
-                    encode(&get_avatar(&mention))
-                ),
-                "Cutie",
-            )
-            .await;
-        }
+    for user in mentions_or_author(msg) {
+        let template = Template::Captcha {
+            caption: "a cutie in them".to_string(),
+            avatar:  get_avatar(user),
+        };
+
+        image_gen(ctx, msg, template, "Cutie").await?;
     }
 
     Ok(())
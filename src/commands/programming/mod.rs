@@ -10,21 +10,36 @@ use serenity::{
     prelude::Context,
 };
 
-use crate::{InoriChannelUtils, MessageCreator};
+use crate::{
+    utils::{
+        chat::parse_code_block,
+        discord::download_attachment,
+        esolang::{
+            brainfuck::{Outcome, Program, Snapshot},
+            execute, interpreter_by_name, Halt, Limits, INTERPRETERS,
+        },
+    },
+    InoriChannelUtils, MessageCreator,
+};
 
 #[group]
-#[commands(brainfuck)]
+#[commands(brainfuck, run)]
 #[description("**Programming**")]
 struct Programming;
 
 static BRAINFUCK_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r"^[+-<>.,\[\]]*$").unwrap());
 
+// Stops infinite loops from running forever
+const STEP_LIMIT: usize = 100_000;
+const SNAPSHOT_LIMIT: usize = 250;
+// Instructions shown around the current one
+const CODE_WIDTH: usize = 48;
+const CELL_WIDTH: usize = 5;
+
 #[command]
 #[aliases("bf")]
-#[description(
-    "Interpret Brainfuck code and return the result. Stepping through iterations will come when paginator is more \
-     stable"
-)]
+#[description("Interpret Brainfuck code and return the result, use `debug` to step through it")]
+#[sub_commands(bf_debug)]
 async fn brainfuck(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
     let bf = args.rest().to_string();
 
@@ -60,3 +75,196 @@ async fn brainfuck(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
         },
     }
 }
+
+fn render_code(code: &str, span: Option<(usize, usize)>) -> String {
+    let code = code.chars().collect::<Vec<char>>();
+    let start = span.map_or(0, |(start, _)| start.saturating_sub(CODE_WIDTH / 2));
+    let start = start.min(code.len().saturating_sub(CODE_WIDTH));
+    let end = (start + CODE_WIDTH).min(code.len());
+
+    let mut rendered = code[start..end].iter().collect::<String>();
+    if let Some((span_start, span_end)) = span {
+        rendered.push_str(&format!(
+            "\n{}{}",
+            " ".repeat(span_start - start),
+            "^".repeat((span_end.min(end - 1) - span_start) + 1)
+        ));
+    }
+
+    rendered
+}
+
+fn render_tape(snapshot: &Snapshot) -> String {
+    let indexes = (0..snapshot.window.len())
+        .map(|idx| format!("{:>width$}", format!("#{}", snapshot.window_start + idx), width = CELL_WIDTH))
+        .collect::<String>();
+    let values = snapshot
+        .window
+        .iter()
+        .map(|value| format!("{:>width$}", value, width = CELL_WIDTH))
+        .collect::<String>();
+    let marker = " ".repeat((snapshot.pointer - snapshot.window_start) * CELL_WIDTH + CELL_WIDTH - 1);
+
+    format!("{}\n{}\n{}^", indexes, values, marker)
+}
+
+fn render_output(output: &str) -> String {
+    if output.is_empty() {
+        "None".to_string()
+    } else {
+        format!("```\n{}\n```", output.replace("```", "`\u{200b}``"))
+    }
+}
+
+fn describe_outcome(outcome: &Outcome, steps: usize) -> String {
+    match outcome {
+        Outcome::Finished => format!("Finished after {} steps", steps),
+        Outcome::StepLimit => format!("Stopped after {} steps, the program might loop forever", steps),
+        Outcome::OutputLimit => format!("Stopped after {} steps, the output is too long", steps),
+        Outcome::Error(why) => format!("Stopped after {} steps: {}", steps, why),
+    }
+}
+
+#[command("debug")]
+#[aliases("step", "trace")]
+#[description(
+    "Step through Brainfuck code, showing the code, tape and output after each instruction. Repeated `+`, `-`, `<` \
+     and `>` are grouped into one step, characters that aren't instructions are ignored and anything after the code \
+     is used as input for `,`"
+)]
+#[usage("<code> [input]")]
+#[example("++++++++[>++++++++<-]>+.")]
+#[example("\",[.,]\" Inori")]
+#[min_args(1)]
+async fn bf_debug(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let code = args.single_quoted::<String>().unwrap_or_default();
+    let input = args.rest().to_string();
+
+    let program = match Program::parse(&code) {
+        Ok(program) => program,
+        Err(why) => {
+            return msg
+                .channel_id
+                .send_tmp(ctx, |m: &mut MessageCreator| m.error().title("Brainfuck Debugger").content(why))
+                .await;
+        },
+    };
+
+    let trace = program.trace(input.as_bytes(), STEP_LIMIT, SNAPSHOT_LIMIT);
+    let code = program.code();
+    let last = trace.snapshots.len() - 1;
+
+    let mut result = describe_outcome(&trace.outcome, trace.steps);
+    if trace.snapshots.len() > SNAPSHOT_LIMIT {
+        result.push_str(&format!(", only the first {} snapshots were kept", SNAPSHOT_LIMIT));
+    }
+
+    let pages = trace
+        .snapshots
+        .iter()
+        .enumerate()
+        .map(|(idx, snapshot)| {
+            let mut content = format!(
+                "**Step {} of {}**\n```brainfuck\n{}\n```\n**Tape**\n```\n{}\n```\n**Output**\n{}",
+                snapshot.step,
+                trace.steps,
+                render_code(&code, snapshot.span),
+                render_tape(snapshot),
+                render_output(&snapshot.output)
+            );
+
+            if idx == last {
+                content.push_str(&format!("\n**Result**\n{}", result));
+            }
+
+            let mut page = MessageCreator::default();
+            page.title("Brainfuck Debugger").content(content);
+
+            page
+        })
+        .collect();
+
+    msg.channel_id.send_paginator_noret(ctx, pages).await
+}
+
+// A leading code block is the code and anything after it is the input
+fn split_code_block(text: &str) -> Option<(String, String)> {
+    let text = text.trim_start();
+    let end = text.strip_prefix("```")?.find("```")? + 6;
+
+    let (_, code) = parse_code_block(&text[..end]);
+    // The newline before the closing fence isn't part of the code, which
+    // matters for Whitespace
+    let code = code.strip_suffix('\n').unwrap_or(&code).to_string();
+
+    Some((code, text[end..].trim().to_string()))
+}
+
+#[command]
+#[aliases("esolang", "eval")]
+#[description(
+    "Run code in an esoteric language, the code can be a code block followed by input, an attachment followed by \
+     input or just the code. Programs are stopped when they run for too long or print too much"
+)]
+#[usage("<language> <code> [input]")]
+#[example("lisp (define (sq x) (* x x)) (sq 12)")]
+#[example("forth : sq dup * ; 12 sq .")]
+#[example("befunge ```\n~:1+!#@_,\n``` Inori")]
+#[min_args(1)]
+async fn run(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let language = args.single::<String>().unwrap_or_default();
+
+    let interpreter = match interpreter_by_name(&language) {
+        Some(interpreter) => interpreter,
+        None => {
+            let languages = INTERPRETERS
+                .iter()
+                .map(|interpreter| format!("`{}`", interpreter.name()))
+                .collect::<Vec<String>>()
+                .join(", ");
+
+            return msg
+                .channel_id
+                .send_tmp(ctx, |m: &mut MessageCreator| {
+                    m.error()
+                        .title("Run")
+                        .content(format!("Unknown language `{}`, try one of {}", language, languages))
+                })
+                .await;
+        },
+    };
+
+    let (code, input) = match download_attachment(msg).await {
+        Some(Ok((_, data))) => (String::from_utf8_lossy(&data).to_string(), args.rest().to_string()),
+        Some(Err(why)) => {
+            return msg
+                .channel_id
+                .send_tmp(ctx, |m: &mut MessageCreator| m.error().title("Run").content(why))
+                .await;
+        },
+        None => split_code_block(args.rest()).unwrap_or_else(|| (args.rest().to_string(), String::new())),
+    };
+
+    let execution = tokio::task::spawn_blocking(move || execute(interpreter, &code, &input, Limits::default())).await?;
+
+    let stats = format!("{} fuel in {}ms", execution.fuel, execution.elapsed.as_millis());
+    let (result, failed) = match &execution.result {
+        Ok(()) => (format!("Finished using {}", stats), false),
+        Err(halt @ Halt::Error(_)) => (format!("{}, after using {}", halt, stats), true),
+        Err(halt) => (format!("{}, stopped after using {}", halt, stats), true),
+    };
+
+    msg.channel_id
+        .send_tmp(ctx, |m: &mut MessageCreator| {
+            if failed {
+                m.error();
+            }
+
+            m.title(format!("Run {}", interpreter.name())).content(format!(
+                "**Output**\n{}\n**Result**\n{}",
+                render_output(&execution.output),
+                result
+            ))
+        })
+        .await
+}
@@ -0,0 +1,164 @@
+use serenity::{
+    framework::standard::{macros::command, Args, CommandResult},
+    model::{
+        channel::{Channel, Message},
+        id::{ChannelId, MessageId},
+    },
+    prelude::Context,
+};
+
+use crate::{
+    utils::{
+        backup::{channel_name, write_archive},
+        chat::{get_channel, is_channel},
+        purge_filter::PurgeFilter,
+    },
+    InoriChannelUtils, InoriMessageUtils, MessageCreator,
+};
+
+#[command]
+#[aliases("archive", "export")]
+#[description(
+    "Save your messages in a channel to `backups/` as JSON and a HTML transcript, attachments are downloaded \
+     alongside them. Use `all` in a DM to save the whole conversation. Accepts the same filters as `purge`"
+)]
+#[usage("[all] [channel] [amount] [filters]")]
+#[example("500")]
+#[example("#general 100 has:attachment")]
+#[example("all")]
+#[example("all 801105575038041266 after:2021-01-01")]
+async fn backup(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let own_only = match args.current() {
+        Some(arg) if arg.eq_ignore_ascii_case("all") => {
+            args.advance();
+
+            false
+        },
+        _ => true,
+    };
+
+    let channel_id = match args.current() {
+        Some(arg) if is_channel(arg) => {
+            let channel_id = get_channel(arg).parse::<u64>().unwrap_or_default();
+            args.advance();
+
+            channel_id
+        },
+        _ => msg.channel_id.0,
+    };
+
+    let amount = match args.current().and_then(|arg| arg.parse::<u64>().ok()) {
+        Some(amount) => {
+            args.advance();
+
+            Some(amount)
+        },
+        None => None,
+    };
+
+    let filter = match PurgeFilter::parse(args.rest()) {
+        Ok(filter) => filter,
+        Err(why) => {
+            return msg
+                .channel_id
+                .send_tmp(ctx, |m: &mut MessageCreator| m.error().title("Backup").content(why))
+                .await;
+        },
+    };
+
+    let channel = match ctx.http.get_channel(channel_id).await {
+        Ok(channel) => channel,
+        Err(_) => {
+            return msg
+                .channel_id
+                .send_tmp(ctx, |m: &mut MessageCreator| {
+                    m.error().title("Backup").content("Unable to get channel")
+                })
+                .await;
+        },
+    };
+
+    if !own_only && !matches!(channel, Channel::Private(_)) {
+        return msg
+            .channel_id
+            .send_tmp(ctx, |m: &mut MessageCreator| {
+                m.error()
+                    .title("Backup")
+                    .content("Whole conversations can only be backed up from DMs")
+            })
+            .await;
+    }
+
+    let mut loading_msg = msg.channel_id.send_loading(ctx, "Backup", "Collecting messages").await?;
+    let user_id = ctx.cache.current_user_id().await;
+
+    let mut messages = Vec::new();
+    let mut before: Option<MessageId> = None;
+    'outer: loop {
+        let batch = ChannelId(channel_id)
+            .messages(&ctx.http, |r| match before {
+                Some(before) => r.before(before).limit(100),
+                None => r.limit(100),
+            })
+            .await;
+
+        let batch = match batch {
+            Ok(batch) => batch,
+            Err(_) => {
+                return loading_msg
+                    .update_tmp(ctx, |m: &mut MessageCreator| {
+                        m.error().title("Backup").content("Unable to get messages")
+                    })
+                    .await;
+            },
+        };
+
+        if batch.is_empty() {
+            break;
+        }
+
+        for message in batch {
+            before = Some(message.id);
+
+            if filter.is_exhausted(&message) {
+                break 'outer;
+            }
+
+            if message.id == loading_msg.id
+                || (own_only && message.author.id != user_id)
+                || !filter.matches(&message)
+            {
+                continue;
+            }
+
+            messages.push(message);
+
+            if amount.map_or(false, |amount| messages.len() as u64 >= amount) {
+                break 'outer;
+            }
+        }
+    }
+
+    if messages.is_empty() {
+        return loading_msg
+            .update_tmp(ctx, |m: &mut MessageCreator| {
+                m.warning().title("Backup").content("No messages to back up")
+            })
+            .await;
+    }
+
+    match write_archive(&channel_name(&channel), channel_id, &messages).await {
+        Ok(path) => {
+            let content = format!("Saved {} messages to `{}`", messages.len(), path.display());
+
+            loading_msg
+                .update_tmp(ctx, |m: &mut MessageCreator| m.success().title("Backup").content(content))
+                .await
+        },
+        Err(why) => {
+            loading_msg
+                .update_tmp(ctx, |m: &mut MessageCreator| m.error().title("Backup").content(why))
+                .await
+        },
+    }
+}
@@ -0,0 +1,137 @@
+use serenity::{
+    framework::standard::{macros::command, Args, CommandResult},
+    model::prelude::Message,
+    prelude::Context,
+};
+
+use crate::{
+    utils::crates_io::{format_count, lookup, CrateInfo},
+    InoriChannelUtils, InoriMessageUtils, MessageCreator, Settings,
+};
+
+fn overview(info: &CrateInfo) -> MessageCreator<'static> {
+    let krate = &info.krate;
+    let mut content = format!("**[{}](https://crates.io/crates/{})**", krate.name, krate.name);
+
+    if let Some(description) = &krate.description {
+        content = format!("{}\n\n{}", content, description.trim());
+    }
+
+    let mut page = MessageCreator::default();
+    page.title("Crate").content(content);
+
+    let version = info.version.as_ref();
+    let license = version.and_then(|version| version.license.as_ref());
+
+    page.field("Version", version.map_or(&krate.max_version, |version| &version.num), true)
+        .field("License", license.map_or("Unknown", String::as_str), true)
+        .field("Updated", krate.updated_at.split('T').next().unwrap_or_default(), true)
+        .field("Downloads", format_count(krate.downloads), true)
+        .field(
+            "Recent Downloads",
+            krate.recent_downloads.map_or("Unknown".to_string(), format_count),
+            true,
+        )
+        .field(
+            "Dependents",
+            info.reverse_dependencies.map_or("Unknown".to_string(), format_count),
+            true,
+        );
+
+    let mut links = Vec::new();
+    if let Some(repository) = &krate.repository {
+        links.push(format!("[Repository]({})", repository));
+    }
+
+    // Plenty of crates set their homepage to the repository
+    let homepage = krate
+        .homepage
+        .as_ref()
+        .filter(|homepage| Some(*homepage) != krate.repository.as_ref());
+    if let Some(homepage) = homepage {
+        links.push(format!("[Homepage]({})", homepage));
+    }
+
+    let docs = krate
+        .documentation
+        .clone()
+        .unwrap_or_else(|| format!("https://docs.rs/{}", krate.name));
+    links.push(format!("[Documentation]({})", docs));
+
+    page.field("Links", links.join(" | "), false);
+
+    page
+}
+
+fn feature_pages(info: &CrateInfo) -> Vec<MessageCreator<'static>> {
+    let version = match &info.version {
+        Some(version) if !version.features.is_empty() => version,
+        _ => return Vec::new(),
+    };
+
+    // Default first so it's clear what's enabled without opting in
+    let mut features = version.features.iter().collect::<Vec<_>>();
+    features.sort_by_key(|(name, _)| (name.as_str() != "default", name.as_str()));
+
+    let lines = features
+        .into_iter()
+        .map(|(name, enables)| {
+            if enables.is_empty() {
+                format!("`{}`", name)
+            } else {
+                format!("`{}` → {}", name, enables.join(", "))
+            }
+        })
+        .collect::<Vec<String>>();
+
+    lines
+        .chunks(15)
+        .map(|chunk| {
+            let mut page = MessageCreator::default();
+            page.title("Crate").content(format!(
+                "**{} {} features**\n\n{}",
+                info.krate.name,
+                version.num,
+                chunk.join("\n")
+            ));
+
+            page
+        })
+        .collect()
+}
+
+#[command("crate")]
+#[aliases("crates", "cratesio")]
+#[description(
+    "Look up a crate on crates.io, showing its latest version, license, downloads, links, dependents and features. \
+     Lookups are cached, the API used can be changed with `api` under `[crates]` in the config"
+)]
+#[usage("<name>")]
+#[example("serenity")]
+#[num_args(1)]
+async fn crate_info(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    let config = {
+        let data = ctx.data.read().await;
+        let settings = data.get::<Settings>().expect("Expected Setting in TypeMap.").lock().await;
+
+        settings.crates.clone()
+    };
+
+    let mut new_msg = msg.channel_id.send_loading(ctx, "Crate", "Looking up crate").await?;
+
+    match lookup(args.rest(), &config).await {
+        Ok(info) => {
+            let mut pages = vec![overview(&info)];
+            pages.extend(feature_pages(&info));
+
+            new_msg.delete(&ctx.http).await?;
+
+            msg.channel_id.send_paginator_noret(ctx, pages).await
+        },
+        Err(why) => {
+            new_msg
+                .update_tmp(ctx, |m: &mut MessageCreator| m.error().title("Crate").content(why))
+                .await
+        },
+    }
+}
@@ -0,0 +1,99 @@
+use std::str;
+
+use serenity::{
+    framework::standard::{macros::command, Args, CommandResult},
+    model::prelude::Message,
+    prelude::Context,
+};
+
+use crate::{
+    utils::codec::{codec_names, decode_chain, encode_chain, hexdump, parse_chain, Step},
+    InoriChannelUtils, MessageCreator,
+};
+
+// Bytes shown when the decoded output isn't text
+const HEXDUMP_LIMIT: usize = 256;
+
+async fn parse_steps(ctx: &Context, msg: &Message, title: &str, spec: &str, allow_auto: bool) -> Option<Vec<Step>> {
+    match parse_chain(spec, allow_auto) {
+        Ok(steps) => Some(steps),
+        Err(why) => {
+            let _ = msg
+                .channel_id
+                .send_tmp(ctx, |m: &mut MessageCreator| {
+                    m.error()
+                        .title(title)
+                        .content(format!("{}\nAvailable formats: {}", why, codec_names().join(", ")))
+                })
+                .await;
+
+            None
+        },
+    }
+}
+
+#[command]
+#[aliases("enc")]
+#[description(
+    "Encode text, formats can be chained with `|` and are applied left to right. Supports binary, morse, hex, base32, \
+     base64, base64url, url, html, rot13 and text"
+)]
+#[usage("<format[|format...]> <message>")]
+#[example("base64 I agree")]
+#[example("morse SOS")]
+#[example("b64|hex Inori")]
+#[min_args(2)]
+async fn encode(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let spec = args.single::<String>().unwrap_or_default();
+    let steps = match parse_steps(ctx, msg, "Encode", &spec, false).await {
+        Some(steps) => steps,
+        None => return Ok(()),
+    };
+
+    let input = args.rest();
+    let content = format!("Input: {}\nOutput: {}", input, encode_chain(&steps, input));
+
+    msg.channel_id
+        .send_tmp(ctx, |m: &mut MessageCreator| m.success().title("Encode").content(content))
+        .await
+}
+
+#[command]
+#[aliases("dec")]
+#[description(
+    "Decode text, formats can be chained with `|` and are applied left to right. `auto` detects the format, output \
+     which isn't valid UTF-8 is shown as a hexdump"
+)]
+#[usage("<format[|format...]|auto> <message>")]
+#[example("base64 SW5vcmkgaXMgdGhlIGJlc3Qgd2FpZnU=")]
+#[example("auto 496e6f7269")]
+#[example("b64|hex NDk2ZTZmNzI2OQ==")]
+#[min_args(2)]
+async fn decode(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let spec = args.single::<String>().unwrap_or_default();
+    let steps = match parse_steps(ctx, msg, "Decode", &spec, true).await {
+        Some(steps) => steps,
+        None => return Ok(()),
+    };
+
+    let input = args.rest();
+    let (bytes, used) = match decode_chain(&steps, input) {
+        Ok(res) => res,
+        Err(why) => {
+            return msg
+                .channel_id
+                .send_tmp(ctx, |m: &mut MessageCreator| m.error().title("Decode").content(why))
+                .await;
+        },
+    };
+
+    let output = match str::from_utf8(&bytes) {
+        Ok(text) => text.to_string(),
+        Err(_) => format!("(not valid UTF-8)\n```\n{}```", hexdump(&bytes, HEXDUMP_LIMIT)),
+    };
+    let content = format!("Format: {}\nInput: {}\nOutput: {}", used.join(" → "), input, output);
+
+    msg.channel_id
+        .send_tmp(ctx, |m: &mut MessageCreator| m.success().title("Decode").content(content))
+        .await
+}
@@ -0,0 +1,184 @@
+use serenity::{
+    framework::standard::{macros::command, Args, CommandResult},
+    model::prelude::Message,
+    prelude::Context,
+};
+
+use crate::{
+    models::currency::{format_amount, load_rates, parse_targets, RateTable, CRYPTO_PROVIDERS},
+    InoriChannelUtils, MessageCreator, Settings,
+};
+
+async fn rates(ctx: &Context) -> Result<RateTable, String> {
+    let config = {
+        let data = ctx.data.read().await;
+        let settings = data.get::<Settings>().expect("Expected Setting in TypeMap.").lock().await;
+
+        settings.currency.clone()
+    };
+
+    load_rates(&config).await
+}
+
+fn footer(table: &RateTable) -> String {
+    match table.crypto {
+        Some(provider) => format!("Rates as of {}, crypto rates from {}", table.date, provider),
+        None => format!("Rates as of {}", table.date),
+    }
+}
+
+#[command]
+#[aliases("exch", "currency")]
+#[description(
+    "Convert between currencies. Rates are cached and the last fetched rates are used if they can't be updated, crypto \
+     rates are included when a provider is set"
+)]
+#[usage("<amount> <from currency> [to] [currencies]")]
+#[example("20 NZD")]
+#[example("500 JPY USD")]
+#[example("20 NZD to USD,EUR,JPY")]
+#[example("0.5 BTC in USD")]
+#[min_args(2)]
+#[sub_commands(currencies)]
+async fn exchange(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let amount = if let Ok(amt) = args.single::<f64>() {
+        amt
+    } else {
+        return msg
+            .channel_id
+            .send_tmp(ctx, |m: &mut MessageCreator| {
+                m.error().title("Exchange").content("Invalid amount specified")
+            })
+            .await;
+    };
+
+    let from = args.single::<String>().unwrap_or_default().to_uppercase();
+    let mut targets = parse_targets(args.rest());
+    if targets.first().map_or(false, |target| target == "TO" || target == "IN") {
+        targets.remove(0);
+    }
+
+    let table = match rates(ctx).await {
+        Ok(table) => table,
+        Err(why) => {
+            return msg
+                .channel_id
+                .send_tmp(ctx, |m: &mut MessageCreator| {
+                    m.error()
+                        .title("Exchange")
+                        .content(format!("Unable to get exchange rates: {}", why))
+                })
+                .await;
+        },
+    };
+
+    let unknown = std::iter::once(&from)
+        .chain(targets.iter())
+        .filter(|code| !table.has(code))
+        .map(|code| format!("`{}`", code))
+        .collect::<Vec<String>>();
+
+    if !unknown.is_empty() {
+        return msg
+            .channel_id
+            .send_tmp(ctx, |m: &mut MessageCreator| {
+                m.error().title("Exchange").content(format!(
+                    "Unknown currency: {}\nUse `exchange currencies` to list the supported currencies",
+                    unknown.join(", ")
+                ))
+            })
+            .await;
+    }
+
+    // No targets lists the amount in every other currency
+    if targets.is_empty() {
+        let rates = table
+            .codes()
+            .into_iter()
+            .filter(|code| *code != from)
+            .filter_map(|code| Some((code, table.convert(amount, &from, code)?)))
+            .collect::<Vec<(&str, f64)>>();
+
+        let msgs = rates
+            .chunks(9)
+            .map(|chunk| {
+                let mut page = MessageCreator::default();
+
+                page.title("Exchange")
+                    .content(format!("**Base**\n{} {}", format_amount(amount), from))
+                    .footer_text(footer(&table));
+
+                for (code, value) in chunk {
+                    page.field(format!("**{}**", code), format_amount(*value), true);
+                }
+
+                page
+            })
+            .collect();
+
+        return msg.channel_id.send_paginator_noret(ctx, msgs).await;
+    }
+
+    let converted = targets
+        .iter()
+        .filter_map(|to| Some((to, table.convert(amount, &from, to)?)))
+        .map(|(to, value)| format!("{} {}", format_amount(value), to))
+        .collect::<Vec<String>>();
+
+    let content = if converted.len() == 1 {
+        format!("{} {} is roughly equal to {}", format_amount(amount), from, converted[0])
+    } else {
+        format!(
+            "{} {} is roughly equal to\n{}",
+            format_amount(amount),
+            from,
+            converted.join("\n")
+        )
+    };
+
+    msg.channel_id
+        .send_tmp(ctx, |m: &mut MessageCreator| {
+            m.title("Exchange").content(content).footer_text(footer(&table))
+        })
+        .await
+}
+
+#[command]
+#[aliases("codes", "list")]
+#[description("List the currencies that can be converted between")]
+async fn currencies(ctx: &Context, msg: &Message) -> CommandResult {
+    let table = match rates(ctx).await {
+        Ok(table) => table,
+        Err(why) => {
+            return msg
+                .channel_id
+                .send_tmp(ctx, |m: &mut MessageCreator| {
+                    m.error()
+                        .title("Exchange")
+                        .content(format!("Unable to get exchange rates: {}", why))
+                })
+                .await;
+        },
+    };
+
+    let codes = table.codes().iter().map(|code| format!("`{}`", code)).collect::<Vec<String>>();
+    let mut content = format!("**{} currencies**\n{}", codes.len(), codes.join(", "));
+
+    if table.crypto.is_none() {
+        let providers = CRYPTO_PROVIDERS
+            .iter()
+            .map(|provider| format!("`{}`", provider))
+            .collect::<Vec<String>>();
+
+        content.push_str(&format!(
+            "\n\nCrypto rates aren't available, the providers `currency.crypto` can be set to are {}",
+            providers.join(", ")
+        ));
+    }
+
+    msg.channel_id
+        .send_tmp(ctx, |m: &mut MessageCreator| {
+            m.info().title("Exchange").content(content).footer_text(footer(&table))
+        })
+        .await
+}
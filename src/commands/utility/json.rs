@@ -0,0 +1,209 @@
+use serde_json::Value;
+use serenity::{
+    framework::standard::{macros::command, Args, CommandResult},
+    model::prelude::Message,
+    prelude::Context,
+};
+
+use crate::{
+    utils::{
+        chat::parse_code_block,
+        data_format::{self, Format},
+        discord::download_attachment,
+    },
+    InoriChannelUtils, MessageCreator,
+};
+
+// Attachments take priority, their format comes from the file extension and
+// code blocks use their language, anything else is detected
+async fn read_document(msg: &Message, input: &str) -> Result<(String, Format), String> {
+    if let Some(attachment) = download_attachment(msg).await {
+        let (filename, data) = attachment?;
+        let text = String::from_utf8(data).map_err(|_| "The attachment isn't valid UTF-8".to_string())?;
+        let format = filename
+            .rsplit('.')
+            .next()
+            .and_then(Format::parse)
+            .unwrap_or_else(|| Format::detect(&text));
+
+        return Ok((text, format));
+    }
+
+    let (lang, text) = parse_code_block(input);
+    if text.trim().is_empty() {
+        return Err("Nothing to read, provide a document, code block or attachment".to_string());
+    }
+
+    let format = lang.as_deref().and_then(Format::parse).unwrap_or_else(|| Format::detect(&text));
+
+    Ok((text, format))
+}
+
+async fn load(msg: &Message, input: &str) -> Result<(Value, Format), String> {
+    let (text, format) = read_document(msg, input).await?;
+
+    data_format::parse(&text, format)
+        .map(|value| (value, format))
+        .map_err(|why| why.render(&text))
+}
+
+async fn send_document(
+    ctx: &Context,
+    msg: &Message,
+    title: &str,
+    output: Result<String, String>,
+    lang: &str,
+) -> CommandResult {
+    match output {
+        Ok(output) => {
+            msg.channel_id
+                .send_tmp(ctx, |m: &mut MessageCreator| {
+                    m.success()
+                        .title(title)
+                        .content(format!("```{}\n{}\n```", lang, output.trim_end()))
+                })
+                .await
+        },
+        Err(why) => {
+            msg.channel_id
+                .send_tmp(ctx, |m: &mut MessageCreator| m.error().title(title).content(why))
+                .await
+        },
+    }
+}
+
+#[command]
+#[description("Format, validate, query and convert JSON, TOML and YAML")]
+#[sub_commands(json_fmt, json_min, json_validate, json_get, json_convert)]
+#[min_args(1)]
+async fn json(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    msg.channel_id
+        .send_tmp(ctx, |m: &mut MessageCreator| {
+            m.error()
+                .title("JSON")
+                .content(format!("Unknown subcommand: {}", args.current().unwrap()))
+        })
+        .await
+}
+
+#[command("fmt")]
+#[aliases("format", "pretty")]
+#[description("Pretty print a document, the format is kept")]
+#[usage("<document|code block|attachment>")]
+#[example("{\"name\":\"Inori\",\"tags\":[1,2]}")]
+async fn json_fmt(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    let output = load(msg, args.rest())
+        .await
+        .and_then(|(value, format)| Ok((data_format::render(&value, format, true)?, format)));
+
+    let lang = output.as_ref().map_or("", |(_, format)| format.name());
+    send_document(ctx, msg, "Format", output.map(|(output, _)| output), lang).await
+}
+
+#[command("min")]
+#[aliases("minify")]
+#[description("Minify a document, YAML is minified to JSON")]
+#[usage("<document|code block|attachment>")]
+#[example("```json\n{\n  \"name\": \"Inori\"\n}\n```")]
+async fn json_min(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    let output = load(msg, args.rest()).await.and_then(|(value, format)| {
+        let format = if format == Format::Yaml { Format::Json } else { format };
+
+        Ok((data_format::render(&value, format, false)?, format))
+    });
+
+    let lang = output.as_ref().map_or("", |(_, format)| format.name());
+    send_document(ctx, msg, "Minify", output.map(|(output, _)| output), lang).await
+}
+
+#[command("validate")]
+#[aliases("check", "lint")]
+#[description("Check that a document is valid, errors show the line and column")]
+#[usage("<document|code block|attachment>")]
+#[example("{\"name\": \"Inori\",}")]
+async fn json_validate(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    let (text, format) = match read_document(msg, args.rest()).await {
+        Ok(document) => document,
+        Err(why) => {
+            return msg
+                .channel_id
+                .send_tmp(ctx, |m: &mut MessageCreator| m.error().title("Validate").content(why))
+                .await;
+        },
+    };
+
+    match data_format::parse(&text, format) {
+        Ok(_) => {
+            msg.channel_id
+                .send_tmp(ctx, |m: &mut MessageCreator| {
+                    m.success()
+                        .title("Validate")
+                        .content(format!("Valid {}", format.name().to_uppercase()))
+                })
+                .await
+        },
+        Err(why) => {
+            msg.channel_id
+                .send_tmp(ctx, |m: &mut MessageCreator| {
+                    m.error().title("Validate").content(why.render(&text))
+                })
+                .await
+        },
+    }
+}
+
+#[command("get")]
+#[aliases("query")]
+#[description(
+    "Query a document with a jq style path. Supports `.key`, `.\"key\"`, `[\"key\"]`, `[index]` with negative indexes \
+     counting from the end and `[]` to get every value"
+)]
+#[usage("<path> <document|code block|attachment>")]
+#[example(".a.b[0] {\"a\": {\"b\": [1, 2]}}")]
+#[example(".users[].name ```yaml\nusers:\n  - name: Inori\n```")]
+#[min_args(1)]
+async fn json_get(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let path = args.single_quoted::<String>().unwrap_or_default();
+
+    let output = load(msg, args.rest()).await.and_then(|(value, _)| {
+        let results = data_format::query(&value, &path)?;
+
+        results
+            .iter()
+            .map(|result| serde_json::to_string_pretty(result).map_err(|why| why.to_string()))
+            .collect::<Result<Vec<String>, String>>()
+            .map(|results| results.join("\n"))
+    });
+
+    send_document(ctx, msg, "Query", output, "json").await
+}
+
+#[command("convert")]
+#[aliases("to")]
+#[description("Convert a document between JSON, TOML and YAML")]
+#[usage("<json|toml|yaml> <document|code block|attachment>")]
+#[example("yaml {\"name\": \"Inori\", \"tags\": [1, 2]}")]
+#[example("toml ```yaml\nname: Inori\n```")]
+#[min_args(1)]
+async fn json_convert(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let target = args.single::<String>().unwrap_or_default();
+    let target = match Format::parse(&target) {
+        Some(target) => target,
+        None => {
+            return msg
+                .channel_id
+                .send_tmp(ctx, |m: &mut MessageCreator| {
+                    m.error()
+                        .title("Convert")
+                        .content(format!("Unknown format `{}`\nAvailable formats: json, toml, yaml", target))
+                })
+                .await;
+        },
+    };
+
+    let output = load(msg, args.rest())
+        .await
+        .and_then(|(value, _)| data_format::render(&value, target, true));
+
+    send_document(ctx, msg, "Convert", output, target.name()).await
+}
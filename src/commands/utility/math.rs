@@ -0,0 +1,123 @@
+use std::sync::Arc;
+
+use serenity::{
+    framework::standard::{macros::command, Args, CommandResult},
+    model::prelude::Message,
+    prelude::Context,
+};
+
+use crate::{
+    models::commands::CalcSessions,
+    utils::calc::{format_value, Session},
+    InoriChannelUtils, MessageCreator,
+};
+
+#[command]
+#[aliases("calc", "calculate")]
+#[description(
+    "Evaluate maths with variables, functions and units. Variables and functions are kept per channel and `ans` is \
+     the last result. Use `in` to convert units or show integers in `hex`, `oct` or `bin`"
+)]
+#[usage("<expression>")]
+#[example("3^(1 + 2)")]
+#[example("x = 5; f(n) = n^2 + x")]
+#[example("5 km in mi")]
+#[example("3 GiB / 200 MB/s")]
+#[example("0xff in bin")]
+#[min_args(1)]
+#[sub_commands(variables, clear)]
+async fn math(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    let expr = args.rest();
+    let sessions = {
+        let data = ctx.data.read().await;
+
+        Arc::clone(data.get::<CalcSessions>().expect("Expected CalcSessions in TypeMap."))
+    };
+
+    // Evaluating can take a while, so it runs off the async threads. The lock
+    // is held throughout so the channel's calculations stay in order
+    let mut sessions = sessions.lock().await;
+    let mut session = sessions.remove(&msg.channel_id.0).unwrap_or_default();
+    let input = expr.to_string();
+    let (session, res) = tokio::task::spawn_blocking(move || {
+        let res = session.eval(&input);
+        (session, res)
+    })
+    .await?;
+    sessions.insert(msg.channel_id.0, session);
+    drop(sessions);
+
+    match res {
+        Ok(result) => {
+            msg.channel_id
+                .send_tmp(ctx, |m: &mut MessageCreator| {
+                    m.success()
+                        .title("Math")
+                        .content(format!("Equation: `{}`\nResult: `{}`", expr, result))
+                })
+                .await
+        },
+        Err(why) => {
+            msg.channel_id
+                .send_tmp(ctx, |m: &mut MessageCreator| m.error().title("Math").content(why.render(expr)))
+                .await
+        },
+    }
+}
+
+#[command]
+#[aliases("vars", "functions")]
+#[description("List the variables and functions defined in this channel")]
+async fn variables(ctx: &Context, msg: &Message) -> CommandResult {
+    let content = {
+        let data = ctx.data.read().await;
+        let sessions = data.get::<CalcSessions>().expect("Expected CalcSessions in TypeMap.").lock().await;
+
+        sessions.get(&msg.channel_id.0).map_or_else(Vec::new, |session| {
+            let mut lines = session
+                .variables
+                .iter()
+                .map(|(name, value)| {
+                    format!("`{}` = {}", name, format_value(value).unwrap_or_else(|_| "Unknown".to_string()))
+                })
+                .chain(session.functions.iter().map(|(name, function)| {
+                    format!("`{}({})` = {}", name, function.params.join(", "), function.source)
+                }))
+                .collect::<Vec<String>>();
+            lines.sort();
+
+            lines
+        })
+    };
+
+    if content.is_empty() {
+        return msg
+            .channel_id
+            .send_tmp(ctx, |m: &mut MessageCreator| {
+                m.warning().title("Math").content("Nothing has been defined in this channel")
+            })
+            .await;
+    }
+
+    msg.channel_id
+        .send_tmp(ctx, |m: &mut MessageCreator| m.info().title("Math").content(content.join("\n")))
+        .await
+}
+
+#[command]
+#[aliases("reset")]
+#[description("Forget the variables and functions defined in this channel")]
+async fn clear(ctx: &Context, msg: &Message) -> CommandResult {
+    {
+        let data = ctx.data.read().await;
+        let mut sessions = data.get::<CalcSessions>().expect("Expected CalcSessions in TypeMap.").lock().await;
+
+        sessions.remove(&msg.channel_id.0);
+    }
+
+    msg.channel_id
+        .send_tmp(ctx, |m: &mut MessageCreator| {
+            m.success().title("Math").content("Cleared variables and functions")
+        })
+        .await
+}
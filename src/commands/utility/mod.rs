@@ -1,13 +1,32 @@
 mod automsg;
+mod backup;
+mod crates;
+mod devtools;
 mod emotestealer;
+mod encoding;
+mod exchange;
+mod json;
+mod math;
 pub mod purge;
+mod regex_tester;
+mod rust_eval;
+mod snipe;
 mod tags;
 
 // use automsg::*;
-use std::{cmp::min, collections::HashMap, time::Instant};
+use std::{collections::HashMap, time::Instant};
 
+use backup::*;
+use crates::*;
+use devtools::*;
 use emotestealer::*;
+use encoding::*;
+use exchange::*;
+use json::*;
+use math::*;
 use purge::*;
+use regex_tester::*;
+use rust_eval::*;
 use serenity::{
     client::bridge::gateway::ShardId,
     constants::GATEWAY_VERSION,
@@ -21,12 +40,13 @@ use serenity::{
     },
     prelude::Context,
 };
+use snipe::*;
 use tags::*;
 use urlencoding::encode;
 
 use crate::{
     models::{
-        commands::{CommandCounter, FrankFurterResponse, ShardManagerContainer},
+        commands::{CommandCounter, ShardManagerContainer},
         discord::BasicUser,
     },
     save_settings,
@@ -34,26 +54,37 @@ use crate::{
         chat::{get_user, is_user},
         discord::{get_member, get_permissions, get_roles, get_top_colour},
         emotes::EMOTES,
+        rustdoc::{is_std, load_index, search},
     },
     InoriChannelUtils, InoriMessageUtils, MessageCreator, Settings,
 };
 
 #[group]
 #[commands(
-    base64,
+    backup,
     checktoken,
+    crate_info,
+    decode,
+    editsnipe,
     emotestealer,
+    encode,
     exchange,
+    hash,
+    json,
+    jwt,
     math,
     ping,
     purge,
+    regex,
     roleinfo,
     rustdoc,
     serverinfo,
     setup,
+    snipe,
     tags,
     usages,
-    userinfo
+    userinfo,
+    uuid
 )]
 #[description("**Utilities**")]
 struct Utility;
@@ -471,238 +502,90 @@ async fn usages(ctx: &Context, msg: &Message) -> CommandResult {
         .await
 }
 
-#[command]
-#[description("Evaluate most mathmatical problems")]
-#[usage("<expression>")]
-#[example("3^(1 + 2)")]
-#[min_args(1)]
-async fn math(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
-    let expr = args.rest();
-    if let Ok(res) = meval::eval_str(expr) {
-        msg.channel_id
-            .send_tmp(ctx, |m: &mut MessageCreator| {
-                m.success()
-                    .title("Math")
-                    .content(format!("Equation: {}\nResult: {}", expr, res))
-            })
-            .await
-    } else {
-        msg.channel_id
-            .send_tmp(ctx, |m: &mut MessageCreator| {
-                m.error().title("Math").content("Unable to evaluate equation")
-            })
-            .await
-    }
-}
-
 #[command]
 #[aliases("rust")]
-#[description("Get a link to a specified libraries Rust Doc")]
+#[description(
+    "Search a libraries Rust Doc, std crates use the local `rustup doc` install when there is one. Without a search \
+     this links to the docs, use `eval` to run Rust code"
+)]
 #[usage("<library> [search]")]
 #[example("serenity")]
 #[example("std Result")]
+#[example("std vec::Vec::push")]
 #[min_args(1)]
+#[sub_commands(rust_eval)]
 async fn rustdoc(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
     let lib = args.single::<String>().unwrap().to_lowercase();
 
-    let search = if !args.is_empty() {
-        format!("?search={}", encode(args.rest()))
-    } else {
-        "".to_string()
-    };
-
-    msg.channel_id
-        .send_tmp(ctx, |m: &mut MessageCreator| {
-            m.title("Rust Doc")
-                .field("Crates.io", format!("https://crates.io/crates/{}", lib), true)
-                .field("docs.rs", format!("https://docs.rs/{}{}", lib, search), true)
-        })
-        .await
-}
-
-#[command]
-#[aliases("exch")]
-#[description("Get exchange rate for specified currency")]
-#[usage("<amount> <from currency> [to currency]")]
-#[example("20 NZD")]
-#[example("500 JPY USD")]
-#[min_args(2)]
-#[max_args(3)]
-async fn exchange(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
-    // Surely there's a better way to do this
-    // Have to use String rather than &str bc
-    // you can't .contains on Vec<&&str> with
-    // a String or &str
-    let currencies = vec![
-        "AUD".to_string(),
-        "BGN".to_string(),
-        "BRL".to_string(),
-        "CAD".to_string(),
-        "CHF".to_string(),
-        "CNY".to_string(),
-        "CZK".to_string(),
-        "DKK".to_string(),
-        "EUR".to_string(),
-        "GBP".to_string(),
-        "HKD".to_string(),
-        "HRK".to_string(),
-        "HUF".to_string(),
-        "IDR".to_string(),
-        "ILS".to_string(),
-        "INR".to_string(),
-        "ISK".to_string(),
-        "JPY".to_string(),
-        "KRW".to_string(),
-        "MXN".to_string(),
-        "MYR".to_string(),
-        "NOK".to_string(),
-        "NZD".to_string(),
-        "PHP".to_string(),
-        "PLN".to_string(),
-        "RON".to_string(),
-        "RUB".to_string(),
-        "SEK".to_string(),
-        "SGD".to_string(),
-        "THB".to_string(),
-        "TRY".to_string(),
-        "ZAR".to_string(),
-    ];
-
-    let amount = if let Ok(amt) = args.single::<f64>() {
-        amt
-    } else {
-        return msg
-            .channel_id
-            .send_tmp(ctx, |m: &mut MessageCreator| {
-                m.error().title("Exchange").content("Invalid amount specified")
-            })
-            .await;
-    };
-
-    let from = args.single::<String>().unwrap().to_uppercase();
-    let to_wrapped = args.single::<String>();
-
-    if !currencies.contains(&from) {
+    if args.is_empty() {
         return msg
             .channel_id
             .send_tmp(ctx, |m: &mut MessageCreator| {
-                m.error().title("Exchange").content("Invalid `from` currency speicifed")
+                if is_std(&lib) {
+                    m.title("Rust Doc")
+                        .field("Docs", format!("https://doc.rust-lang.org/stable/{}/", lib), true)
+                } else {
+                    m.title("Rust Doc")
+                        .field("Crates.io", format!("https://crates.io/crates/{}", lib), true)
+                        .field("docs.rs", format!("https://docs.rs/{}", lib), true)
+                }
             })
             .await;
     }
 
-    let res = reqwest::get(&format!("https://api.frankfurter.app/latest?from={}&amount={}", from, amount))
-        .await?
-        .text()
-        .await?;
-
-    let res: FrankFurterResponse = serde_json::from_str::<FrankFurterResponse>(&res).expect("Couldn't parse response");
-
-    match to_wrapped {
-        Ok(to) => {
-            let to = to.to_uppercase();
-
-            if !currencies.contains(&to) {
-                return msg
-                    .channel_id
-                    .send_tmp(ctx, |m: &mut MessageCreator| {
-                        m.error().title("Exchange").content("Invalid `to` currency speicifed")
-                    })
-                    .await;
-            }
+    let query = args.rest().trim().to_string();
+    let mut new_msg = msg.channel_id.send_loading(ctx, "Rust Doc", "Loading the search index").await?;
 
-            let val: f64 = res.rates[&to];
+    let items = match load_index(&lib).await {
+        Ok(items) => items,
+        Err(why) => {
+            // Still link to the docs' own search when the index can't be read
+            let url = if is_std(&lib) {
+                format!("https://doc.rust-lang.org/stable/{}/?search={}", lib, encode(&query))
+            } else {
+                format!("https://docs.rs/{}?search={}", lib, encode(&query))
+            };
 
-            return msg
-                .channel_id
-                .send_tmp(ctx, |m: &mut MessageCreator| {
-                    m.title("Exchange")
-                        .content(format!("{:.2} {} is roughly equal to {:.2} {}", amount, from, val, to))
+            return new_msg
+                .update_tmp(ctx, |m: &mut MessageCreator| {
+                    m.error().title("Rust Doc").content(why).field("Search", url, false)
                 })
                 .await;
         },
-        Err(_) => {
-            let mut rates: Vec<(String, f64)> = Vec::new();
+    };
 
-            for (cur, val) in &res.rates {
-                rates.push((cur.to_string(), *val));
-            }
+    let results = search(&items, &query, 5);
 
-            rates.sort_by(|(cur1, _val1), (cur2, _val2)| cur1.partial_cmp(cur2).unwrap());
-            let msg_count = (rates.len() as f64 / 9.0).ceil();
-            let mut msgs = Vec::new();
+    if results.is_empty() {
+        return new_msg
+            .update_tmp(ctx, |m: &mut MessageCreator| {
+                m.error()
+                    .title("Rust Doc")
+                    .content(format!("No results for `{}` in `{}`", query, lib))
+            })
+            .await;
+    }
 
-            for idx in 0..msg_count as u64 {
-                let mut msg = MessageCreator::default();
+    new_msg
+        .update_tmp(ctx, |m: &mut MessageCreator| {
+            m.title("Rust Doc");
 
-                msg.title("Exchange").content(&format!(
-                    "**Base**\n{} {}\n\nExchange rates as of {}",
-                    res.amount, res.base, res.date
-                ));
+            for item in &results {
+                let mut value = format!("{} - {}", item.kind_name(), item.url());
 
-                let field_count = min(rates.len() as u64, (idx + 1) * 9) - idx * 9;
-                for i in 0..field_count {
-                    let rate_idx = idx * 9 + i;
-                    let rate = &rates[rate_idx as usize];
+                if let Some(signature) = &item.signature {
+                    value.push_str(&format!("\n`{}`", signature));
+                }
 
-                    msg.field(&format!("**{}**", rate.0), &format!("{:.2}", rate.1), true);
+                if !item.description.is_empty() {
+                    value.push_str(&format!("\n{}", item.description));
                 }
 
-                msgs.push(msg);
+                m.field(item.full_path(), value, false);
             }
 
-            return msg.channel_id.send_paginator_noret(ctx, msg, msgs).await;
-        },
-    }
-}
-
-#[command]
-#[aliases("b64")]
-#[description("Encode/decode base64")]
-#[usage("<encode/decode> <message>")]
-#[example("decode SW5vcmkgaXMgdGhlIGJlc3Qgd2FpZnU=")]
-#[example("encode I agree")]
-#[min_args(2)]
-async fn base64(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
-    let mode = args.single::<String>().unwrap_or_default();
-    let encode = match mode.to_lowercase().as_ref() {
-        "encode" | "enc" => true,
-        "decode" | "dec" => false,
-        _ => {
-            return msg
-                .channel_id
-                .send_tmp(ctx, |m: &mut MessageCreator| {
-                    m.error().title("Base64").content("Invalid argument")
-                })
-                .await;
-        },
-    };
-
-    let content = if encode {
-        let msg = args.rest();
-        let enc = base64::encode(msg);
-
-        format!("Input: {}\nOutput: {}", msg, enc)
-    } else {
-        let enc_msg = args.rest();
-        let dec_bytes = base64::decode(enc_msg).unwrap_or_default();
-        let dec = match String::from_utf8(dec_bytes) {
-            Ok(dec) => dec,
-            Err(err) => {
-                return msg
-                    .channel_id
-                    .send_tmp(ctx, |m: &mut MessageCreator| {
-                        m.error().title("Base64").content(format!("Invalid UTF-8 sequence: {}", err))
-                    })
-                    .await;
-            },
-        };
-
-        format!("Input: {}\nOutput: {:?}", enc_msg, dec)
-    };
-
-    msg.channel_id
-        .send_tmp(ctx, |m: &mut MessageCreator| m.success().title("Base64").content(content))
+            m
+        })
         .await
 }
 
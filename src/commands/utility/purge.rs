@@ -1,19 +1,23 @@
 use core::future::Future;
 
 use colored::Colorize;
-use regex::Regex;
 use serenity::{
     framework::standard::{macros::command, Args, CommandResult},
     model::{
-        channel::{Channel, Message},
-        id::ChannelId,
+        channel::{Channel, ChannelType, Message},
+        id::{ChannelId, GuildId},
     },
     prelude::Context,
 };
 
 use crate::{
     inori_error, inori_info, inori_success,
-    utils::chat::{get_channel, is_channel},
+    models::purge::{cancel_purge_job, is_purge_job_running, start_purge_job, PurgeJob},
+    utils::{
+        backup::{channel_name, write_archive},
+        chat::{get_channel, is_channel},
+        purge_filter::PurgeFilter,
+    },
     InoriChannelUtils, InoriMessageUtils, MessageCreator,
 };
 
@@ -23,13 +27,19 @@ where
     Fut: Future<Output = bool>, {
     let mut current = args.single::<String>().unwrap_or_default();
 
-    let silent = if args.len() >= 2 && current.to_lowercase().eq("silent") {
-        current = args.single::<String>().unwrap_or_default();
+    let mut silent = false;
+    let mut dry_run = false;
+    let mut archive = false;
+    while !args.is_empty() {
+        match current.to_lowercase().as_str() {
+            "silent" => silent = true,
+            "dry" | "dryrun" => dry_run = true,
+            "archive" | "backup" => archive = true,
+            _ => break,
+        }
 
-        true
-    } else {
-        false
-    };
+        current = args.single::<String>().unwrap_or_default();
+    }
 
     let channel_id = if args.len() >= 2 && is_channel(&current) {
         let channel = get_channel(&current);
@@ -56,41 +66,39 @@ where
         };
     };
 
-    let rest = args.rest();
-    let regex = if let Ok(regex) = if !rest.is_empty() {
-        Regex::new(rest)
-    } else {
-        Regex::new(r".*")
-    } {
-        regex
-    } else {
-        return if silent {
-            inori_error!(title, "Unable to parse regex");
-
-            Ok(())
-        } else {
-            msg.channel_id
-                .send_tmp(ctx, |m: &mut MessageCreator| {
-                    m.error().title(title).content("Unable to parse regex")
-                })
-                .await
-        };
+    let filter = match PurgeFilter::parse(args.rest()) {
+        Ok(filter) => filter,
+        Err(why) => {
+            return if silent {
+                inori_error!(title, "{}", why);
+
+                Ok(())
+            } else {
+                msg.channel_id
+                    .send_tmp(ctx, |m: &mut MessageCreator| m.error().title(title).content(why))
+                    .await
+            };
+        },
     };
 
     let loading_msg = if silent {
-        inori_info!(title, "Finding and deleting {} messages", amount);
+        inori_info!(title, "Finding {} messages", amount);
 
         None
     } else {
         Some(
             msg.channel_id
-                .send_loading(ctx, title, &format!("Finding and deleting {} messages", amount))
+                .send_loading(ctx, title, &format!("Finding {} messages", amount))
                 .await
                 .unwrap(),
         )
     };
 
     let mut purge_count = 0;
+    // Matching messages are held back instead of deleted for dry runs and
+    // archiving, archived messages are deleted after the archive is written
+    let mut matched = Vec::new();
+    let defer = dry_run || archive;
 
     let find_msg = if channel_id == msg.channel_id.0 && loading_msg.is_some() {
         loading_msg.clone().unwrap()
@@ -98,8 +106,13 @@ where
         let msgs = ChannelId(channel_id).messages(ctx, |r| r.limit(1)).await?;
         let msg = msgs.get(0).unwrap().clone();
 
-        if regex.is_match(&msg.content) && f(msg.clone()).await {
-            ctx.http.delete_message(msg.channel_id.0, msg.id.0).await.unwrap_or(());
+        if filter.matches(&msg) && f(msg.clone()).await {
+            if defer {
+                matched.push(msg.clone());
+            } else {
+                ctx.http.delete_message(msg.channel_id.0, msg.id.0).await.unwrap_or(());
+            }
+
             purge_count += 1;
         }
 
@@ -135,8 +148,16 @@ where
         };
 
         for message in &messages {
-            if purge_count < amount && regex.is_match(&message.content) && f(message.clone()).await {
-                ctx.http.delete_message(message.channel_id.0, message.id.0).await.unwrap_or(());
+            if filter.is_exhausted(message) {
+                break 'outer;
+            }
+
+            if purge_count < amount && filter.matches(message) && f(message.clone()).await {
+                if defer {
+                    matched.push(message.clone());
+                } else {
+                    ctx.http.delete_message(message.channel_id.0, message.id.0).await.unwrap_or(());
+                }
 
                 purge_count += 1;
             }
@@ -158,13 +179,59 @@ where
         "{}{}",
         if purge_count == 1 { "" } else { "s" },
         if total_count >= 1000 {
-            " (Reached 1000 messages)"
+            " (Reached 1000 messages, use `purge all` to purge everything)"
         } else {
             ""
         }
     );
-    let content = format!("Deleted {} message{}", purge_count, end);
+    let mut archive_path = None;
+    if archive && !dry_run {
+        match write_archive(&channel_name(channel), channel_id, &matched).await {
+            Ok(path) => {
+                for message in &matched {
+                    ctx.http.delete_message(message.channel_id.0, message.id.0).await.unwrap_or(());
+                }
+
+                archive_path = Some(path);
+            },
+            Err(why) => {
+                let content = format!("Unable to archive messages, nothing was deleted\n{}", why);
+
+                return if let Some(mut loading_msg) = loading_msg {
+                    loading_msg
+                        .update_tmp(ctx, |m: &mut MessageCreator| m.error().title(title).content(content))
+                        .await
+                } else {
+                    inori_error!(title, "{}", content);
+
+                    Ok(())
+                };
+            },
+        }
+    }
 
+    let content = if dry_run {
+        let preview = matched
+            .iter()
+            .map(|message| {
+                let content = message.content.replace('\n', " ").replace('`', "'");
+                let content = if content.chars().count() > 60 {
+                    format!("{}...", content.chars().take(57).collect::<String>())
+                } else {
+                    content
+                };
+
+                format!("`{}` {}", message.timestamp.format("%Y-%m-%d %H:%M"), content)
+            })
+            .collect::<Vec<String>>()
+            .join("\n");
+
+        format!("Would delete {} message{}\n{}", purge_count, end, preview)
+    } else if let Some(path) = archive_path {
+        format!("Deleted {} message{}\nArchived to `{}`", purge_count, end, path.display())
+    } else {
+        format!("Deleted {} message{}", purge_count, end)
+    };
 
     if let Some(mut loading_msg) = loading_msg {
         loading_msg
@@ -179,14 +246,15 @@ where
 
 #[command]
 #[aliases("embed", "emb")]
-#[description("Purge messages that contain embeds")]
-#[usage("[channel] [silent] <amount> [regex]")]
+#[description("Purge messages that contain embeds, accepts the same filters as `purge`")]
+#[usage("[silent] [dry] [archive] [channel] <amount> [filters]")]
 #[example("20")]
 #[example("#general 20")]
 #[example("801105575038041266 20")]
 #[example("20 \\[[a-zA-Z]*]")]
 #[example("#general 20 \\[[a-zA-Z]*]")]
 #[example("silent #general 20 \\[[a-zA-Z]*]")]
+#[example("dry 20 before:2021-01-01 -has:reaction")]
 #[min_args(1)]
 async fn embeds(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
     _purge(ctx, msg, "Purge", args, async move |message: Message| {
@@ -197,8 +265,13 @@ async fn embeds(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
 
 #[command]
 #[aliases("prune", "clear")]
-#[description("Purge messages sent by yourself")]
-#[usage("[silent] [channel] <amount> [regex]")]
+#[description(
+    "Purge messages sent by yourself\n**Filters**\n`before:<date|message id>`, `after:<date|message id>`\n\
+     `has:<attachment|image|link|embed|reaction|mention|emote>`\n`contains:\"text\"`, `regex:<regex>`, \
+     `is:pinned`\nPrefix a filter with `-` to negate it, anything else is treated as a regex. Use `dry` to list what \
+     would be deleted without deleting anything or `archive` to back up messages before deleting them"
+)]
+#[usage("[silent] [dry] [archive] [channel] <amount> [filters]")]
 #[example("20")]
 #[example("silent 20")]
 #[example("#general 20")]
@@ -206,11 +279,165 @@ async fn embeds(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
 #[example("20 \\[[a-zA-Z]*]")]
 #[example("#general 20 \\[[a-zA-Z]*]")]
 #[example("silent #general 20 \\[[a-zA-Z]*]")]
+#[example("20 has:link contains:\"discord.gg\"")]
+#[example("dry 50 after:801105575038041266 has:attachment")]
+#[example("archive 100")]
 #[min_args(1)]
-#[sub_commands(embeds)]
+#[sub_commands(embeds, all, cancel, status)]
 async fn purge(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
     _purge(ctx, msg, "Purge", args, async move |message: Message| {
         message.author.id == ctx.http.get_current_user().await.unwrap().id
     })
     .await
 }
+
+#[command]
+#[aliases("history", "everything")]
+#[description(
+    "Delete all of your messages in a channel, a guild or every open DM in the background. Progress is saved so the \
+     purge resumes after a restart. Accepts the same filters as `purge`"
+)]
+#[usage("<channel|guild|dms> [channel|guild id] [filters]")]
+#[example("channel")]
+#[example("channel #general")]
+#[example("guild")]
+#[example("guild 801105575038041266 before:2021-01-01")]
+#[example("dms -has:attachment")]
+#[min_args(1)]
+async fn all(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let scope = args.single::<String>().unwrap().to_lowercase();
+
+    let (scope, channels) = match scope.as_str() {
+        "channel" | "c" => {
+            let channel_id = match args.current() {
+                Some(arg) if is_channel(arg) => {
+                    let channel = get_channel(arg).parse::<u64>().unwrap_or_default();
+                    args.advance();
+
+                    channel
+                },
+                _ => msg.channel_id.0,
+            };
+
+            (format!("<#{}>", channel_id), vec![channel_id])
+        },
+        "guild" | "server" | "g" => {
+            let guild_id = match args.current().and_then(|arg| arg.parse::<u64>().ok()) {
+                Some(guild_id) => {
+                    args.advance();
+
+                    guild_id
+                },
+                None => match msg.guild_id {
+                    Some(guild_id) => guild_id.0,
+                    None => {
+                        return msg
+                            .channel_id
+                            .send_tmp(ctx, |m: &mut MessageCreator| {
+                                m.error().title("Purge").content("Specify a guild ID when not in a guild")
+                            })
+                            .await;
+                    },
+                },
+            };
+
+            let channels = match GuildId(guild_id).channels(&ctx.http).await {
+                Ok(channels) => channels
+                    .values()
+                    .filter(|channel| channel.kind == ChannelType::Text || channel.kind == ChannelType::News)
+                    .map(|channel| channel.id.0)
+                    .collect::<Vec<u64>>(),
+                Err(_) => {
+                    return msg
+                        .channel_id
+                        .send_tmp(ctx, |m: &mut MessageCreator| {
+                            m.error().title("Purge").content("Unable to get guild channels")
+                        })
+                        .await;
+                },
+            };
+
+            (format!("Guild {}", guild_id), channels)
+        },
+        "dms" | "dm" | "d" => match ctx.http.get_user_dm_channels().await {
+            Ok(channels) => (
+                "All DMs".to_string(),
+                channels.iter().map(|channel| channel.id.0).collect::<Vec<u64>>(),
+            ),
+            Err(_) => {
+                return msg
+                    .channel_id
+                    .send_tmp(ctx, |m: &mut MessageCreator| {
+                        m.error().title("Purge").content("Unable to get DM channels")
+                    })
+                    .await;
+            },
+        },
+        _ => {
+            return msg
+                .channel_id
+                .send_tmp(ctx, |m: &mut MessageCreator| {
+                    m.error()
+                        .title("Purge")
+                        .content(format!("Unknown scope: {}\nValid scopes: `channel`, `guild`, `dms`", scope))
+                })
+                .await;
+        },
+    };
+
+    let filter = args.rest().to_string();
+    if let Err(why) = PurgeFilter::parse(&filter) {
+        return msg
+            .channel_id
+            .send_tmp(ctx, |m: &mut MessageCreator| m.error().title("Purge").content(why))
+            .await;
+    }
+
+    let job = PurgeJob::new(scope, filter, channels, msg.channel_id.0);
+    if !start_purge_job(ctx, job).await {
+        return msg
+            .channel_id
+            .send_tmp(ctx, |m: &mut MessageCreator| {
+                m.error()
+                    .title("Purge")
+                    .content("A purge is already running, use `purge cancel` to stop it")
+            })
+            .await;
+    }
+
+    Ok(())
+}
+
+#[command]
+#[aliases("stop")]
+#[description("Cancel the running background purge")]
+async fn cancel(ctx: &Context, msg: &Message) -> CommandResult {
+    if cancel_purge_job(ctx).await {
+        msg.channel_id
+            .send_tmp(ctx, |m: &mut MessageCreator| {
+                m.success().title("Purge").content("Cancelling purge")
+            })
+            .await
+    } else {
+        msg.channel_id
+            .send_tmp(ctx, |m: &mut MessageCreator| {
+                m.error().title("Purge").content("No purge is running")
+            })
+            .await
+    }
+}
+
+#[command]
+#[aliases("progress")]
+#[description("Show the progress of the running background purge")]
+async fn status(ctx: &Context, msg: &Message) -> CommandResult {
+    let content = match PurgeJob::load() {
+        Some(job) if is_purge_job_running(ctx).await => format!("Running\n{}", job.progress()),
+        Some(job) => format!("Paused, resumes on restart\n{}", job.progress()),
+        None => "No purge is running".to_string(),
+    };
+
+    msg.channel_id
+        .send_tmp(ctx, |m: &mut MessageCreator| m.info().title("Purge").content(content))
+        .await
+}
@@ -0,0 +1,108 @@
+use serenity::{
+    framework::standard::{macros::command, Args, CommandResult},
+    model::prelude::Message,
+    prelude::Context,
+};
+
+use crate::{
+    utils::regex_tester::{compile, find_matches, highlight},
+    InoriChannelUtils, MessageCreator,
+};
+
+// Matches listed individually, the rest are only counted
+const MATCH_LIMIT: usize = 10;
+// Longer texts aren't highlighted as they'd push the matches out of view
+const HIGHLIGHT_LIMIT: usize = 1000;
+
+async fn replied_content(ctx: &Context, msg: &Message) -> Option<String> {
+    let reference = msg.message_reference.as_ref()?;
+    let message_id = reference.message_id?;
+
+    let replied = reference.channel_id.message(&ctx.http, message_id).await.ok()?;
+
+    Some(replied.content)
+}
+
+#[command]
+#[aliases("regexp", "re")]
+#[description(
+    "Test a regex against some text or the message being replied to. Patterns containing spaces need to be quoted"
+)]
+#[usage("<pattern> [text]")]
+#[example("\\d+ There are 3 apples and 12 oranges")]
+#[example("\"(?P<user>\\w+)@(?P<domain>\\S+)\" contact inori@example.com")]
+#[min_args(1)]
+async fn regex(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let pattern = args.single_quoted::<String>().unwrap_or_default();
+    let pattern = pattern.trim_matches('`');
+
+    let text = match args.rest().trim() {
+        "" => replied_content(ctx, msg).await.unwrap_or_default(),
+        text => text.to_string(),
+    };
+
+    if text.is_empty() {
+        return msg
+            .channel_id
+            .send_tmp(ctx, |m: &mut MessageCreator| {
+                m.error()
+                    .title("Regex")
+                    .content("Provide some text or reply to a message to test against")
+            })
+            .await;
+    }
+
+    let regex = match compile(pattern) {
+        Ok(regex) => regex,
+        Err(why) => {
+            return msg
+                .channel_id
+                .send_tmp(ctx, |m: &mut MessageCreator| {
+                    m.error().title("Regex").content(why.render(pattern))
+                })
+                .await;
+        },
+    };
+
+    let (matches, total) = find_matches(&regex, &text, MATCH_LIMIT);
+    if total == 0 {
+        return msg
+            .channel_id
+            .send_tmp(ctx, |m: &mut MessageCreator| {
+                m.warning()
+                    .title("Regex")
+                    .content(format!("`{}` didn't match anything", pattern))
+            })
+            .await;
+    }
+
+    let mut content = format!("**Pattern**\n`{}`\n**Matches**\n{}", pattern, total);
+    if text.chars().count() <= HIGHLIGHT_LIMIT {
+        content.push_str(&format!("\n\n{}", highlight(&regex, &text)));
+    }
+
+    for (idx, found) in matches.iter().enumerate() {
+        content.push_str(&format!(
+            "\n\n**Match {}** ({}..{})\n`{}`",
+            idx + 1,
+            found.start,
+            found.end,
+            found.text
+        ));
+
+        for (name, value) in &found.groups {
+            match value {
+                Some(value) => content.push_str(&format!("\n`{}`: `{}`", name, value)),
+                None => content.push_str(&format!("\n`{}`: no match", name)),
+            }
+        }
+    }
+
+    if total > matches.len() {
+        content.push_str(&format!("\n\n...and {} more", total - matches.len()));
+    }
+
+    msg.channel_id
+        .send_tmp(ctx, |m: &mut MessageCreator| m.success().title("Regex").content(content))
+        .await
+}
@@ -0,0 +1,130 @@
+use serenity::{
+    framework::standard::{macros::command, Args, CommandResult},
+    model::prelude::Message,
+    prelude::Context,
+};
+
+use crate::{
+    utils::{
+        chat::parse_code_block,
+        discord::download_attachment,
+        rust_eval::{evaluate, Evaluation, Stage},
+    },
+    InoriChannelUtils, InoriMessageUtils, MessageCreator, Settings,
+};
+
+fn code_block(text: &str) -> String {
+    format!("```\n{}\n```", text.trim_end().replace("```", "`\u{200b}``"))
+}
+
+fn render_evaluation(evaluation: &Evaluation) -> String {
+    let mut content = String::new();
+
+    if evaluation.stage == Stage::Compile {
+        content.push_str(&format!("**Compiler Errors**\n{}\n", code_block(&evaluation.stderr)));
+    } else {
+        if !evaluation.stdout.trim().is_empty() {
+            content.push_str(&format!("**Output**\n{}\n", code_block(&evaluation.stdout)));
+        }
+
+        if !evaluation.stderr.trim().is_empty() {
+            content.push_str(&format!("**Errors**\n{}\n", code_block(&evaluation.stderr)));
+        }
+
+        if content.is_empty() {
+            content.push_str("**Output**\nNone\n");
+        }
+    }
+
+    // The status of a timed out run already says how long it ran for
+    if evaluation.timed_out {
+        content.push_str(&format!("**Result**\n{}", evaluation.status));
+    } else {
+        content.push_str(&format!(
+            "**Result**\n{} in {}ms",
+            evaluation.status,
+            evaluation.elapsed.as_millis()
+        ));
+    }
+
+    if evaluation.truncated {
+        content.push_str(", the output was cut short");
+    }
+
+    content
+}
+
+#[command("eval")]
+#[aliases("run")]
+#[description(
+    "Compile and run Rust code with the local toolchain, code without a `main` function is used as its body. The \
+     program can't see the bot's files or the network and is stopped when it runs for too long, uses too much memory \
+     or prints too much. This is disabled by default, set `enabled = true` under `[rust_eval]` in the config to use \
+     it"
+)]
+#[usage("<code>")]
+#[example("println!(\"{}\", 1 + 1);")]
+#[example("```rust\nfn main() {\n    println!(\"Hello, World!\");\n}\n```")]
+async fn rust_eval(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    let config = {
+        let data = ctx.data.read().await;
+        let settings = data.get::<Settings>().expect("Expected Setting in TypeMap.").lock().await;
+
+        settings.rust_eval.clone()
+    };
+
+    if !config.enabled {
+        return msg
+            .channel_id
+            .send_tmp(ctx, |m: &mut MessageCreator| {
+                m.error()
+                    .title("Rust Eval")
+                    .content("Rust eval is disabled, set `enabled = true` under `[rust_eval]` in the config to use it")
+            })
+            .await;
+    }
+
+    let code = match download_attachment(msg).await {
+        Some(Ok((_, data))) => String::from_utf8_lossy(&data).to_string(),
+        Some(Err(why)) => {
+            return msg
+                .channel_id
+                .send_tmp(ctx, |m: &mut MessageCreator| m.error().title("Rust Eval").content(why))
+                .await;
+        },
+        None => parse_code_block(args.rest()).1,
+    };
+
+    if code.trim().is_empty() {
+        return msg
+            .channel_id
+            .send_tmp(ctx, |m: &mut MessageCreator| {
+                m.error()
+                    .title("Rust Eval")
+                    .content("No code given, provide a code block or attachment")
+            })
+            .await;
+    }
+
+    let mut new_msg = msg.channel_id.send_loading(ctx, "Rust Eval", "Compiling").await?;
+    let evaluation = tokio::task::spawn_blocking(move || evaluate(&code, &config)).await?;
+
+    match evaluation {
+        Ok(evaluation) => {
+            new_msg
+                .update_tmp(ctx, |m: &mut MessageCreator| {
+                    if !evaluation.success {
+                        m.error();
+                    }
+
+                    m.title("Rust Eval").content(render_evaluation(&evaluation))
+                })
+                .await
+        },
+        Err(why) => {
+            new_msg
+                .update_tmp(ctx, |m: &mut MessageCreator| m.error().title("Rust Eval").content(why))
+                .await
+        },
+    }
+}
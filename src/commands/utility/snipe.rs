@@ -0,0 +1,150 @@
+use chrono::Utc;
+use serenity::{
+    framework::standard::{macros::command, Args, CommandResult},
+    model::prelude::Message,
+    prelude::Context,
+};
+
+use crate::{models::message_log::MessageLogContainer, parse_arg, InoriChannelUtils, MessageCreator, Settings};
+
+async fn logger_enabled(ctx: &Context) -> bool {
+    let data = ctx.data.read().await;
+    let settings = data.get::<Settings>().expect("Expected Setting in TypeMap.").lock().await;
+
+    settings.message_logger.enabled
+}
+
+// Empty field values are rejected by Discord
+fn or_empty(content: &str) -> &str {
+    if content.is_empty() {
+        "*Empty*"
+    } else {
+        content
+    }
+}
+
+fn format_ago(seconds: i64) -> String {
+    match seconds {
+        s if s < 60 => format!("{}s ago", s),
+        s if s < 3600 => format!("{}m ago", s / 60),
+        s => format!("{}h ago", s / 3600),
+    }
+}
+
+#[command]
+#[description("Show a recently deleted message in this channel, requires `messagelogger` to be enabled")]
+#[usage("[index]")]
+#[example("2")]
+#[max_args(1)]
+async fn snipe(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let index = if args.is_empty() {
+        0
+    } else {
+        parse_arg!(ctx, msg, args, "index", usize).saturating_sub(1)
+    };
+
+    if !logger_enabled(ctx).await {
+        return msg
+            .channel_id
+            .send_tmp(ctx, |m: &mut MessageCreator| {
+                m.error()
+                    .title("Snipe")
+                    .content("The message logger is disabled, enable it with `messagelogger enable`")
+            })
+            .await;
+    }
+
+    let deleted = {
+        let data = ctx.data.read().await;
+
+        match data.get::<MessageLogContainer>() {
+            Some(log) => log.lock().await.deleted(msg.channel_id.0, index).cloned(),
+            None => None,
+        }
+    };
+
+    let deleted = match deleted {
+        Some(deleted) => deleted,
+        None => {
+            return msg
+                .channel_id
+                .send_tmp(ctx, |m: &mut MessageCreator| {
+                    m.error().title("Snipe").content("Nothing to snipe")
+                })
+                .await;
+        },
+    };
+
+    let ago = format_ago((Utc::now() - deleted.deleted_at).num_seconds());
+    msg.channel_id
+        .send_tmp(ctx, |m: &mut MessageCreator| {
+            m.title(format!("Snipe - {}", deleted.message.author))
+                .thumbnail(&deleted.message.avatar)
+                .content(&deleted.message.content)
+                .footer_text(format!("Deleted {}", ago));
+
+            if !deleted.message.attachments.is_empty() {
+                m.field("Attachments", deleted.message.attachments.join("\n"), false);
+            }
+
+            m
+        })
+        .await
+}
+
+#[command]
+#[aliases("esnipe")]
+#[description("Show a recently edited message in this channel, requires `messagelogger` to be enabled")]
+#[usage("[index]")]
+#[example("2")]
+#[max_args(1)]
+async fn editsnipe(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let index = if args.is_empty() {
+        0
+    } else {
+        parse_arg!(ctx, msg, args, "index", usize).saturating_sub(1)
+    };
+
+    if !logger_enabled(ctx).await {
+        return msg
+            .channel_id
+            .send_tmp(ctx, |m: &mut MessageCreator| {
+                m.error()
+                    .title("Edit Snipe")
+                    .content("The message logger is disabled, enable it with `messagelogger enable`")
+            })
+            .await;
+    }
+
+    let edited = {
+        let data = ctx.data.read().await;
+
+        match data.get::<MessageLogContainer>() {
+            Some(log) => log.lock().await.edited(msg.channel_id.0, index).cloned(),
+            None => None,
+        }
+    };
+
+    let edited = match edited {
+        Some(edited) => edited,
+        None => {
+            return msg
+                .channel_id
+                .send_tmp(ctx, |m: &mut MessageCreator| {
+                    m.error().title("Edit Snipe").content("Nothing to snipe")
+                })
+                .await;
+        },
+    };
+
+    let ago = format_ago((Utc::now() - edited.edited_at).num_seconds());
+    msg.channel_id
+        .send_tmp(ctx, |m: &mut MessageCreator| {
+            m.title(format!("Edit Snipe - {}", edited.message.author))
+                .thumbnail(&edited.message.avatar)
+                .field("Before", or_empty(&edited.before), false)
+                .field("After", or_empty(&edited.message.content), false)
+                .footer_text(format!("Edited {}", ago))
+        })
+        .await
+}
@@ -13,8 +13,10 @@ use serenity::{
 };
 
 use crate::{
+    events::highlight::handle_highlight,
     inori_error, inori_info, inori_success,
-    models::{commands::CommandCounter, settings::Settings},
+    models::{commands::CommandCounter, paginator::handle_paginator_input, settings::Settings},
+    utils::chat::expand_snippets,
 };
 
 #[hook]
@@ -60,6 +62,35 @@ static SLOTBOT_PREFIX_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r"`.*grab`").
 
 #[hook]
 pub async fn normal_message(ctx: &Context, msg: &Message) {
+    let is_own = msg.author.id == ctx.cache.current_user_id().await;
+
+    if is_own && handle_paginator_input(ctx, msg).await {
+        return;
+    }
+
+    let snippets = {
+        let data = ctx.data.read().await;
+        let settings = data.get::<Settings>().expect("Expected Settings in TypeMap.").lock().await;
+
+        if settings.snippets.enabled {
+            Some((settings.snippets.clone(), settings.tags.clone()))
+        } else {
+            None
+        }
+    };
+
+    if let Some((config, tags)) = snippets {
+        if is_own {
+            if let Some(content) = expand_snippets(&msg.content, &config.prefix, &config.suffix, &tags) {
+                let mut msg = msg.clone();
+
+                if let Err(why) = msg.edit(ctx, |m| m.content(content)).await {
+                    inori_error!("Snippets", "Unable to expand snippets: {}", why);
+                }
+            }
+        }
+    }
+
     let nitro_enabled = {
         let data = ctx.data.read().await;
         let settings = data.get::<Settings>().expect("Expected Settings in TypeMap.").lock().await;
@@ -137,6 +168,8 @@ pub async fn normal_message(ctx: &Context, msg: &Message) {
         }
     }
 
+    handle_highlight(ctx, msg, is_own).await;
+
     if msg.author.id.0 == 346353957029019648
         && msg
             .content
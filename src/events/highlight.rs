@@ -0,0 +1,135 @@
+use std::{sync::Arc, time::Duration};
+
+use colored::Colorize;
+use serenity::{
+    model::{channel::Message, id::ChannelId},
+    prelude::Context,
+};
+
+use crate::{
+    inori_error, inori_info,
+    models::{highlight::HighlightState, settings::Settings},
+    utils::chat::truncate,
+    InoriChannelUtils, MessageCreator,
+};
+
+// Discord returns at most 100 messages per request
+const MAX_CONTEXT: u64 = 100;
+
+// Messages are shown on one line each
+fn preview(content: &str) -> String {
+    truncate(&content.replace('\n', " "), 200)
+}
+
+pub async fn handle_highlight(ctx: &Context, msg: &Message, is_own: bool) {
+    // DMs already notify so only guilds are checked
+    let guild_id = match msg.guild_id {
+        Some(guild_id) => guild_id,
+        None => return,
+    };
+
+    let (config, state) = {
+        let data = ctx.data.read().await;
+        let settings = data.get::<Settings>().expect("Expected Setting in TypeMap.").lock().await;
+
+        if !settings.highlight.enabled {
+            return;
+        }
+
+        let state = data.get::<HighlightState>().expect("Expected HighlightState in TypeMap.");
+
+        (settings.highlight.clone(), Arc::clone(state))
+    };
+
+    let user_id = ctx.cache.current_user_id().await;
+    let keyword = {
+        let mut state = state.lock().await;
+
+        // We're already reading the channel if we're talking in it
+        if is_own {
+            state.start_cooldown(msg.channel_id.0);
+
+            return;
+        }
+
+        if state.on_cooldown(msg.channel_id.0, Duration::from_secs(config.cooldown)) {
+            return;
+        }
+
+        let keyword = if config.mentions && msg.mentions.iter().any(|user| user.id == user_id) {
+            Some("Mention".to_string())
+        } else {
+            let keywords = config
+                .keywords
+                .iter()
+                .chain(config.guilds.get(&guild_id.0.to_string()).into_iter().flatten());
+
+            state.find_match(&msg.content, keywords)
+        };
+
+        if keyword.is_some() {
+            state.start_cooldown(msg.channel_id.0);
+        }
+
+        keyword
+    };
+
+    let keyword = match keyword {
+        Some(keyword) => keyword,
+        None => return,
+    };
+
+    let guild_name = guild_id.name(&ctx.cache).await.unwrap_or_else(|| "Unknown".to_string());
+    let channel_name = msg
+        .channel_id
+        .name(&ctx.cache)
+        .await
+        .unwrap_or_else(|| "Unknown".to_string());
+
+    if config.channel == 0 {
+        inori_info!(
+            "Highlight",
+            "'{}' in [{} > {}] from {}: {}",
+            keyword,
+            guild_name,
+            channel_name,
+            msg.author.tag(),
+            preview(&msg.content)
+        );
+
+        return;
+    }
+
+    let mut context = if config.context > 0 {
+        msg.channel_id
+            .messages(&ctx.http, |r| r.before(msg.id).limit(config.context.min(MAX_CONTEXT)))
+            .await
+            .unwrap_or_default()
+    } else {
+        Vec::new()
+    };
+    context.reverse();
+    context.push(msg.clone());
+
+    let context = context
+        .iter()
+        .map(|message| format!("**{}**: {}", message.author.name, preview(&message.content)))
+        .collect::<Vec<String>>()
+        .join("\n");
+
+    let content = format!(
+        "**Keyword**: {}\n**Where**: {} > #{}\n**Jump**: {}\n\n{}",
+        keyword,
+        guild_name,
+        channel_name,
+        msg.link(),
+        context
+    );
+
+    if let Err(why) = ChannelId(config.channel)
+        .send_noret(ctx, |m: &mut MessageCreator| m.info().title("Highlight").content(content))
+        .await
+    {
+        inori_error!("Highlight", "Unable to forward highlight: {}", why);
+    }
+}
@@ -0,0 +1,81 @@
+use std::sync::Arc;
+
+use serenity::{
+    model::{channel::Message, event::MessageUpdateEvent, id::MessageId},
+    prelude::{Context, TypeMap},
+};
+use tokio::sync::Mutex;
+
+use crate::models::{
+    message_log::{LoggedMessage, MessageLog, MessageLogContainer},
+    settings::Settings,
+};
+
+fn log_container(data: &TypeMap) -> Arc<Mutex<MessageLog>> {
+    let log = data.get::<MessageLogContainer>().expect("Expected MessageLogContainer in TypeMap.");
+
+    Arc::clone(log)
+}
+
+pub async fn log_message(ctx: &Context, msg: &Message) {
+    if msg.author.id == ctx.cache.current_user_id().await {
+        return;
+    }
+
+    let (log, max_messages, max_age) = {
+        let data = ctx.data.read().await;
+        let settings = data.get::<Settings>().expect("Expected Setting in TypeMap.").lock().await;
+
+        if !settings.message_logger.is_logged(msg.guild_id.map(|guild_id| guild_id.0)) {
+            return;
+        }
+
+        (
+            log_container(&data),
+            settings.message_logger.max_messages,
+            settings.message_logger.max_age(),
+        )
+    };
+
+    log.lock().await.insert(LoggedMessage::from(msg), max_messages, max_age);
+}
+
+pub async fn log_update(ctx: &Context, event: &MessageUpdateEvent) {
+    let content = match &event.content {
+        Some(content) => content.clone(),
+        None => return,
+    };
+
+    let log = {
+        let data = ctx.data.read().await;
+        let settings = data.get::<Settings>().expect("Expected Setting in TypeMap.").lock().await;
+
+        if !settings.message_logger.is_logged(event.guild_id.map(|guild_id| guild_id.0)) {
+            return;
+        }
+
+        log_container(&data)
+    };
+
+    log.lock().await.edit(event.id.0, content);
+}
+
+pub async fn log_delete(ctx: &Context, ids: &[MessageId]) {
+    // Deletes don't say which guild they're from, only logged messages are
+    // touched though
+    let log = {
+        let data = ctx.data.read().await;
+        let settings = data.get::<Settings>().expect("Expected Setting in TypeMap.").lock().await;
+
+        if !settings.message_logger.enabled {
+            return;
+        }
+
+        log_container(&data)
+    };
+
+    let mut log = log.lock().await;
+    for id in ids {
+        log.delete(id.0);
+    }
+}
@@ -1,18 +1,34 @@
 pub mod chat;
 pub mod error;
 pub mod help;
+mod highlight;
+mod logger;
 
 use std::{fs::DirEntry, io::Error, path::Path, sync::Arc};
 
 use colored::Colorize;
 use rand::Rng;
-use serenity::{async_trait, model::gateway::Ready, prelude::*, utils::read_image};
+use serenity::{
+    async_trait,
+    model::{
+        channel::Message,
+        event::MessageUpdateEvent,
+        gateway::Ready,
+        id::{ChannelId, MessageId},
+    },
+    prelude::*,
+    utils::read_image,
+};
 use tokio::{
     task,
     time::{delay_for, Duration},
 };
 
-use crate::{inori_info, inori_success, models::settings::Settings, utils::consts};
+use crate::{
+    inori_info, inori_success,
+    models::{purge::resume_purge_job, settings::Settings, watchlist::start_watchlist_checker},
+    utils::consts,
+};
 
 pub struct Handler;
 
@@ -27,8 +43,33 @@ impl EventHandler for Handler {
             ready.user.discriminator,
         );
 
+        resume_purge_job(&ctx).await;
+        start_watchlist_checker(&ctx);
+
         spawn_pfp_change_thread(Arc::new(Mutex::new(ctx))).await;
     }
+
+    async fn message(&self, ctx: Context, msg: Message) {
+        logger::log_message(&ctx, &msg).await;
+    }
+
+    async fn message_update(
+        &self,
+        ctx: Context,
+        _old_if_available: Option<Message>,
+        _new: Option<Message>,
+        event: MessageUpdateEvent,
+    ) {
+        logger::log_update(&ctx, &event).await;
+    }
+
+    async fn message_delete(&self, ctx: Context, _channel_id: ChannelId, deleted_message_id: MessageId) {
+        logger::log_delete(&ctx, &[deleted_message_id]).await;
+    }
+
+    async fn message_delete_bulk(&self, ctx: Context, _channel_id: ChannelId, deleted_message_ids: Vec<MessageId>) {
+        logger::log_delete(&ctx, &deleted_message_ids).await;
+    }
 }
 
 async fn spawn_pfp_change_thread(ctx: Arc<Mutex<Context>>) {
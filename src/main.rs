@@ -24,9 +24,14 @@ use crate::{
         Handler,
     },
     models::{
-        commands::{CommandCounter, ShardManagerContainer},
+        commands::{CalcSessions, CommandCounter, PermissionCache, ShardManagerContainer},
         discord::{InoriChannelUtils, InoriMessageUtils, MessageCreator},
+        highlight::HighlightState,
+        message_log::MessageLogContainer,
+        paginator::PaginatorInputs,
+        purge::PurgeJobControl,
         settings::Settings,
+        watchlist::Watchlist,
     },
     settings::{load_settings, save_settings, setup_settings},
     utils::{
@@ -114,9 +119,16 @@ async fn main() {
 
     {
         let mut data = client.data.write().await;
+        data.insert::<CalcSessions>(Default::default());
         data.insert::<CommandCounter>(HashMap::default());
+        data.insert::<PermissionCache>(Default::default());
+        data.insert::<HighlightState>(Default::default());
+        data.insert::<MessageLogContainer>(Default::default());
+        data.insert::<PaginatorInputs>(HashMap::default());
+        data.insert::<PurgeJobControl>(None);
         data.insert::<Settings>(Arc::new(Mutex::new(settings)));
         data.insert::<ShardManagerContainer>(Arc::clone(&client.shard_manager));
+        data.insert::<Watchlist>(Arc::new(Mutex::new(Watchlist::load())));
     }
 
     inori_info!("Bot", "Loaded client");
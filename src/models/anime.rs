@@ -0,0 +1,276 @@
+use async_trait::async_trait;
+use colored::Colorize;
+use serde::{Deserialize, Serialize};
+use serenity::prelude::Context;
+
+use crate::{
+    inori_warn,
+    models::settings::{AnimeConfig, Settings},
+    utils::{anilist::AniList, jikan::Jikan},
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MediaKind {
+    Anime,
+    Manga,
+}
+
+impl Default for MediaKind {
+    fn default() -> MediaKind {
+        MediaKind::Anime
+    }
+}
+
+impl MediaKind {
+    pub fn name(self) -> &'static str {
+        match self {
+            MediaKind::Anime => "anime",
+            MediaKind::Manga => "manga",
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct Link {
+    pub name: String,
+    pub url:  String,
+}
+
+impl Link {
+    pub fn new<D: ToString, U: ToString>(name: D, url: U) -> Link {
+        Link {
+            name: name.to_string(),
+            url:  url.to_string(),
+        }
+    }
+
+    pub fn markdown(&self) -> String {
+        format!("[{}]({})", self.name, self.url)
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct Relation {
+    pub relation: String,
+    pub entries:  Vec<Link>,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct CharacterRole {
+    pub character: Link,
+    pub role:      String,
+    pub actor:     Option<Link>,
+}
+
+// Anime and manga share most of their fields, anything that doesn't apply
+// is left as None
+#[derive(Clone, Debug, Default)]
+pub struct Media {
+    pub source:     &'static str,
+    pub kind:       MediaKind,
+    pub mal_id:     Option<u64>,
+    pub url:        String,
+    pub image:      Option<String>,
+    pub title:      String,
+    pub title_alt:  Option<String>,
+    pub format:     Option<String>,
+    pub status:     Option<String>,
+    pub dates:      Option<String>,
+    pub episodes:   Option<u64>,
+    pub volumes:    Option<u64>,
+    pub chapters:   Option<u64>,
+    pub season:     Option<String>,
+    pub rating:     Option<String>,
+    // Out of 10 regardless of provider
+    pub score:      Option<f64>,
+    pub rank:       Option<u64>,
+    pub members:    Option<u64>,
+    pub synopsis:   Option<String>,
+    pub genres:     Vec<Link>,
+    pub themes:     Vec<Link>,
+    // Studios for anime, authors for manga
+    pub creators:   Vec<Link>,
+    pub publishers: Vec<Link>,
+    // Only filled in for detail lookups
+    pub streaming:  Vec<Link>,
+    pub relations:  Vec<Relation>,
+    pub characters: Vec<CharacterRole>,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct Appearance {
+    pub media: Link,
+    pub role:  String,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct Character {
+    pub source:      &'static str,
+    pub id:          u64,
+    pub url:         String,
+    pub image:       Option<String>,
+    pub name:        String,
+    pub name_native: Option<String>,
+    pub nicknames:   Vec<String>,
+    pub favorites:   Option<u64>,
+    pub about:       Option<String>,
+    // Only filled in for detail lookups
+    pub anime:       Vec<Appearance>,
+    pub manga:       Vec<Appearance>,
+    pub actors:      Vec<Appearance>,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct VoiceRole {
+    pub character: Link,
+    pub media:     Link,
+    pub role:      String,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct Person {
+    pub source:          &'static str,
+    pub id:              u64,
+    pub url:             String,
+    pub image:           Option<String>,
+    pub name:            String,
+    pub alternate_names: Vec<String>,
+    pub birthday:        Option<String>,
+    pub favorites:       Option<u64>,
+    pub about:           Option<String>,
+    // Only filled in for detail lookups
+    pub roles:           Vec<VoiceRole>,
+}
+
+// Anime and manga are always looked up by their MAL ID so lookups work the
+// same no matter which provider answers. Characters and people don't have a
+// shared ID so those are specific to the provider which returned them
+#[async_trait]
+pub trait AnimeProvider: Send + Sync {
+    fn name(&self) -> &'static str;
+
+    async fn search_media(&self, kind: MediaKind, query: &str) -> Result<Vec<Media>, String>;
+
+    async fn media(&self, kind: MediaKind, mal_id: u64) -> Result<Media, String>;
+
+    async fn search_characters(&self, query: &str) -> Result<Vec<Character>, String>;
+
+    async fn character(&self, id: u64) -> Result<Character, String>;
+
+    async fn search_people(&self, query: &str) -> Result<Vec<Person>, String>;
+
+    async fn person(&self, id: u64) -> Result<Person, String>;
+}
+
+pub fn provider_by_name(name: &str) -> Option<Box<dyn AnimeProvider>> {
+    match name.to_lowercase().as_str() {
+        "jikan" | "mal" | "myanimelist" => Some(Box::new(Jikan)),
+        "anilist" => Some(Box::new(AniList)),
+        _ => None,
+    }
+}
+
+pub static PROVIDERS: [&str; 2] = ["jikan", "anilist"];
+
+// Tries each provider in order until one succeeds, an empty result still
+// counts as a success
+pub struct FallbackProvider {
+    providers: Vec<Box<dyn AnimeProvider>>,
+}
+
+impl FallbackProvider {
+    pub fn new(config: &AnimeConfig) -> FallbackProvider {
+        let primary = provider_by_name(&config.provider).unwrap_or_else(|| Box::new(Jikan));
+        let mut providers = vec![primary];
+
+        if config.fallback {
+            for name in PROVIDERS.iter() {
+                if *name != providers[0].name() {
+                    providers.extend(provider_by_name(name));
+                }
+            }
+        }
+
+        FallbackProvider {
+            providers,
+        }
+    }
+
+    pub async fn from_ctx(ctx: &Context) -> FallbackProvider {
+        let data = ctx.data.read().await;
+        let settings = data.get::<Settings>().expect("Expected Setting in TypeMap.").lock().await;
+
+        FallbackProvider::new(&settings.anime)
+    }
+
+    pub fn primary(&self) -> &dyn AnimeProvider {
+        self.providers[0].as_ref()
+    }
+
+    // Character and person IDs are only valid for the provider which returned
+    // them, `anilist:123` is looked up on AniList while a bare ID goes to the
+    // primary provider
+    pub fn parse_id(&self, query: &str) -> Option<(Box<dyn AnimeProvider>, u64)> {
+        let (name, id) = query.split_once(':').unwrap_or((self.primary().name(), query));
+        let id = id.trim().parse::<u64>().ok()?;
+
+        Some((provider_by_name(name.trim())?, id))
+    }
+}
+
+// The ID to show for a character or person, what `parse_id` expects
+pub fn lookup_id(source: &str, id: u64) -> String {
+    format!("{}:{}", source.to_lowercase(), id)
+}
+
+macro_rules! try_providers {
+    ($providers:expr, $provider:ident => $call:expr) => {{
+        let mut error = None;
+
+        for $provider in $providers.iter() {
+            match $call.await {
+                Ok(res) => return Ok(res),
+                Err(why) => {
+                    inori_warn!("Anime", "{} lookup failed: {}", $provider.name(), why);
+                    error.get_or_insert(why);
+                },
+            }
+        }
+
+        Err(error.unwrap_or_else(|| "No anime providers are available".to_string()))
+    }};
+}
+
+#[async_trait]
+impl AnimeProvider for FallbackProvider {
+    fn name(&self) -> &'static str {
+        self.primary().name()
+    }
+
+    async fn search_media(&self, kind: MediaKind, query: &str) -> Result<Vec<Media>, String> {
+        try_providers!(self.providers, provider => provider.search_media(kind, query))
+    }
+
+    async fn media(&self, kind: MediaKind, mal_id: u64) -> Result<Media, String> {
+        try_providers!(self.providers, provider => provider.media(kind, mal_id))
+    }
+
+    async fn search_characters(&self, query: &str) -> Result<Vec<Character>, String> {
+        try_providers!(self.providers, provider => provider.search_characters(query))
+    }
+
+    // IDs from one provider mean nothing to another so there's no fallback,
+    // use `parse_id` to find the provider an ID belongs to
+    async fn character(&self, id: u64) -> Result<Character, String> {
+        self.primary().character(id).await
+    }
+
+    async fn search_people(&self, query: &str) -> Result<Vec<Person>, String> {
+        try_providers!(self.providers, provider => provider.search_people(query))
+    }
+
+    async fn person(&self, id: u64) -> Result<Person, String> {
+        self.primary().person(id).await
+    }
+}
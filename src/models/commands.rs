@@ -1,8 +1,10 @@
-use std::{collections::HashMap, sync::Arc};
+use std::{collections::HashMap, sync::Arc, time::Instant};
 
-use serde::{Deserialize, Deserializer};
-use serenity::{client::bridge::gateway::ShardManager, prelude::TypeMapKey};
-use tokio::sync::Mutex;
+use serde::Deserialize;
+use serenity::{client::bridge::gateway::ShardManager, model::Permissions, prelude::TypeMapKey};
+use tokio::sync::{Mutex, RwLock};
+
+use crate::utils::calc::Session;
 
 #[derive(Debug, Deserialize)]
 pub struct NekosLifeResponse {
@@ -55,100 +57,260 @@ impl TypeMapKey for CommandCounter {
     type Value = HashMap<String, u64>;
 }
 
+// Calculator variables and functions, keyed by channel ID. Kept behind its
+// own lock so evaluating doesn't hold up the rest of the TypeMap
+pub struct CalcSessions;
+
+impl TypeMapKey for CalcSessions {
+    type Value = Arc<Mutex<HashMap<u64, Session>>>;
+}
+
+#[derive(Clone, Debug)]
+pub struct CachedPermissions {
+    pub guild_id: Option<u64>,
+    pub perms:    Permissions,
+    pub fetched:  Instant,
+}
+
+// Read on every send so it has its own lock rather than holding the TypeMap
+pub struct PermissionCache;
+
+impl TypeMapKey for PermissionCache {
+    type Value = Arc<RwLock<HashMap<u64, CachedPermissions>>>;
+}
+
 #[derive(Clone, Debug, Deserialize)]
 pub struct FrankFurterResponse {
-    pub amount: f64,
-    pub base:   String,
-    pub date:   String,
-    pub rates:  HashMap<String, f64>,
+    pub base:  String,
+    pub date:  String,
+    pub rates: HashMap<String, f64>,
 }
 
-#[derive(Clone, Debug, Deserialize)]
-pub struct MALMangaSearchResult {
-    pub mal_id:     u64,
-    pub url:        String,
-    pub image_url:  String,
-    pub title:      String,
-    pub publishing: bool,
-    pub synopsis:   String,
-    pub chapters:   u64,
-    pub volumes:    u64,
-    pub score:      f64,
-    #[serde(deserialize_with = "parse_start_date")]
-    pub start_date: String,
-    #[serde(deserialize_with = "parse_end_date")]
-    pub end_date:   String,
-    pub members:    u64,
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct MALImage {
+    pub image_url:       Option<String>,
+    pub large_image_url: Option<String>,
 }
 
-#[derive(Clone, Debug, Deserialize)]
-pub struct MALAnimeSearchResult {
-    pub mal_id:     u64,
-    pub url:        String,
-    pub image_url:  String,
-    pub title:      String,
-    pub airing:     bool,
-    pub synopsis:   String,
-    pub episodes:   u64,
-    pub score:      f64,
-    #[serde(deserialize_with = "parse_start_date")]
-    pub start_date: String,
-    #[serde(deserialize_with = "parse_end_date")]
-    pub end_date:   String,
-    pub members:    u64,
-    #[serde(deserialize_with = "parse_rated")]
-    pub rated:      String,
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct MALImages {
+    pub jpg: MALImage,
 }
 
-fn parse_start_date<'de, D>(d: D) -> Result<String, D::Error>
-where
-    D: Deserializer<'de>, {
-    Deserialize::deserialize(d).map(|x: Option<_>| x.unwrap_or_else(|| "NYA".to_string()))
+impl MALImages {
+    pub fn url(&self) -> Option<&String> {
+        self.jpg.large_image_url.as_ref().or_else(|| self.jpg.image_url.as_ref())
+    }
 }
 
-fn parse_end_date<'de, D>(d: D) -> Result<String, D::Error>
-where
-    D: Deserializer<'de>, {
-    Deserialize::deserialize(d).map(|x: Option<_>| x.unwrap_or_else(|| "TBD".to_string()))
+// Genres, studios, authors and relation entries all share this shape
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct MALEntry {
+    pub mal_id: u64,
+    #[serde(rename = "type")]
+    pub kind:   String,
+    pub name:   String,
+    pub url:    String,
 }
 
-fn parse_rated<'de, D>(d: D) -> Result<String, D::Error>
-where
-    D: Deserializer<'de>, {
-    Deserialize::deserialize(d).map(|x: Option<_>| x.unwrap_or_else(|| "Unrated".to_string()))
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct MALRelation {
+    pub relation: String,
+    pub entry:    Vec<MALEntry>,
 }
 
-#[derive(Clone, Debug, Deserialize)]
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct MALLink {
+    pub name: String,
+    pub url:  String,
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct MALDateRange {
+    pub string: Option<String>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct MALAnime {
+    pub mal_id:        u64,
+    pub url:           String,
+    pub images:        MALImages,
+    pub title:         String,
+    pub title_english: Option<String>,
+    #[serde(rename = "type")]
+    pub kind:          Option<String>,
+    pub episodes:      Option<u64>,
+    pub status:        Option<String>,
+    pub aired:         MALDateRange,
+    pub rating:        Option<String>,
+    pub score:         Option<f64>,
+    pub rank:          Option<u64>,
+    pub members:       Option<u64>,
+    pub synopsis:      Option<String>,
+    pub season:        Option<String>,
+    pub year:          Option<u64>,
+    pub genres:        Vec<MALEntry>,
+    pub themes:        Vec<MALEntry>,
+    pub studios:       Vec<MALEntry>,
+    // Only included by the /full endpoint
+    pub relations:     Vec<MALRelation>,
+    pub streaming:     Vec<MALLink>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct MALManga {
+    pub mal_id:         u64,
+    pub url:            String,
+    pub images:         MALImages,
+    pub title:          String,
+    pub title_english:  Option<String>,
+    #[serde(rename = "type")]
+    pub kind:           Option<String>,
+    pub chapters:       Option<u64>,
+    pub volumes:        Option<u64>,
+    pub status:         Option<String>,
+    pub published:      MALDateRange,
+    pub score:          Option<f64>,
+    pub rank:           Option<u64>,
+    pub members:        Option<u64>,
+    pub synopsis:       Option<String>,
+    pub genres:         Vec<MALEntry>,
+    pub themes:         Vec<MALEntry>,
+    pub authors:        Vec<MALEntry>,
+    pub serializations: Vec<MALEntry>,
+    // Only included by the /full endpoint
+    pub relations:      Vec<MALRelation>,
+}
+
+// Characters and people embedded in other responses, anime and manga use
+// `title` where everything else uses `name`
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default)]
 pub struct MALPreview {
     pub mal_id: u64,
-    pub name:   String,
     pub url:    String,
+    pub images: MALImages,
+    #[serde(alias = "title")]
+    pub name:   String,
 }
 
-#[derive(Clone, Debug, Deserialize)]
-pub struct MALCharacterSearchResult {
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct MALVoiceActor {
+    pub person:   MALPreview,
+    pub language: String,
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct MALCharacterRole {
+    pub character:    MALPreview,
+    pub role:         String,
+    pub voice_actors: Vec<MALVoiceActor>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct MALAppearance {
+    pub role:  String,
+    #[serde(alias = "manga")]
+    pub anime: MALPreview,
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct MALCharacter {
+    pub mal_id:     u64,
+    pub url:        String,
+    pub images:     MALImages,
+    pub name:       String,
+    pub name_kanji: Option<String>,
+    pub nicknames:  Vec<String>,
+    pub favorites:  Option<u64>,
+    pub about:      Option<String>,
+    // Only included by the /full endpoint
+    pub anime:      Vec<MALAppearance>,
+    pub manga:      Vec<MALAppearance>,
+    pub voices:     Vec<MALVoiceActor>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct MALVoiceRole {
+    pub role:      String,
+    pub anime:     MALPreview,
+    pub character: MALPreview,
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct MALPerson {
     pub mal_id:          u64,
     pub url:             String,
-    pub image_url:       String,
+    pub images:          MALImages,
     pub name:            String,
     pub alternate_names: Vec<String>,
-    pub anime:           Vec<MALPreview>,
-    pub manga:           Vec<MALPreview>,
+    pub birthday:        Option<String>,
+    pub favorites:       Option<u64>,
+    pub about:           Option<String>,
+    // Only included by the /full endpoint
+    pub voices:          Vec<MALVoiceRole>,
 }
 
 #[derive(Clone, Debug, Deserialize)]
-pub struct MALPersonSearchResult {
-    pub mal_id:          u64,
-    pub url:             String,
-    pub image_url:       String,
-    pub name:            String,
-    pub alternate_names: Vec<String>,
+pub struct MALResponse<T> {
+    pub data: T,
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct CratesIoCrate {
+    pub name:               String,
+    pub description:        Option<String>,
+    pub max_version:        String,
+    pub max_stable_version: Option<String>,
+    pub downloads:          u64,
+    pub recent_downloads:   Option<u64>,
+    pub repository:         Option<String>,
+    pub homepage:           Option<String>,
+    pub documentation:      Option<String>,
+    pub updated_at:         String,
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct CratesIoVersion {
+    pub num:      String,
+    pub license:  Option<String>,
+    pub yanked:   bool,
+    pub features: HashMap<String, Vec<String>>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct CratesIoResponse {
+    #[serde(rename = "crate")]
+    pub krate:    CratesIoCrate,
+    #[serde(default)]
+    pub versions: Vec<CratesIoVersion>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct CratesIoMeta {
+    pub total: u64,
 }
 
 #[derive(Clone, Debug, Deserialize)]
-pub struct MALSearchResponse<T> {
-    pub last_page: u64,
-    pub results:   Vec<T>,
+pub struct CratesIoReverseDependencies {
+    pub meta: CratesIoMeta,
 }
 
 #[derive(Clone, Debug, Deserialize)]
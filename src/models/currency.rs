@@ -0,0 +1,175 @@
+use std::collections::HashMap;
+
+use async_trait::async_trait;
+use colored::Colorize;
+
+use crate::{
+    inori_warn,
+    models::settings::CurrencyConfig,
+    utils::{coingecko::CoinGecko, frankfurter},
+};
+
+// Rates are stored as how much of each currency one unit of the base is
+// worth, so any two currencies in the table can be converted between
+#[derive(Clone, Debug, Default)]
+pub struct RateTable {
+    pub base:   String,
+    pub date:   String,
+    pub rates:  HashMap<String, f64>,
+    // Name of the provider crypto rates came from, if any were added
+    pub crypto: Option<&'static str>,
+}
+
+impl RateTable {
+    pub fn new(base: &str, date: &str, mut rates: HashMap<String, f64>) -> RateTable {
+        rates.insert(base.to_uppercase(), 1.0);
+
+        RateTable {
+            base: base.to_uppercase(),
+            date: date.to_string(),
+            rates,
+            crypto: None,
+        }
+    }
+
+    // Prices are what one coin costs in the base currency
+    pub fn add_prices(&mut self, provider: &'static str, prices: HashMap<String, f64>) {
+        for (code, price) in prices {
+            if price > 0.0 && !self.rates.contains_key(&code) {
+                self.rates.insert(code, 1.0 / price);
+            }
+        }
+
+        self.crypto = Some(provider);
+    }
+
+    pub fn has(&self, code: &str) -> bool {
+        self.rates.contains_key(code)
+    }
+
+    pub fn codes(&self) -> Vec<&str> {
+        let mut codes = self.rates.keys().map(String::as_str).collect::<Vec<&str>>();
+        codes.sort_unstable();
+
+        codes
+    }
+
+    pub fn convert(&self, amount: f64, from: &str, to: &str) -> Option<f64> {
+        let from = self.rates.get(from)?;
+        let to = self.rates.get(to)?;
+
+        Some(amount / from * to)
+    }
+}
+
+// Crypto amounts tend to be tiny so small values keep a few significant
+// digits instead of rounding to 0.00
+pub fn format_amount(amount: f64) -> String {
+    if amount == 0.0 || amount.abs() >= 1.0 {
+        return format!("{:.2}", amount);
+    }
+
+    let decimals = (-amount.abs().log10().floor() as usize + 3).min(12);
+    let formatted = format!("{:.*}", decimals, amount);
+
+    formatted.trim_end_matches('0').trim_end_matches('.').to_string()
+}
+
+// `USD,EUR JPY` -> ["USD", "EUR", "JPY"]
+pub fn parse_targets(input: &str) -> Vec<String> {
+    let mut targets = Vec::new();
+
+    for code in input.split(|c: char| c == ',' || c.is_whitespace()) {
+        let code = code.trim().to_uppercase();
+
+        if !code.is_empty() && !targets.contains(&code) {
+            targets.push(code);
+        }
+    }
+
+    targets
+}
+
+#[async_trait]
+pub trait CryptoProvider: Send + Sync {
+    fn name(&self) -> &'static str;
+
+    // Price of each supported coin in `base`, keyed by its ticker
+    async fn prices(&self, base: &str, ttl: u64) -> Result<HashMap<String, f64>, String>;
+}
+
+pub fn crypto_provider_by_name(name: &str) -> Option<Box<dyn CryptoProvider>> {
+    match name.to_lowercase().as_str() {
+        "coingecko" => Some(Box::new(CoinGecko)),
+        _ => None,
+    }
+}
+
+pub static CRYPTO_PROVIDERS: [&str; 1] = ["coingecko"];
+
+// Fiat rates are required, crypto rates are added on top when a provider is
+// set and reachable. Both fall back to the last cached response if the API
+// can't be reached
+pub async fn load_rates(config: &CurrencyConfig) -> Result<RateTable, String> {
+    let ttl = config.ttl.saturating_mul(60);
+    let mut table = frankfurter::latest(ttl).await?;
+
+    if let Some(provider) = crypto_provider_by_name(&config.crypto) {
+        match provider.prices(&table.base, ttl).await {
+            Ok(prices) => table.add_prices(provider.name(), prices),
+            Err(why) => inori_warn!("Currency", "Unable to get crypto rates from {}: {}", provider.name(), why),
+        }
+    }
+
+    Ok(table)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn table() -> RateTable {
+        let mut rates = HashMap::new();
+        rates.insert("USD".to_string(), 1.1);
+        rates.insert("JPY".to_string(), 160.0);
+        rates.insert("NZD".to_string(), 1.8);
+
+        let mut table = RateTable::new("eur", "2021-01-01", rates);
+        let mut prices = HashMap::new();
+        prices.insert("BTC".to_string(), 40_000.0);
+        prices.insert("USD".to_string(), 2.0);
+        table.add_prices("test", prices);
+
+        table
+    }
+
+    #[test]
+    fn test_convert() {
+        let table = table();
+
+        assert!(table.has("EUR"));
+        assert_eq!(table.codes(), vec!["BTC", "EUR", "JPY", "NZD", "USD"]);
+        assert_eq!(table.convert(2.0, "EUR", "EUR"), Some(2.0));
+        assert!((table.convert(18.0, "NZD", "USD").unwrap() - 11.0).abs() < 1e-9);
+        assert!((table.convert(1.0, "BTC", "USD").unwrap() - 44_000.0).abs() < 1e-6);
+        // Crypto prices never replace fiat rates
+        assert!((table.convert(1.0, "EUR", "USD").unwrap() - 1.1).abs() < 1e-9);
+        assert_eq!(table.convert(1.0, "EUR", "XYZ"), None);
+    }
+
+    #[test]
+    fn test_format_amount() {
+        assert_eq!(format_amount(12.346), "12.35");
+        assert_eq!(format_amount(0.0), "0.00");
+        assert_eq!(format_amount(0.5), "0.5");
+        assert_eq!(format_amount(0.000_025_123), "0.00002512");
+        assert_eq!(format_amount(-0.25), "-0.25");
+    }
+
+    #[test]
+    fn test_parse_targets() {
+        assert_eq!(parse_targets("usd,EUR, jpy"), vec!["USD", "EUR", "JPY"]);
+        assert_eq!(parse_targets("USD USD"), vec!["USD"]);
+        assert!(parse_targets(" , ").is_empty());
+    }
+}
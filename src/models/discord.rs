@@ -1,4 +1,4 @@
-use std::collections::HashMap;
+use std::{cmp::min, collections::HashMap};
 
 use async_trait::async_trait;
 use chrono::prelude::{DateTime, Utc};
@@ -15,7 +15,11 @@ use serenity_utils::menu::{Menu, MenuOptions};
 use tokio::time::{delay_for, Duration};
 
 use crate::{
-    utils::discord::{get_permissions, DM_PERMISSIONS},
+    models::{paginator::Paginator, settings::Theme},
+    utils::{
+        chat::split_message,
+        discord::get_cached_permissions,
+    },
     Settings,
 };
 
@@ -104,6 +108,16 @@ impl Clone for Emote {
     }
 }
 
+const MESSAGE_LIMIT: usize = 2000;
+const EMBED_DESCRIPTION_LIMIT: usize = 2048;
+const EMBED_FIELD_LIMIT: usize = 1024;
+const EMBED_FIELD_COUNT_LIMIT: usize = 25;
+// Room for the mode emote or prefix and a paginator footer
+const SPLIT_RESERVE: usize = 64;
+// Characters used by a field title in text mode, `\n_[_****_]_\n`
+const FIELD_OVERHEAD: usize = 12;
+const MIN_PAGE_BUDGET: usize = 200;
+
 #[derive(Debug, Clone)]
 pub struct MessageField {
     title:   String,
@@ -153,66 +167,77 @@ impl<'a> Default for MessageCreator<'a> {
 }
 
 impl<'a> MessageCreator<'a> {
-    pub fn to_message(&self, emotes: HashMap<String, u64>) -> CreateMessage {
+    fn mode_prefix(&self, options: &RenderOptions) -> String {
+        let theme_mode = options.theme.mode(self.mode);
+
+        if !theme_mode.emoji.is_empty() {
+            return format!("{} ", theme_mode.emoji);
+        }
+
+        let emote_name = match self.mode {
+            0 => return theme_mode.prefix.clone(),
+            1 => "loading",
+            2 => "response_success",
+            3 => "response_info",
+            4 => "response_warning",
+            _ => "response_error",
+        };
+
+        if let Some(emote) = options.emotes.get(emote_name) {
+            if self.mode == 1 {
+                format!("<a:{}:{}> ", emote_name, emote)
+            } else {
+                format!("<:{}:{}> ", emote_name, emote)
+            }
+        } else {
+            theme_mode.prefix.clone()
+        }
+    }
+
+    fn footer(&self, options: &RenderOptions) -> Option<String> {
+        match &self.footer_text {
+            Some(text) => Some(text.clone()),
+            None if !options.theme.footer_text.is_empty() => Some(options.theme.footer_text.clone()),
+            None => None,
+        }
+    }
+
+    pub fn to_message(&self, options: &RenderOptions) -> CreateMessage {
         let mut message = CreateMessage::default();
         let mut ctnt = String::new();
 
+        if let Some(file) = &self.attachment {
+            message.2.push(file.clone());
+        }
+
         if let Some(title) = &self.title {
-            ctnt = format!("[{}]", title);
+            ctnt = options.theme.format_title(title);
         }
 
         if let Some(content) = &self.content {
-            let content = if self.mode == 0 {
-                content.to_string()
-            } else {
-                let emote_name = match self.mode {
-                    1 => "loading",
-                    2 => "response_success",
-                    3 => "response_info",
-                    4 => "response_warning",
-                    _ => "response_error",
-                };
-
-                let emote = if let Some(emote) = emotes.get(emote_name) {
-                    if self.mode == 1 {
-                        format!("<a:{}:{}> ", emote_name, emote)
-                    } else {
-                        format!("<:{}:{}> ", emote_name, emote)
-                    }
-                } else {
-                    match self.mode {
-                        1 => "Loading: ",
-                        2 => "Success: ",
-                        3 => "Info: ",
-                        4 => "Warning: ",
-                        _ => "Error: ",
-                    }
-                    .to_string()
-                };
-
-                format!("{} {}", emote, content)
-            };
-
-            ctnt = format!("{}\n{}", ctnt, content);
+            ctnt = format!("{}\n{}{}", ctnt, self.mode_prefix(options), content);
         }
 
         for field in &self.fields {
             ctnt = format!("{}\n_[_**{}**_]_\n{}", ctnt, field.title, field.content);
         }
 
-        if let Some(footer_text) = &self.footer_text {
+        if let Some(footer_text) = self.footer(options) {
             ctnt = format!("{}\n_{}_", ctnt, footer_text);
         }
 
-        // Append image URL to the end of the message
+        // Append image URL to the end of the message, attachments
+        // are uploaded with the message so don't need linking
         if let Some(image) = &self.image {
-            ctnt = format!("{}\n{}", ctnt, image);
+            if self.attachment.is_none() {
+                ctnt = format!("{}\n{}", ctnt, image);
+            }
         }
 
         message.content(ctnt).clone()
     }
 
-    pub fn to_embed(&self, emotes: HashMap<String, u64>) -> CreateMessage {
+    pub fn to_embed(&self, options: &RenderOptions) -> CreateMessage {
         let mut message = CreateMessage::default();
 
         if let Some(file) = &self.attachment {
@@ -223,51 +248,15 @@ impl<'a> MessageCreator<'a> {
             if let Some(colour) = self.colour {
                 e.colour(colour);
             } else {
-                e.colour(match self.mode {
-                    0 => Colour::FABLED_PINK,
-                    1 => Colour::BLURPLE,
-                    2 => Colour::FOOYOO,
-                    3 => Colour::KERBAL,
-                    4 => Colour::ORANGE,
-                    _ => Colour::MEIBE_PINK,
-                });
+                e.colour(Colour::new(options.theme.mode(self.mode).colour));
             }
 
             if let Some(title) = &self.title {
-                e.title(format!("[{}]", title));
+                e.title(options.theme.format_title(title));
             }
 
             if let Some(content) = &self.content {
-                let emote = if self.mode == 0 {
-                    String::default()
-                } else {
-                    let emote_name = match self.mode {
-                        1 => "loading",
-                        2 => "response_success",
-                        3 => "response_info",
-                        4 => "response_warning",
-                        _ => "response_error",
-                    };
-
-                    if let Some(emote) = emotes.get(emote_name) {
-                        if self.mode == 1 {
-                            format!("<a:{}:{}> ", emote_name, emote)
-                        } else {
-                            format!("<:{}:{}> ", emote_name, emote)
-                        }
-                    } else {
-                        match self.mode {
-                            1 => "Loading: ",
-                            2 => "Success: ",
-                            3 => "Info: ",
-                            4 => "Warning: ",
-                            _ => "Error: ",
-                        }
-                        .to_string()
-                    }
-                };
-
-                e.description(format!("{} {}", emote, content));
+                e.description(format!("{}{}", self.mode_prefix(options), content));
             }
 
             if let Some(image) = &self.image {
@@ -287,7 +276,7 @@ impl<'a> MessageCreator<'a> {
             }
 
             e.footer(|f| {
-                if let Some(text) = &self.footer_text {
+                if let Some(text) = self.footer(options) {
                     f.text(text);
                 }
 
@@ -300,14 +289,78 @@ impl<'a> MessageCreator<'a> {
         message
     }
 
-    pub fn to_auto(&self, perms: Permissions, emotes: HashMap<String, u64>) -> CreateMessage {
-        if perms.embed_links() {
-            self.to_embed(emotes)
+    pub fn to_auto(&self, options: &RenderOptions) -> CreateMessage {
+        if options.perms.embed_links() {
+            self.to_embed(options)
         } else {
-            self.to_message(emotes)
+            self.to_message(options)
+        }
+    }
+
+    pub fn render(&self, options: &RenderOptions) -> CreateMessage {
+        match options.embed_mode {
+            0 => self.to_message(options),
+            1 => self.to_auto(options),
+            _ => self.to_embed(options),
         }
     }
 
+    // Splits the message into pages which fit within Discord's limits.
+    // Pages are sized for the text renderer as it's the stricter of the
+    // two, so text and embed output always have the same pages
+    pub fn split(&self) -> Vec<MessageCreator<'a>> {
+        let overhead = SPLIT_RESERVE
+            + self.title.as_ref().map_or(0, |title| title.chars().count() + 3)
+            + self.footer_text.as_ref().map_or(0, |footer| footer.chars().count() + 3)
+            + self.image.as_ref().map_or(0, |image| image.chars().count() + 1);
+        let budget = MESSAGE_LIMIT.saturating_sub(overhead).max(MIN_PAGE_BUDGET);
+
+        let mut base = self.clone();
+        base.content = None;
+        base.fields = Vec::new();
+
+        let mut pages = Vec::new();
+        if let Some(content) = &self.content {
+            for chunk in split_message(content, min(budget, EMBED_DESCRIPTION_LIMIT)) {
+                let mut page = base.clone();
+                page.content = Some(chunk);
+
+                pages.push(page);
+            }
+        }
+
+        if pages.is_empty() {
+            pages.push(base.clone());
+        }
+
+        let mut used = pages.last().unwrap().content.as_ref().map_or(0, |c| c.chars().count());
+        for field in &self.fields {
+            let title_len = field.title.chars().count() + FIELD_OVERHEAD;
+            let limit = min(EMBED_FIELD_LIMIT, budget.saturating_sub(title_len).max(1));
+
+            for chunk in split_message(&field.content, limit) {
+                let len = title_len + chunk.chars().count();
+                let page = pages.last().unwrap();
+
+                if page.fields.len() >= EMBED_FIELD_COUNT_LIMIT || used + len > budget {
+                    pages.push(base.clone());
+                    used = 0;
+                }
+
+                pages.last_mut().unwrap().fields.push(MessageField::new(&field.title, &chunk, field.inline));
+                used += len;
+            }
+        }
+
+        // Images and attachments only need to be on the first page
+        for page in pages.iter_mut().skip(1) {
+            page.image = None;
+            page.attachment = None;
+        }
+
+        pages
+    }
+
     pub fn title<D: ToString>(&mut self, title: D) -> &mut Self {
         self.title = Some(title.to_string());
 
@@ -420,14 +473,12 @@ pub trait InoriChannelUtils {
     async fn send_paginator<'a>(
         &self,
         ctx: &Context,
-        msg: &Message,
         embeds: Vec<MessageCreator<'a>>,
     ) -> Result<Option<Message>, CommandError>;
 
     async fn send_paginator_noret<'a>(
         &self,
         ctx: &Context,
-        msg: &Message,
         embeds: Vec<MessageCreator<'a>>,
     ) -> Result<(), CommandError>;
 
@@ -448,15 +499,129 @@ pub trait InoriChannelUtils {
     ) -> Result<(), CommandError>;
 }
 
+#[derive(Clone)]
+pub struct RenderOptions {
+    pub embed_mode: u8,
+    pub perms:      Permissions,
+    pub emotes:     HashMap<String, u64>,
+    pub theme:      Theme,
+}
+
+pub async fn get_render_options(ctx: &Context, channel_id: ChannelId) -> RenderOptions {
+    let cached = get_cached_permissions(ctx, channel_id).await;
+
+    let data = ctx.data.read().await;
+    let settings = data.get::<Settings>().expect("Expected Settings in TypeMap.").lock().await;
+
+    // Channel overrides take priority over guild overrides
+    let embed_mode = settings
+        .embed_overrides
+        .get(&channel_id.0.to_string())
+        .or_else(|| {
+            cached
+                .guild_id
+                .and_then(|guild_id| settings.embed_overrides.get(&guild_id.to_string()))
+        })
+        .copied()
+        .unwrap_or(settings.embed_mode);
+
+    // TODO: Check if has nitro
+    RenderOptions {
+        embed_mode,
+        perms: cached.perms,
+        emotes: settings.sb_emotes.clone(),
+        theme: settings.theme.current(),
+    }
+}
+
+pub async fn send_pages<'a>(
+    ctx: &Context,
+    channel_id: ChannelId,
+    msg: &MessageCreator<'a>,
+    options: &RenderOptions,
+) -> Result<Vec<Message>, CommandError> {
+    let mut sent = Vec::new();
+
+    for page in msg.split() {
+        let res = channel_id
+            .send_message(&ctx, |m| {
+                let rendered = page.render(options);
+                m.0 = rendered.0;
+                m.2 = rendered.2;
+
+                m
+            })
+            .await;
+
+        match res {
+            Ok(msg) => sent.push(msg),
+            Err(why) => return Err(CommandError::from(why)),
+        }
+    }
+
+    Ok(sent)
+}
+
+// Edits the message to show the first page. Edits can't add extra messages
+// so the rest are sent after, and returned
+async fn update_pages<'a>(
+    ctx: &Context,
+    message: &mut Message,
+    msg: &MessageCreator<'a>,
+    options: &RenderOptions,
+) -> Result<Vec<Message>, CommandError> {
+    let mut pages = msg.split();
+    let first = pages.remove(0);
+
+    let res = message
+        .edit(&ctx.http, |m| {
+            m.0 = first.render(options).0;
+
+            m
+        })
+        .await;
+
+    if let Err(why) = res {
+        return Err(CommandError::from(why));
+    }
+
+    let mut sent = Vec::new();
+    for page in pages {
+        let res = message
+            .channel_id
+            .send_message(&ctx, |m| {
+                let rendered = page.render(options);
+                m.0 = rendered.0;
+                m.2 = rendered.2;
+
+                m
+            })
+            .await;
+
+        match res {
+            Ok(msg) => sent.push(msg),
+            Err(why) => return Err(CommandError::from(why)),
+        }
+    }
+
+    Ok(sent)
+}
+
 #[async_trait]
 impl InoriChannelUtils for ChannelId {
     async fn send_tmp<'a, F: std::marker::Send>(&self, ctx: &Context, f: F) -> Result<(), CommandError>
     where
         for<'b> F: FnOnce(&'b mut MessageCreator<'a>) -> &'b mut MessageCreator<'a>, {
-        match self.send(ctx, f).await {
-            Ok(msg) => msg.autodelete(ctx).await,
-            Err(err) => return Err(err),
+        let mut msg_creator = MessageCreator::default();
+        let msg = f(&mut msg_creator);
+
+        let options = get_render_options(ctx, *self).await;
+
+        for msg in send_pages(ctx, *self, msg, &options).await? {
+            msg.autodelete(ctx).await?;
         }
+
+        Ok(())
     }
 
     async fn send_noret<'a, F: std::marker::Send>(&self, ctx: &Context, f: F) -> Result<(), CommandError>
@@ -473,47 +638,11 @@ impl InoriChannelUtils for ChannelId {
         for<'b> F: FnOnce(&'b mut MessageCreator<'a>) -> &'b mut MessageCreator<'a>, {
         let mut msg_creator = MessageCreator::default();
         let msg = f(&mut msg_creator);
-        let perms = {
-            if let Ok(chnl) = ctx.http.get_channel(self.0).await {
-                if let Some(guild) = chnl.guild() {
-                    get_permissions(ctx, guild.guild_id, None, None).await
-                } else {
-                    DM_PERMISSIONS.unwrap()
-                }
-            } else {
-                DM_PERMISSIONS.unwrap()
-            }
-        };
-        let embed_mode;
-        let emotes = {
-            let data = ctx.data.read().await;
-            let settings = data.get::<Settings>().expect("Expected Settings in TypeMap.").lock().await;
-            embed_mode = settings.embed_mode;
-
-            // TODO: Check if has nitro
-            if let Ok(_user) = ctx.http.get_current_user().await {
-                settings.sb_emotes.clone()
-            } else {
-                HashMap::new()
-            }
-        };
 
-        let res = self
-            .send_message(&ctx, |m| {
-                m.0 = match embed_mode {
-                    0 => msg.to_message(emotes).0,
-                    1 => msg.to_auto(perms, emotes).0,
-                    _ => msg.to_embed(emotes).0,
-                };
+        let options = get_render_options(ctx, *self).await;
 
-                m
-            })
-            .await;
-
-        match res {
-            Ok(msg) => Ok(msg),
-            Err(why) => Err(CommandError::from(why)),
-        }
+        // Always at least one page so this will never be None
+        Ok(send_pages(ctx, *self, msg, &options).await?.remove(0))
     }
 
     async fn send_loading<D: ToString + std::marker::Send>(
@@ -531,19 +660,20 @@ impl InoriChannelUtils for ChannelId {
     async fn send_paginator<'a>(
         &self,
         ctx: &Context,
-        msg: &Message,
         embeds: Vec<MessageCreator<'a>>,
     ) -> Result<Option<Message>, CommandError> {
-        self.send_paginatorwo(ctx, msg, embeds, MenuOptions::default()).await
+        match Paginator::new(embeds).run(ctx, *self).await {
+            Ok(msg) => Ok(Some(msg)),
+            Err(why) => Err(why),
+        }
     }
 
     async fn send_paginator_noret<'a>(
         &self,
         ctx: &Context,
-        msg: &Message,
         embeds: Vec<MessageCreator<'a>>,
     ) -> Result<(), CommandError> {
-        match self.send_paginator(ctx, msg, embeds).await {
+        match self.send_paginator(ctx, embeds).await {
             Ok(_) => Ok(()),
             Err(why) => Err(why),
         }
@@ -556,38 +686,16 @@ impl InoriChannelUtils for ChannelId {
         embeds: Vec<MessageCreator<'a>>,
         options: MenuOptions,
     ) -> Result<Option<Message>, CommandError> {
-        let perms = {
-            if let Ok(chnl) = ctx.http.get_channel(self.0).await {
-                if let Some(guild) = chnl.guild() {
-                    get_permissions(ctx, guild.guild_id, None, None).await
-                } else {
-                    DM_PERMISSIONS.unwrap()
-                }
-            } else {
-                DM_PERMISSIONS.unwrap()
-            }
-        };
+        let render_options = get_render_options(ctx, *self).await;
+        let pages = embeds.iter().flat_map(|embed| embed.split()).collect::<Vec<MessageCreator>>();
         let mut formatted_embeds = Vec::new();
 
-        let embed_mode;
-        let emotes = {
-            let data = ctx.data.read().await;
-            let settings = data.get::<Settings>().expect("Expected Settings in TypeMap").lock().await;
-
-            embed_mode = settings.embed_mode;
-            settings.sb_emotes.clone()
-        };
-
-        for (idx, embed) in embeds.iter().enumerate() {
+        for (idx, embed) in pages.iter().enumerate() {
             let mut msg = CreateMessage::default();
             let mut embed = embed.clone();
-            embed.footer_text(format!("Page {} of {}", idx + 1, embeds.len()));
+            embed.footer_text(format!("Page {} of {}", idx + 1, pages.len()));
 
-            msg.0 = match embed_mode {
-                0 => embed.to_message(emotes.clone()).0,
-                1 => embed.to_auto(perms, emotes.clone()).0,
-                _ => embed.to_embed(emotes.clone()).0,
-            };
+            msg.0 = embed.render(&render_options).0;
 
             formatted_embeds.push(msg);
         }
@@ -662,10 +770,18 @@ impl InoriMessageUtils for Message {
     async fn update_tmp<'a, F: std::marker::Send>(&'a mut self, ctx: &Context, f: F) -> Result<(), CommandError>
     where
         for<'b> F: FnOnce(&'b mut MessageCreator<'a>) -> &'b mut MessageCreator<'a>, {
-        match self.update(ctx, f).await {
-            Ok(msg) => msg.autodelete(ctx).await,
-            Err(err) => return Err(err),
+        let mut msg_creator = MessageCreator::default();
+        let msg = f(&mut msg_creator);
+        let options = get_render_options(ctx, self.channel_id).await;
+
+        let overflow = update_pages(ctx, self, msg, &options).await?;
+
+        self.autodelete(ctx).await?;
+        for msg in overflow {
+            msg.autodelete(ctx).await?;
         }
+
+        Ok(())
     }
 
     async fn update_noret<'a, F: std::marker::Send>(&'a mut self, ctx: &Context, f: F) -> Result<(), CommandError>
@@ -682,42 +798,10 @@ impl InoriMessageUtils for Message {
         for<'b> F: FnOnce(&'b mut MessageCreator<'a>) -> &'b mut MessageCreator<'a>, {
         let mut msg_creator = MessageCreator::default();
         let msg = f(&mut msg_creator);
-        let perms = {
-            if let Ok(chnl) = ctx.http.get_channel(self.id.0).await {
-                if let Some(guild) = chnl.guild() {
-                    get_permissions(ctx, guild.guild_id, None, None).await
-                } else {
-                    DM_PERMISSIONS.unwrap()
-                }
-            } else {
-                DM_PERMISSIONS.unwrap()
-            }
-        };
-
-        let embed_mode;
-        let emotes = {
-            let data = ctx.data.read().await;
-            let settings = data.get::<Settings>().expect("Expected Settings in TypeMap.").lock().await;
+        let options = get_render_options(ctx, self.channel_id).await;
 
-            embed_mode = settings.embed_mode;
-            settings.sb_emotes.clone()
-        };
-
-        let res = self
-            .edit(&ctx.http, |m| {
-                m.0 = match embed_mode {
-                    0 => msg.to_message(emotes).0,
-                    1 => msg.to_auto(perms, emotes).0,
-                    _ => msg.to_embed(emotes).0,
-                };
+        update_pages(ctx, self, msg, &options).await?;
 
-                m
-            })
-            .await;
-
-        match res {
-            Ok(_) => Ok(self),
-            Err(why) => Err(CommandError::from(why)),
-        }
+        Ok(self)
     }
 }
@@ -0,0 +1,135 @@
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use regex::Regex;
+use serenity::prelude::TypeMapKey;
+use tokio::sync::Mutex;
+
+use crate::models::settings::HighlightConfig;
+
+// Keywords wrapped in slashes are treated as a regex, anything else matches
+// as a whole word. Both are case insensitive
+pub fn compile_keyword(keyword: &str) -> Result<Regex, String> {
+    let pattern = if keyword.len() > 2 && keyword.starts_with('/') && keyword.ends_with('/') {
+        format!("(?i){}", &keyword[1..keyword.len() - 1])
+    } else {
+        // Word boundaries only work next to word characters, `c++` would
+        // never match otherwise
+        let is_word = |c: Option<char>| c.map_or(false, |c| c.is_alphanumeric() || c == '_');
+        let start = if is_word(keyword.chars().next()) { r"\b" } else { "" };
+        let end = if is_word(keyword.chars().last()) { r"\b" } else { "" };
+
+        format!("(?i){}{}{}", start, regex::escape(keyword), end)
+    };
+
+    Regex::new(&pattern).map_err(|_| format!("Unable to parse regex: `{}`", keyword))
+}
+
+#[derive(Default)]
+pub struct HighlightState {
+    // Compiled keywords, None if the keyword failed to compile
+    regexes:   HashMap<String, Option<Regex>>,
+    // Last time a channel was highlighted or we spoke in it
+    cooldowns: HashMap<u64, Instant>,
+}
+
+impl HighlightState {
+    pub fn find_match<'a, I>(&mut self, content: &str, keywords: I) -> Option<String>
+    where
+        I: IntoIterator<Item = &'a String>, {
+        for keyword in keywords {
+            let regex = self
+                .regexes
+                .entry(keyword.clone())
+                .or_insert_with(|| compile_keyword(keyword).ok());
+
+            if regex.as_ref().map_or(false, |regex| regex.is_match(content)) {
+                return Some(keyword.clone());
+            }
+        }
+
+        None
+    }
+
+    // Forgets compiled keywords which are no longer configured
+    pub fn prune(&mut self, config: &HighlightConfig) {
+        let keywords = config
+            .keywords
+            .iter()
+            .chain(config.guilds.values().flatten())
+            .collect::<HashSet<&String>>();
+
+        self.regexes.retain(|keyword, _| keywords.contains(keyword));
+    }
+
+    pub fn on_cooldown(&self, channel_id: u64, cooldown: Duration) -> bool {
+        self.cooldowns
+            .get(&channel_id)
+            .map_or(false, |last| last.elapsed() < cooldown)
+    }
+
+    pub fn start_cooldown(&mut self, channel_id: u64) {
+        self.cooldowns.insert(channel_id, Instant::now());
+
+        // Drop stale entries so this doesn't grow forever
+        if self.cooldowns.len() > 1000 {
+            self.cooldowns.retain(|_, last| last.elapsed() < Duration::from_secs(86400));
+        }
+    }
+}
+
+// Every guild message touches this so it has its own lock rather than
+// holding the TypeMap
+impl TypeMapKey for HighlightState {
+    type Value = Arc<Mutex<HighlightState>>;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compile_keyword() {
+        let regex = compile_keyword("rust").unwrap();
+        assert!(regex.is_match("I love Rust!"));
+        assert!(!regex.is_match("trusty"));
+
+        let regex = compile_keyword("c++").unwrap();
+        assert!(regex.is_match("Anyone know c++ here"));
+
+        let regex = compile_keyword("/inori(-rs)?/").unwrap();
+        assert!(regex.is_match("inori-rs is neat"));
+
+        assert!(compile_keyword("/(/").is_err());
+    }
+
+    #[test]
+    fn test_find_match() {
+        let mut state = HighlightState::default();
+        let keywords = vec!["/(/".to_string(), "selfbot".to_string()];
+
+        assert_eq!(
+            state.find_match("which selfbot is that", &keywords),
+            Some("selfbot".to_string())
+        );
+        assert_eq!(state.find_match("nothing here", &keywords), None);
+
+        let mut config = HighlightConfig::default();
+        config.keywords.push("selfbot".to_string());
+        state.prune(&config);
+        assert_eq!(state.regexes.keys().collect::<Vec<&String>>(), vec!["selfbot"]);
+    }
+
+    #[test]
+    fn test_cooldown() {
+        let mut state = HighlightState::default();
+        assert!(!state.on_cooldown(1, Duration::from_secs(60)));
+
+        state.start_cooldown(1);
+        assert!(state.on_cooldown(1, Duration::from_secs(60)));
+        assert!(!state.on_cooldown(1, Duration::from_secs(0)));
+    }
+}
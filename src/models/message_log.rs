@@ -0,0 +1,219 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::Arc,
+};
+
+use chrono::{DateTime, Duration, Utc};
+use serenity::{model::channel::Message, prelude::TypeMapKey};
+use tokio::sync::Mutex;
+
+// Snipes kept per channel for both deletes and edits
+const SNIPES_PER_CHANNEL: usize = 10;
+
+#[derive(Clone, Debug)]
+pub struct LoggedMessage {
+    pub id:          u64,
+    pub channel_id:  u64,
+    pub guild_id:    Option<u64>,
+    pub author_id:   u64,
+    pub author:      String,
+    pub avatar:      String,
+    pub content:     String,
+    pub attachments: Vec<String>,
+    pub logged_at:   DateTime<Utc>,
+}
+
+impl From<&Message> for LoggedMessage {
+    fn from(msg: &Message) -> LoggedMessage {
+        LoggedMessage {
+            id:          msg.id.0,
+            channel_id:  msg.channel_id.0,
+            guild_id:    msg.guild_id.map(|guild_id| guild_id.0),
+            author_id:   msg.author.id.0,
+            author:      msg.author.tag(),
+            avatar:      msg.author.face(),
+            content:     msg.content.clone(),
+            attachments: msg.attachments.iter().map(|attachment| attachment.url.clone()).collect(),
+            logged_at:   Utc::now(),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct DeletedMessage {
+    pub message:    LoggedMessage,
+    pub deleted_at: DateTime<Utc>,
+}
+
+#[derive(Clone, Debug)]
+pub struct EditedMessage {
+    // Content after the edit
+    pub message:   LoggedMessage,
+    pub before:    String,
+    pub edited_at: DateTime<Utc>,
+}
+
+#[derive(Default)]
+pub struct MessageLog {
+    messages: HashMap<u64, LoggedMessage>,
+    order:    VecDeque<u64>,
+    deleted:  HashMap<u64, VecDeque<DeletedMessage>>,
+    edited:   HashMap<u64, VecDeque<EditedMessage>>,
+}
+
+fn push_snipe<T>(snipes: &mut HashMap<u64, VecDeque<T>>, channel_id: u64, snipe: T) {
+    let snipes = snipes.entry(channel_id).or_insert_with(VecDeque::new);
+    snipes.push_front(snipe);
+    snipes.truncate(SNIPES_PER_CHANNEL);
+}
+
+impl MessageLog {
+    pub fn insert(&mut self, message: LoggedMessage, max_messages: usize, max_age: Duration) {
+        self.order.push_back(message.id);
+        self.messages.insert(message.id, message);
+
+        self.prune(max_messages, max_age, Utc::now());
+    }
+
+    pub fn prune(&mut self, max_messages: usize, max_age: Duration, now: DateTime<Utc>) {
+        let oldest = now - max_age;
+
+        while let Some(id) = self.order.front() {
+            let expired = self.messages.get(id).map_or(true, |message| message.logged_at < oldest);
+
+            if self.order.len() <= max_messages && !expired {
+                break;
+            }
+
+            if let Some(id) = self.order.pop_front() {
+                self.messages.remove(&id);
+            }
+        }
+
+        for snipes in self.deleted.values_mut() {
+            snipes.retain(|snipe| snipe.deleted_at >= oldest);
+        }
+
+        for snipes in self.edited.values_mut() {
+            snipes.retain(|snipe| snipe.edited_at >= oldest);
+        }
+    }
+
+    pub fn edit(&mut self, id: u64, content: String) {
+        let message = match self.messages.get_mut(&id) {
+            Some(message) => message,
+            None => return,
+        };
+
+        // Embeds resolving also fire updates, only keep content changes
+        if message.content == content {
+            return;
+        }
+
+        let before = std::mem::replace(&mut message.content, content);
+        let snipe = EditedMessage {
+            message: message.clone(),
+            before,
+            edited_at: Utc::now(),
+        };
+
+        push_snipe(&mut self.edited, snipe.message.channel_id, snipe);
+    }
+
+    pub fn delete(&mut self, id: u64) {
+        let message = match self.messages.remove(&id) {
+            Some(message) => message,
+            None => return,
+        };
+
+        self.order.retain(|logged| *logged != id);
+
+        let channel_id = message.channel_id;
+        push_snipe(&mut self.deleted, channel_id, DeletedMessage {
+            message,
+            deleted_at: Utc::now(),
+        });
+    }
+
+    // Index 0 is the most recent
+    pub fn deleted(&self, channel_id: u64, index: usize) -> Option<&DeletedMessage> {
+        self.deleted.get(&channel_id).and_then(|snipes| snipes.get(index))
+    }
+
+    pub fn edited(&self, channel_id: u64, index: usize) -> Option<&EditedMessage> {
+        self.edited.get(&channel_id).and_then(|snipes| snipes.get(index))
+    }
+
+    pub fn message_count(&self) -> usize {
+        self.messages.len()
+    }
+
+    pub fn clear(&mut self) {
+        self.messages.clear();
+        self.order.clear();
+        self.deleted.clear();
+        self.edited.clear();
+    }
+}
+
+// Kept behind its own lock as it's touched by every message event
+pub struct MessageLogContainer;
+
+impl TypeMapKey for MessageLogContainer {
+    type Value = Arc<Mutex<MessageLog>>;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message(id: u64, content: &str) -> LoggedMessage {
+        LoggedMessage {
+            id,
+            channel_id: 1,
+            guild_id: None,
+            author_id: 2,
+            author: "Inori#0001".to_string(),
+            avatar: String::new(),
+            content: content.to_string(),
+            attachments: Vec::new(),
+            logged_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_snipes() {
+        let mut log = MessageLog::default();
+        log.insert(message(1, "foo"), 10, Duration::hours(1));
+        log.insert(message(2, "bar"), 10, Duration::hours(1));
+
+        log.edit(1, "foo".to_string());
+        assert!(log.edited(1, 0).is_none());
+
+        log.edit(1, "baz".to_string());
+        let edited = log.edited(1, 0).unwrap();
+        assert_eq!(edited.before, "foo");
+        assert_eq!(edited.message.content, "baz");
+
+        log.delete(2);
+        log.delete(1);
+        assert_eq!(log.deleted(1, 0).unwrap().message.content, "baz");
+        assert_eq!(log.deleted(1, 1).unwrap().message.content, "bar");
+        assert_eq!(log.message_count(), 0);
+    }
+
+    #[test]
+    fn test_prune() {
+        let mut log = MessageLog::default();
+        for id in 0..5 {
+            log.insert(message(id, "foo"), 3, Duration::hours(1));
+        }
+
+        assert_eq!(log.message_count(), 3);
+        assert!(log.messages.contains_key(&4));
+        assert!(!log.messages.contains_key(&1));
+
+        log.prune(3, Duration::hours(1), Utc::now() + Duration::hours(2));
+        assert_eq!(log.message_count(), 0);
+    }
+}
@@ -1,4 +1,11 @@
+pub mod anime;
 pub mod commands;
+pub mod currency;
 pub mod discord;
+pub mod highlight;
+pub mod message_log;
+pub mod paginator;
+pub mod purge;
 pub mod quotes;
 pub mod settings;
+pub mod watchlist;
@@ -0,0 +1,194 @@
+use std::{cmp::min, collections::HashMap};
+
+use serenity::{
+    framework::standard::CommandError,
+    model::{channel::Message, id::ChannelId},
+    prelude::{Context, TypeMapKey},
+};
+use tokio::{
+    sync::mpsc::{unbounded_channel, UnboundedSender},
+    time::{timeout, Duration},
+};
+
+use crate::models::discord::{get_render_options, MessageCreator};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PaginatorInput {
+    Next,
+    Previous,
+    First,
+    Last,
+    Page(usize),
+    Quit,
+}
+
+impl PaginatorInput {
+    // Only single character controls and page numbers that exist are taken so
+    // normal chat like "2021" or "no" isn't swallowed
+    pub fn parse(input: &str, page_count: usize) -> Option<PaginatorInput> {
+        match input.trim().to_lowercase().as_str() {
+            "n" | ">" => Some(PaginatorInput::Next),
+            "p" | "<" => Some(PaginatorInput::Previous),
+            "f" => Some(PaginatorInput::First),
+            "l" => Some(PaginatorInput::Last),
+            "q" => Some(PaginatorInput::Quit),
+            page => {
+                page.parse::<usize>()
+                    .ok()
+                    .filter(|page| (1..=page_count).contains(page))
+                    .map(PaginatorInput::Page)
+            },
+        }
+    }
+}
+
+// Active paginators keyed by channel ID, holding the paginator message ID,
+// its page count and where to send navigation input typed in that channel
+pub struct PaginatorInputs;
+
+impl TypeMapKey for PaginatorInputs {
+    type Value = HashMap<u64, (u64, usize, UnboundedSender<PaginatorInput>)>;
+}
+
+// Passes navigation input typed by the user to the active paginator in the
+// channel, returns true if the message was consumed. Messages which aren't
+// input are left alone
+pub async fn handle_paginator_input(ctx: &Context, msg: &Message) -> bool {
+    let sent = {
+        let data = ctx.data.read().await;
+
+        data.get::<PaginatorInputs>()
+            .and_then(|inputs| inputs.get(&msg.channel_id.0))
+            .and_then(|(_, page_count, sender)| {
+                PaginatorInput::parse(&msg.content, *page_count).map(|input| sender.send(input).is_ok())
+            })
+            .unwrap_or(false)
+    };
+
+    if sent {
+        let _ = msg.delete(&ctx.http).await;
+    }
+
+    sent
+}
+
+pub struct Paginator<'a> {
+    pages:   Vec<MessageCreator<'a>>,
+    page:    usize,
+    timeout: Duration,
+}
+
+impl<'a> Paginator<'a> {
+    pub fn new(pages: Vec<MessageCreator<'a>>) -> Paginator<'a> {
+        let mut pages = pages.iter().flat_map(|page| page.split()).collect::<Vec<MessageCreator>>();
+        if pages.is_empty() {
+            pages.push(MessageCreator::default());
+        }
+
+        Paginator {
+            pages,
+            page: 0,
+            timeout: Duration::from_secs(120),
+        }
+    }
+
+    fn render_page(&self) -> MessageCreator<'a> {
+        let mut page = self.pages[self.page].clone();
+
+        if self.pages.len() > 1 {
+            page.footer_text(format!(
+                "Page {} of {} | Type n, p, a page number or q",
+                self.page + 1,
+                self.pages.len()
+            ));
+        }
+
+        page
+    }
+
+    pub async fn run(&mut self, ctx: &Context, channel_id: ChannelId) -> Result<Message, CommandError> {
+        let options = get_render_options(ctx, channel_id).await;
+        let page = self.render_page();
+
+        let mut message = channel_id
+            .send_message(&ctx, |m| {
+                let rendered = page.render(&options);
+                m.0 = rendered.0;
+                m.2 = rendered.2;
+
+                m
+            })
+            .await?;
+
+        if self.pages.len() <= 1 {
+            return Ok(message);
+        }
+
+        let (sender, mut receiver) = unbounded_channel();
+        {
+            let mut data = ctx.data.write().await;
+            if let Some(inputs) = data.get_mut::<PaginatorInputs>() {
+                inputs.insert(channel_id.0, (message.id.0, self.pages.len(), sender));
+            }
+        }
+
+        let last = self.pages.len() - 1;
+        loop {
+            // Timeout resets after every input, a newer paginator in the
+            // same channel drops our sender which also ends this one
+            let input = match timeout(self.timeout, receiver.recv()).await {
+                Ok(Some(input)) => input,
+                _ => break,
+            };
+
+            let page = match input {
+                PaginatorInput::Next => min(self.page + 1, last),
+                PaginatorInput::Previous => self.page.saturating_sub(1),
+                PaginatorInput::First => 0,
+                PaginatorInput::Last => last,
+                PaginatorInput::Page(page) => min(page - 1, last),
+                PaginatorInput::Quit => break,
+            };
+
+            if page == self.page {
+                continue;
+            }
+
+            self.page = page;
+            let page = self.render_page();
+
+            let _ = message
+                .edit(&ctx.http, |m| {
+                    m.0 = page.render(&options).0;
+
+                    m
+                })
+                .await;
+        }
+
+        let mut data = ctx.data.write().await;
+        if let Some(inputs) = data.get_mut::<PaginatorInputs>() {
+            if inputs.get(&channel_id.0).map_or(false, |(id, ..)| *id == message.id.0) {
+                inputs.remove(&channel_id.0);
+            }
+        }
+
+        Ok(message)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_input() {
+        assert_eq!(PaginatorInput::parse("n", 3), Some(PaginatorInput::Next));
+        assert_eq!(PaginatorInput::parse(" Q ", 3), Some(PaginatorInput::Quit));
+        assert_eq!(PaginatorInput::parse("3", 3), Some(PaginatorInput::Page(3)));
+        assert_eq!(PaginatorInput::parse("0", 3), None);
+        assert_eq!(PaginatorInput::parse("2021", 3), None);
+        assert_eq!(PaginatorInput::parse("next", 3), None);
+        assert_eq!(PaginatorInput::parse("no", 3), None);
+    }
+}
@@ -0,0 +1,345 @@
+use std::{
+    fs,
+    path::Path,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::Instant,
+};
+
+use colored::Colorize;
+use serde_derive::{Deserialize, Serialize};
+use serenity::{
+    http::error::Error as HttpError,
+    model::id::{ChannelId, MessageId},
+    prelude::{Context, TypeMapKey},
+    Error as SerenityError,
+};
+use tokio::time::{delay_for, Duration};
+
+use crate::{
+    inori_error, inori_info, inori_success, inori_warn,
+    models::discord::{InoriChannelUtils, InoriMessageUtils, MessageCreator},
+    utils::purge_filter::PurgeFilter,
+};
+
+const PURGE_JOB_FILE: &str = "purge_job.json";
+const REPORT_INTERVAL: u64 = 30;
+const MAX_RETRIES: u64 = 5;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct PurgeTarget {
+    pub channel_id: u64,
+    // Oldest message scanned so far, scanning resumes from before it
+    pub before:     Option<u64>,
+    pub done:       bool,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct PurgeJob {
+    pub scope:          String,
+    pub filter:         String,
+    pub targets:        Vec<PurgeTarget>,
+    pub scanned:        u64,
+    pub deleted:        u64,
+    pub status_channel: u64,
+    pub status_message: Option<u64>,
+}
+
+impl PurgeJob {
+    pub fn new(scope: String, filter: String, channels: Vec<u64>, status_channel: u64) -> PurgeJob {
+        PurgeJob {
+            scope,
+            filter,
+            targets: channels
+                .into_iter()
+                .map(|channel_id| PurgeTarget {
+                    channel_id,
+                    before: None,
+                    done: false,
+                })
+                .collect(),
+            scanned: 0,
+            deleted: 0,
+            status_channel,
+            status_message: None,
+        }
+    }
+
+    pub fn load() -> Option<PurgeJob> {
+        if !Path::new(PURGE_JOB_FILE).exists() {
+            return None;
+        }
+
+        match fs::read_to_string(PURGE_JOB_FILE).map(|contents| serde_json::from_str::<PurgeJob>(&contents)) {
+            Ok(Ok(job)) => Some(job),
+            _ => {
+                inori_error!("Purge", "Unable to read '{}', ignoring saved purge", PURGE_JOB_FILE);
+
+                None
+            },
+        }
+    }
+
+    pub fn save(&self) {
+        let res = serde_json::to_string(self)
+            .map_err(|why| why.to_string())
+            .and_then(|contents| fs::write(PURGE_JOB_FILE, contents).map_err(|why| why.to_string()));
+
+        if let Err(why) = res {
+            inori_error!("Purge", "Unable to save purge progress: {}", why);
+        }
+    }
+
+    pub fn remove() {
+        if Path::new(PURGE_JOB_FILE).exists() {
+            if let Err(why) = fs::remove_file(PURGE_JOB_FILE) {
+                inori_error!("Purge", "Unable to remove '{}': {}", PURGE_JOB_FILE, why);
+            }
+        }
+    }
+
+    pub fn progress(&self) -> String {
+        let done = self.targets.iter().filter(|target| target.done).count();
+
+        format!(
+            "**Scope**: {}\n**Filter**: {}\n**Channels**: {}/{}\n**Scanned**: {}\n**Deleted**: {}",
+            self.scope,
+            if self.filter.is_empty() { "None" } else { self.filter.as_str() },
+            done,
+            self.targets.len(),
+            self.scanned,
+            self.deleted
+        )
+    }
+}
+
+// Cancel flag of the running purge job, None if no job is running
+pub struct PurgeJobControl;
+
+impl TypeMapKey for PurgeJobControl {
+    type Value = Option<Arc<AtomicBool>>;
+}
+
+// Starts the job in the background, returns false if a job is already running
+pub async fn start_purge_job(ctx: &Context, job: PurgeJob) -> bool {
+    let cancel = Arc::new(AtomicBool::new(false));
+    {
+        let mut data = ctx.data.write().await;
+        let control = data
+            .get_mut::<PurgeJobControl>()
+            .expect("Expected PurgeJobControl in TypeMap.");
+
+        if control.is_some() {
+            return false;
+        }
+
+        *control = Some(cancel.clone());
+    }
+
+    job.save();
+
+    let ctx = ctx.clone();
+    tokio::spawn(async move {
+        run_purge_job(&ctx, job, cancel).await;
+
+        let mut data = ctx.data.write().await;
+        if let Some(control) = data.get_mut::<PurgeJobControl>() {
+            *control = None;
+        }
+    });
+
+    true
+}
+
+pub async fn resume_purge_job(ctx: &Context) {
+    if let Some(job) = PurgeJob::load() {
+        if start_purge_job(ctx, job).await {
+            inori_info!("Purge", "Resuming saved purge");
+        }
+    }
+}
+
+// Returns true if there was a running or saved job to cancel
+pub async fn cancel_purge_job(ctx: &Context) -> bool {
+    let data = ctx.data.read().await;
+
+    match data.get::<PurgeJobControl>().and_then(|control| control.clone()) {
+        Some(cancel) => {
+            cancel.store(true, Ordering::Relaxed);
+
+            true
+        },
+        None if Path::new(PURGE_JOB_FILE).exists() => {
+            PurgeJob::remove();
+
+            true
+        },
+        None => false,
+    }
+}
+
+pub async fn is_purge_job_running(ctx: &Context) -> bool {
+    let data = ctx.data.read().await;
+
+    data.get::<PurgeJobControl>().map_or(false, |control| control.is_some())
+}
+
+async fn report_progress(ctx: &Context, job: &mut PurgeJob, status: &str, finished: bool) {
+    let content = format!("{}\n{}", status, job.progress());
+    let channel_id = ChannelId(job.status_channel);
+
+    if let Some(id) = job.status_message {
+        if let Ok(mut message) = ctx.http.get_message(job.status_channel, id).await {
+            let res = message
+                .update_noret(ctx, |m: &mut MessageCreator| {
+                    if finished {
+                        m.success();
+                    } else {
+                        m.loading();
+                    }
+
+                    m.title("Purge").content(content.clone())
+                })
+                .await;
+
+            if res.is_ok() {
+                return;
+            }
+        }
+    }
+
+    let res = channel_id
+        .send(ctx, |m: &mut MessageCreator| {
+            if finished {
+                m.success();
+            } else {
+                m.loading();
+            }
+
+            m.title("Purge").content(content.clone())
+        })
+        .await;
+
+    match res {
+        Ok(message) => job.status_message = Some(message.id.0),
+        Err(_) => inori_info!("Purge", "{}", content.replace("**", "")),
+    }
+}
+
+// Rate limits, server errors and dropped connections are worth retrying,
+// missing channels or permissions won't change
+fn is_retryable(why: &SerenityError) -> bool {
+    match why {
+        SerenityError::Http(why) => match why.as_ref() {
+            HttpError::UnsuccessfulRequest(response) => matches!(response.status_code.as_u16(), 429 | 500..=599),
+            HttpError::Request(_) => true,
+            _ => false,
+        },
+        _ => false,
+    }
+}
+
+async fn run_purge_job(ctx: &Context, mut job: PurgeJob, cancel: Arc<AtomicBool>) {
+    let filter = match PurgeFilter::parse(&job.filter) {
+        Ok(filter) => filter,
+        Err(why) => {
+            inori_error!("Purge", "{}", why);
+            PurgeJob::remove();
+
+            return;
+        },
+    };
+
+    let user_id = ctx.cache.current_user_id().await;
+    let mut last_report = Instant::now();
+    let mut retries = 0;
+
+    report_progress(ctx, &mut job, "Purging", false).await;
+    job.save();
+
+    while let Some(idx) = job.targets.iter().position(|target| !target.done) {
+        if cancel.load(Ordering::Relaxed) {
+            PurgeJob::remove();
+            report_progress(ctx, &mut job, "Cancelled", true).await;
+            inori_success!("Purge", "Cancelled purge, deleted {} messages", job.deleted);
+
+            return;
+        }
+
+        let target = job.targets[idx].clone();
+        let messages = ChannelId(target.channel_id)
+            .messages(&ctx.http, |r| match target.before {
+                Some(before) => r.before(MessageId(before)).limit(100),
+                None => r.limit(100),
+            })
+            .await;
+
+        let messages = match messages {
+            Ok(messages) => {
+                retries = 0;
+
+                messages
+            },
+            Err(why) if is_retryable(&why) && retries + 1 < MAX_RETRIES => {
+                retries += 1;
+
+                // Back off and retry, progress is saved so a restart picks up
+                // where we left off
+                delay_for(Duration::from_secs(5 * retries)).await;
+
+                continue;
+            },
+            Err(why) => {
+                inori_warn!("Purge", "Unable to get messages in {}, skipping: {}", target.channel_id, why);
+                job.targets[idx].done = true;
+                job.save();
+                retries = 0;
+
+                continue;
+            },
+        };
+
+        if messages.is_empty() {
+            job.targets[idx].done = true;
+        }
+
+        for message in &messages {
+            if cancel.load(Ordering::Relaxed) {
+                break;
+            }
+
+            job.scanned += 1;
+            job.targets[idx].before = Some(message.id.0);
+
+            if filter.is_exhausted(message) {
+                job.targets[idx].done = true;
+
+                break;
+            }
+
+            if message.author.id == user_id
+                && Some(message.id.0) != job.status_message
+                && filter.matches(message)
+            {
+                match ctx.http.delete_message(message.channel_id.0, message.id.0).await {
+                    Ok(_) => job.deleted += 1,
+                    Err(why) => inori_warn!("Purge", "Unable to delete {}: {}", message.id.0, why),
+                }
+            }
+        }
+
+        job.save();
+
+        if last_report.elapsed().as_secs() >= REPORT_INTERVAL {
+            report_progress(ctx, &mut job, "Purging", false).await;
+            job.save();
+            last_report = Instant::now();
+        }
+    }
+
+    PurgeJob::remove();
+    report_progress(ctx, &mut job, "Finished", true).await;
+    inori_success!("Purge", "Finished purge, deleted {} messages", job.deleted);
+}
@@ -1,7 +1,8 @@
 use std::{collections::HashMap, sync::Arc};
 
+use chrono::Duration;
 use serde_derive::{Deserialize, Serialize};
-use serenity::prelude::TypeMapKey;
+use serenity::{prelude::TypeMapKey, utils::Colour};
 use tokio::sync::Mutex;
 
 #[derive(Default, Serialize, Deserialize, Clone)]
@@ -38,6 +39,321 @@ pub struct SlotBotConfig {
     pub blacklisted_guilds: Vec<u64>,
 }
 
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct SnippetConfig {
+    pub enabled: bool,
+    pub prefix:  String,
+    pub suffix:  String,
+}
+
+impl Default for SnippetConfig {
+    fn default() -> SnippetConfig {
+        SnippetConfig {
+            enabled: false,
+            prefix:  "{{".to_string(),
+            suffix:  "}}".to_string(),
+        }
+    }
+}
+
+// A year in hours, chrono panics on durations much longer than this
+pub const MAX_LOG_AGE: u64 = 24 * 365;
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct MessageLoggerConfig {
+    pub enabled:      bool,
+    pub dms:          bool,
+    pub guilds:       Vec<u64>,
+    pub max_messages: usize,
+    // Hours
+    pub max_age:      u64,
+}
+
+impl Default for MessageLoggerConfig {
+    fn default() -> MessageLoggerConfig {
+        MessageLoggerConfig {
+            enabled:      false,
+            dms:          true,
+            guilds:       Vec::new(),
+            max_messages: 5000,
+            max_age:      24,
+        }
+    }
+}
+
+impl MessageLoggerConfig {
+    pub fn is_logged(&self, guild_id: Option<u64>) -> bool {
+        self.enabled
+            && match guild_id {
+                Some(guild_id) => self.guilds.contains(&guild_id),
+                None => self.dms,
+            }
+    }
+
+    // Clamped as the config file could have any value
+    pub fn max_age(&self) -> Duration {
+        Duration::hours(self.max_age.min(MAX_LOG_AGE) as i64)
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct HighlightConfig {
+    pub enabled:  bool,
+    pub mentions: bool,
+    // Channel to forward highlights to, 0 logs them to the console
+    pub channel:  u64,
+    // Seconds
+    pub cooldown: u64,
+    pub context:  u64,
+    pub keywords: Vec<String>,
+    // Keywords only matched in a guild, keyed by guild ID
+    pub guilds:   HashMap<String, Vec<String>>,
+}
+
+impl Default for HighlightConfig {
+    fn default() -> HighlightConfig {
+        HighlightConfig {
+            enabled:  false,
+            mentions: false,
+            channel:  0,
+            cooldown: 300,
+            context:  3,
+            keywords: Vec::new(),
+            guilds:   HashMap::new(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct AnimeConfig {
+    // Provider used for anime lookups, either "jikan" or "anilist"
+    pub provider: String,
+    // Try the other provider when the main one errors
+    pub fallback: bool,
+}
+
+impl Default for AnimeConfig {
+    fn default() -> AnimeConfig {
+        AnimeConfig {
+            provider: "jikan".to_string(),
+            fallback: true,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct WatchlistConfig {
+    // Notify when a show on the watchlist gets a new episode
+    pub notify:   bool,
+    // Channel to send notifications to, 0 logs them to the console
+    pub channel:  u64,
+    // Minutes between checks
+    pub interval: u64,
+}
+
+impl Default for WatchlistConfig {
+    fn default() -> WatchlistConfig {
+        WatchlistConfig {
+            notify:   true,
+            channel:  0,
+            interval: 30,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct CurrencyConfig {
+    // Provider used for crypto rates, anything else disables them
+    pub crypto: String,
+    // Minutes before cached rates are refreshed
+    pub ttl:    u64,
+}
+
+impl Default for CurrencyConfig {
+    fn default() -> CurrencyConfig {
+        CurrencyConfig {
+            crypto: "coingecko".to_string(),
+            ttl:    60,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct RustEvalConfig {
+    // Compiles and runs snippets with the local toolchain. Both run without
+    // network access in an empty root where only the toolchain and system
+    // libraries are visible, read-only. This needs unprivileged user
+    // namespaces, and code still runs as the bot's user on a shared kernel,
+    // so only enable this on a machine you trust it with
+    pub enabled:         bool,
+    pub rustc:           String,
+    pub edition:         String,
+    // Seconds
+    pub compile_timeout: u64,
+    pub timeout:         u64,
+    // MiB, only applies to the compiled program
+    pub memory:          u64,
+    // Bytes of stdout and stderr kept
+    pub output:          usize,
+}
+
+impl Default for RustEvalConfig {
+    fn default() -> RustEvalConfig {
+        RustEvalConfig {
+            enabled:         false,
+            rustc:           "rustc".to_string(),
+            edition:         "2018".to_string(),
+            compile_timeout: 30,
+            timeout:         5,
+            memory:          256,
+            output:          4000,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct CratesConfig {
+    // Base URL of the crates.io API, can point at a mirror or local stand-in
+    pub api: String,
+    // Minutes before cached lookups are refreshed
+    pub ttl: u64,
+}
+
+impl Default for CratesConfig {
+    fn default() -> CratesConfig {
+        CratesConfig {
+            api: "https://crates.io/api/v1".to_string(),
+            ttl: 60,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct ImageGenConfig {
+    // Renderer used for image gen commands, either "nekobot" or "local". The
+    // local templates are drawn from scratch so look rougher than nekobot's
+    pub renderer: String,
+    // Try the other renderer when the main one errors
+    pub fallback: bool,
+}
+
+impl Default for ImageGenConfig {
+    fn default() -> ImageGenConfig {
+        ImageGenConfig {
+            renderer: "nekobot".to_string(),
+            fallback: true,
+        }
+    }
+}
+
+#[derive(Default, Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct ThemeMode {
+    pub colour: u32,
+    pub emoji:  String,
+    pub prefix: String,
+}
+
+impl ThemeMode {
+    fn new(colour: Colour, prefix: &str) -> ThemeMode {
+        ThemeMode {
+            colour: colour.0,
+            emoji:  String::new(),
+            prefix: prefix.to_string(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct Theme {
+    pub title_format: String,
+    pub footer_text:  String,
+    pub default:      ThemeMode,
+    pub loading:      ThemeMode,
+    pub success:      ThemeMode,
+    pub info:         ThemeMode,
+    pub warning:      ThemeMode,
+    pub error:        ThemeMode,
+}
+
+impl Default for Theme {
+    fn default() -> Theme {
+        Theme {
+            title_format: "[{}]".to_string(),
+            footer_text:  String::new(),
+            default:      ThemeMode::new(Colour::FABLED_PINK, ""),
+            loading:      ThemeMode::new(Colour::BLURPLE, "Loading: "),
+            success:      ThemeMode::new(Colour::FOOYOO, "Success: "),
+            info:         ThemeMode::new(Colour::KERBAL, "Info: "),
+            warning:      ThemeMode::new(Colour::ORANGE, "Warning: "),
+            error:        ThemeMode::new(Colour::MEIBE_PINK, "Error: "),
+        }
+    }
+}
+
+impl Theme {
+    pub fn mode(&self, mode: u64) -> &ThemeMode {
+        match mode {
+            0 => &self.default,
+            1 => &self.loading,
+            2 => &self.success,
+            3 => &self.info,
+            4 => &self.warning,
+            _ => &self.error,
+        }
+    }
+
+    pub fn mode_mut(&mut self, mode: u64) -> &mut ThemeMode {
+        match mode {
+            0 => &mut self.default,
+            1 => &mut self.loading,
+            2 => &mut self.success,
+            3 => &mut self.info,
+            4 => &mut self.warning,
+            _ => &mut self.error,
+        }
+    }
+
+    pub fn format_title(&self, title: &str) -> String {
+        self.title_format.replace("{}", title)
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct ThemeConfig {
+    pub current: String,
+    pub themes:  HashMap<String, Theme>,
+}
+
+impl Default for ThemeConfig {
+    fn default() -> ThemeConfig {
+        let mut themes = HashMap::new();
+        themes.insert("default".to_string(), Theme::default());
+
+        ThemeConfig {
+            current: "default".to_string(),
+            themes,
+        }
+    }
+}
+
+impl ThemeConfig {
+    pub fn current(&self) -> Theme {
+        self.themes.get(&self.current).cloned().unwrap_or_default()
+    }
+}
+
 #[derive(Default, Serialize, Deserialize, Clone)]
 pub struct Settings {
     pub user_token: String,
@@ -45,12 +361,23 @@ pub struct Settings {
     pub global_nsfw_level: u8,
     pub is_male: bool,
     pub embed_mode: u8,
+    pub embed_overrides: HashMap<String, u8>,
     pub emoteserver: u64,
     pub nitrosniper: bool,
     pub pfp_switcher: PfpSwitcher,
     pub giveaway: GiveawayConfig,
     pub autodelete: AutoDeleteConfig,
     pub slotbot: SlotBotConfig,
+    pub snippets: SnippetConfig,
+    pub theme: ThemeConfig,
+    pub message_logger: MessageLoggerConfig,
+    pub highlight: HighlightConfig,
+    pub anime: AnimeConfig,
+    pub watchlist: WatchlistConfig,
+    pub currency: CurrencyConfig,
+    pub rust_eval: RustEvalConfig,
+    pub crates: CratesConfig,
+    pub imggen: ImageGenConfig,
     pub tags: HashMap<String, String>,
     pub sb_emotes: HashMap<String, u64>,
 }
@@ -0,0 +1,417 @@
+use std::{
+    fs,
+    path::Path,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+};
+
+use colored::Colorize;
+use serde_derive::{Deserialize, Serialize};
+use serenity::{
+    model::id::ChannelId,
+    prelude::{Context, TypeMapKey},
+};
+use tokio::{
+    sync::Mutex,
+    time::{delay_for, Duration},
+};
+
+use crate::{
+    inori_error, inori_info, inori_warn,
+    models::{
+        anime::MediaKind,
+        discord::{InoriChannelUtils, MessageCreator},
+        settings::Settings,
+    },
+    utils::anilist::AniList,
+};
+
+const WATCHLIST_FILE: &str = "watchlist.json";
+
+static CHECKER_RUNNING: AtomicBool = AtomicBool::new(false);
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WatchStatus {
+    Watching,
+    Completed,
+    OnHold,
+    Dropped,
+    Planned,
+}
+
+impl WatchStatus {
+    pub const ALL: [WatchStatus; 5] = [
+        WatchStatus::Watching,
+        WatchStatus::Completed,
+        WatchStatus::OnHold,
+        WatchStatus::Dropped,
+        WatchStatus::Planned,
+    ];
+
+    pub fn parse(status: &str) -> Option<WatchStatus> {
+        let status = status
+            .chars()
+            .filter(|c| c.is_alphanumeric())
+            .collect::<String>()
+            .to_lowercase();
+
+        match status.as_str() {
+            "watching" | "reading" | "w" => Some(WatchStatus::Watching),
+            "completed" | "done" | "c" => Some(WatchStatus::Completed),
+            "onhold" | "hold" | "paused" => Some(WatchStatus::OnHold),
+            "dropped" | "d" => Some(WatchStatus::Dropped),
+            "planned" | "plantowatch" | "plantoread" | "ptw" => Some(WatchStatus::Planned),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            WatchStatus::Watching => "Watching",
+            WatchStatus::Completed => "Completed",
+            WatchStatus::OnHold => "On Hold",
+            WatchStatus::Dropped => "Dropped",
+            WatchStatus::Planned => "Planned",
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct WatchEntry {
+    pub mal_id:   u64,
+    pub kind:     MediaKind,
+    pub title:    String,
+    pub url:      String,
+    // Episodes for anime, chapters for manga
+    pub total:    Option<u64>,
+    pub status:   WatchStatus,
+    pub progress: u64,
+    pub score:    Option<u8>,
+    // Episodes aired when the entry was last checked, used to notice new
+    // episodes
+    #[serde(default)]
+    pub aired:    Option<u64>,
+}
+
+impl WatchEntry {
+    // Sets progress, capped to the total if it's known. Finishing moves the
+    // entry to completed and starting a planned entry moves it to watching
+    pub fn set_progress(&mut self, progress: u64) {
+        self.progress = self.total.map_or(progress, |total| progress.min(total));
+
+        if self.total.map_or(false, |total| total > 0 && self.progress == total) {
+            self.status = WatchStatus::Completed;
+        } else if self.progress > 0 && self.status == WatchStatus::Planned {
+            self.status = WatchStatus::Watching;
+        }
+    }
+
+    pub fn progress_text(&self) -> String {
+        format!(
+            "{}/{}",
+            self.progress,
+            self.total.map_or("?".to_string(), |total| total.to_string())
+        )
+    }
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Watchlist {
+    pub entries: Vec<WatchEntry>,
+}
+
+// Kept behind its own lock so commands and the airing checker don't need to
+// write lock the whole TypeMap
+impl TypeMapKey for Watchlist {
+    type Value = Arc<Mutex<Watchlist>>;
+}
+
+impl Watchlist {
+    pub fn load() -> Watchlist {
+        if !Path::new(WATCHLIST_FILE).exists() {
+            return Watchlist::default();
+        }
+
+        match fs::read_to_string(WATCHLIST_FILE).map(|contents| serde_json::from_str::<Watchlist>(&contents)) {
+            Ok(Ok(watchlist)) => watchlist,
+            _ => {
+                // Keep the broken file around instead of overwriting it on the next save
+                let backup = format!("{}.bak", WATCHLIST_FILE);
+                let _ = fs::rename(WATCHLIST_FILE, &backup);
+                inori_error!("Watchlist", "Unable to read '{}', moved it to '{}'", WATCHLIST_FILE, backup);
+
+                Watchlist::default()
+            },
+        }
+    }
+
+    pub fn save(&self) {
+        let res = serde_json::to_string_pretty(self)
+            .map_err(|why| why.to_string())
+            .and_then(|contents| fs::write(WATCHLIST_FILE, contents).map_err(|why| why.to_string()));
+
+        if let Err(why) = res {
+            inori_error!("Watchlist", "Unable to save watchlist: {}", why);
+        }
+    }
+
+    // Finds an entry by MAL ID, exact title or a unique part of a title.
+    // Anime and manga IDs overlap so an ID can be prefixed with its kind
+    pub fn find(&self, query: &str) -> Result<usize, String> {
+        let words = query.split_whitespace().collect::<Vec<&str>>();
+        let (kind, id) = match words.as_slice() {
+            [kind, id] if kind.eq_ignore_ascii_case("anime") => (Some(MediaKind::Anime), *id),
+            [kind, id] if kind.eq_ignore_ascii_case("manga") => (Some(MediaKind::Manga), *id),
+            _ => (None, query),
+        };
+
+        if let Ok(mal_id) = id.parse::<u64>() {
+            let matches = self
+                .entries
+                .iter()
+                .enumerate()
+                .filter(|(_, entry)| entry.mal_id == mal_id && (kind.is_none() || kind == Some(entry.kind)))
+                .collect::<Vec<_>>();
+
+            match matches.len() {
+                0 => {},
+                1 => return Ok(matches[0].0),
+                _ => {
+                    return Err(format!(
+                        "An anime and a manga both have the MAL ID `{0}`, use `anime {0}` or `manga {0}`",
+                        mal_id
+                    ))
+                },
+            }
+        }
+
+        let lower = query.to_lowercase();
+        if let Some(idx) = self.entries.iter().position(|entry| entry.title.to_lowercase() == lower) {
+            return Ok(idx);
+        }
+
+        let matches = self
+            .entries
+            .iter()
+            .enumerate()
+            .filter(|(_, entry)| entry.title.to_lowercase().contains(&lower))
+            .collect::<Vec<_>>();
+
+        match matches.len() {
+            0 => Err(format!("Nothing in your watchlist matches `{}`", query)),
+            1 => Ok(matches[0].0),
+            _ => Err(format!(
+                "Multiple entries match, be more specific\n{}",
+                matches
+                    .iter()
+                    .map(|(_, entry)| format!("`{}` {}", entry.mal_id, entry.title))
+                    .collect::<Vec<String>>()
+                    .join("\n")
+            )),
+        }
+    }
+}
+
+// `+N` and `-N` are relative to the current progress, anything else sets it
+pub fn parse_progress(arg: &str, current: u64) -> Option<u64> {
+    if let Some(amount) = arg.strip_prefix('+') {
+        amount.parse::<u64>().ok().map(|amount| current.saturating_add(amount))
+    } else if let Some(amount) = arg.strip_prefix('-') {
+        amount.parse::<u64>().ok().map(|amount| current.saturating_sub(amount))
+    } else {
+        arg.parse::<u64>().ok()
+    }
+}
+
+// Runs for the lifetime of the bot, ready can fire more than once so only the
+// first call starts it
+pub fn start_watchlist_checker(ctx: &Context) {
+    if CHECKER_RUNNING.swap(true, Ordering::Relaxed) {
+        return;
+    }
+
+    let ctx = ctx.clone();
+    tokio::spawn(async move {
+        loop {
+            let config = {
+                let data = ctx.data.read().await;
+                let settings = data.get::<Settings>().expect("Expected Setting in TypeMap.").lock().await;
+
+                settings.watchlist.clone()
+            };
+
+            if config.notify {
+                check_airing(&ctx, config.channel).await;
+            }
+
+            delay_for(Duration::from_secs(config.interval.max(5) * 60)).await;
+        }
+    });
+}
+
+async fn check_airing(ctx: &Context, channel: u64) {
+    let mal_ids = {
+        let data = ctx.data.read().await;
+        let watchlist = data.get::<Watchlist>().expect("Expected Watchlist in TypeMap.").lock().await;
+
+        watchlist
+            .entries
+            .iter()
+            .filter(|entry| {
+                entry.kind == MediaKind::Anime
+                    && (entry.status == WatchStatus::Watching || entry.status == WatchStatus::Planned)
+            })
+            .map(|entry| entry.mal_id)
+            .collect::<Vec<u64>>()
+    };
+
+    if mal_ids.is_empty() {
+        return;
+    }
+
+    // Jikan doesn't expose how many episodes have aired so AniList is always
+    // used here
+    let aired = match AniList.aired_episodes(&mal_ids).await {
+        Ok(aired) => aired,
+        // Skip this cycle, counts are only compared against fresh data
+        Err(why) => {
+            inori_warn!("Watchlist", "Unable to check for new episodes: {}", why);
+
+            return;
+        },
+    };
+
+    let mut notifications = Vec::new();
+    {
+        let data = ctx.data.read().await;
+        let mut watchlist = data.get::<Watchlist>().expect("Expected Watchlist in TypeMap.").lock().await;
+        let mut changed = false;
+
+        for entry in watchlist.entries.iter_mut() {
+            let count = match aired.get(&entry.mal_id) {
+                Some(count) if entry.aired != Some(*count) => *count,
+                _ => continue,
+            };
+
+            // The first check only records the count
+            if entry.aired.map_or(false, |old| count > old) {
+                notifications.push(format!(
+                    "Episode {} of [{}]({}) is out, you're on episode {}",
+                    count,
+                    entry.title,
+                    entry.url,
+                    entry.progress_text()
+                ));
+            }
+
+            entry.aired = Some(count);
+            changed = true;
+        }
+
+        if changed {
+            watchlist.save();
+        }
+    }
+
+    for notification in notifications {
+        if channel == 0 {
+            inori_info!("Watchlist", "{}", notification);
+
+            continue;
+        }
+
+        if let Err(why) = ChannelId(channel)
+            .send_noret(ctx, |m: &mut MessageCreator| m.info().title("Watchlist").content(notification))
+            .await
+        {
+            inori_error!("Watchlist", "Unable to send notification: {}", why);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(mal_id: u64, title: &str, total: Option<u64>) -> WatchEntry {
+        WatchEntry {
+            mal_id,
+            kind: MediaKind::Anime,
+            title: title.to_string(),
+            url: String::new(),
+            total,
+            status: WatchStatus::Planned,
+            progress: 0,
+            score: None,
+            aired: None,
+        }
+    }
+
+    #[test]
+    fn test_parse_status() {
+        assert_eq!(WatchStatus::parse("Watching"), Some(WatchStatus::Watching));
+        assert_eq!(WatchStatus::parse("on-hold"), Some(WatchStatus::OnHold));
+        assert_eq!(WatchStatus::parse("Plan to watch"), Some(WatchStatus::Planned));
+        assert_eq!(WatchStatus::parse("finished"), None);
+    }
+
+    #[test]
+    fn test_parse_progress() {
+        assert_eq!(parse_progress("+1", 4), Some(5));
+        assert_eq!(parse_progress("-2", 1), Some(0));
+        assert_eq!(parse_progress("12", 4), Some(12));
+        assert_eq!(parse_progress("+x", 4), None);
+    }
+
+    #[test]
+    fn test_set_progress() {
+        let mut show = entry(1, "Cowboy Bebop", Some(26));
+
+        show.set_progress(1);
+        assert_eq!(show.status, WatchStatus::Watching);
+
+        show.set_progress(30);
+        assert_eq!(show.progress, 26);
+        assert_eq!(show.status, WatchStatus::Completed);
+    }
+
+    #[test]
+    fn test_find() {
+        let watchlist = Watchlist {
+            entries: vec![
+                entry(5114, "Fullmetal Alchemist: Brotherhood", Some(64)),
+                entry(121, "Fullmetal Alchemist", Some(51)),
+                entry(1, "Cowboy Bebop", Some(26)),
+            ],
+        };
+
+        assert_eq!(watchlist.find("cowboy"), Ok(2));
+        assert_eq!(watchlist.find("5114"), Ok(0));
+        assert_eq!(watchlist.find("fullmetal alchemist"), Ok(1));
+        assert!(watchlist.find("fullmetal").is_err());
+        assert!(watchlist.find("naruto").is_err());
+    }
+
+    #[test]
+    fn test_find_by_kind() {
+        let mut manga = entry(1, "Monster", Some(162));
+        manga.kind = MediaKind::Manga;
+        let watchlist = Watchlist {
+            entries: vec![
+                entry(1, "Cowboy Bebop", Some(26)),
+                manga,
+                entry(5114, "Fullmetal Alchemist", None),
+            ],
+        };
+
+        assert_eq!(watchlist.find("anime 1"), Ok(0));
+        assert_eq!(watchlist.find("Manga 1"), Ok(1));
+        assert_eq!(watchlist.find("5114"), Ok(2));
+        assert!(watchlist.find("manga 5114").is_err());
+        assert!(watchlist.find("1").unwrap_err().contains("`anime 1`"));
+    }
+}
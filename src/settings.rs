@@ -12,7 +12,11 @@ use crate::{
     inori_error, inori_info, inori_panic, inori_success,
     models::{
         discord::BasicUser,
-        settings::{AutoDeleteConfig, GiveawayConfig, PfpSwitcher, Settings, SlotBotConfig},
+        settings::{
+            AnimeConfig, AutoDeleteConfig, CratesConfig, CurrencyConfig, GiveawayConfig, HighlightConfig,
+            ImageGenConfig, MessageLoggerConfig, PfpSwitcher, RustEvalConfig, Settings, SlotBotConfig, SnippetConfig,
+            ThemeConfig, WatchlistConfig,
+        },
     },
     try_or_string_err,
 };
@@ -187,6 +191,17 @@ pub async fn setup_settings(settings: &toml::map::Map<String, toml::Value>) -> S
         settings.get("embed_mode").unwrap().as_integer().unwrap() as u8
     };
 
+    let embed_overrides =
+        if settings.contains_key("embed_overrides") && settings.get("embed_overrides").unwrap().is_table() {
+            let list = settings.get("embed_overrides").unwrap().as_table().unwrap().clone();
+            list.into_iter()
+                .filter(|(_key, val)| val.is_integer())
+                .map(|(key, val)| (key, val.as_integer().unwrap() as u8))
+                .collect::<HashMap<String, u8>>()
+        } else {
+            HashMap::new()
+        };
+
     let slotbot = if settings.contains_key("slotbot") && settings.get("slotbot").unwrap().is_table() {
         settings.get("slotbot").unwrap().as_table().unwrap().clone()
     } else {
@@ -579,6 +594,117 @@ pub async fn setup_settings(settings: &toml::map::Map<String, toml::Value>) -> S
         HashMap::new()
     };
 
+    let snippets = if settings.contains_key("snippets") && settings.get("snippets").unwrap().is_table() {
+        settings
+            .get("snippets")
+            .unwrap()
+            .clone()
+            .try_into::<SnippetConfig>()
+            .unwrap_or_default()
+    } else {
+        SnippetConfig::default()
+    };
+
+    let theme = if settings.contains_key("theme") && settings.get("theme").unwrap().is_table() {
+        settings
+            .get("theme")
+            .unwrap()
+            .clone()
+            .try_into::<ThemeConfig>()
+            .unwrap_or_default()
+    } else {
+        ThemeConfig::default()
+    };
+
+    let message_logger =
+        if settings.contains_key("message_logger") && settings.get("message_logger").unwrap().is_table() {
+            settings
+                .get("message_logger")
+                .unwrap()
+                .clone()
+                .try_into::<MessageLoggerConfig>()
+                .unwrap_or_default()
+        } else {
+            MessageLoggerConfig::default()
+        };
+
+    let highlight = if settings.contains_key("highlight") && settings.get("highlight").unwrap().is_table() {
+        settings
+            .get("highlight")
+            .unwrap()
+            .clone()
+            .try_into::<HighlightConfig>()
+            .unwrap_or_default()
+    } else {
+        HighlightConfig::default()
+    };
+
+    let anime = if settings.contains_key("anime") && settings.get("anime").unwrap().is_table() {
+        settings
+            .get("anime")
+            .unwrap()
+            .clone()
+            .try_into::<AnimeConfig>()
+            .unwrap_or_default()
+    } else {
+        AnimeConfig::default()
+    };
+
+    let watchlist = if settings.contains_key("watchlist") && settings.get("watchlist").unwrap().is_table() {
+        settings
+            .get("watchlist")
+            .unwrap()
+            .clone()
+            .try_into::<WatchlistConfig>()
+            .unwrap_or_default()
+    } else {
+        WatchlistConfig::default()
+    };
+
+    let currency = if settings.contains_key("currency") && settings.get("currency").unwrap().is_table() {
+        settings
+            .get("currency")
+            .unwrap()
+            .clone()
+            .try_into::<CurrencyConfig>()
+            .unwrap_or_default()
+    } else {
+        CurrencyConfig::default()
+    };
+
+    let rust_eval = if settings.contains_key("rust_eval") && settings.get("rust_eval").unwrap().is_table() {
+        settings
+            .get("rust_eval")
+            .unwrap()
+            .clone()
+            .try_into::<RustEvalConfig>()
+            .unwrap_or_default()
+    } else {
+        RustEvalConfig::default()
+    };
+
+    let crates = if settings.contains_key("crates") && settings.get("crates").unwrap().is_table() {
+        settings
+            .get("crates")
+            .unwrap()
+            .clone()
+            .try_into::<CratesConfig>()
+            .unwrap_or_default()
+    } else {
+        CratesConfig::default()
+    };
+
+    let imggen = if settings.contains_key("imggen") && settings.get("imggen").unwrap().is_table() {
+        settings
+            .get("imggen")
+            .unwrap()
+            .clone()
+            .try_into::<ImageGenConfig>()
+            .unwrap_or_default()
+    } else {
+        ImageGenConfig::default()
+    };
+
     let emoteserver = if settings.contains_key("emoteserver") && settings.get("emoteserver").unwrap().is_integer() {
         settings.get("emoteserver").unwrap().as_integer().unwrap() as u64
     } else {
@@ -604,12 +730,23 @@ pub async fn setup_settings(settings: &toml::map::Map<String, toml::Value>) -> S
         global_nsfw_level,
         is_male,
         embed_mode,
+        embed_overrides,
         emoteserver,
         nitrosniper,
         pfp_switcher,
         giveaway,
         autodelete,
         slotbot,
+        snippets,
+        theme,
+        message_logger,
+        highlight,
+        anime,
+        watchlist,
+        currency,
+        rust_eval,
+        crates,
+        imggen,
         tags,
         sb_emotes,
     };
@@ -0,0 +1,767 @@
+use std::{collections::HashMap, time::Duration};
+
+use async_trait::async_trait;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use reqwest::StatusCode;
+use serde::{de::DeserializeOwned, Deserialize};
+use serde_json::json;
+
+use crate::{
+    models::anime::{
+        AnimeProvider, Appearance, Character, CharacterRole, Link, Media, MediaKind, Person, Relation, VoiceRole,
+    },
+    utils::cache::cached,
+};
+
+static API_URL: &str = "https://graphql.anilist.co";
+
+// How long responses are cached for, in seconds
+const SEARCH_TTL: u64 = 60 * 60;
+const DETAILS_TTL: u64 = 24 * 60 * 60;
+
+static MEDIA_FIELDS: &str = "
+fragment fields on Media {
+    idMal siteUrl format status episodes chapters volumes season seasonYear averageScore popularity genres
+    description(asHtml: false)
+    title { romaji english }
+    coverImage { large }
+    startDate { year month day }
+    endDate { year month day }
+    rankings { rank type allTime }
+    studios(isMain: true) { nodes { name siteUrl } }
+    staff(perPage: 6) { edges { role node { name { full } siteUrl } } }
+}";
+
+static MEDIA_DETAILS: &str = "
+relations { edges { relationType node { type format siteUrl title { romaji } } } }
+characters(sort: [ROLE, RELEVANCE], perPage: 25) {
+    edges { role node { name { full } siteUrl } voiceActors(language: JAPANESE) { name { full } siteUrl } }
+}
+externalLinks { site url type }";
+
+static CHARACTER_FIELDS: &str = "id siteUrl favourites description name { full native alternative } image { large }";
+
+static STAFF_FIELDS: &str = "
+id siteUrl favourites description
+name { full native alternative }
+image { large }
+dateOfBirth { year month day }";
+
+static TAG_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?i)<br\s*/?>|</?[a-z]+[^>]*>").unwrap());
+
+#[derive(Deserialize)]
+struct GraphQLError {
+    message: String,
+}
+
+#[derive(Deserialize)]
+struct GraphQLResponse<T> {
+    data:   Option<T>,
+    #[serde(default)]
+    errors: Vec<GraphQLError>,
+}
+
+// Queries alias the root field to `result` or `results` so a single wrapper
+// works for every query
+#[derive(Deserialize)]
+struct Single<T> {
+    result: Option<T>,
+}
+
+#[derive(Deserialize)]
+struct Page<T> {
+    results: Vec<T>,
+}
+
+#[derive(Deserialize)]
+struct PageData<T> {
+    #[serde(rename = "Page")]
+    page: Page<T>,
+}
+
+#[derive(Deserialize)]
+struct Nodes<T> {
+    nodes: Option<Vec<T>>,
+}
+
+#[derive(Deserialize)]
+struct Edges<T> {
+    edges: Option<Vec<T>>,
+}
+
+#[derive(Deserialize)]
+struct Title {
+    romaji:  Option<String>,
+    english: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct Name {
+    full:        Option<String>,
+    native:      Option<String>,
+    alternative: Option<Vec<String>>,
+}
+
+#[derive(Deserialize)]
+struct Image {
+    large: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct FuzzyDate {
+    year:  Option<u64>,
+    month: Option<u64>,
+    day:   Option<u64>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Ranking {
+    rank:     u64,
+    #[serde(rename = "type")]
+    kind:     String,
+    all_time: Option<bool>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Studio {
+    name:     String,
+    site_url: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct NamedNode {
+    name:     Option<Name>,
+    site_url: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct MediaNode {
+    #[serde(rename = "type")]
+    kind:     Option<String>,
+    format:   Option<String>,
+    site_url: Option<String>,
+    title:    Option<Title>,
+}
+
+#[derive(Deserialize)]
+struct StaffEdge {
+    role: Option<String>,
+    node: Option<NamedNode>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RelationEdge {
+    relation_type: Option<String>,
+    node:          Option<MediaNode>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CharacterEdge {
+    role:         Option<String>,
+    node:         Option<NamedNode>,
+    voice_actors: Option<Vec<NamedNode>>,
+}
+
+#[derive(Deserialize)]
+struct ExternalLink {
+    site: Option<String>,
+    url:  Option<String>,
+    #[serde(rename = "type")]
+    kind: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct AniMedia {
+    id_mal:         Option<u64>,
+    site_url:       Option<String>,
+    format:         Option<String>,
+    status:         Option<String>,
+    episodes:       Option<u64>,
+    chapters:       Option<u64>,
+    volumes:        Option<u64>,
+    season:         Option<String>,
+    season_year:    Option<u64>,
+    average_score:  Option<f64>,
+    popularity:     Option<u64>,
+    genres:         Option<Vec<String>>,
+    description:    Option<String>,
+    title:          Option<Title>,
+    cover_image:    Option<Image>,
+    start_date:     Option<FuzzyDate>,
+    end_date:       Option<FuzzyDate>,
+    rankings:       Option<Vec<Ranking>>,
+    studios:        Option<Nodes<Studio>>,
+    staff:          Option<Edges<StaffEdge>>,
+    relations:      Option<Edges<RelationEdge>>,
+    characters:     Option<Edges<CharacterEdge>>,
+    external_links: Option<Vec<ExternalLink>>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CharacterMediaEdge {
+    character_role: Option<String>,
+    node:           Option<MediaNode>,
+    voice_actors:   Option<Vec<NamedNode>>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct AniCharacter {
+    id:          u64,
+    site_url:    Option<String>,
+    favourites:  Option<u64>,
+    description: Option<String>,
+    name:        Option<Name>,
+    image:       Option<Image>,
+    media:       Option<Edges<CharacterMediaEdge>>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct StaffMediaEdge {
+    character_role: Option<String>,
+    node:           Option<MediaNode>,
+    characters:     Option<Vec<NamedNode>>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct AniStaff {
+    id:              u64,
+    site_url:        Option<String>,
+    favourites:      Option<u64>,
+    description:     Option<String>,
+    name:            Option<Name>,
+    image:           Option<Image>,
+    date_of_birth:   Option<FuzzyDate>,
+    character_media: Option<Edges<StaffMediaEdge>>,
+}
+
+#[derive(Deserialize)]
+struct AiringEpisode {
+    episode: u64,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct AniAiring {
+    id_mal:              Option<u64>,
+    status:              Option<String>,
+    episodes:            Option<u64>,
+    next_airing_episode: Option<AiringEpisode>,
+}
+
+async fn post(body: &str) -> Result<String, String> {
+    let res = reqwest::Client::new()
+        .post(API_URL)
+        .header("Content-Type", "application/json")
+        .header("Accept", "application/json")
+        .body(body.to_string())
+        .send()
+        .await
+        .map_err(|_| "Unable to reach AniList".to_string())?;
+
+    let status = res.status();
+    if status == StatusCode::TOO_MANY_REQUESTS {
+        return Err("Ratelimited, try again in a bit".to_string());
+    }
+
+    let text = res.text().await.map_err(|_| "Unable to read the response".to_string())?;
+
+    if status.is_success() {
+        return Ok(text);
+    }
+
+    // Errors still come back as GraphQL responses
+    match serde_json::from_str::<GraphQLResponse<serde_json::Value>>(&text) {
+        Ok(res) if !res.errors.is_empty() => Err(res.errors[0].message.clone()),
+        _ => Err(format!("AniList returned an error ({})", status.as_str())),
+    }
+}
+
+async fn query<T: DeserializeOwned>(query: &str, variables: serde_json::Value, ttl: u64) -> Result<T, String> {
+    let body = json!({ "query": query, "variables": variables }).to_string();
+    let res = cached(&format!("{}{}", API_URL, body), Duration::from_secs(ttl), || post(&body)).await?;
+
+    parse_response(&res)
+}
+
+fn parse_response<T: DeserializeOwned>(res: &str) -> Result<T, String> {
+    let res = serde_json::from_str::<GraphQLResponse<T>>(res)
+        .map_err(|_| "Unable to parse the response from AniList".to_string())?;
+
+    match res.data {
+        Some(data) if res.errors.is_empty() => Ok(data),
+        _ => Err(res
+            .errors
+            .into_iter()
+            .next()
+            .map_or_else(|| "AniList returned no data".to_string(), |error| error.message)),
+    }
+}
+
+fn media_type(kind: MediaKind) -> &'static str {
+    match kind {
+        MediaKind::Anime => "ANIME",
+        MediaKind::Manga => "MANGA",
+    }
+}
+
+// AniList enums are SCREAMING_CASE, `TV_SHORT` becomes `TV Short`
+fn pretty_enum(value: &str) -> String {
+    value
+        .split('_')
+        .map(|word| {
+            if ["TV", "OVA", "ONA"].contains(&word) {
+                word.to_string()
+            } else {
+                let word = word.to_lowercase();
+                let mut chars = word.chars();
+
+                match chars.next() {
+                    Some(first) => first.to_uppercase().chain(chars).collect(),
+                    None => String::new(),
+                }
+            }
+        })
+        .collect::<Vec<String>>()
+        .join(" ")
+}
+
+// Descriptions contain a mix of markdown and HTML, spoilers are wrapped in
+// `~!` and `!~` which maps nicely onto Discord spoilers
+fn clean_description(description: Option<String>) -> Option<String> {
+    description.map(|description| {
+        TAG_REGEX
+            .replace_all(&description, "")
+            .replace("~!", "||")
+            .replace("!~", "||")
+            .trim()
+            .to_string()
+    })
+}
+
+fn format_date(date: &Option<FuzzyDate>) -> Option<String> {
+    let date = date.as_ref()?;
+
+    match (date.year, date.month, date.day) {
+        (Some(year), Some(month), Some(day)) => Some(format!("{}-{:02}-{:02}", year, month, day)),
+        (Some(year), Some(month), None) => Some(format!("{}-{:02}", year, month)),
+        (Some(year), None, _) => Some(year.to_string()),
+        _ => None,
+    }
+}
+
+fn full_name(name: &Option<Name>) -> String {
+    name.as_ref()
+        .and_then(|name| name.full.clone())
+        .unwrap_or_else(|| "Unknown".to_string())
+}
+
+fn media_title(title: &Option<Title>) -> String {
+    title
+        .as_ref()
+        .and_then(|title| title.romaji.clone().or_else(|| title.english.clone()))
+        .unwrap_or_else(|| "Unknown".to_string())
+}
+
+fn node_link(node: &NamedNode) -> Link {
+    Link::new(full_name(&node.name), node.site_url.clone().unwrap_or_default())
+}
+
+fn media_link(node: &MediaNode) -> Link {
+    Link::new(media_title(&node.title), node.site_url.clone().unwrap_or_default())
+}
+
+fn edges<T>(edges: Option<Edges<T>>) -> Vec<T> {
+    edges.and_then(|edges| edges.edges).unwrap_or_default()
+}
+
+impl AniMedia {
+    fn into_media(self, kind: MediaKind) -> Media {
+        let dates = match (format_date(&self.start_date), format_date(&self.end_date)) {
+            (Some(start), Some(end)) if start != end => Some(format!("{} to {}", start, end)),
+            (Some(start), _) => Some(start),
+            _ => None,
+        };
+
+        let rank = self.rankings.as_ref().and_then(|rankings| {
+            rankings
+                .iter()
+                .find(|ranking| ranking.kind == "RATED" && ranking.all_time.unwrap_or(false))
+                .map(|ranking| ranking.rank)
+        });
+
+        let genres = self
+            .genres
+            .unwrap_or_default()
+            .into_iter()
+            .map(|genre| {
+                let url = format!("https://anilist.co/search/{}?genres={}", kind.name(), genre.replace(' ', "%20"));
+
+                Link::new(genre, url)
+            })
+            .collect();
+
+        // Manga don't have studios, the story and art staff are used instead
+        let creators = match kind {
+            MediaKind::Anime => self
+                .studios
+                .and_then(|studios| studios.nodes)
+                .unwrap_or_default()
+                .into_iter()
+                .map(|studio| Link::new(studio.name, studio.site_url.unwrap_or_default()))
+                .collect(),
+            MediaKind::Manga => edges(self.staff)
+                .into_iter()
+                .filter(|edge| {
+                    edge.role
+                        .as_ref()
+                        .map_or(false, |role| role.contains("Story") || role.contains("Art"))
+                })
+                .filter_map(|edge| edge.node.as_ref().map(node_link))
+                .collect(),
+        };
+
+        let relations = edges(self.relations)
+            .into_iter()
+            .filter_map(|edge| {
+                let node = edge.node?;
+                let format = node.format.as_ref().or_else(|| node.kind.as_ref()).map(|f| pretty_enum(f));
+                let mut link = media_link(&node);
+
+                if let Some(format) = format {
+                    link.name = format!("{} ({})", link.name, format);
+                }
+
+                Some((pretty_enum(&edge.relation_type.unwrap_or_default()), link))
+            })
+            .fold(Vec::<Relation>::new(), |mut relations, (relation, link)| {
+                match relations.iter_mut().find(|r| r.relation == relation) {
+                    Some(existing) => existing.entries.push(link),
+                    None => relations.push(Relation {
+                        relation,
+                        entries: vec![link],
+                    }),
+                }
+
+                relations
+            });
+
+        let characters = edges(self.characters)
+            .into_iter()
+            .filter_map(|edge| {
+                Some(CharacterRole {
+                    character: node_link(edge.node.as_ref()?),
+                    role:      pretty_enum(&edge.role.unwrap_or_default()),
+                    actor:     edge.voice_actors.unwrap_or_default().first().map(node_link),
+                })
+            })
+            .collect();
+
+        let streaming = self
+            .external_links
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|link| link.kind.as_deref() == Some("STREAMING"))
+            .filter_map(|link| Some(Link::new(link.site?, link.url?)))
+            .collect();
+
+        Media {
+            source: "AniList",
+            kind,
+            mal_id: self.id_mal,
+            url: self.site_url.unwrap_or_default(),
+            image: self.cover_image.and_then(|image| image.large),
+            title: media_title(&self.title),
+            title_alt: self.title.and_then(|title| title.english),
+            format: self.format.map(|format| pretty_enum(&format)),
+            status: self.status.map(|status| pretty_enum(&status)),
+            dates,
+            episodes: self.episodes,
+            volumes: self.volumes,
+            chapters: self.chapters,
+            season: match (self.season, self.season_year) {
+                (Some(season), Some(year)) => Some(format!("{} {}", pretty_enum(&season), year)),
+                _ => None,
+            },
+            rating: None,
+            score: self.average_score.map(|score| score / 10.0),
+            rank,
+            members: self.popularity,
+            synopsis: clean_description(self.description),
+            genres,
+            themes: Vec::new(),
+            creators,
+            publishers: Vec::new(),
+            streaming,
+            relations,
+            characters,
+        }
+    }
+}
+
+impl From<AniCharacter> for Character {
+    fn from(character: AniCharacter) -> Character {
+        let mut anime = Vec::new();
+        let mut manga = Vec::new();
+        let mut actors: Vec<Appearance> = Vec::new();
+
+        for edge in edges(character.media) {
+            let node = match edge.node {
+                Some(node) => node,
+                None => continue,
+            };
+
+            let appearance = Appearance {
+                media: media_link(&node),
+                role:  pretty_enum(&edge.character_role.unwrap_or_default()),
+            };
+
+            if node.kind.as_deref() == Some("MANGA") {
+                manga.push(appearance);
+            } else {
+                anime.push(appearance);
+            }
+
+            // The same voice actor is listed once per anime
+            for actor in edge.voice_actors.unwrap_or_default() {
+                let link = node_link(&actor);
+
+                if !actors.iter().any(|existing| existing.media.url == link.url) {
+                    actors.push(Appearance {
+                        media: link,
+                        role:  "Japanese".to_string(),
+                    });
+                }
+            }
+        }
+
+        Character {
+            source: "AniList",
+            id: character.id,
+            url: character.site_url.unwrap_or_default(),
+            image: character.image.and_then(|image| image.large),
+            name: full_name(&character.name),
+            name_native: character.name.as_ref().and_then(|name| name.native.clone()),
+            nicknames: character
+                .name
+                .and_then(|name| name.alternative)
+                .unwrap_or_default()
+                .into_iter()
+                .filter(|name| !name.is_empty())
+                .collect(),
+            favorites: character.favourites,
+            about: clean_description(character.description),
+            anime,
+            manga,
+            actors,
+        }
+    }
+}
+
+impl From<AniStaff> for Person {
+    fn from(staff: AniStaff) -> Person {
+        let roles = edges(staff.character_media)
+            .into_iter()
+            .flat_map(|edge| {
+                let media = edge.node.as_ref().map(media_link).unwrap_or_default();
+                let role = pretty_enum(&edge.character_role.unwrap_or_default());
+
+                edge.characters
+                    .unwrap_or_default()
+                    .iter()
+                    .map(|character| VoiceRole {
+                        character: node_link(character),
+                        media:     media.clone(),
+                        role:      role.clone(),
+                    })
+                    .collect::<Vec<VoiceRole>>()
+            })
+            .collect();
+
+        Person {
+            source: "AniList",
+            id: staff.id,
+            url: staff.site_url.unwrap_or_default(),
+            image: staff.image.and_then(|image| image.large),
+            name: full_name(&staff.name),
+            alternate_names: staff
+                .name
+                .map(|name| {
+                    let mut names = name.alternative.unwrap_or_default();
+                    names.extend(name.native);
+
+                    names
+                })
+                .unwrap_or_default()
+                .into_iter()
+                .filter(|name| !name.is_empty())
+                .collect(),
+            birthday: format_date(&staff.date_of_birth),
+            favorites: staff.favourites,
+            about: clean_description(staff.description),
+            roles,
+        }
+    }
+}
+
+pub struct AniList;
+
+impl AniList {
+    // Episodes aired so far keyed by MAL ID, shows AniList has no airing
+    // information for are left out. Always fetched fresh since it's polled,
+    // a failed request is an error rather than an old count
+    pub async fn aired_episodes(&self, mal_ids: &[u64]) -> Result<HashMap<u64, u64>, String> {
+        let gql = "query ($ids: [Int]) { Page(perPage: 50) { results: media(idMal_in: $ids, type: ANIME) { idMal \
+                   status episodes nextAiringEpisode { episode } } } }";
+        let mut aired = HashMap::new();
+
+        for ids in mal_ids.chunks(50) {
+            // A ttl of 0 skips the cache, stale data is worse than none for polling
+            let data = query::<PageData<AniAiring>>(gql, json!({ "ids": ids }), 0).await?;
+
+            for media in data.page.results {
+                let count = match (media.next_airing_episode, media.status.as_deref()) {
+                    (Some(next), _) => next.episode.saturating_sub(1),
+                    (None, Some("FINISHED")) => media.episodes.unwrap_or_default(),
+                    _ => continue,
+                };
+
+                if let Some(mal_id) = media.id_mal {
+                    aired.insert(mal_id, count);
+                }
+            }
+        }
+
+        Ok(aired)
+    }
+}
+
+#[async_trait]
+impl AnimeProvider for AniList {
+    fn name(&self) -> &'static str {
+        "anilist"
+    }
+
+    async fn search_media(&self, kind: MediaKind, search: &str) -> Result<Vec<Media>, String> {
+        let gql = format!(
+            "query ($search: String, $type: MediaType) {{ Page(perPage: 10) {{ results: media(search: $search, type: \
+             $type, sort: SEARCH_MATCH) {{ ...fields }} }} }} {}",
+            MEDIA_FIELDS
+        );
+
+        query::<PageData<AniMedia>>(&gql, json!({ "search": search, "type": media_type(kind) }), SEARCH_TTL)
+            .await
+            .map(|data| data.page.results.into_iter().map(|media| media.into_media(kind)).collect())
+    }
+
+    async fn media(&self, kind: MediaKind, mal_id: u64) -> Result<Media, String> {
+        let gql = format!(
+            "query ($id: Int, $type: MediaType) {{ result: Media(idMal: $id, type: $type) {{ ...fields {} }} }} {}",
+            MEDIA_DETAILS, MEDIA_FIELDS
+        );
+
+        query::<Single<AniMedia>>(&gql, json!({ "id": mal_id, "type": media_type(kind) }), DETAILS_TTL)
+            .await?
+            .result
+            .map(|media| media.into_media(kind))
+            .ok_or_else(|| "Nothing was found".to_string())
+    }
+
+    async fn search_characters(&self, search: &str) -> Result<Vec<Character>, String> {
+        let gql = format!(
+            "query ($search: String) {{ Page(perPage: 10) {{ results: characters(search: $search) {{ {} }} }} }}",
+            CHARACTER_FIELDS
+        );
+
+        query::<PageData<AniCharacter>>(&gql, json!({ "search": search }), SEARCH_TTL)
+            .await
+            .map(|data| data.page.results.into_iter().map(Character::from).collect())
+    }
+
+    async fn character(&self, id: u64) -> Result<Character, String> {
+        let gql = format!(
+            "query ($id: Int) {{ result: Character(id: $id) {{ {} media(perPage: 25, sort: POPULARITY_DESC) {{ \
+             edges {{ characterRole node {{ type format siteUrl title {{ romaji }} }} voiceActors(language: \
+             JAPANESE) {{ name {{ full }} siteUrl }} }} }} }} }}",
+            CHARACTER_FIELDS
+        );
+
+        query::<Single<AniCharacter>>(&gql, json!({ "id": id }), DETAILS_TTL)
+            .await?
+            .result
+            .map(Character::from)
+            .ok_or_else(|| "Nothing was found".to_string())
+    }
+
+    async fn search_people(&self, search: &str) -> Result<Vec<Person>, String> {
+        let gql = format!(
+            "query ($search: String) {{ Page(perPage: 10) {{ results: staff(search: $search) {{ {} }} }} }}",
+            STAFF_FIELDS
+        );
+
+        query::<PageData<AniStaff>>(&gql, json!({ "search": search }), SEARCH_TTL)
+            .await
+            .map(|data| data.page.results.into_iter().map(Person::from).collect())
+    }
+
+    async fn person(&self, id: u64) -> Result<Person, String> {
+        let gql = format!(
+            "query ($id: Int) {{ result: Staff(id: $id) {{ {} characterMedia(perPage: 30, sort: POPULARITY_DESC) {{ \
+             edges {{ characterRole node {{ siteUrl title {{ romaji }} }} characters {{ name {{ full }} siteUrl }} \
+             }} }} }} }}",
+            STAFF_FIELDS
+        );
+
+        query::<Single<AniStaff>>(&gql, json!({ "id": id }), DETAILS_TTL)
+            .await?
+            .result
+            .map(Person::from)
+            .ok_or_else(|| "Nothing was found".to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pretty_enum() {
+        assert_eq!(pretty_enum("TV_SHORT"), "TV Short");
+        assert_eq!(pretty_enum("NOT_YET_RELEASED"), "Not Yet Released");
+        assert_eq!(pretty_enum("SIDE_STORY"), "Side Story");
+    }
+
+    #[test]
+    fn test_clean_description() {
+        assert_eq!(
+            clean_description(Some("<i>Hello</i><br>world ~!spoiler!~".to_string())),
+            Some("Helloworld ||spoiler||".to_string())
+        );
+    }
+
+    #[test]
+    fn test_format_date() {
+        let date = |year, month, day| {
+            Some(FuzzyDate {
+                year,
+                month,
+                day,
+            })
+        };
+
+        assert_eq!(format_date(&date(Some(2011), Some(4), Some(6))), Some("2011-04-06".to_string()));
+        assert_eq!(format_date(&date(Some(2011), None, None)), Some("2011".to_string()));
+        assert_eq!(format_date(&date(None, Some(4), None)), None);
+    }
+}
@@ -0,0 +1,373 @@
+use std::path::{Path, PathBuf};
+
+use chrono::Utc;
+use colored::Colorize;
+use serde_derive::{Deserialize, Serialize};
+use serenity::model::channel::{Channel, Embed, Message};
+use tokio::fs;
+
+use crate::inori_warn;
+
+pub const BACKUP_DIR: &str = "backups";
+
+static IMAGE_EXTENSIONS: [&str; 5] = ["png", "jpg", "jpeg", "gif", "webp"];
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ArchivedAttachment {
+    pub filename: String,
+    pub url:      String,
+    pub size:     u64,
+    // Relative to the archive directory, None if the download failed
+    pub path:     Option<String>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ArchivedEmbedField {
+    pub name:   String,
+    pub value:  String,
+    pub inline: bool,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ArchivedEmbed {
+    pub author:      Option<String>,
+    pub title:       Option<String>,
+    pub url:         Option<String>,
+    pub description: Option<String>,
+    pub colour:      u32,
+    pub fields:      Vec<ArchivedEmbedField>,
+    pub image:       Option<String>,
+    pub thumbnail:   Option<String>,
+    pub footer:      Option<String>,
+}
+
+impl From<&Embed> for ArchivedEmbed {
+    fn from(embed: &Embed) -> ArchivedEmbed {
+        ArchivedEmbed {
+            author:      embed.author.as_ref().map(|author| author.name.clone()),
+            title:       embed.title.clone(),
+            url:         embed.url.clone(),
+            description: embed.description.clone(),
+            colour:      embed.colour.0,
+            fields:      embed
+                .fields
+                .iter()
+                .map(|field| ArchivedEmbedField {
+                    name:   field.name.clone(),
+                    value:  field.value.clone(),
+                    inline: field.inline,
+                })
+                .collect(),
+            image:       embed.image.as_ref().map(|image| image.url.clone()),
+            thumbnail:   embed.thumbnail.as_ref().map(|thumbnail| thumbnail.url.clone()),
+            footer:      embed.footer.as_ref().map(|footer| footer.text.clone()),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ArchivedMessage {
+    pub id:          u64,
+    pub author_id:   u64,
+    pub author:      String,
+    pub timestamp:   String,
+    pub edited:      Option<String>,
+    pub content:     String,
+    pub attachments: Vec<ArchivedAttachment>,
+    pub embeds:      Vec<ArchivedEmbed>,
+    pub reply_to:    Option<u64>,
+}
+
+impl From<&Message> for ArchivedMessage {
+    fn from(msg: &Message) -> ArchivedMessage {
+        ArchivedMessage {
+            id:          msg.id.0,
+            author_id:   msg.author.id.0,
+            author:      msg.author.tag(),
+            timestamp:   msg.timestamp.to_rfc3339(),
+            edited:      msg.edited_timestamp.map(|edited| edited.to_rfc3339()),
+            content:     msg.content.clone(),
+            attachments: msg
+                .attachments
+                .iter()
+                .map(|attachment| ArchivedAttachment {
+                    filename: attachment.filename.clone(),
+                    url:      attachment.url.clone(),
+                    size:     attachment.size,
+                    path:     None,
+                })
+                .collect(),
+            embeds:      msg.embeds.iter().map(ArchivedEmbed::from).collect(),
+            reply_to:    msg
+                .message_reference
+                .as_ref()
+                .and_then(|reference| reference.message_id)
+                .map(|id| id.0),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Archive {
+    pub channel_id:  u64,
+    pub channel:     String,
+    pub exported_at: String,
+    pub messages:    Vec<ArchivedMessage>,
+}
+
+pub fn channel_name(channel: &Channel) -> String {
+    match channel {
+        Channel::Guild(channel) => format!("#{}", channel.name),
+        Channel::Private(channel) => format!("DM with {}", channel.recipient.tag()),
+        _ => "Unknown".to_string(),
+    }
+}
+
+pub fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;")
+}
+
+fn sanitize_filename(filename: &str) -> String {
+    filename
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '.' || c == '-' || c == '_' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+fn is_image(filename: &str) -> bool {
+    Path::new(filename)
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .map_or(false, |ext| IMAGE_EXTENSIONS.contains(&ext.to_lowercase().as_str()))
+}
+
+fn render_embed(embed: &ArchivedEmbed) -> String {
+    let mut html = format!("<div class=\"embed\" style=\"border-color: #{:06X}\">", embed.colour);
+
+    if let Some(author) = &embed.author {
+        html.push_str(&format!("<div class=\"embed-author\">{}</div>", escape_html(author)));
+    }
+
+    match (&embed.title, &embed.url) {
+        (Some(title), Some(url)) => html.push_str(&format!(
+            "<div class=\"embed-title\"><a href=\"{}\">{}</a></div>",
+            escape_html(url),
+            escape_html(title)
+        )),
+        (Some(title), None) => html.push_str(&format!("<div class=\"embed-title\">{}</div>", escape_html(title))),
+        _ => {},
+    }
+
+    if let Some(description) = &embed.description {
+        html.push_str(&format!(
+            "<div class=\"embed-description\">{}</div>",
+            escape_html(description).replace('\n', "<br>")
+        ));
+    }
+
+    for field in &embed.fields {
+        html.push_str(&format!(
+            "<div class=\"embed-field\"><b>{}</b><br>{}</div>",
+            escape_html(&field.name),
+            escape_html(&field.value).replace('\n', "<br>")
+        ));
+    }
+
+    if let Some(image) = embed.image.as_ref().or_else(|| embed.thumbnail.as_ref()) {
+        html.push_str(&format!("<img src=\"{}\">", escape_html(image)));
+    }
+
+    if let Some(footer) = &embed.footer {
+        html.push_str(&format!("<div class=\"embed-footer\">{}</div>", escape_html(footer)));
+    }
+
+    html.push_str("</div>");
+
+    html
+}
+
+pub fn render_html(archive: &Archive) -> String {
+    let mut html = format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{}</title>\n<style>\nbody {{ \
+         background: #36393F; color: #DCDDDE; font-family: sans-serif; }}\n.message {{ padding: 4px 16px; }}\n.author \
+         {{ font-weight: bold; color: #FFFFFF; }}\n.timestamp, .reply, .edited {{ color: #72767D; font-size: 12px; \
+         }}\n.reply a {{ color: #72767D; }}\n.embed {{ border-left: 4px solid; background: #2F3136; padding: 8px; \
+         margin: 4px 0; max-width: 520px; }}\n.embed-title {{ font-weight: bold; }}\n.embed-footer {{ font-size: \
+         12px; color: #72767D; }}\nimg {{ max-width: 400px; display: block; }}\na {{ color: #00B0F4; }}\n</style>\n\
+         </head>\n<body>\n<h2>{}</h2>\n<p class=\"timestamp\">Exported {} - {} messages</p>\n",
+        escape_html(&archive.channel),
+        escape_html(&archive.channel),
+        escape_html(&archive.exported_at),
+        archive.messages.len()
+    );
+
+    for message in &archive.messages {
+        html.push_str(&format!("<div class=\"message\" id=\"{}\">\n", message.id));
+
+        if let Some(reply_to) = message.reply_to {
+            html.push_str(&format!(
+                "<div class=\"reply\">Replying to <a href=\"#{}\">{}</a></div>\n",
+                reply_to, reply_to
+            ));
+        }
+
+        html.push_str(&format!(
+            "<span class=\"author\">{}</span> <span class=\"timestamp\">{}</span>",
+            escape_html(&message.author),
+            escape_html(&message.timestamp)
+        ));
+
+        if message.edited.is_some() {
+            html.push_str(" <span class=\"edited\">(edited)</span>");
+        }
+
+        html.push_str(&format!(
+            "\n<div class=\"content\">{}</div>\n",
+            escape_html(&message.content).replace('\n', "<br>")
+        ));
+
+        for attachment in &message.attachments {
+            let src = escape_html(attachment.path.as_ref().unwrap_or(&attachment.url));
+
+            if is_image(&attachment.filename) {
+                html.push_str(&format!("<a href=\"{}\"><img src=\"{}\"></a>\n", src, src));
+            } else {
+                html.push_str(&format!(
+                    "<div><a href=\"{}\">{}</a></div>\n",
+                    src,
+                    escape_html(&attachment.filename)
+                ));
+            }
+        }
+
+        for embed in &message.embeds {
+            html.push_str(&render_embed(embed));
+            html.push('\n');
+        }
+
+        html.push_str("</div>\n");
+    }
+
+    html.push_str("</body>\n</html>\n");
+
+    html
+}
+
+// Writes messages.json, transcript.html and downloaded attachments into a
+// new directory under BACKUP_DIR, returns the directory
+pub async fn write_archive(channel: &str, channel_id: u64, messages: &[Message]) -> Result<PathBuf, String> {
+    let now = Utc::now();
+    let dir = Path::new(BACKUP_DIR).join(format!("{}-{}", channel_id, now.format("%Y%m%d-%H%M%S")));
+    let attachment_dir = dir.join("attachments");
+
+    if let Err(why) = fs::create_dir_all(&attachment_dir).await {
+        return Err(format!("Unable to create '{}': {}", attachment_dir.display(), why));
+    }
+
+    let mut messages = messages.iter().collect::<Vec<&Message>>();
+    messages.sort_by_key(|message| message.id.0);
+
+    let mut archived = Vec::new();
+    for message in messages {
+        let mut archived_message = ArchivedMessage::from(message);
+
+        for (idx, attachment) in message.attachments.iter().enumerate() {
+            let filename = format!("{}-{}", message.id.0, sanitize_filename(&attachment.filename));
+
+            let res = match attachment.download().await {
+                Ok(bytes) => fs::write(attachment_dir.join(&filename), bytes)
+                    .await
+                    .map_err(|why| why.to_string()),
+                Err(why) => Err(why.to_string()),
+            };
+
+            match res {
+                Ok(_) => archived_message.attachments[idx].path = Some(format!("attachments/{}", filename)),
+                Err(why) => inori_warn!("Backup", "Unable to download {}: {}", attachment.url, why),
+            }
+        }
+
+        archived.push(archived_message);
+    }
+
+    let archive = Archive {
+        channel_id,
+        channel: channel.to_string(),
+        exported_at: now.to_rfc3339(),
+        messages: archived,
+    };
+
+    let json = match serde_json::to_string_pretty(&archive) {
+        Ok(json) => json,
+        Err(why) => return Err(format!("Unable to serialize archive: {}", why)),
+    };
+
+    if let Err(why) = fs::write(dir.join("messages.json"), json).await {
+        return Err(format!("Unable to write messages.json: {}", why));
+    }
+
+    if let Err(why) = fs::write(dir.join("transcript.html"), render_html(&archive)).await {
+        return Err(format!("Unable to write transcript.html: {}", why));
+    }
+
+    Ok(dir)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_escape_html() {
+        assert_eq!(
+            escape_html("<b>\"Tom\" & 'Jerry'</b>"),
+            "&lt;b&gt;&quot;Tom&quot; &amp; &#39;Jerry&#39;&lt;/b&gt;"
+        );
+    }
+
+    #[test]
+    fn test_sanitize_filename() {
+        assert_eq!(sanitize_filename("my file (1).png"), "my_file__1_.png");
+        assert_eq!(sanitize_filename("../../etc/passwd"), ".._.._etc_passwd");
+    }
+
+    #[test]
+    fn test_render_html() {
+        let archive = Archive {
+            channel_id:  1,
+            channel:     "#general".to_string(),
+            exported_at: "2021-01-01T00:00:00+00:00".to_string(),
+            messages:    vec![ArchivedMessage {
+                id:          2,
+                author_id:   3,
+                author:      "Inori#0001".to_string(),
+                timestamp:   "2021-01-01T00:00:00+00:00".to_string(),
+                edited:      None,
+                content:     "<script>\nhi".to_string(),
+                attachments: vec![ArchivedAttachment {
+                    filename: "cat.png".to_string(),
+                    url:      "https://cdn.discordapp.com/cat.png".to_string(),
+                    size:     10,
+                    path:     Some("attachments/2-cat.png".to_string()),
+                }],
+                embeds:      Vec::new(),
+                reply_to:    Some(1),
+            }],
+        };
+
+        let html = render_html(&archive);
+        assert!(html.contains("&lt;script&gt;<br>hi"));
+        assert!(html.contains("<img src=\"attachments/2-cat.png\">"));
+        assert!(html.contains("<a href=\"#1\">1</a>"));
+    }
+}
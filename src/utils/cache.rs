@@ -0,0 +1,134 @@
+use std::{
+    future::Future,
+    path::PathBuf,
+    sync::atomic::{AtomicU64, Ordering},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use colored::Colorize;
+use reqwest::{RequestBuilder, StatusCode};
+use tokio::fs;
+
+use crate::inori_warn;
+
+pub const CACHE_DIR: &str = "cache";
+// Entries past their ttl are still used when fetching fails, until they're
+// this old
+const MAX_AGE: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+// Seconds between looking for entries to prune
+const PRUNE_INTERVAL: u64 = 60 * 60;
+
+static LAST_PRUNE: AtomicU64 = AtomicU64::new(0);
+
+// `DefaultHasher` can change between Rust versions and would leave the old
+// entries behind, blake3 is stable and won't collide
+fn cache_path(key: &str) -> PathBuf {
+    PathBuf::from(CACHE_DIR).join(blake3::hash(key.as_bytes()).to_hex().as_str())
+}
+
+fn age(modified: SystemTime) -> Duration {
+    SystemTime::now().duration_since(modified).unwrap_or_default()
+}
+
+async fn read_cache(key: &str, ttl: Option<Duration>) -> Option<String> {
+    let path = cache_path(key);
+
+    if let Some(ttl) = ttl {
+        let modified = fs::metadata(&path).await.ok()?.modified().ok()?;
+
+        if age(modified) > ttl {
+            return None;
+        }
+    }
+
+    fs::read_to_string(&path).await.ok()
+}
+
+async fn write_cache(key: &str, content: &str) {
+    if let Err(why) = fs::create_dir_all(CACHE_DIR).await {
+        inori_warn!("Cache", "Unable to create cache directory: {}", why);
+
+        return;
+    }
+
+    if let Err(why) = fs::write(cache_path(key), content).await {
+        inori_warn!("Cache", "Unable to write cache entry: {}", why);
+    }
+
+    prune_cache().await;
+}
+
+// Removes entries older than `MAX_AGE`, at most once per `PRUNE_INTERVAL`
+async fn prune_cache() {
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+    let last = LAST_PRUNE.load(Ordering::Relaxed);
+
+    if now < last.saturating_add(PRUNE_INTERVAL)
+        || LAST_PRUNE
+            .compare_exchange(last, now, Ordering::Relaxed, Ordering::Relaxed)
+            .is_err()
+    {
+        return;
+    }
+
+    let mut entries = match fs::read_dir(CACHE_DIR).await {
+        Ok(entries) => entries,
+        Err(_) => return,
+    };
+
+    while let Ok(Some(entry)) = entries.next_entry().await {
+        let modified = entry.metadata().await.ok().and_then(|metadata| metadata.modified().ok());
+
+        if matches!(modified, Some(modified) if age(modified) > MAX_AGE) {
+            if let Err(why) = fs::remove_file(entry.path()).await {
+                inori_warn!("Cache", "Unable to remove expired cache entry: {}", why);
+            }
+        }
+    }
+}
+
+// Returns the cached value for `key` if it's younger than `ttl`, otherwise
+// runs `fetch` and caches the result. If fetching fails a stale entry is
+// returned instead so lookups keep working while offline. A `ttl` of 0 is
+// never cached
+pub async fn cached<F, Fut>(key: &str, ttl: Duration, fetch: F) -> Result<String, String>
+where
+    F: FnOnce() -> Fut,
+    Fut: Future<Output = Result<String, String>>, {
+    if ttl.as_secs() == 0 {
+        return fetch().await;
+    }
+
+    if let Some(content) = read_cache(key, Some(ttl)).await {
+        return Ok(content);
+    }
+
+    match fetch().await {
+        Ok(content) => {
+            write_cache(key, &content).await;
+
+            Ok(content)
+        },
+        Err(why) => read_cache(key, None).await.ok_or(why),
+    }
+}
+
+pub async fn fetch_text(url: &str) -> Result<String, String> {
+    fetch_request(reqwest::Client::new().get(url)).await
+}
+
+// For APIs that need headers, such as a User-Agent, on every request
+pub async fn fetch_request(request: RequestBuilder) -> Result<String, String> {
+    let res = request.send().await.map_err(|_| "Unable to reach the API".to_string())?;
+
+    match res.status() {
+        StatusCode::OK => res.text().await.map_err(|_| "Unable to read the response".to_string()),
+        StatusCode::NOT_FOUND => Err("Nothing was found".to_string()),
+        StatusCode::TOO_MANY_REQUESTS => Err("Ratelimited, try again in a bit".to_string()),
+        status => Err(format!("The API returned an error ({})", status.as_str())),
+    }
+}
+
+pub async fn get_cached(url: &str, ttl: Duration) -> Result<String, String> {
+    cached(url, ttl, || fetch_text(url)).await
+}
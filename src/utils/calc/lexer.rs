@@ -0,0 +1,209 @@
+use num_bigint::BigInt;
+use num_traits::Num;
+
+use super::CalcError;
+
+// Character offsets into the input, used to point at errors
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Span {
+    pub start: usize,
+    pub end:   usize,
+}
+
+impl Span {
+    pub fn new(start: usize, end: usize) -> Span {
+        Span {
+            start,
+            end,
+        }
+    }
+
+    pub fn to(self, other: Span) -> Span {
+        Span::new(self.start, other.end)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Token {
+    Int(BigInt),
+    Float(f64),
+    Ident(String),
+    Plus,
+    Minus,
+    Star,
+    Slash,
+    Percent,
+    Caret,
+    Bang,
+    LParen,
+    RParen,
+    Comma,
+    Equals,
+    Semicolon,
+}
+
+impl Token {
+    pub fn describe(&self) -> String {
+        match self {
+            Token::Int(int) => format!("`{}`", int),
+            Token::Float(float) => format!("`{}`", float),
+            Token::Ident(name) => format!("`{}`", name),
+            Token::Plus => "`+`".to_string(),
+            Token::Minus => "`-`".to_string(),
+            Token::Star => "`*`".to_string(),
+            Token::Slash => "`/`".to_string(),
+            Token::Percent => "`%`".to_string(),
+            Token::Caret => "`^`".to_string(),
+            Token::Bang => "`!`".to_string(),
+            Token::LParen => "`(`".to_string(),
+            Token::RParen => "`)`".to_string(),
+            Token::Comma => "`,`".to_string(),
+            Token::Equals => "`=`".to_string(),
+            Token::Semicolon => "`;`".to_string(),
+        }
+    }
+}
+
+fn is_ident_start(c: char) -> bool {
+    c.is_alphabetic() || c == '_' || c == '°' || c == 'µ'
+}
+
+fn is_ident_char(c: char) -> bool {
+    is_ident_start(c) || c.is_ascii_digit()
+}
+
+pub fn tokenize(input: &str) -> Result<Vec<(Token, Span)>, CalcError> {
+    let chars = input.chars().collect::<Vec<char>>();
+    let mut tokens = Vec::new();
+    let mut idx = 0;
+
+    while idx < chars.len() {
+        let start = idx;
+        let c = chars[idx];
+
+        if c.is_whitespace() {
+            idx += 1;
+
+            continue;
+        }
+
+        let token = if c.is_ascii_digit() || (c == '.' && chars.get(idx + 1).map_or(false, char::is_ascii_digit)) {
+            let (token, end) = number(&chars, idx)?;
+            idx = end;
+
+            token
+        } else if is_ident_start(c) {
+            while idx < chars.len() && is_ident_char(chars[idx]) {
+                idx += 1;
+            }
+
+            Token::Ident(chars[start..idx].iter().collect())
+        } else {
+            idx += 1;
+
+            match c {
+                '+' => Token::Plus,
+                '-' | '−' => Token::Minus,
+                '*' if chars.get(idx) == Some(&'*') => {
+                    idx += 1;
+
+                    Token::Caret
+                },
+                '*' | '×' | '·' => Token::Star,
+                '/' | '÷' => Token::Slash,
+                '%' => Token::Percent,
+                '^' => Token::Caret,
+                '!' => Token::Bang,
+                '(' => Token::LParen,
+                ')' => Token::RParen,
+                ',' => Token::Comma,
+                '=' => Token::Equals,
+                ';' => Token::Semicolon,
+                _ => return Err(CalcError::new(format!("Unexpected character `{}`", c), Span::new(start, idx))),
+            }
+        };
+
+        tokens.push((token, Span::new(start, idx)));
+    }
+
+    Ok(tokens)
+}
+
+fn number(chars: &[char], start: usize) -> Result<(Token, usize), CalcError> {
+    let mut idx = start;
+
+    // Prefixed integers, 0xff, 0b1010 and 0o17
+    if chars[idx] == '0' {
+        let radix = match chars.get(idx + 1) {
+            Some('x') | Some('X') => Some(16),
+            Some('b') | Some('B') => Some(2),
+            Some('o') | Some('O') => Some(8),
+            _ => None,
+        };
+
+        if let Some(radix) = radix {
+            idx += 2;
+            while idx < chars.len() && (chars[idx].is_digit(radix) || chars[idx] == '_') {
+                idx += 1;
+            }
+
+            let digits = chars[start + 2..idx].iter().filter(|c| **c != '_').collect::<String>();
+
+            // Something like `0b` on its own or `0b102`
+            if digits.is_empty() || chars.get(idx).map_or(false, |c| c.is_ascii_alphanumeric()) {
+                while idx < chars.len() && chars[idx].is_ascii_alphanumeric() {
+                    idx += 1;
+                }
+
+                return Err(CalcError::new("Invalid number", Span::new(start, idx)));
+            }
+
+            let int = BigInt::from_str_radix(&digits, radix)
+                .map_err(|_| CalcError::new("Invalid number", Span::new(start, idx)))?;
+
+            return Ok((Token::Int(int), idx));
+        }
+    }
+
+    let mut is_float = false;
+    while idx < chars.len() && (chars[idx].is_ascii_digit() || chars[idx] == '_') {
+        idx += 1;
+    }
+
+    if chars.get(idx) == Some(&'.') && chars.get(idx + 1).map_or(false, char::is_ascii_digit) {
+        is_float = true;
+        idx += 1;
+
+        while idx < chars.len() && chars[idx].is_ascii_digit() {
+            idx += 1;
+        }
+    }
+
+    // Only treated as an exponent if digits follow, otherwise `2e` is 2 * e
+    if chars.get(idx) == Some(&'e') || chars.get(idx) == Some(&'E') {
+        let digits_at = match chars.get(idx + 1) {
+            Some('+') | Some('-') => idx + 2,
+            _ => idx + 1,
+        };
+
+        if chars.get(digits_at).map_or(false, char::is_ascii_digit) {
+            is_float = true;
+            idx = digits_at;
+
+            while idx < chars.len() && chars[idx].is_ascii_digit() {
+                idx += 1;
+            }
+        }
+    }
+
+    let text = chars[start..idx].iter().filter(|c| **c != '_').collect::<String>();
+    let span = Span::new(start, idx);
+
+    let token = if is_float {
+        Token::Float(text.parse::<f64>().map_err(|_| CalcError::new("Invalid number", span))?)
+    } else {
+        Token::Int(text.parse::<BigInt>().map_err(|_| CalcError::new("Invalid number", span))?)
+    };
+
+    Ok((token, idx))
+}
@@ -0,0 +1,677 @@
+mod lexer;
+mod parser;
+mod units;
+mod value;
+
+use std::{cell::Cell, collections::HashMap};
+
+use num_integer::Integer;
+use num_traits::{Signed, ToPrimitive};
+
+use self::{
+    lexer::{tokenize, Span},
+    parser::{BinOp, Expr, ExprKind, Parser, Statement, Target},
+    units::{describe, is_dimensionless, UnitExpr},
+    value::{Number, Value},
+};
+
+// Guards against user functions which recurse forever or blow up
+// exponentially
+const MAX_DEPTH: usize = 64;
+const MAX_STEPS: usize = 100_000;
+
+static CONSTANTS: [(&str, f64); 7] = [
+    ("pi", std::f64::consts::PI),
+    ("π", std::f64::consts::PI),
+    ("e", std::f64::consts::E),
+    ("tau", std::f64::consts::TAU),
+    ("τ", std::f64::consts::TAU),
+    ("phi", 1.618_033_988_749_895),
+    ("φ", 1.618_033_988_749_895),
+];
+
+static FUNCTIONS: [&str; 23] = [
+    "sqrt", "cbrt", "abs", "exp", "ln", "log", "log2", "sin", "cos", "tan", "asin", "acos", "atan", "sinh", "cosh",
+    "tanh", "floor", "ceil", "round", "min", "max", "gcd", "lcm",
+];
+
+static RESERVED: [&str; 3] = ["ans", "in", "to"];
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct CalcError {
+    pub message: String,
+    pub span:    Option<Span>,
+}
+
+impl CalcError {
+    fn new<D: ToString>(message: D, span: Span) -> CalcError {
+        CalcError {
+            message: message.to_string(),
+            span:    Some(span),
+        }
+    }
+
+    // Shows the message with the offending part of the input underlined
+    pub fn render(&self, input: &str) -> String {
+        let span = match self.span {
+            Some(span) => span,
+            None => return self.message.clone(),
+        };
+
+        let mut line_start = 0;
+        for (idx, c) in input.chars().enumerate() {
+            if idx >= span.start {
+                break;
+            }
+
+            if c == '\n' {
+                line_start = idx + 1;
+            }
+        }
+
+        let line = input.chars().skip(line_start).take_while(|c| *c != '\n').collect::<String>();
+        let column = span.start - line_start;
+        let width = (span.end.saturating_sub(span.start)).max(1);
+
+        format!(
+            "{}\n```\n{}\n{}{}\n```",
+            self.message,
+            line,
+            " ".repeat(column),
+            "^".repeat(width)
+        )
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Function {
+    pub params: Vec<String>,
+    pub body:   Expr,
+    pub source: String,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct Session {
+    pub variables: HashMap<String, Value>,
+    pub functions: HashMap<String, Function>,
+}
+
+fn slice(input: &str, span: Span) -> String {
+    input.chars().skip(span.start).take(span.end - span.start).collect()
+}
+
+fn is_constant(name: &str) -> bool {
+    CONSTANTS.iter().any(|(constant, _)| *constant == name)
+}
+
+fn check_name(name: &str, span: Span) -> Result<(), CalcError> {
+    if is_constant(name) || FUNCTIONS.contains(&name) || RESERVED.contains(&name) {
+        Err(CalcError::new(format!("`{}` is reserved", name), span))
+    } else {
+        Ok(())
+    }
+}
+
+pub fn format_value(value: &Value) -> Result<String, String> {
+    if let Number::Float(float) = value.number {
+        if !float.is_finite() {
+            return Err("Result is not a finite number".to_string());
+        }
+    }
+
+    if let Some(radix) = value.radix {
+        if !is_dimensionless(&value.dims) {
+            return Err("Only plain numbers can be shown in another base".to_string());
+        }
+
+        let int = value.number.to_int().ok_or("Only integers can be shown in another base")?;
+        let (sign, digits) = if int.is_negative() {
+            ("-", (-int).to_str_radix(radix))
+        } else {
+            ("", int.to_str_radix(radix))
+        };
+        let prefix = match radix {
+            16 => "0x",
+            8 => "0o",
+            2 => "0b",
+            _ => "",
+        };
+
+        return Ok(format!("{}{}{}", sign, prefix, digits));
+    }
+
+    // Fall back to base units when the units used don't line up, `GiB s/MB`
+    // is shown in seconds
+    let unit = match &value.unit {
+        Some(unit) if unit.is_simple() && unit.dims() == value.dims => Some(unit.clone()),
+        _ if is_dimensionless(&value.dims) => None,
+        _ => Some(UnitExpr::base(&value.dims)),
+    };
+
+    Ok(match unit {
+        Some(unit) if !unit.is_empty() => format!("{} {}", value.number.unscale(unit.factor()), unit),
+        _ => value.number.to_string(),
+    })
+}
+
+impl Session {
+    // Evaluates every `;` separated statement and returns the output of the
+    // last one
+    pub fn eval(&mut self, input: &str) -> Result<String, CalcError> {
+        let tokens = tokenize(input)?;
+        let statements = Parser::new(tokens, input.chars().count()).statements()?;
+        let mut output = String::new();
+
+        for statement in statements {
+            output = self.run(input, statement)?;
+        }
+
+        Ok(output)
+    }
+
+    fn run(&mut self, input: &str, statement: Statement) -> Result<String, CalcError> {
+        let evaluator = Evaluator {
+            session: self,
+            steps:   Cell::new(0),
+        };
+
+        match statement {
+            Statement::Assign(name, expr) => {
+                check_name(&name, expr.span)?;
+                let mut value = evaluator.eval(&expr, &HashMap::new(), 0)?;
+                value.radix = None;
+                let output = format_value(&value).map_err(|why| CalcError::new(why, expr.span))?;
+
+                self.variables.insert(name.clone(), value);
+
+                Ok(format!("{} = {}", name, output))
+            },
+            Statement::Define(name, params, body) => {
+                check_name(&name, body.span)?;
+                let source = slice(input, body.span);
+                let output = format!("{}({}) = {}", name, params.join(", "), source);
+
+                self.functions.insert(name, Function {
+                    params,
+                    body,
+                    source,
+                });
+
+                Ok(output)
+            },
+            Statement::Eval(expr, target) => {
+                let mut value = evaluator.eval(&expr, &HashMap::new(), 0)?;
+
+                match target {
+                    Some(Target::Radix(radix)) => value.radix = Some(radix),
+                    Some(Target::Unit(target)) => {
+                        let unit = evaluator.eval(&target, &HashMap::new(), 0)?;
+
+                        if unit.dims != value.dims {
+                            return Err(CalcError::new(
+                                format!("Can't convert {} to {}", describe(&value.dims), describe(&unit.dims)),
+                                expr.span.to(target.span),
+                            ));
+                        }
+
+                        // Temperatures are stored relative to their own zero
+                        // so converting between them has to shift the value
+                        let from = value.unit.as_ref().map_or(0.0, UnitExpr::offset);
+                        let to = unit.unit.as_ref().map_or(0.0, UnitExpr::offset);
+                        if from != 0.0 || to != 0.0 {
+                            value.number = Number::Float(value.number.to_f64() + from - to);
+                        }
+
+                        value.unit = unit.unit;
+                    },
+                    None => {},
+                }
+
+                let output = format_value(&value).map_err(|why| CalcError::new(why, expr.span))?;
+
+                value.radix = None;
+                self.variables.insert("ans".to_string(), value);
+
+                Ok(output)
+            },
+        }
+    }
+}
+
+struct Evaluator<'a> {
+    session: &'a Session,
+    steps:   Cell<usize>,
+}
+
+impl<'a> Evaluator<'a> {
+    fn eval(&self, expr: &Expr, locals: &HashMap<String, Value>, depth: usize) -> Result<Value, CalcError> {
+        self.steps.set(self.steps.get() + 1);
+        if self.steps.get() > MAX_STEPS {
+            return Err(CalcError::new("Calculation took too long", expr.span));
+        }
+
+        let span = expr.span;
+        let error = |why: String| CalcError::new(why, span);
+
+        match &expr.kind {
+            ExprKind::Number(number) => Ok(Value::number(number.clone())),
+            ExprKind::Ident(name) => self
+                .lookup(name, locals)
+                .ok_or_else(|| CalcError::new(format!("Unknown variable or unit `{}`", name), span)),
+            ExprKind::Neg(inner) => {
+                let mut value = self.eval(inner, locals, depth)?;
+                value.number = value.number.neg();
+
+                Ok(value)
+            },
+            ExprKind::Factorial(inner) => {
+                let value = self.dimensionless(self.eval(inner, locals, depth)?, inner.span)?;
+
+                value.number.factorial().map(Value::number).map_err(error)
+            },
+            ExprKind::Binary(op, lhs, rhs) => {
+                let lhs = self.eval(lhs, locals, depth)?;
+                let rhs = self.eval(rhs, locals, depth)?;
+
+                binary(*op, lhs, rhs).map_err(error)
+            },
+            ExprKind::Call(name, args) => {
+                let args = args
+                    .iter()
+                    .map(|arg| self.eval(arg, locals, depth))
+                    .collect::<Result<Vec<Value>, CalcError>>()?;
+
+                self.call(name, args, locals, depth, span)
+            },
+        }
+    }
+
+    fn lookup(&self, name: &str, locals: &HashMap<String, Value>) -> Option<Value> {
+        if let Some(value) = locals.get(name).or_else(|| self.session.variables.get(name)) {
+            return Some(value.clone());
+        }
+
+        if let Some((_, constant)) = CONSTANTS.iter().find(|(constant, _)| *constant == name) {
+            return Some(Value::float(*constant));
+        }
+
+        let unit = units::lookup(name)?;
+
+        Some(Value {
+            number: Number::Int(1.into()).scale(unit.factor).ok()?,
+            dims:   unit.dims,
+            unit:   Some(UnitExpr::single(unit)),
+            radix:  None,
+        })
+    }
+
+    fn dimensionless(&self, value: Value, span: Span) -> Result<Value, CalcError> {
+        if is_dimensionless(&value.dims) {
+            Ok(value)
+        } else {
+            Err(CalcError::new(
+                format!("Expected a plain number but got {}", describe(&value.dims)),
+                span,
+            ))
+        }
+    }
+
+    fn call(
+        &self,
+        name: &str,
+        args: Vec<Value>,
+        locals: &HashMap<String, Value>,
+        depth: usize,
+        span: Span,
+    ) -> Result<Value, CalcError> {
+        if let Some(function) = self.session.functions.get(name) {
+            if args.len() != function.params.len() {
+                return Err(CalcError::new(
+                    format!("`{}` takes {} argument(s) but got {}", name, function.params.len(), args.len()),
+                    span,
+                ));
+            }
+
+            if depth >= MAX_DEPTH {
+                return Err(CalcError::new("Too much recursion", span));
+            }
+
+            let locals = function.params.iter().cloned().zip(args).collect();
+
+            return self.eval(&function.body, &locals, depth + 1);
+        }
+
+        if FUNCTIONS.contains(&name) {
+            return builtin(name, args).map_err(|why| CalcError::new(why, span));
+        }
+
+        // `x(2)` where x is a variable or unit is a multiplication
+        match self.lookup(name, locals) {
+            Some(value) if args.len() == 1 => {
+                binary(BinOp::Mul, value, args[0].clone()).map_err(|why| CalcError::new(why, span))
+            },
+            _ => Err(CalcError::new(format!("Unknown function `{}`", name), span)),
+        }
+    }
+}
+
+fn binary(op: BinOp, lhs: Value, rhs: Value) -> Result<Value, String> {
+    match op {
+        BinOp::Add | BinOp::Sub => {
+            if lhs.dims != rhs.dims {
+                return Err(format!(
+                    "Can't {} {} and {}",
+                    if op == BinOp::Add { "add" } else { "subtract" },
+                    describe(&lhs.dims),
+                    describe(&rhs.dims)
+                ));
+            }
+
+            let number = if op == BinOp::Add {
+                lhs.number.add(&rhs.number)?
+            } else {
+                lhs.number.sub(&rhs.number)?
+            };
+
+            Ok(Value {
+                number,
+                dims: lhs.dims,
+                unit: lhs.unit.or(rhs.unit),
+                radix: None,
+            })
+        },
+        BinOp::Mul | BinOp::Div => {
+            let sign = if op == BinOp::Mul { 1 } else { -1 };
+            let number = if op == BinOp::Mul {
+                lhs.number.mul(&rhs.number)?
+            } else {
+                lhs.number.div(&rhs.number)?
+            };
+
+            let mut dims = lhs.dims;
+            for (dim, other) in dims.iter_mut().zip(rhs.dims.iter()) {
+                *dim += other * sign as i8;
+            }
+
+            let unit = match (lhs.unit, rhs.unit) {
+                (Some(lhs), Some(rhs)) => Some(lhs.merge(&rhs, sign)),
+                (Some(lhs), None) => Some(lhs),
+                (None, Some(rhs)) => Some(rhs.pow(sign)),
+                (None, None) => None,
+            };
+
+            Ok(Value {
+                number,
+                dims,
+                unit: unit.filter(|unit| !unit.is_empty()),
+                radix: None,
+            })
+        },
+        BinOp::Rem => {
+            if lhs.dims != rhs.dims {
+                return Err(format!("Can't take {} modulo {}", describe(&lhs.dims), describe(&rhs.dims)));
+            }
+
+            Ok(Value {
+                number: lhs.number.rem(&rhs.number)?,
+                ..lhs
+            })
+        },
+        BinOp::Pow => {
+            if !is_dimensionless(&rhs.dims) {
+                return Err(format!("Exponents must be plain numbers, not {}", describe(&rhs.dims)));
+            }
+
+            if is_dimensionless(&lhs.dims) && lhs.unit.is_none() {
+                return Ok(Value::number(lhs.number.pow(&rhs.number)?));
+            }
+
+            // Units can only be raised to small whole powers
+            let exponent = match rhs.number.to_int().and_then(|int| int.to_i32()) {
+                Some(exponent) if exponent.abs() <= 16 => exponent,
+                _ => return Err("Values with units can only be raised to whole powers up to 16".to_string()),
+            };
+
+            let mut dims = lhs.dims;
+            for dim in dims.iter_mut() {
+                *dim *= exponent as i8;
+            }
+
+            Ok(Value {
+                number: lhs.number.pow(&rhs.number)?,
+                dims,
+                unit: lhs.unit.map(|unit| unit.pow(exponent)).filter(|unit| !unit.is_empty()),
+                radix: None,
+            })
+        },
+    }
+}
+
+fn builtin(name: &str, args: Vec<Value>) -> Result<Value, String> {
+    let expected = match name {
+        "log" => 1..=2,
+        "min" | "max" => 1..=usize::MAX,
+        "gcd" | "lcm" => 2..=2,
+        _ => 1..=1,
+    };
+
+    if !expected.contains(&args.len()) {
+        return Err(format!("`{}` got the wrong number of arguments", name));
+    }
+
+    match name {
+        "abs" => Ok(Value {
+            number: match &args[0].number {
+                Number::Int(int) => Number::Int(int.abs()),
+                Number::Float(float) => Number::Float(float.abs()),
+            },
+            ..args[0].clone()
+        }),
+        "min" | "max" => {
+            let mut best = args[0].clone();
+
+            for arg in args.into_iter().skip(1) {
+                if arg.dims != best.dims {
+                    return Err(format!("Can't compare {} and {}", describe(&best.dims), describe(&arg.dims)));
+                }
+
+                let better = if name == "min" {
+                    arg.number.to_f64() < best.number.to_f64()
+                } else {
+                    arg.number.to_f64() > best.number.to_f64()
+                };
+
+                if better {
+                    best = arg;
+                }
+            }
+
+            Ok(best)
+        },
+        "sqrt" | "cbrt" => {
+            let root = if name == "sqrt" { 2 } else { 3 };
+            let value = &args[0];
+
+            if value.dims.iter().any(|dim| dim % root != 0) {
+                return Err(format!("Can't take the {} of {}", name, describe(&value.dims)));
+            }
+
+            let mut dims = value.dims;
+            for dim in dims.iter_mut() {
+                *dim /= root;
+            }
+
+            let float = value.number.to_f64();
+            let result = if root == 2 { float.sqrt() } else { float.cbrt() };
+
+            // Keep perfect roots as integers
+            let number = match Number::Float(result.round()).to_int() {
+                Some(int) if Number::Int(int.clone()).pow(&Number::Int(root.into()))? == value.number => {
+                    Number::Int(int)
+                },
+                _ => Number::Float(result),
+            };
+
+            Ok(Value {
+                number,
+                dims,
+                unit: value.unit.as_ref().and_then(|unit| unit.root(root as i32)),
+                radix: None,
+            })
+        },
+        "gcd" | "lcm" => {
+            let ints = args
+                .iter()
+                .map(|arg| arg.number.to_int().filter(|_| is_dimensionless(&arg.dims)))
+                .collect::<Option<Vec<_>>>()
+                .ok_or_else(|| format!("`{}` only works on integers", name))?;
+
+            Ok(Value::number(Number::Int(if name == "gcd" {
+                ints[0].gcd(&ints[1])
+            } else {
+                ints[0].lcm(&ints[1])
+            })))
+        },
+        _ => {
+            if !args.iter().all(|arg| is_dimensionless(&arg.dims)) {
+                return Err(format!("`{}` only works on plain numbers", name));
+            }
+
+            let x = args[0].number.to_f64();
+
+            let result = match name {
+                "exp" => x.exp(),
+                "ln" => x.ln(),
+                "log" if args.len() == 2 => x.log(args[1].number.to_f64()),
+                "log" => x.log10(),
+                "log2" => x.log2(),
+                "sin" => x.sin(),
+                "cos" => x.cos(),
+                "tan" => x.tan(),
+                "asin" => x.asin(),
+                "acos" => x.acos(),
+                "atan" => x.atan(),
+                "sinh" => x.sinh(),
+                "cosh" => x.cosh(),
+                "tanh" => x.tanh(),
+                "floor" | "ceil" | "round" => {
+                    let rounded = match name {
+                        "floor" => x.floor(),
+                        "ceil" => x.ceil(),
+                        _ => x.round(),
+                    };
+
+                    // Integers are already whole
+                    if let Number::Int(_) = args[0].number {
+                        return Ok(Value::number(args[0].number.clone()));
+                    }
+
+                    let number = Number::Float(rounded).to_int().map_or(Number::Float(rounded), Number::Int);
+
+                    return Ok(Value::number(number));
+                },
+                _ => return Err(format!("Unknown function `{}`", name)),
+            };
+
+            Ok(Value::float(result))
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn eval(session: &mut Session, input: &str) -> String {
+        session.eval(input).unwrap_or_else(|why| why.render(input))
+    }
+
+    #[test]
+    fn test_arithmetic() {
+        let mut session = Session::default();
+
+        assert_eq!(eval(&mut session, "3^(1 + 2)"), "27");
+        assert_eq!(eval(&mut session, "-2^2"), "-4");
+        assert_eq!(eval(&mut session, "2^3^2"), "512");
+        assert_eq!(eval(&mut session, "7 / 2"), "3.5");
+        assert_eq!(eval(&mut session, "8 / 2"), "4");
+        assert_eq!(eval(&mut session, "2 pi"), "6.283185307");
+        assert_eq!(eval(&mut session, "sqrt(16)"), "4");
+        assert_eq!(eval(&mut session, "2^128"), "340282366920938463463374607431768211456");
+        assert_eq!(eval(&mut session, "25!"), "15511210043330985984000000");
+    }
+
+    #[test]
+    fn test_variables_and_functions() {
+        let mut session = Session::default();
+
+        assert_eq!(eval(&mut session, "x = 5"), "x = 5");
+        assert_eq!(eval(&mut session, "x * 2"), "10");
+        assert_eq!(eval(&mut session, "ans + 1"), "11");
+        assert_eq!(eval(&mut session, "f(a, b) = a^2 + b"), "f(a, b) = a^2 + b");
+        assert_eq!(eval(&mut session, "f(x, 1)"), "26");
+        assert_eq!(eval(&mut session, "y = 2; y * x"), "10");
+        assert!(session.eval("pi = 3").is_err());
+        assert!(session.eval("g(n) = g(n - 1); g(1)").is_err());
+    }
+
+    #[test]
+    fn test_units() {
+        let mut session = Session::default();
+
+        assert_eq!(eval(&mut session, "5 km in mi"), "3.106855961 mi");
+        assert_eq!(eval(&mut session, "3 GiB / 200 MB/s"), "16.10612736 s");
+        assert_eq!(eval(&mut session, "2 km + 500 m"), "2.5 km");
+        assert_eq!(eval(&mut session, "100 km / 2 h in m/s"), "13.88888889 m/s");
+        assert_eq!(eval(&mut session, "100 °C in °F"), "212 °F");
+        assert_eq!(eval(&mut session, "(3 m)^2"), "9 m^2");
+        assert!(session.eval("5 km + 3 s").is_err());
+        assert!(session.eval("5 km in s").is_err());
+    }
+
+    #[test]
+    fn test_bases() {
+        let mut session = Session::default();
+
+        assert_eq!(eval(&mut session, "0xff in bin"), "0b11111111");
+        assert_eq!(eval(&mut session, "255 in hex"), "0xff");
+        assert_eq!(eval(&mut session, "0b1010 + 0o10"), "18");
+        assert!(session.eval("5 km in hex").is_err());
+    }
+
+    #[test]
+    fn test_errors() {
+        let mut session = Session::default();
+
+        let err = session.eval("3 + * 4").unwrap_err();
+        assert_eq!(err.span, Some(Span::new(4, 5)));
+        assert_eq!(err.render("3 + * 4"), "Expected a value, found `*`\n```\n3 + * 4\n    ^\n```");
+
+        let err = session.eval("(1 + 2").unwrap_err();
+        assert_eq!(err.span, Some(Span::new(6, 7)));
+
+        let err = session.eval("1 + foo").unwrap_err();
+        assert_eq!(err.message, "Unknown variable or unit `foo`");
+        assert_eq!(err.span, Some(Span::new(4, 7)));
+
+        assert_eq!(session.eval("1 / 0").unwrap_err().message, "Division by zero");
+        assert!(session.eval("a = 2^40000; a = a * a").is_ok());
+        assert_eq!(session.eval("a * a").unwrap_err().message, "Result is too large");
+    }
+
+    #[test]
+    fn test_nesting() {
+        let mut session = Session::default();
+        let nested = |open: &str, close: &str, depth| format!("{}1{}", open.repeat(depth), close.repeat(depth));
+
+        assert_eq!(eval(&mut session, &nested("(", ")", 99)), "1");
+        assert_eq!(eval(&mut session, &nested("1+", "", 99)), "100");
+
+        for input in &[nested("(", ")", 1000), nested("-", "", 1000), nested("1+", "", 1000), nested("f(", ")", 1000)] {
+            assert_eq!(
+                session.eval(input).unwrap_err().message,
+                "Expressions can't be nested more than 100 deep"
+            );
+        }
+    }
+}
@@ -0,0 +1,454 @@
+use super::{
+    lexer::{Span, Token},
+    value::Number,
+    CalcError,
+};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum BinOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Rem,
+    Pow,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum ExprKind {
+    Number(Number),
+    Ident(String),
+    Neg(Box<Expr>),
+    Factorial(Box<Expr>),
+    Binary(BinOp, Box<Expr>, Box<Expr>),
+    Call(String, Vec<Expr>),
+}
+
+// Parsing and evaluating both recurse, so anything deeper would overflow
+// the stack
+const MAX_NESTING: usize = 100;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Expr {
+    pub kind: ExprKind,
+    pub span: Span,
+    depth:    usize,
+}
+
+impl Expr {
+    fn new(kind: ExprKind, span: Span) -> Expr {
+        let depth = match &kind {
+            ExprKind::Neg(inner) | ExprKind::Factorial(inner) => inner.depth + 1,
+            ExprKind::Binary(_, lhs, rhs) => lhs.depth.max(rhs.depth) + 1,
+            ExprKind::Call(_, args) => args.iter().map(|arg| arg.depth).max().unwrap_or(0) + 1,
+            _ => 1,
+        };
+
+        Expr {
+            kind,
+            span,
+            depth,
+        }
+    }
+
+    fn binary(op: BinOp, lhs: Expr, rhs: Expr) -> Expr {
+        let span = lhs.span.to(rhs.span);
+
+        Expr::new(ExprKind::Binary(op, Box::new(lhs), Box::new(rhs)), span)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Target {
+    Radix(u32),
+    Unit(Expr),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Statement {
+    Assign(String, Expr),
+    Define(String, Vec<String>, Expr),
+    Eval(Expr, Option<Target>),
+}
+
+fn radix(name: &str) -> Option<u32> {
+    match name {
+        "hex" | "hexadecimal" => Some(16),
+        "bin" | "binary" => Some(2),
+        "oct" | "octal" => Some(8),
+        "dec" | "decimal" => Some(10),
+        _ => None,
+    }
+}
+
+fn is_conversion(name: &str) -> bool {
+    name == "in" || name == "to"
+}
+
+pub struct Parser {
+    tokens: Vec<(Token, Span)>,
+    pos:    usize,
+    // Span pointed at when the input ends early
+    end:    Span,
+    depth:  usize,
+}
+
+impl Parser {
+    pub fn new(tokens: Vec<(Token, Span)>, len: usize) -> Parser {
+        Parser {
+            tokens,
+            pos: 0,
+            end: Span::new(len, len + 1),
+            depth: 0,
+        }
+    }
+
+    fn too_deep(span: Span) -> CalcError {
+        CalcError::new(format!("Expressions can't be nested more than {} deep", MAX_NESTING), span)
+    }
+
+    // Guards the parser's own recursion, through brackets and signs
+    fn nested<T>(&mut self, parse: fn(&mut Parser) -> Result<T, CalcError>) -> Result<T, CalcError> {
+        if self.depth >= MAX_NESTING {
+            return Err(Parser::too_deep(self.span()));
+        }
+
+        self.depth += 1;
+        let result = parse(self);
+        self.depth -= 1;
+
+        result
+    }
+
+    // Guards the depth of the tree, which long chains like `1+1+1` grow
+    // without recursing
+    fn checked(expr: Expr) -> Result<Expr, CalcError> {
+        if expr.depth > MAX_NESTING {
+            return Err(Parser::too_deep(expr.span));
+        }
+
+        Ok(expr)
+    }
+
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos).map(|(token, _)| token)
+    }
+
+    fn peek_at(&self, offset: usize) -> Option<&Token> {
+        self.tokens.get(self.pos + offset).map(|(token, _)| token)
+    }
+
+    fn span(&self) -> Span {
+        self.tokens.get(self.pos).map_or(self.end, |(_, span)| *span)
+    }
+
+    fn prev_span(&self) -> Span {
+        self.tokens[self.pos - 1].1
+    }
+
+    fn eat(&mut self, token: &Token) -> bool {
+        if self.peek() == Some(token) {
+            self.pos += 1;
+
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, token: Token) -> Result<Span, CalcError> {
+        if self.eat(&token) {
+            Ok(self.prev_span())
+        } else {
+            Err(self.unexpected(&format!("Expected {}", token.describe())))
+        }
+    }
+
+    fn unexpected(&self, expected: &str) -> CalcError {
+        match self.peek() {
+            Some(token) => CalcError::new(format!("{}, found {}", expected, token.describe()), self.span()),
+            None => CalcError::new(format!("{}, found the end of the input", expected), self.end),
+        }
+    }
+
+    fn ident(&mut self) -> Option<String> {
+        match self.peek() {
+            Some(Token::Ident(name)) => {
+                let name = name.clone();
+                self.pos += 1;
+
+                Some(name)
+            },
+            _ => None,
+        }
+    }
+
+    pub fn statements(&mut self) -> Result<Vec<Statement>, CalcError> {
+        let mut statements = Vec::new();
+
+        while self.peek().is_some() {
+            if self.eat(&Token::Semicolon) {
+                continue;
+            }
+
+            statements.push(self.statement()?);
+
+            if self.peek().is_some() {
+                self.expect(Token::Semicolon)?;
+            }
+        }
+
+        if statements.is_empty() {
+            return Err(CalcError::new("Nothing to calculate", self.end));
+        }
+
+        Ok(statements)
+    }
+
+    fn statement(&mut self) -> Result<Statement, CalcError> {
+        if let (Some(Token::Ident(_)), Some(Token::Equals)) = (self.peek(), self.peek_at(1)) {
+            let name = self.ident().unwrap_or_default();
+            self.pos += 1;
+
+            return Ok(Statement::Assign(name, self.sum()?));
+        }
+
+        if let Some(define) = self.define()? {
+            return Ok(define);
+        }
+
+        let expr = self.sum()?;
+
+        let target = match self.peek() {
+            Some(Token::Ident(name)) if is_conversion(name) => {
+                self.pos += 1;
+
+                match self.peek() {
+                    Some(Token::Ident(name)) if radix(name).is_some() => {
+                        let radix = radix(name);
+                        self.pos += 1;
+
+                        radix.map(Target::Radix)
+                    },
+                    _ => Some(Target::Unit(self.sum()?)),
+                }
+            },
+            _ => None,
+        };
+
+        Ok(Statement::Eval(expr, target))
+    }
+
+    // `f(x, y) = ...`, rewinds if it turns out to be a call instead
+    fn define(&mut self) -> Result<Option<Statement>, CalcError> {
+        if !matches!((self.peek(), self.peek_at(1)), (Some(Token::Ident(_)), Some(Token::LParen))) {
+            return Ok(None);
+        }
+
+        let start = self.pos;
+        let name = self.ident().unwrap_or_default();
+        self.pos += 1;
+
+        let mut params = Vec::new();
+        if !self.eat(&Token::RParen) {
+            loop {
+                match self.ident() {
+                    Some(param) => params.push(param),
+                    None => {
+                        self.pos = start;
+
+                        return Ok(None);
+                    },
+                }
+
+                if self.eat(&Token::RParen) {
+                    break;
+                }
+
+                if !self.eat(&Token::Comma) {
+                    self.pos = start;
+
+                    return Ok(None);
+                }
+            }
+        }
+
+        if !self.eat(&Token::Equals) {
+            self.pos = start;
+
+            return Ok(None);
+        }
+
+        Ok(Some(Statement::Define(name, params, self.sum()?)))
+    }
+
+    fn sum(&mut self) -> Result<Expr, CalcError> {
+        let mut expr = self.product()?;
+
+        loop {
+            let op = match self.peek() {
+                Some(Token::Plus) => BinOp::Add,
+                Some(Token::Minus) => BinOp::Sub,
+                _ => break,
+            };
+            self.pos += 1;
+
+            expr = Parser::checked(Expr::binary(op, expr, self.product()?))?;
+        }
+
+        Ok(expr)
+    }
+
+    fn product(&mut self) -> Result<Expr, CalcError> {
+        let mut expr = self.unary()?;
+
+        loop {
+            let op = match self.peek() {
+                Some(Token::Star) => BinOp::Mul,
+                Some(Token::Slash) => BinOp::Div,
+                Some(Token::Percent) => BinOp::Rem,
+                _ => break,
+            };
+            self.pos += 1;
+
+            expr = Parser::checked(Expr::binary(op, expr, self.unary()?))?;
+        }
+
+        Ok(expr)
+    }
+
+    fn unary(&mut self) -> Result<Expr, CalcError> {
+        if self.eat(&Token::Minus) {
+            let start = self.prev_span();
+            let expr = self.nested(Parser::unary)?;
+            let span = start.to(expr.span);
+
+            return Parser::checked(Expr::new(ExprKind::Neg(Box::new(expr)), span));
+        }
+
+        if self.eat(&Token::Plus) {
+            return self.nested(Parser::unary);
+        }
+
+        self.implicit()
+    }
+
+    fn starts_implicit(&self) -> bool {
+        match self.peek() {
+            Some(Token::Ident(name)) => !is_conversion(name),
+            Some(Token::LParen) => true,
+            _ => false,
+        }
+    }
+
+    // Juxtaposition binds tighter than `*` and `/` so `5 km` and `2 pi` work.
+    // Once something has been juxtaposed a `/` followed by a name stays in
+    // the group, `3 GiB / 200 MB/s` divides by `200 MB/s`
+    fn implicit(&mut self) -> Result<Expr, CalcError> {
+        let mut expr = self.power()?;
+        let mut juxtaposed = false;
+
+        loop {
+            if self.starts_implicit() {
+                expr = Parser::checked(Expr::binary(BinOp::Mul, expr, self.power()?))?;
+                juxtaposed = true;
+            } else if juxtaposed
+                && self.peek() == Some(&Token::Slash)
+                && matches!(self.peek_at(1), Some(Token::Ident(name)) if !is_conversion(name))
+            {
+                self.pos += 1;
+                expr = Parser::checked(Expr::binary(BinOp::Div, expr, self.power()?))?;
+            } else {
+                break;
+            }
+        }
+
+        Ok(expr)
+    }
+
+    fn power(&mut self) -> Result<Expr, CalcError> {
+        let base = self.postfix()?;
+
+        if self.eat(&Token::Caret) {
+            // Right associative and allows `2^-1`
+            let exponent = self.nested(Parser::unary_power)?;
+
+            return Parser::checked(Expr::binary(BinOp::Pow, base, exponent));
+        }
+
+        Ok(base)
+    }
+
+    fn unary_power(&mut self) -> Result<Expr, CalcError> {
+        if self.eat(&Token::Minus) {
+            let start = self.prev_span();
+            let expr = self.nested(Parser::unary_power)?;
+            let span = start.to(expr.span);
+
+            return Parser::checked(Expr::new(ExprKind::Neg(Box::new(expr)), span));
+        }
+
+        self.power()
+    }
+
+    fn postfix(&mut self) -> Result<Expr, CalcError> {
+        let mut expr = self.primary()?;
+
+        while self.eat(&Token::Bang) {
+            let span = expr.span.to(self.prev_span());
+            expr = Parser::checked(Expr::new(ExprKind::Factorial(Box::new(expr)), span))?;
+        }
+
+        Ok(expr)
+    }
+
+    fn primary(&mut self) -> Result<Expr, CalcError> {
+        let span = self.span();
+
+        match self.peek().cloned() {
+            Some(Token::Int(int)) => {
+                self.pos += 1;
+
+                Ok(Expr::new(ExprKind::Number(Number::Int(int)), span))
+            },
+            Some(Token::Float(float)) => {
+                self.pos += 1;
+
+                Ok(Expr::new(ExprKind::Number(Number::Float(float)), span))
+            },
+            Some(Token::Ident(name)) if !is_conversion(&name) => {
+                self.pos += 1;
+
+                if !self.eat(&Token::LParen) {
+                    return Ok(Expr::new(ExprKind::Ident(name), span));
+                }
+
+                let mut args = Vec::new();
+                if !self.eat(&Token::RParen) {
+                    loop {
+                        args.push(self.nested(Parser::sum)?);
+
+                        if self.eat(&Token::RParen) {
+                            break;
+                        }
+
+                        if !self.eat(&Token::Comma) {
+                            return Err(self.unexpected("Expected `,` or `)`"));
+                        }
+                    }
+                }
+
+                Parser::checked(Expr::new(ExprKind::Call(name, args), span.to(self.prev_span())))
+            },
+            Some(Token::LParen) => {
+                self.pos += 1;
+                let mut expr = self.nested(Parser::sum)?;
+                let end = self.expect(Token::RParen)?;
+                expr.span = span.to(end);
+
+                Ok(expr)
+            },
+            _ => Err(self.unexpected("Expected a value")),
+        }
+    }
+}
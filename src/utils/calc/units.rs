@@ -0,0 +1,320 @@
+// Exponents of length, mass, time, data and temperature
+pub type Dims = [i8; 5];
+
+pub const NONE: Dims = [0, 0, 0, 0, 0];
+const LENGTH: Dims = [1, 0, 0, 0, 0];
+const AREA: Dims = [2, 0, 0, 0, 0];
+const VOLUME: Dims = [3, 0, 0, 0, 0];
+const MASS: Dims = [0, 1, 0, 0, 0];
+const TIME: Dims = [0, 0, 1, 0, 0];
+const DATA: Dims = [0, 0, 0, 1, 0];
+const TEMPERATURE: Dims = [0, 0, 0, 0, 1];
+const FREQUENCY: Dims = [0, 0, -1, 0, 0];
+const SPEED: Dims = [1, 0, -1, 0, 0];
+const FORCE: Dims = [1, 1, -2, 0, 0];
+const PRESSURE: Dims = [-1, 1, -2, 0, 0];
+const ENERGY: Dims = [2, 1, -2, 0, 0];
+const POWER: Dims = [2, 1, -3, 0, 0];
+
+const BASE_NAMES: [&str; 5] = ["length", "mass", "time", "data", "temperature"];
+
+#[derive(Debug, PartialEq)]
+pub struct Unit {
+    pub names:  &'static [&'static str],
+    // Size of the unit in base units, metres, kilograms, seconds, bytes and
+    // kelvin
+    pub factor: f64,
+    // Where the unit's zero is in kelvin, only used when converting
+    // temperatures
+    pub offset: f64,
+    pub dims:   Dims,
+    // Used when a result has to be shown in base units
+    pub base:   bool,
+}
+
+impl Unit {
+    pub fn symbol(&self) -> &'static str {
+        self.names[0]
+    }
+}
+
+const fn unit(names: &'static [&'static str], factor: f64, dims: Dims) -> Unit {
+    Unit {
+        names,
+        factor,
+        offset: 0.0,
+        dims,
+        base: false,
+    }
+}
+
+const fn base(names: &'static [&'static str], dims: Dims) -> Unit {
+    Unit {
+        names,
+        factor: 1.0,
+        offset: 0.0,
+        dims,
+        base: true,
+    }
+}
+
+const fn temperature(names: &'static [&'static str], factor: f64, offset: f64) -> Unit {
+    Unit {
+        names,
+        factor,
+        offset,
+        dims: TEMPERATURE,
+        base: false,
+    }
+}
+
+static UNITS: &[Unit] = &[
+    // Length
+    base(&["m", "meter", "meters", "metre", "metres"], LENGTH),
+    unit(&["km", "kilometer", "kilometers", "kilometre", "kilometres"], 1e3, LENGTH),
+    unit(&["cm", "centimeter", "centimeters", "centimetre", "centimetres"], 1e-2, LENGTH),
+    unit(&["mm", "millimeter", "millimeters", "millimetre", "millimetres"], 1e-3, LENGTH),
+    unit(&["µm", "um", "micrometer", "micrometers", "micron", "microns"], 1e-6, LENGTH),
+    unit(&["nm", "nanometer", "nanometers"], 1e-9, LENGTH),
+    unit(&["mi", "mile", "miles"], 1609.344, LENGTH),
+    unit(&["yd", "yard", "yards"], 0.9144, LENGTH),
+    unit(&["ft", "foot", "feet"], 0.3048, LENGTH),
+    unit(&["inch", "inches"], 0.0254, LENGTH),
+    unit(&["nmi"], 1852.0, LENGTH),
+    unit(&["au"], 149_597_870_700.0, LENGTH),
+    unit(&["ly", "lightyear", "lightyears"], 9_460_730_472_580_800.0, LENGTH),
+    // Area and volume
+    unit(&["ha", "hectare", "hectares"], 1e4, AREA),
+    unit(&["acre", "acres"], 4_046.856_422_4, AREA),
+    unit(&["L", "l", "liter", "liters", "litre", "litres"], 1e-3, VOLUME),
+    unit(
+        &["mL", "ml", "milliliter", "milliliters", "millilitre", "millilitres"],
+        1e-6,
+        VOLUME,
+    ),
+    unit(&["gal", "gallon", "gallons"], 0.003_785_411_784, VOLUME),
+    // Mass
+    base(&["kg", "kilogram", "kilograms"], MASS),
+    unit(&["g", "gram", "grams"], 1e-3, MASS),
+    unit(&["mg", "milligram", "milligrams"], 1e-6, MASS),
+    unit(&["t", "tonne", "tonnes"], 1e3, MASS),
+    unit(&["lb", "lbs", "pound", "pounds"], 0.453_592_37, MASS),
+    unit(&["oz", "ounce", "ounces"], 0.028_349_523_125, MASS),
+    unit(&["st", "stone"], 6.350_293_18, MASS),
+    // Time
+    base(&["s", "sec", "secs", "second", "seconds"], TIME),
+    unit(&["ms", "millisecond", "milliseconds"], 1e-3, TIME),
+    unit(&["µs", "us", "microsecond", "microseconds"], 1e-6, TIME),
+    unit(&["ns", "nanosecond", "nanoseconds"], 1e-9, TIME),
+    unit(&["min", "mins", "minute", "minutes"], 60.0, TIME),
+    unit(&["h", "hr", "hrs", "hour", "hours"], 3600.0, TIME),
+    unit(&["d", "day", "days"], 86400.0, TIME),
+    unit(&["wk", "week", "weeks"], 604_800.0, TIME),
+    unit(&["month", "months"], 2_629_746.0, TIME),
+    unit(&["yr", "year", "years"], 31_556_952.0, TIME),
+    // Data, both SI and binary prefixes
+    base(&["B", "byte", "bytes"], DATA),
+    unit(&["bit", "bits", "b"], 0.125, DATA),
+    unit(&["kB", "KB", "kilobyte", "kilobytes"], 1e3, DATA),
+    unit(&["MB", "megabyte", "megabytes"], 1e6, DATA),
+    unit(&["GB", "gigabyte", "gigabytes"], 1e9, DATA),
+    unit(&["TB", "terabyte", "terabytes"], 1e12, DATA),
+    unit(&["PB", "petabyte", "petabytes"], 1e15, DATA),
+    unit(&["KiB", "kibibyte", "kibibytes"], 1024.0, DATA),
+    unit(&["MiB", "mebibyte", "mebibytes"], 1_048_576.0, DATA),
+    unit(&["GiB", "gibibyte", "gibibytes"], 1_073_741_824.0, DATA),
+    unit(&["TiB", "tebibyte", "tebibytes"], 1_099_511_627_776.0, DATA),
+    unit(&["PiB", "pebibyte", "pebibytes"], 1_125_899_906_842_624.0, DATA),
+    unit(&["kbit", "Kb", "kb", "kilobit", "kilobits"], 125.0, DATA),
+    unit(&["Mbit", "Mb", "megabit", "megabits"], 125_000.0, DATA),
+    unit(&["Gbit", "Gb", "gigabit", "gigabits"], 125_000_000.0, DATA),
+    // Temperature
+    base(&["K", "kelvin"], TEMPERATURE),
+    temperature(&["°C", "C", "degC", "celsius"], 1.0, 273.15),
+    temperature(&["°F", "F", "degF", "fahrenheit"], 5.0 / 9.0, 459.67 * 5.0 / 9.0),
+    // Derived
+    base(&["Hz", "hertz"], FREQUENCY),
+    unit(&["kHz", "kilohertz"], 1e3, FREQUENCY),
+    unit(&["MHz", "megahertz"], 1e6, FREQUENCY),
+    unit(&["GHz", "gigahertz"], 1e9, FREQUENCY),
+    unit(&["mph"], 0.447_04, SPEED),
+    unit(&["kph", "kmh"], 1.0 / 3.6, SPEED),
+    unit(&["kn", "knot", "knots"], 1852.0 / 3600.0, SPEED),
+    base(&["N", "newton", "newtons"], FORCE),
+    base(&["Pa", "pascal", "pascals"], PRESSURE),
+    unit(&["kPa", "kilopascal", "kilopascals"], 1e3, PRESSURE),
+    unit(&["bar", "bars"], 1e5, PRESSURE),
+    unit(&["atm"], 101_325.0, PRESSURE),
+    unit(&["psi"], 6_894.757_293_168, PRESSURE),
+    base(&["J", "joule", "joules"], ENERGY),
+    unit(&["kJ", "kilojoule", "kilojoules"], 1e3, ENERGY),
+    unit(&["cal", "calorie", "calories"], 4.184, ENERGY),
+    unit(&["kcal", "kilocalorie", "kilocalories"], 4184.0, ENERGY),
+    unit(&["Wh"], 3600.0, ENERGY),
+    unit(&["kWh"], 3_600_000.0, ENERGY),
+    unit(&["eV"], 1.602_176_634e-19, ENERGY),
+    base(&["W", "watt", "watts"], POWER),
+    unit(&["kW", "kilowatt", "kilowatts"], 1e3, POWER),
+    unit(&["hp", "horsepower"], 745.699_871_582_270_2, POWER),
+    // Angles are dimensionless, radians are plain numbers
+    unit(&["deg", "degree", "degrees", "°"], std::f64::consts::PI / 180.0, NONE),
+    unit(&["rad", "radian", "radians"], 1.0, NONE),
+];
+
+pub fn lookup(name: &str) -> Option<&'static Unit> {
+    UNITS.iter().find(|unit| unit.names.contains(&name))
+}
+
+pub fn is_dimensionless(dims: &Dims) -> bool {
+    dims.iter().all(|dim| *dim == 0)
+}
+
+// Describes dimensions for error messages, `length/time` for a speed
+pub fn describe(dims: &Dims) -> String {
+    if is_dimensionless(dims) {
+        return "a plain number".to_string();
+    }
+
+    let part = |positive: bool| {
+        dims.iter()
+            .zip(BASE_NAMES.iter())
+            .filter(|(dim, _)| if positive { **dim > 0 } else { **dim < 0 })
+            .map(|(dim, name)| {
+                if dim.abs() == 1 {
+                    name.to_string()
+                } else {
+                    format!("{}^{}", name, dim.abs())
+                }
+            })
+            .collect::<Vec<String>>()
+            .join("·")
+    };
+
+    match (part(true), part(false)) {
+        (top, bottom) if bottom.is_empty() => top,
+        (top, bottom) if top.is_empty() => format!("1/{}", bottom),
+        (top, bottom) => format!("{}/{}", top, bottom),
+    }
+}
+
+// A product of units with exponents, `km/h` is `[(km, 1), (h, -1)]`
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct UnitExpr(pub Vec<(&'static Unit, i32)>);
+
+impl UnitExpr {
+    pub fn single(unit: &'static Unit) -> UnitExpr {
+        UnitExpr(vec![(unit, 1)])
+    }
+
+    // Expresses dimensions in base units, using a named unit if one matches
+    pub fn base(dims: &Dims) -> UnitExpr {
+        if let Some(unit) = UNITS.iter().find(|unit| unit.base && unit.dims == *dims) {
+            return UnitExpr::single(unit);
+        }
+
+        let bases = [LENGTH, MASS, TIME, DATA, TEMPERATURE];
+        UnitExpr(
+            dims.iter()
+                .zip(bases.iter())
+                .filter(|(dim, _)| **dim != 0)
+                .filter_map(|(dim, base)| {
+                    UNITS
+                        .iter()
+                        .find(|unit| unit.base && unit.dims == *base)
+                        .map(|unit| (unit, *dim as i32))
+                })
+                .collect(),
+        )
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn factor(&self) -> f64 {
+        self.0.iter().map(|(unit, power)| unit.factor.powi(*power)).product()
+    }
+
+    // Offsets only make sense for a lone temperature
+    pub fn offset(&self) -> f64 {
+        match self.0.as_slice() {
+            [(unit, 1)] => unit.offset,
+            _ => 0.0,
+        }
+    }
+
+    pub fn dims(&self) -> Dims {
+        let mut dims = NONE;
+
+        for (unit, power) in self.0.iter() {
+            for (dim, unit_dim) in dims.iter_mut().zip(unit.dims.iter()) {
+                *dim += unit_dim * *power as i8;
+            }
+        }
+
+        dims
+    }
+
+    pub fn merge(&self, other: &UnitExpr, sign: i32) -> UnitExpr {
+        let mut units = self.0.clone();
+
+        for (unit, power) in other.0.iter() {
+            match units.iter_mut().find(|(existing, _)| existing == unit) {
+                Some((_, existing)) => *existing += power * sign,
+                None => units.push((unit, power * sign)),
+            }
+        }
+
+        units.retain(|(_, power)| *power != 0);
+
+        UnitExpr(units)
+    }
+
+    pub fn pow(&self, exponent: i32) -> UnitExpr {
+        UnitExpr(self.0.iter().map(|(unit, power)| (*unit, power * exponent)).collect())
+    }
+
+    // Every power divisible by `root`, used for square and cube roots
+    pub fn root(&self, root: i32) -> Option<UnitExpr> {
+        if self.0.iter().all(|(_, power)| power % root == 0) {
+            Some(UnitExpr(self.0.iter().map(|(unit, power)| (*unit, power / root)).collect()))
+        } else {
+            None
+        }
+    }
+
+    // False if two units measure the same thing, `km m` is better shown as
+    // `m^2`
+    pub fn is_simple(&self) -> bool {
+        self.0.iter().enumerate().all(|(idx, (unit, _))| {
+            self.0[idx + 1..]
+                .iter()
+                .all(|(other, _)| unit.dims.iter().zip(other.dims.iter()).all(|(a, b)| *a == 0 || *b == 0))
+        })
+    }
+}
+
+impl std::fmt::Display for UnitExpr {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let part = |positive: bool| {
+            self.0
+                .iter()
+                .filter(|(_, power)| if positive { *power > 0 } else { *power < 0 })
+                .map(|(unit, power)| {
+                    if power.abs() == 1 {
+                        unit.symbol().to_string()
+                    } else {
+                        format!("{}^{}", unit.symbol(), power.abs())
+                    }
+                })
+                .collect::<Vec<String>>()
+                .join("·")
+        };
+
+        match (part(true), part(false)) {
+            (top, bottom) if bottom.is_empty() => write!(f, "{}", top),
+            (top, bottom) if top.is_empty() => write!(f, "1/{}", bottom),
+            (top, bottom) => write!(f, "{}/{}", top, bottom),
+        }
+    }
+}
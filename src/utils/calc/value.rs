@@ -0,0 +1,234 @@
+use num_bigint::BigInt;
+use num_traits::{FromPrimitive, Signed, ToPrimitive, Zero};
+
+use super::units::{Dims, UnitExpr, NONE};
+
+// Results are capped so a single message can't tie the bot up
+const MAX_BITS: u64 = 100_000;
+const MAX_FACTORIAL: u64 = 5000;
+const MAX_DIGITS: usize = 1000;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Number {
+    Int(BigInt),
+    Float(f64),
+}
+
+impl Number {
+    pub fn to_f64(&self) -> f64 {
+        match self {
+            Number::Int(int) => int.to_f64().unwrap_or(f64::NAN),
+            Number::Float(float) => *float,
+        }
+    }
+
+    // Floats without a fractional part count as integers
+    pub fn to_int(&self) -> Option<BigInt> {
+        match self {
+            Number::Int(int) => Some(int.clone()),
+            Number::Float(float) if float.fract() == 0.0 => BigInt::from_f64(*float),
+            Number::Float(_) => None,
+        }
+    }
+
+    pub fn is_zero(&self) -> bool {
+        match self {
+            Number::Int(int) => int.is_zero(),
+            Number::Float(float) => *float == 0.0,
+        }
+    }
+
+    pub fn neg(&self) -> Number {
+        match self {
+            Number::Int(int) => Number::Int(-int),
+            Number::Float(float) => Number::Float(-float),
+        }
+    }
+
+    // Integers are capped the same as `pow`, otherwise repeated squaring can
+    // grow a number until the bot runs out of memory
+    fn checked(int: BigInt) -> Result<Number, String> {
+        if int.bits() > MAX_BITS {
+            Err("Result is too large".to_string())
+        } else {
+            Ok(Number::Int(int))
+        }
+    }
+
+    pub fn add(&self, other: &Number) -> Result<Number, String> {
+        match (self, other) {
+            (Number::Int(a), Number::Int(b)) => Number::checked(a + b),
+            (a, b) => Ok(Number::Float(a.to_f64() + b.to_f64())),
+        }
+    }
+
+    pub fn sub(&self, other: &Number) -> Result<Number, String> {
+        self.add(&other.neg())
+    }
+
+    pub fn mul(&self, other: &Number) -> Result<Number, String> {
+        match (self, other) {
+            // Checked up front so the product is never allocated
+            (Number::Int(a), Number::Int(b)) if a.bits() + b.bits() > MAX_BITS + 1 => {
+                Err("Result is too large".to_string())
+            },
+            (Number::Int(a), Number::Int(b)) => Number::checked(a * b),
+            (a, b) => Ok(Number::Float(a.to_f64() * b.to_f64())),
+        }
+    }
+
+    // Stays an integer when the division is exact
+    pub fn div(&self, other: &Number) -> Result<Number, String> {
+        if other.is_zero() {
+            return Err("Division by zero".to_string());
+        }
+
+        match (self, other) {
+            (Number::Int(a), Number::Int(b)) if (a % b).is_zero() => Ok(Number::Int(a / b)),
+            (a, b) => Ok(Number::Float(a.to_f64() / b.to_f64())),
+        }
+    }
+
+    pub fn rem(&self, other: &Number) -> Result<Number, String> {
+        if other.is_zero() {
+            return Err("Division by zero".to_string());
+        }
+
+        match (self, other) {
+            (Number::Int(a), Number::Int(b)) => Ok(Number::Int(a % b)),
+            (a, b) => Ok(Number::Float(a.to_f64() % b.to_f64())),
+        }
+    }
+
+    pub fn pow(&self, exponent: &Number) -> Result<Number, String> {
+        if let (Number::Int(base), Number::Int(exponent)) = (self, exponent) {
+            if !exponent.is_negative() {
+                let exponent = exponent.to_u64().unwrap_or(u64::MAX);
+
+                if base.bits().saturating_mul(exponent) > MAX_BITS && base.abs() > BigInt::from(1) {
+                    return Err("Result is too large".to_string());
+                }
+
+                // Only 0, 1 and -1 get past the size check with a huge exponent
+                let exponent = if exponent > u32::MAX as u64 {
+                    (exponent % 2 + 2) as u32
+                } else {
+                    exponent as u32
+                };
+
+                return Ok(Number::Int(base.pow(exponent)));
+            }
+        }
+
+        Ok(Number::Float(self.to_f64().powf(exponent.to_f64())))
+    }
+
+    pub fn factorial(&self) -> Result<Number, String> {
+        let n = match self.to_int().and_then(|int| int.to_u64()) {
+            Some(n) => n,
+            None => return Err("Factorials are only defined for positive integers".to_string()),
+        };
+
+        if n > MAX_FACTORIAL {
+            return Err(format!("Factorials are limited to {}!", MAX_FACTORIAL));
+        }
+
+        Ok(Number::Int((1..=n).fold(BigInt::from(1), |acc, i| acc * i)))
+    }
+
+    // Multiplies by a unit factor, keeping integers when the factor is whole
+    pub fn scale(&self, factor: f64) -> Result<Number, String> {
+        match self {
+            Number::Int(int) if factor.fract() == 0.0 && factor.abs() < 9e15 => {
+                Number::checked(int * BigInt::from(factor as i64))
+            },
+            _ => Ok(Number::Float(self.to_f64() * factor)),
+        }
+    }
+
+    pub fn unscale(&self, factor: f64) -> Number {
+        if factor.fract() == 0.0 && factor.abs() < 9e15 {
+            if let Ok(number) = self.div(&Number::Int(BigInt::from(factor as i64))) {
+                return number;
+            }
+        }
+
+        Number::Float(self.to_f64() / factor)
+    }
+}
+
+impl std::fmt::Display for Number {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Number::Int(int) => {
+                let digits = int.to_string();
+                let len = digits.trim_start_matches('-').len();
+
+                if len > MAX_DIGITS {
+                    let sign = if int.is_negative() { "-" } else { "" };
+                    let digits = digits.trim_start_matches('-');
+
+                    write!(f, "{}{}.{}e{} ({} digits)", sign, &digits[..1], &digits[1..10], len - 1, len)
+                } else {
+                    write!(f, "{}", digits)
+                }
+            },
+            Number::Float(float) => write!(f, "{}", format_float(*float)),
+        }
+    }
+}
+
+// Up to 10 significant digits, switching to scientific notation for very
+// large and very small numbers
+pub fn format_float(float: f64) -> String {
+    if float == 0.0 {
+        return "0".to_string();
+    }
+
+    let magnitude = float.abs().log10().floor() as i32;
+
+    if !(-6..15).contains(&magnitude) {
+        let formatted = format!("{:.9e}", float);
+        let (mantissa, exponent) = formatted.split_at(formatted.find('e').unwrap_or(formatted.len()));
+        let mantissa = trim_zeros(mantissa);
+
+        return format!("{}{}", mantissa, exponent);
+    }
+
+    let decimals = (9 - magnitude).max(0) as usize;
+
+    trim_zeros(&format!("{:.*}", decimals, float))
+}
+
+fn trim_zeros(number: &str) -> String {
+    if number.contains('.') {
+        number.trim_end_matches('0').trim_end_matches('.').to_string()
+    } else {
+        number.to_string()
+    }
+}
+
+// Numbers are stored in base units, `unit` is only used for showing them
+#[derive(Clone, Debug, PartialEq)]
+pub struct Value {
+    pub number: Number,
+    pub dims:   Dims,
+    pub unit:   Option<UnitExpr>,
+    // Set by `in hex` and friends
+    pub radix:  Option<u32>,
+}
+
+impl Value {
+    pub fn number(number: Number) -> Value {
+        Value {
+            number,
+            dims: NONE,
+            unit: None,
+            radix: None,
+        }
+    }
+
+    pub fn float(float: f64) -> Value {
+        Value::number(Number::Float(float))
+    }
+}
@@ -1,11 +1,13 @@
+use std::collections::HashMap;
+
 use once_cell::sync::Lazy;
-use regex::Regex;
+use regex::{Captures, Regex};
 
 use crate::models::discord::Emote;
 
 static MENTION_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r"<@!?\d{18}>").unwrap());
 static USER_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r"(<@)?\d{18}>?").unwrap());
-static CHANNEL_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r"(<#)?\d{18}>?").unwrap());
+static CHANNEL_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r"^(<#)?\d{18}>?$").unwrap());
 static EMOTE_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r"<a?:[a-zA-Z0-9_]*?:\d{18}>").unwrap());
 static EMOTE_NAME_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r"[^:]{0,}[a-zA-Z0-9][^:]").unwrap());
 static EMOTE_ID_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r"\d{18}").unwrap());
@@ -89,6 +91,226 @@ pub fn get_emotes(arg: &str) -> Vec<Emote> {
     matches
 }
 
+pub fn expand_snippets(message: &str, prefix: &str, suffix: &str, tags: &HashMap<String, String>) -> Option<String> {
+    if prefix.is_empty() || !message.contains(prefix) {
+        return None;
+    }
+
+    let pattern = format!(r"{}([a-zA-Z0-9_\-]+){}", regex::escape(prefix), regex::escape(suffix));
+    let regex = Regex::new(&pattern).ok()?;
+
+    let mut expanded = false;
+    let output = regex.replace_all(message, |caps: &Captures| {
+        if let Some(tag) = tags.get(&caps[1]) {
+            expanded = true;
+
+            tag.to_string()
+        } else {
+            caps[0].to_string()
+        }
+    });
+
+    if expanded {
+        Some(output.to_string())
+    } else {
+        None
+    }
+}
+
+// Splits a message which is a single code block into its language and
+// contents, anything else is returned as is with inline code unwrapped
+pub fn parse_code_block(input: &str) -> (Option<String>, String) {
+    let trimmed = input.trim();
+
+    if trimmed.len() >= 6 && trimmed.starts_with("```") && trimmed.ends_with("```") {
+        let inner = &trimmed[3..trimmed.len() - 3];
+
+        // The first line is only a language if it looks like one, so
+        // ```{"a": 1}``` keeps its contents
+        if let Some((lang, body)) = inner.split_once('\n') {
+            let lang = lang.trim();
+
+            if lang.chars().all(|c| c.is_ascii_alphanumeric() || "+#-_.".contains(c)) {
+                return (Some(lang.to_lowercase()).filter(|lang| !lang.is_empty()), body.to_string());
+            }
+        }
+
+        return (None, inner.to_string());
+    }
+
+    if trimmed.len() >= 2 && trimmed.starts_with('`') && trimmed.ends_with('`') {
+        return (None, trimmed.trim_matches('`').to_string());
+    }
+
+    (None, trimmed.to_string())
+}
+
+// Cuts text down to `length` characters, ending it with an ellipsis
+pub fn truncate(content: &str, length: usize) -> String {
+    if content.chars().count() > length {
+        format!("{}...", content.chars().take(length.saturating_sub(3)).collect::<String>())
+    } else {
+        content.to_string()
+    }
+}
+
+fn code_block_language(line: &str) -> String {
+    let lang = line.rsplit("```").next().unwrap_or_default();
+
+    lang.split_whitespace().next().unwrap_or_default().to_string()
+}
+
+// Inline formatting, longest first so `**` isn't read as two `*`
+const INLINE_MARKERS: [&str; 4] = ["**", "__", "*", "`"];
+// Room to close every inline marker at once
+const INLINE_RESERVE: usize = 6;
+
+// Inline formatting still open at the end of `text`, in the order it was
+// opened
+fn open_spans(text: &str) -> Vec<&'static str> {
+    let mut open: Vec<&'static str> = Vec::new();
+    let mut idx = 0;
+
+    while let Some(ch) = text[idx..].chars().next() {
+        if ch == '\\' {
+            idx += ch.len_utf8() + text[idx + 1..].chars().next().map_or(0, char::len_utf8);
+            continue;
+        }
+
+        // Nothing is formatted inside inline code
+        let in_code = open.last() == Some(&"`");
+        let marker = INLINE_MARKERS
+            .iter()
+            .find(|marker| text[idx..].starts_with(*marker) && (!in_code || **marker == "`"));
+
+        match marker {
+            Some(marker) => {
+                idx += marker.len();
+
+                if let Some(pos) = open.iter().rposition(|open| open == marker) {
+                    open.remove(pos);
+                } else if matches!(text[idx..].chars().next(), Some(next) if !next.is_whitespace()) {
+                    // Markers only open when followed by text, so `2 * 3` is left alone
+                    open.push(marker);
+                }
+            },
+            None => idx += ch.len_utf8(),
+        }
+    }
+
+    open
+}
+
+// Byte index to cut a line at so at most `width` characters are kept,
+// preferring the last space
+fn cut_line(line: &str, width: usize) -> usize {
+    let cut = line
+        .char_indices()
+        .nth(width.max(1))
+        .map(|(idx, _)| idx)
+        .unwrap_or_else(|| line.len());
+
+    match line[..cut].rfind(' ') {
+        Some(idx) if idx > 0 => idx + 1,
+        _ => cut,
+    }
+}
+
+// Splits text into chunks no longer than `limit` characters, breaking on
+// newlines where possible. Code blocks that get cut are closed at the end of
+// the chunk and reopened with the same language at the start of the next,
+// as is inline formatting in lines which have to be cut.
+pub fn split_message(content: &str, limit: usize) -> Vec<String> {
+    if content.chars().count() <= limit {
+        return vec![content.to_string()];
+    }
+
+    let mut chunks = Vec::new();
+    let mut chunk = String::new();
+    let mut fresh = true;
+    let mut code_block: Option<String> = None;
+
+    for line in content.split('\n') {
+        // Leave room to close a code block if the chunk ends inside one. A
+        // whole line leaves the block as it is after the line, while part of
+        // a line leaves it as it was before
+        let toggles = line.matches("```").count() % 2 == 1;
+        let reserve = if code_block.is_some() != toggles { 4 } else { 0 };
+        let cut_reserve = if code_block.is_some() { 4 } else { 0 };
+        let mut rest = line.to_string();
+
+        loop {
+            let separator = if chunk.is_empty() { 0 } else { 1 };
+            let used = chunk.chars().count() + separator + reserve;
+            let available = limit.saturating_sub(used);
+
+            if rest.chars().count() <= available {
+                if separator == 1 {
+                    chunk.push('\n');
+                }
+                chunk.push_str(&rest);
+                fresh = false;
+
+                break;
+            }
+
+            if fresh {
+                // Line is too long to fit in an empty chunk so cut it,
+                // preferring the last space before the limit
+                let available = limit.saturating_sub(chunk.chars().count() + separator + cut_reserve);
+                let mut cut = cut_line(&rest, available);
+                let mut spans = Vec::new();
+
+                // Formatting cut in half is closed and reopened, with a
+                // shorter cut to leave room. Tiny chunks would never make
+                // progress so they are cut as is
+                if code_block.is_none() && available > INLINE_RESERVE * 2 && !open_spans(&rest[..cut]).is_empty() {
+                    cut = cut_line(&rest, available - INLINE_RESERVE);
+                    spans = open_spans(&rest[..cut]);
+                }
+
+                if separator == 1 {
+                    chunk.push('\n');
+                }
+
+                if spans.is_empty() {
+                    chunk.push_str(&rest[..cut]);
+                } else {
+                    // Closing markers can't follow a space
+                    chunk.push_str(rest[..cut].trim_end());
+                    chunk.extend(spans.iter().rev().copied());
+                }
+
+                rest = format!("{}{}", spans.concat(), &rest[cut..]);
+            }
+
+            if code_block.is_some() {
+                chunk.push_str("\n```");
+            }
+            chunks.push(chunk);
+
+            chunk = match &code_block {
+                Some(lang) => format!("```{}", lang),
+                None => String::new(),
+            };
+            fresh = true;
+        }
+
+        if toggles {
+            code_block = match code_block {
+                Some(_) => None,
+                None => Some(code_block_language(line)),
+            };
+        }
+    }
+
+    if !fresh {
+        chunks.push(chunk);
+    }
+
+    chunks
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -108,6 +330,17 @@ mod tests {
         assert_eq!(is_mention("This isn't an ID"), false);
     }
 
+    #[test]
+    fn test_is_channel() {
+        // Mention or plain ID
+        assert_eq!(is_channel("<#801105575038041266>"), true);
+        assert_eq!(is_channel("801105575038041266"), true);
+
+        // Filters which contain an ID
+        assert_eq!(is_channel("after:801105575038041266"), false);
+        assert_eq!(is_channel("801105575038041266a"), false);
+    }
+
     #[test]
     fn test_has_emotes() {
         // Valid emote
@@ -150,4 +383,101 @@ mod tests {
         assert_eq!(emotes.get(2).unwrap().id, 785150570591551491);
         assert_eq!(emotes.get(3).unwrap().id, 800797540739579924);
     }
+
+    #[test]
+    fn test_expand_snippets() {
+        let mut tags = HashMap::new();
+        tags.insert("sig".to_string(), "- L3af".to_string());
+        tags.insert("shrug".to_string(), "¯\\_(ツ)_/¯".to_string());
+
+        // Wrapped trigger
+        assert_eq!(
+            expand_snippets("Hello {{sig}}", "{{", "}}", &tags),
+            Some("Hello - L3af".to_string())
+        );
+
+        // Prefix only trigger
+        assert_eq!(
+            expand_snippets("idk ;;shrug", ";;", "", &tags),
+            Some("idk ¯\\_(ツ)_/¯".to_string())
+        );
+
+        // Unknown tags are left alone
+        assert_eq!(expand_snippets("Hello {{unknown}}", "{{", "}}", &tags), None);
+        assert_eq!(
+            expand_snippets("{{unknown}} {{sig}}", "{{", "}}", &tags),
+            Some("{{unknown}} - L3af".to_string())
+        );
+
+        // No trigger at all
+        assert_eq!(expand_snippets("Just a message", "{{", "}}", &tags), None);
+    }
+
+    #[test]
+    fn test_parse_code_block() {
+        // Language and contents
+        assert_eq!(
+            parse_code_block("```JSON\n{\"a\": 1}\n```"),
+            (Some("json".to_string()), "{\"a\": 1}\n".to_string())
+        );
+
+        // No language
+        assert_eq!(parse_code_block("```\nabc```"), (None, "abc".to_string()));
+        assert_eq!(parse_code_block("```{\"a\": 1}\n```"), (None, "{\"a\": 1}\n".to_string()));
+        assert_eq!(parse_code_block("```x = 1```"), (None, "x = 1".to_string()));
+
+        // Inline code and plain text
+        assert_eq!(parse_code_block("`abc`"), (None, "abc".to_string()));
+        assert_eq!(parse_code_block(" abc "), (None, "abc".to_string()));
+    }
+
+    #[test]
+    fn test_truncate() {
+        assert_eq!(truncate("Hello", 5), "Hello");
+        assert_eq!(truncate("Hello world", 8), "Hello...");
+        assert_eq!(truncate("ありがとう", 4), "あ...");
+        assert_eq!(truncate("Hello", 2), "...");
+    }
+
+    #[test]
+    fn test_split_message() {
+        // Short messages are untouched
+        assert_eq!(split_message("Hello", 2000), vec!["Hello".to_string()]);
+
+        // Splits on newlines
+        let chunks = split_message("aaaa\nbbbb\ncccc", 10);
+        assert_eq!(chunks, vec!["aaaa\nbbbb".to_string(), "cccc".to_string()]);
+
+        // Long lines are cut on spaces
+        let chunks = split_message("aaaa bbbb cccc", 10);
+        assert_eq!(chunks, vec!["aaaa bbbb ".to_string(), "cccc".to_string()]);
+
+        // Code blocks are closed and reopened
+        let content = format!("Output: ```rust\n{}\n```", vec!["let x = 1;"; 10].join("\n"));
+        let chunks = split_message(&content, 50);
+
+        assert!(chunks.len() > 1);
+        for chunk in &chunks {
+            assert!(chunk.chars().count() <= 50);
+            assert_eq!(chunk.matches("```").count() % 2, 0);
+        }
+        assert!(chunks[1].starts_with("```rust\n"));
+
+        // The fence which opens a block counts towards the room to close it
+        let chunks = split_message("aaaaaa\n```\nbb\n```", 10);
+        for chunk in &chunks {
+            assert!(chunk.chars().count() <= 10);
+            assert_eq!(chunk.matches("```").count() % 2, 0);
+        }
+
+        // Inline formatting is closed and reopened
+        let chunks = split_message("some **bold text which goes on** and `code spans too`", 20);
+        assert_eq!(chunks, vec![
+            "some **bold**".to_string(),
+            "**text which**".to_string(),
+            "**goes on** and ".to_string(),
+            "`code spans too`".to_string(),
+        ]);
+        assert_eq!(open_spans("2 * 3 = `*6` *and __more"), vec!["*", "__"]);
+    }
 }
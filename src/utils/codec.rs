@@ -0,0 +1,684 @@
+use std::{fmt::Write, str};
+
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+// Encoders take text since that's all that can come from chat, decoders give
+// back bytes as the result isn't always valid UTF-8
+pub struct Codec {
+    pub name:    &'static str,
+    pub aliases: &'static [&'static str],
+    pub encode:  fn(&str) -> String,
+    pub decode:  fn(&str) -> Result<Vec<u8>, String>,
+    // Whether the input looks like this format, codecs without one are never
+    // picked by auto detection
+    pub detect:  Option<fn(&str) -> bool>,
+}
+
+// Ordered from most to least specific, auto detection picks the first match
+pub static CODECS: [Codec; 10] = [
+    Codec {
+        name:    "binary",
+        aliases: &["bin"],
+        encode:  binary_encode,
+        decode:  binary_decode,
+        detect:  Some(binary_detect),
+    },
+    Codec {
+        name:    "morse",
+        aliases: &[],
+        encode:  morse_encode,
+        decode:  morse_decode,
+        detect:  Some(morse_detect),
+    },
+    Codec {
+        name:    "hex",
+        aliases: &["base16"],
+        encode:  hex_encode,
+        decode:  hex_decode,
+        detect:  Some(hex_detect),
+    },
+    Codec {
+        name:    "base32",
+        aliases: &["b32"],
+        encode:  base32_encode,
+        decode:  base32_decode,
+        detect:  Some(base32_detect),
+    },
+    Codec {
+        name:    "base64",
+        aliases: &["b64"],
+        encode:  base64_encode,
+        decode:  base64_decode,
+        detect:  Some(base64_detect),
+    },
+    Codec {
+        name:    "base64url",
+        aliases: &["b64url", "b64u"],
+        encode:  base64url_encode,
+        decode:  base64url_decode,
+        detect:  Some(base64url_detect),
+    },
+    Codec {
+        name:    "url",
+        aliases: &["percent", "uri"],
+        encode:  url_encode,
+        decode:  url_decode,
+        detect:  Some(url_detect),
+    },
+    Codec {
+        name:    "html",
+        aliases: &["entities", "htmlentities"],
+        encode:  html_encode,
+        decode:  html_decode,
+        detect:  Some(html_detect),
+    },
+    Codec {
+        name:    "rot13",
+        aliases: &["rot"],
+        encode:  rot13,
+        decode:  rot13_decode,
+        detect:  None,
+    },
+    Codec {
+        name:    "text",
+        aliases: &["utf8", "plain"],
+        encode:  str::to_string,
+        decode:  text_decode,
+        detect:  None,
+    },
+];
+
+pub fn codec_by_name(name: &str) -> Option<&'static Codec> {
+    let name = name.to_lowercase();
+
+    CODECS
+        .iter()
+        .find(|codec| codec.name == name || codec.aliases.contains(&name.as_str()))
+}
+
+pub fn codec_names() -> Vec<&'static str> {
+    CODECS.iter().map(|codec| codec.name).collect()
+}
+
+#[derive(Clone, Copy)]
+pub enum Step {
+    Codec(&'static Codec),
+    Auto,
+}
+
+// `b64|hex` -> [base64, hex]
+pub fn parse_chain(spec: &str, allow_auto: bool) -> Result<Vec<Step>, String> {
+    spec.split('|')
+        .map(|name| {
+            let name = name.trim();
+
+            match codec_by_name(name) {
+                Some(codec) => Ok(Step::Codec(codec)),
+                None if allow_auto && name.eq_ignore_ascii_case("auto") => Ok(Step::Auto),
+                None => Err(format!("Unknown format `{}`", name)),
+            }
+        })
+        .collect()
+}
+
+pub fn encode_chain(steps: &[Step], input: &str) -> String {
+    steps.iter().fold(input.to_string(), |text, step| match step {
+        Step::Codec(codec) => (codec.encode)(&text),
+        Step::Auto => text,
+    })
+}
+
+// Returns the decoded bytes along with the name of every codec used, which
+// matters for auto detection
+pub fn decode_chain(steps: &[Step], input: &str) -> Result<(Vec<u8>, Vec<&'static str>), String> {
+    let mut bytes = input.as_bytes().to_vec();
+    let mut used = Vec::new();
+
+    for (idx, step) in steps.iter().enumerate() {
+        let text = str::from_utf8(&bytes).map_err(|_| {
+            format!(
+                "The output of {} isn't valid UTF-8 so it can't be decoded further",
+                used[idx - 1]
+            )
+        })?;
+
+        let (decoded, name) = match step {
+            Step::Codec(codec) => {
+                let decoded =
+                    (codec.decode)(text).map_err(|why| format!("Unable to decode {}: {}", codec.name, why))?;
+
+                (decoded, codec.name)
+            },
+            Step::Auto => {
+                let (codec, decoded) = detect(text).ok_or_else(|| "Unable to detect the format".to_string())?;
+
+                (decoded, codec.name)
+            },
+        };
+
+        bytes = decoded;
+        used.push(name);
+    }
+
+    Ok((bytes, used))
+}
+
+// Prefers a codec which decodes to valid UTF-8, otherwise the first codec
+// which decoded at all
+pub fn detect(input: &str) -> Option<(&'static Codec, Vec<u8>)> {
+    let mut fallback = None;
+
+    for codec in CODECS.iter() {
+        let matches = codec.detect.map_or(false, |detect| detect(input));
+
+        if !matches {
+            continue;
+        }
+
+        if let Ok(decoded) = (codec.decode)(input) {
+            if str::from_utf8(&decoded).is_ok() {
+                return Some((codec, decoded));
+            }
+
+            fallback.get_or_insert((codec, decoded));
+        }
+    }
+
+    fallback
+}
+
+// Same layout as `hexdump -C`
+pub fn hexdump(bytes: &[u8], limit: usize) -> String {
+    let mut dump = String::new();
+
+    for (idx, chunk) in bytes[..bytes.len().min(limit)].chunks(16).enumerate() {
+        let mut hex = String::new();
+
+        for (i, byte) in chunk.iter().enumerate() {
+            if i == 8 {
+                hex.push(' ');
+            }
+
+            let _ = write!(hex, "{:02x} ", byte);
+        }
+
+        let ascii = chunk
+            .iter()
+            .map(|byte| {
+                if byte.is_ascii_graphic() || *byte == b' ' {
+                    *byte as char
+                } else {
+                    '.'
+                }
+            })
+            .collect::<String>();
+
+        let _ = writeln!(dump, "{:08x}  {:<49} |{}|", idx * 16, hex, ascii);
+    }
+
+    if bytes.len() > limit {
+        let _ = writeln!(dump, "... {} more bytes", bytes.len() - limit);
+    }
+
+    dump
+}
+
+fn strip_whitespace(input: &str) -> String {
+    input.chars().filter(|c| !c.is_whitespace()).collect()
+}
+
+fn binary_encode(input: &str) -> String {
+    input
+        .bytes()
+        .map(|byte| format!("{:08b}", byte))
+        .collect::<Vec<String>>()
+        .join(" ")
+}
+
+fn binary_decode(input: &str) -> Result<Vec<u8>, String> {
+    let digits = strip_whitespace(input);
+
+    if digits.is_empty() || digits.len() % 8 != 0 || !digits.chars().all(|c| c == '0' || c == '1') {
+        return Err("Expected groups of 8 binary digits".to_string());
+    }
+
+    Ok(digits
+        .as_bytes()
+        .chunks(8)
+        .map(|chunk| chunk.iter().fold(0, |byte, bit| (byte << 1) | (bit - b'0')))
+        .collect())
+}
+
+fn binary_detect(input: &str) -> bool {
+    binary_decode(input).is_ok()
+}
+
+static MORSE: [(char, &str); 54] = [
+    ('A', ".-"),
+    ('B', "-..."),
+    ('C', "-.-."),
+    ('D', "-.."),
+    ('E', "."),
+    ('F', "..-."),
+    ('G', "--."),
+    ('H', "...."),
+    ('I', ".."),
+    ('J', ".---"),
+    ('K', "-.-"),
+    ('L', ".-.."),
+    ('M', "--"),
+    ('N', "-."),
+    ('O', "---"),
+    ('P', ".--."),
+    ('Q', "--.-"),
+    ('R', ".-."),
+    ('S', "..."),
+    ('T', "-"),
+    ('U', "..-"),
+    ('V', "...-"),
+    ('W', ".--"),
+    ('X', "-..-"),
+    ('Y', "-.--"),
+    ('Z', "--.."),
+    ('0', "-----"),
+    ('1', ".----"),
+    ('2', "..---"),
+    ('3', "...--"),
+    ('4', "....-"),
+    ('5', "....."),
+    ('6', "-...."),
+    ('7', "--..."),
+    ('8', "---.."),
+    ('9', "----."),
+    ('.', ".-.-.-"),
+    (',', "--..--"),
+    ('?', "..--.."),
+    ('\'', ".----."),
+    ('!', "-.-.--"),
+    ('/', "-..-."),
+    ('(', "-.--."),
+    (')', "-.--.-"),
+    ('&', ".-..."),
+    (':', "---..."),
+    (';', "-.-.-."),
+    ('=', "-...-"),
+    ('+', ".-.-."),
+    ('-', "-....-"),
+    ('_', "..--.-"),
+    ('"', ".-..-."),
+    ('$', "...-..-"),
+    ('@', ".--.-."),
+];
+
+// Characters without a Morse code are dropped
+fn morse_encode(input: &str) -> String {
+    input
+        .split_whitespace()
+        .map(|word| {
+            word.chars()
+                .filter_map(|c| MORSE.iter().find(|(letter, _)| *letter == c.to_ascii_uppercase()))
+                .map(|(_, code)| *code)
+                .collect::<Vec<&str>>()
+                .join(" ")
+        })
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<String>>()
+        .join(" / ")
+}
+
+fn morse_decode(input: &str) -> Result<Vec<u8>, String> {
+    // Dots and dashes are often pasted as lookalike characters
+    let input = input
+        .chars()
+        .map(|c| match c {
+            '·' | '•' => '.',
+            '−' | '—' | '_' => '-',
+            c => c,
+        })
+        .collect::<String>();
+    let mut words = Vec::new();
+
+    for word in input.split(|c| c == '/' || c == '|') {
+        let mut decoded = String::new();
+
+        for code in word.split_whitespace() {
+            match MORSE.iter().find(|(_, morse)| *morse == code) {
+                Some((letter, _)) => decoded.push(*letter),
+                None => return Err(format!("Unknown Morse code `{}`", code)),
+            }
+        }
+
+        if !decoded.is_empty() {
+            words.push(decoded);
+        }
+    }
+
+    if words.is_empty() {
+        return Err("Nothing to decode".to_string());
+    }
+
+    Ok(words.join(" ").into_bytes())
+}
+
+fn morse_detect(input: &str) -> bool {
+    input.contains(|c| c == '.' || c == '-') && input.chars().all(|c| ".-/|·•−—_".contains(c) || c.is_whitespace())
+}
+
+fn hex_encode(input: &str) -> String {
+    input.bytes().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+fn hex_decode(input: &str) -> Result<Vec<u8>, String> {
+    let digits = strip_whitespace(input).replace(':', "");
+    let digits = digits.strip_prefix("0x").unwrap_or(&digits);
+
+    if digits.is_empty() || digits.len() % 2 != 0 || !digits.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err("Expected pairs of hex digits".to_string());
+    }
+
+    (0..digits.len())
+        .step_by(2)
+        .map(|idx| u8::from_str_radix(&digits[idx..idx + 2], 16).map_err(|_| "Invalid hex digit".to_string()))
+        .collect()
+}
+
+fn hex_detect(input: &str) -> bool {
+    hex_decode(input).is_ok()
+}
+
+static BASE32_ALPHABET: &[u8; 32] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567";
+
+fn base32_encode(input: &str) -> String {
+    let mut encoded = String::new();
+
+    for chunk in input.as_bytes().chunks(5) {
+        let mut buffer = [0u8; 5];
+        buffer[..chunk.len()].copy_from_slice(chunk);
+        let bits = buffer.iter().fold(0u64, |bits, byte| (bits << 8) | *byte as u64);
+
+        // Each input byte covers 8 bits, rounded up to whole 5 bit characters
+        let chars = (chunk.len() * 8 + 4) / 5;
+        for idx in 0..8 {
+            if idx < chars {
+                encoded.push(BASE32_ALPHABET[((bits >> (35 - idx * 5)) & 31) as usize] as char);
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+
+    encoded
+}
+
+fn base32_decode(input: &str) -> Result<Vec<u8>, String> {
+    let input = strip_whitespace(input).to_uppercase();
+    let input = input.trim_end_matches('=');
+
+    let mut bits = 0u64;
+    let mut count = 0;
+    let mut decoded = Vec::new();
+
+    for c in input.bytes() {
+        let value = BASE32_ALPHABET
+            .iter()
+            .position(|letter| *letter == c)
+            .ok_or_else(|| format!("Invalid base32 character `{}`", c as char))?;
+
+        bits = (bits << 5) | value as u64;
+        count += 5;
+
+        if count >= 8 {
+            count -= 8;
+            decoded.push((bits >> count) as u8);
+        }
+    }
+
+    if decoded.is_empty() {
+        return Err("Nothing to decode".to_string());
+    }
+
+    Ok(decoded)
+}
+
+fn base32_detect(input: &str) -> bool {
+    let input = strip_whitespace(input);
+
+    input.len() % 8 == 0
+        && input.trim_end_matches('=').bytes().all(|c| BASE32_ALPHABET.contains(&c))
+        && base32_decode(&input).is_ok()
+}
+
+fn base64_encode(input: &str) -> String {
+    base64::encode(input)
+}
+
+fn base64_decode(input: &str) -> Result<Vec<u8>, String> {
+    base64::decode(strip_whitespace(input)).map_err(|why| why.to_string())
+}
+
+fn base64_detect(input: &str) -> bool {
+    let input = strip_whitespace(input);
+
+    !input.is_empty()
+        && input.len() % 4 == 0
+        && input
+            .trim_end_matches('=')
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '+' || c == '/')
+}
+
+fn base64url_encode(input: &str) -> String {
+    base64::encode_config(input, base64::URL_SAFE_NO_PAD)
+}
+
+fn base64url_decode(input: &str) -> Result<Vec<u8>, String> {
+    let input = strip_whitespace(input);
+
+    base64::decode_config(input.trim_end_matches('='), base64::URL_SAFE_NO_PAD).map_err(|why| why.to_string())
+}
+
+// Plain words are valid base64url too, so without a `-` or `_` to go by
+// it only counts if the result is readable
+fn base64url_detect(input: &str) -> bool {
+    let input = strip_whitespace(input);
+    let input = input.trim_end_matches('=');
+
+    if input.is_empty()
+        || input.len() % 4 == 1
+        || !input.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    {
+        return false;
+    }
+
+    input.contains(|c| c == '-' || c == '_')
+        || base64url_decode(input).map_or(false, |decoded| str::from_utf8(&decoded).is_ok())
+}
+
+fn url_encode(input: &str) -> String {
+    urlencoding::encode(input)
+}
+
+fn url_decode(input: &str) -> Result<Vec<u8>, String> {
+    urlencoding::decode(&input.replace('+', " "))
+        .map(String::into_bytes)
+        .map_err(|_| "Invalid percent encoding".to_string())
+}
+
+static PERCENT_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r"%[0-9A-Fa-f]{2}").unwrap());
+
+fn url_detect(input: &str) -> bool {
+    PERCENT_REGEX.is_match(input)
+}
+
+static ENTITIES: [(&str, char); 24] = [
+    ("amp", '&'),
+    ("lt", '<'),
+    ("gt", '>'),
+    ("quot", '"'),
+    ("apos", '\''),
+    ("nbsp", '\u{a0}'),
+    ("copy", '©'),
+    ("reg", '®'),
+    ("trade", '™'),
+    ("hellip", '…'),
+    ("mdash", '—'),
+    ("ndash", '–'),
+    ("lsquo", '‘'),
+    ("rsquo", '’'),
+    ("ldquo", '“'),
+    ("rdquo", '”'),
+    ("euro", '€'),
+    ("pound", '£'),
+    ("yen", '¥'),
+    ("cent", '¢'),
+    ("deg", '°'),
+    ("times", '×'),
+    ("divide", '÷'),
+    ("middot", '·'),
+];
+
+static ENTITY_REGEX: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"&(#[0-9]+|#[xX][0-9A-Fa-f]+|[A-Za-z][A-Za-z0-9]*);").unwrap());
+
+// Only escapes what's needed, anything outside of ASCII becomes a numeric
+// entity
+fn html_encode(input: &str) -> String {
+    let mut encoded = String::new();
+
+    for c in input.chars() {
+        match c {
+            '&' => encoded.push_str("&amp;"),
+            '<' => encoded.push_str("&lt;"),
+            '>' => encoded.push_str("&gt;"),
+            '"' => encoded.push_str("&quot;"),
+            '\'' => encoded.push_str("&#39;"),
+            c if !c.is_ascii() => {
+                let _ = write!(encoded, "&#{};", c as u32);
+            },
+            c => encoded.push(c),
+        }
+    }
+
+    encoded
+}
+
+// Unknown entities are left as they are
+fn html_decode(input: &str) -> Result<Vec<u8>, String> {
+    let decoded = ENTITY_REGEX.replace_all(input, |caps: &regex::Captures| {
+        let entity = &caps[1];
+        let c = if let Some(hex) = entity.strip_prefix("#x").or_else(|| entity.strip_prefix("#X")) {
+            u32::from_str_radix(hex, 16).ok().and_then(std::char::from_u32)
+        } else if let Some(dec) = entity.strip_prefix('#') {
+            dec.parse::<u32>().ok().and_then(std::char::from_u32)
+        } else {
+            ENTITIES.iter().find(|(name, _)| *name == entity).map(|(_, c)| *c)
+        };
+
+        c.map_or_else(|| caps[0].to_string(), |c| c.to_string())
+    });
+
+    Ok(decoded.into_owned().into_bytes())
+}
+
+fn html_detect(input: &str) -> bool {
+    ENTITY_REGEX.is_match(input)
+}
+
+fn rot13(input: &str) -> String {
+    input
+        .chars()
+        .map(|c| match c {
+            'a'..='z' => ((c as u8 - b'a' + 13) % 26 + b'a') as char,
+            'A'..='Z' => ((c as u8 - b'A' + 13) % 26 + b'A') as char,
+            _ => c,
+        })
+        .collect()
+}
+
+fn rot13_decode(input: &str) -> Result<Vec<u8>, String> {
+    Ok(rot13(input).into_bytes())
+}
+
+fn text_decode(input: &str) -> Result<Vec<u8>, String> {
+    Ok(input.as_bytes().to_vec())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn roundtrip(name: &str, input: &str, encoded: &str) {
+        let codec = codec_by_name(name).unwrap();
+
+        assert_eq!((codec.encode)(input), encoded, "{} encode", name);
+        assert_eq!((codec.decode)(encoded).unwrap(), input.as_bytes(), "{} decode", name);
+    }
+
+    #[test]
+    fn test_codecs() {
+        roundtrip("bin", "Hi", "01001000 01101001");
+        roundtrip("morse", "SOS HELP", "... --- ... / .... . .-.. .--.");
+        roundtrip("hex", "Inori", "496e6f7269");
+        roundtrip("b32", "foobar", "MZXW6YTBOI======");
+        roundtrip("b32", "f", "MY======");
+        roundtrip("b64", "I agree", "SSBhZ3JlZQ==");
+        roundtrip("b64url", "??>", "Pz8-");
+        roundtrip("url", "a b&c", "a%20b%26c");
+        roundtrip("html", "<a href=\"x\">é</a>", "&lt;a href=&quot;x&quot;&gt;&#233;&lt;/a&gt;");
+        roundtrip("rot13", "Hello", "Uryyb");
+
+        assert_eq!(hex_decode("0xDE AD").unwrap(), vec![0xde, 0xad]);
+        assert_eq!(html_decode("&hellip;&#x41;&bogus;").unwrap(), "…A&bogus;".as_bytes());
+        assert_eq!(morse_decode("···· ··").unwrap(), b"HI");
+        assert!(binary_decode("0101").is_err());
+        assert!(morse_decode("......-").is_err());
+        assert!(codec_by_name("auto").is_none());
+    }
+
+    #[test]
+    fn test_detect() {
+        let detected = |input: &str| detect(input).map(|(codec, _)| codec.name);
+
+        assert_eq!(detected("01001000 01101001"), Some("binary"));
+        assert_eq!(detected(".... .."), Some("morse"));
+        assert_eq!(detected("496e6f7269"), Some("hex"));
+        assert_eq!(detected("MZXW6YTBOI======"), Some("base32"));
+        assert_eq!(detected("SW5vcmkgaXMgdGhlIGJlc3Qgd2FpZnU="), Some("base64"));
+        assert_eq!(detected("SW5vcmk"), Some("base64url"));
+        assert_eq!(detected("a%20b"), Some("url"));
+        assert_eq!(detected("&lt;3"), Some("html"));
+        assert_eq!(detected("hello there"), None);
+    }
+
+    #[test]
+    fn test_chains() {
+        let steps = parse_chain("b64|hex", false).unwrap();
+        assert_eq!(encode_chain(&steps, "hi"), "61476b3d");
+
+        let steps = parse_chain("hex | b64", true).unwrap();
+        let (decoded, used) = decode_chain(&steps, "61476b3d").unwrap();
+        assert_eq!(decoded, b"hi");
+        assert_eq!(used, vec!["hex", "base64"]);
+
+        let steps = parse_chain("auto|auto", true).unwrap();
+        let (decoded, used) = decode_chain(&steps, "61476b3d").unwrap();
+        assert_eq!(decoded, b"hi");
+        assert_eq!(used, vec!["hex", "base64"]);
+
+        assert!(parse_chain("auto", false).is_err());
+        assert!(parse_chain("b64|nope", true).is_err());
+
+        let steps = parse_chain("hex|b64", false).unwrap();
+        assert!(decode_chain(&steps, "ff").unwrap_err().contains("UTF-8"));
+    }
+
+    #[test]
+    fn test_hexdump() {
+        assert_eq!(
+            hexdump(b"Hello\xffworld", 256),
+            "00000000  48 65 6c 6c 6f ff 77 6f  72 6c 64                 |Hello.world|\n"
+        );
+        assert!(hexdump(&[0; 40], 16).ends_with("... 24 more bytes\n"));
+    }
+}
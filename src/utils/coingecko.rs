@@ -0,0 +1,49 @@
+use std::{collections::HashMap, time::Duration};
+
+use async_trait::async_trait;
+
+use crate::{models::currency::CryptoProvider, utils::cache::get_cached};
+
+static API_URL: &str = "https://api.coingecko.com/api/v3/simple/price";
+
+// CoinGecko ID and the ticker it's shown as
+static COINS: [(&str, &str); 10] = [
+    ("bitcoin", "BTC"),
+    ("ethereum", "ETH"),
+    ("tether", "USDT"),
+    ("binancecoin", "BNB"),
+    ("ripple", "XRP"),
+    ("cardano", "ADA"),
+    ("solana", "SOL"),
+    ("dogecoin", "DOGE"),
+    ("litecoin", "LTC"),
+    ("monero", "XMR"),
+];
+
+pub struct CoinGecko;
+
+#[async_trait]
+impl CryptoProvider for CoinGecko {
+    fn name(&self) -> &'static str {
+        "coingecko"
+    }
+
+    async fn prices(&self, base: &str, ttl: u64) -> Result<HashMap<String, f64>, String> {
+        let ids = COINS.iter().map(|(id, _)| *id).collect::<Vec<&str>>().join(",");
+        let base = base.to_lowercase();
+        let url = format!("{}?ids={}&vs_currencies={}", API_URL, ids, base);
+
+        let res = get_cached(&url, Duration::from_secs(ttl)).await?;
+        let res = serde_json::from_str::<HashMap<String, HashMap<String, f64>>>(&res)
+            .map_err(|_| "Unable to parse the response from coingecko".to_string())?;
+
+        Ok(COINS
+            .iter()
+            .filter_map(|(id, ticker)| {
+                let price = res.get(*id)?.get(&base)?;
+
+                Some((ticker.to_string(), *price))
+            })
+            .collect())
+    }
+}
@@ -0,0 +1,122 @@
+use std::time::Duration;
+
+use reqwest::header::USER_AGENT;
+use serde::de::DeserializeOwned;
+use urlencoding::encode;
+
+use crate::{
+    models::{
+        commands::{CratesIoCrate, CratesIoResponse, CratesIoReverseDependencies, CratesIoVersion},
+        settings::CratesConfig,
+    },
+    utils::cache::{cached, fetch_request},
+};
+
+// crates.io rejects requests without a User-Agent
+static AGENT: &str = concat!("inori-rs/", env!("CARGO_PKG_VERSION"), " (https://github.com/L3afMe/inori-rs)");
+
+pub struct CrateInfo {
+    pub krate:                CratesIoCrate,
+    pub version:              Option<CratesIoVersion>,
+    pub reverse_dependencies: Option<u64>,
+}
+
+async fn crates_io<T: DeserializeOwned>(config: &CratesConfig, path: &str) -> Result<T, String> {
+    let url = format!("{}/{}", config.api.trim_end_matches('/'), path);
+    let request = || fetch_request(reqwest::Client::new().get(&url).header(USER_AGENT, AGENT));
+
+    let res = cached(&url, Duration::from_secs(config.ttl * 60), request).await?;
+
+    serde_json::from_str::<T>(&res).map_err(|_| "Unable to parse the response from crates.io".to_string())
+}
+
+// 1234567 -> 1,234,567
+pub fn format_count(count: u64) -> String {
+    let digits = count.to_string();
+    let mut formatted = String::new();
+
+    for (idx, digit) in digits.chars().enumerate() {
+        if idx > 0 && (digits.len() - idx) % 3 == 0 {
+            formatted.push(',');
+        }
+
+        formatted.push(digit);
+    }
+
+    formatted
+}
+
+// The version crates.io shows as the latest, falling back to the newest
+// version which hasn't been yanked
+pub fn latest_version(krate: &CratesIoCrate, versions: Vec<CratesIoVersion>) -> Option<CratesIoVersion> {
+    let latest = krate.max_stable_version.as_ref().unwrap_or(&krate.max_version);
+
+    match versions.iter().position(|version| &version.num == latest) {
+        Some(idx) => versions.into_iter().nth(idx),
+        None => versions.into_iter().find(|version| !version.yanked),
+    }
+}
+
+pub async fn lookup(name: &str, config: &CratesConfig) -> Result<CrateInfo, String> {
+    let name = encode(name.trim());
+    let res = crates_io::<CratesIoResponse>(config, &format!("crates/{}", name)).await?;
+
+    // Only the total is needed so a single result is requested
+    let reverse_dependencies =
+        crates_io::<CratesIoReverseDependencies>(config, &format!("crates/{}/reverse_dependencies?per_page=1", name))
+            .await
+            .ok()
+            .map(|res| res.meta.total);
+
+    Ok(CrateInfo {
+        version: latest_version(&res.krate, res.versions),
+        krate: res.krate,
+        reverse_dependencies,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RESPONSE: &str = r#"{
+        "crate": {
+            "name": "serde",
+            "description": "A generic serialization/deserialization framework",
+            "max_version": "2.0.0-alpha",
+            "max_stable_version": "1.0.2",
+            "downloads": 1000,
+            "repository": "https://github.com/serde-rs/serde",
+            "updated_at": "2021-03-01T00:00:00.000000+00:00"
+        },
+        "versions": [
+            {"num": "2.0.0-alpha", "license": "MIT", "yanked": false, "features": {}},
+            {"num": "1.0.2", "license": "MIT OR Apache-2.0", "yanked": false, "features": {"std": []}},
+            {"num": "1.0.1", "license": "MIT OR Apache-2.0", "yanked": true, "features": {}}
+        ]
+    }"#;
+
+    #[test]
+    fn test_format_count() {
+        assert_eq!(format_count(0), "0");
+        assert_eq!(format_count(999), "999");
+        assert_eq!(format_count(1000), "1,000");
+        assert_eq!(format_count(1234567), "1,234,567");
+    }
+
+    #[test]
+    fn test_latest_version() {
+        let res = serde_json::from_str::<CratesIoResponse>(RESPONSE).unwrap();
+        let version = latest_version(&res.krate, res.versions.clone()).unwrap();
+
+        assert_eq!(version.num, "1.0.2");
+        assert_eq!(version.license.as_deref(), Some("MIT OR Apache-2.0"));
+        assert!(version.features.contains_key("std"));
+
+        let mut krate = res.krate;
+        krate.max_stable_version = Some("0.9.0".to_string());
+        let versions = res.versions.into_iter().rev().collect();
+
+        assert_eq!(latest_version(&krate, versions).unwrap().num, "1.0.2");
+    }
+}
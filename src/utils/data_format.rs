@@ -0,0 +1,361 @@
+use serde_json::{Map, Number, Value};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Format {
+    Json,
+    Toml,
+    Yaml,
+}
+
+impl Format {
+    pub fn parse(name: &str) -> Option<Format> {
+        match name.to_lowercase().as_str() {
+            "json" => Some(Format::Json),
+            "toml" => Some(Format::Toml),
+            "yaml" | "yml" => Some(Format::Yaml),
+            _ => None,
+        }
+    }
+
+    // Also used as the code block language
+    pub fn name(self) -> &'static str {
+        match self {
+            Format::Json => "json",
+            Format::Toml => "toml",
+            Format::Yaml => "yaml",
+        }
+    }
+
+    // Only used when there's no code block language or file extension to go by
+    pub fn detect(input: &str) -> Format {
+        let trimmed = input.trim_start();
+
+        if trimmed.starts_with('{') || (trimmed.starts_with('[') && serde_json::from_str::<Value>(input).is_ok()) {
+            return Format::Json;
+        }
+
+        let is_toml = input.lines().map(str::trim).any(|line| {
+            (line.starts_with('[') && line.ends_with(']'))
+                || line.split_once('=').map_or(false, |(key, _)| {
+                    !key.trim().is_empty() && key.trim().chars().all(|c| c.is_alphanumeric() || "_-.\"' ".contains(c))
+                })
+        });
+
+        if is_toml {
+            Format::Toml
+        } else {
+            Format::Yaml
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct ParseError {
+    pub format:   Format,
+    pub message:  String,
+    // Both start at 1
+    pub position: Option<(usize, usize)>,
+}
+
+impl ParseError {
+    fn new(format: Format, message: String, position: Option<(usize, usize)>) -> ParseError {
+        // Every parser appends the position to its message, it's shown
+        // separately instead
+        let message = match message.rsplit_once(" at line ") {
+            Some((message, _)) if position.is_some() => message.to_string(),
+            _ => message,
+        };
+
+        ParseError {
+            format,
+            message,
+            position,
+        }
+    }
+
+    // Shows the message with the offending line and a marker under the column
+    pub fn render(&self, input: &str) -> String {
+        let (line, column) = match self.position {
+            Some(position) => position,
+            None => return format!("Invalid {}: {}", self.format.name().to_uppercase(), self.message),
+        };
+
+        let text = input.lines().nth(line.saturating_sub(1)).unwrap_or_default();
+
+        format!(
+            "Invalid {} at line {}, column {}: {}\n```\n{}\n{}^\n```",
+            self.format.name().to_uppercase(),
+            line,
+            column,
+            self.message,
+            text,
+            " ".repeat(column.saturating_sub(1))
+        )
+    }
+}
+
+fn toml_to_json(value: toml::Value) -> Value {
+    match value {
+        toml::Value::String(string) => Value::String(string),
+        toml::Value::Integer(int) => Value::Number(int.into()),
+        toml::Value::Float(float) => Number::from_f64(float).map_or(Value::Null, Value::Number),
+        toml::Value::Boolean(boolean) => Value::Bool(boolean),
+        toml::Value::Datetime(datetime) => Value::String(datetime.to_string()),
+        toml::Value::Array(array) => Value::Array(array.into_iter().map(toml_to_json).collect()),
+        toml::Value::Table(table) => Value::Object(
+            table
+                .into_iter()
+                .map(|(key, value)| (key, toml_to_json(value)))
+                .collect::<Map<_, _>>(),
+        ),
+    }
+}
+
+pub fn parse(input: &str, format: Format) -> Result<Value, ParseError> {
+    match format {
+        Format::Json => serde_json::from_str::<Value>(input)
+            .map_err(|why| ParseError::new(format, why.to_string(), Some((why.line(), why.column())))),
+        Format::Toml => toml::from_str::<toml::Value>(input).map(toml_to_json).map_err(|why| {
+            let position = why.line_col().map(|(line, column)| (line + 1, column + 1));
+
+            ParseError::new(format, why.to_string(), position)
+        }),
+        Format::Yaml => serde_yaml::from_str::<Value>(input).map_err(|why| {
+            let position = why.location().map(|location| (location.line(), location.column()));
+
+            ParseError::new(format, why.to_string(), position)
+        }),
+    }
+}
+
+// Minified YAML is written as JSON, which YAML is a superset of
+pub fn render(value: &Value, format: Format, pretty: bool) -> Result<String, String> {
+    match (format, pretty) {
+        (Format::Json, true) => serde_json::to_string_pretty(value).map_err(|why| why.to_string()),
+        (Format::Json, false) | (Format::Yaml, false) => serde_json::to_string(value).map_err(|why| why.to_string()),
+        (Format::Toml, _) => {
+            if !value.is_object() {
+                return Err("TOML documents have to be a table at the top level".to_string());
+            }
+
+            let value = toml::Value::try_from(value).map_err(|why| format!("Unable to convert to TOML: {}", why))?;
+
+            if pretty {
+                toml::to_string_pretty(&value).map_err(|why| why.to_string())
+            } else {
+                toml::to_string(&value).map_err(|why| why.to_string())
+            }
+        },
+        (Format::Yaml, true) => serde_yaml::to_string(value)
+            .map(|yaml| yaml.trim_start_matches("---\n").to_string())
+            .map_err(|why| why.to_string()),
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum Segment {
+    Key(String),
+    Index(i64),
+    Iterate,
+}
+
+fn quoted(chars: &[char], start: usize) -> Result<(String, usize), String> {
+    let mut key = String::new();
+    let mut idx = start + 1;
+
+    while idx < chars.len() {
+        match chars[idx] {
+            '"' => return Ok((key, idx + 1)),
+            '\\' if idx + 1 < chars.len() => {
+                key.push(chars[idx + 1]);
+                idx += 2;
+            },
+            c => {
+                key.push(c);
+                idx += 1;
+            },
+        }
+    }
+
+    Err("Unclosed `\"` in path".to_string())
+}
+
+// A subset of jq paths: `.a.b`, `.["a b"]`, `."a b"`, `[0]`, `[-1]` and `[]`
+fn parse_path(path: &str) -> Result<Vec<Segment>, String> {
+    let chars = path.trim().chars().collect::<Vec<char>>();
+    let mut segments = Vec::new();
+    let mut idx = 0;
+
+    while idx < chars.len() {
+        match chars[idx] {
+            '.' => {
+                idx += 1;
+
+                if chars.get(idx) == Some(&'"') {
+                    let (key, end) = quoted(&chars, idx)?;
+                    segments.push(Segment::Key(key));
+                    idx = end;
+                }
+            },
+            '[' => {
+                let end = match chars[idx..].iter().position(|c| *c == ']') {
+                    Some(end) => idx + end,
+                    None => return Err("Unclosed `[` in path".to_string()),
+                };
+                let inner = chars[idx + 1..end].iter().collect::<String>();
+                let inner = inner.trim();
+
+                if inner.is_empty() {
+                    segments.push(Segment::Iterate);
+                } else if inner.starts_with('"') {
+                    let inner_chars = inner.chars().collect::<Vec<char>>();
+                    let (key, key_end) = quoted(&inner_chars, 0)?;
+
+                    if key_end != inner_chars.len() {
+                        return Err(format!("Invalid index `[{}]`", inner));
+                    }
+
+                    segments.push(Segment::Key(key));
+                } else {
+                    match inner.parse::<i64>() {
+                        Ok(index) => segments.push(Segment::Index(index)),
+                        Err(_) => return Err(format!("Invalid index `[{}]`", inner)),
+                    }
+                }
+
+                idx = end + 1;
+            },
+            _ => {
+                let end = chars[idx..]
+                    .iter()
+                    .position(|c| *c == '.' || *c == '[')
+                    .map_or(chars.len(), |end| idx + end);
+
+                segments.push(Segment::Key(chars[idx..end].iter().collect()));
+                idx = end;
+            },
+        }
+    }
+
+    Ok(segments)
+}
+
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+// Missing keys and indexes give null like they do in jq
+pub fn query(value: &Value, path: &str) -> Result<Vec<Value>, String> {
+    let mut results = vec![value.clone()];
+
+    for segment in parse_path(path)? {
+        let mut next = Vec::new();
+
+        for value in results {
+            match (&segment, value) {
+                (_, Value::Null) if segment != Segment::Iterate => next.push(Value::Null),
+                (Segment::Key(key), Value::Object(mut map)) => next.push(map.remove(key).unwrap_or(Value::Null)),
+                (Segment::Index(index), Value::Array(mut array)) => {
+                    let index = if *index < 0 { array.len() as i64 + index } else { *index };
+
+                    if index >= 0 && (index as usize) < array.len() {
+                        next.push(array.swap_remove(index as usize));
+                    } else {
+                        next.push(Value::Null);
+                    }
+                },
+                (Segment::Iterate, Value::Array(array)) => next.extend(array),
+                (Segment::Iterate, Value::Object(map)) => next.extend(map.into_iter().map(|(_, value)| value)),
+                (Segment::Key(key), value) => {
+                    return Err(format!("Cannot get `{}` from {}", key, type_name(&value)));
+                },
+                (Segment::Index(index), value) => {
+                    return Err(format!("Cannot get index {} from {}", index, type_name(&value)));
+                },
+                (Segment::Iterate, value) => return Err(format!("Cannot iterate over {}", type_name(&value))),
+            }
+        }
+
+        results = next;
+    }
+
+    Ok(results)
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_detect() {
+        assert_eq!(Format::detect("{\"a\": 1}"), Format::Json);
+        assert_eq!(Format::detect("[1, 2]"), Format::Json);
+        assert_eq!(Format::detect("[server]\nport = 80"), Format::Toml);
+        assert_eq!(Format::detect("a = \"b\""), Format::Toml);
+        assert_eq!(Format::detect("a: 1\nb:\n  - c"), Format::Yaml);
+    }
+
+    #[test]
+    fn test_errors() {
+        let err = parse("{\n  \"a\": 1,\n  \"b\" 2\n}", Format::Json).unwrap_err();
+        assert_eq!(err.position, Some((3, 7)));
+        assert_eq!(err.message, "expected `:`");
+        assert!(err.render("{\n  \"a\": 1,\n  \"b\" 2\n}").ends_with("  \"b\" 2\n      ^\n```"));
+
+        let err = parse("a = 1\nb = ", Format::Toml).unwrap_err();
+        assert_eq!(err.position.map(|(line, _)| line), Some(2));
+        assert!(!err.message.contains(" at line "));
+
+        let err = parse("a: [1, 2", Format::Yaml).unwrap_err();
+        assert!(err.position.is_some());
+    }
+
+    #[test]
+    fn test_convert() {
+        let value = parse("title = \"Inori\"\n[owner]\nname = \"L3af\"\nids = [1, 2]", Format::Toml).unwrap();
+        assert_eq!(value, json!({"title": "Inori", "owner": {"name": "L3af", "ids": [1, 2]}}));
+
+        let value = json!({"owner": {"name": "L3af"}, "title": "Inori"});
+        assert_eq!(
+            render(&value, Format::Json, false).unwrap(),
+            r#"{"owner":{"name":"L3af"},"title":"Inori"}"#
+        );
+        assert_eq!(
+            render(&value, Format::Yaml, true).unwrap(),
+            "owner:\n  name: L3af\ntitle: Inori\n"
+        );
+        assert_eq!(
+            render(&value, Format::Toml, true).unwrap(),
+            "title = 'Inori'\n\n[owner]\nname = 'L3af'\n"
+        );
+        assert!(render(&json!([1]), Format::Toml, true).is_err());
+        assert!(render(&json!({ "a": null }), Format::Toml, true).is_err());
+    }
+
+    #[test]
+    fn test_query() {
+        let value = json!({"a": {"b": [1, {"c": "d"}]}, "e f": true});
+
+        assert_eq!(query(&value, ".a.b[0]").unwrap(), vec![json!(1)]);
+        assert_eq!(query(&value, ".a.b[-1].c").unwrap(), vec![json!("d")]);
+        assert_eq!(query(&value, ".[\"e f\"]").unwrap(), vec![json!(true)]);
+        assert_eq!(query(&value, ".\"e f\"").unwrap(), vec![json!(true)]);
+        assert_eq!(query(&value, "a.b[]").unwrap(), vec![json!(1), json!({"c": "d"})]);
+        assert_eq!(query(&value, ".").unwrap(), vec![value.clone()]);
+        assert_eq!(query(&value, ".missing.key").unwrap(), vec![Value::Null]);
+        assert_eq!(query(&value, ".a.b[5]").unwrap(), vec![Value::Null]);
+
+        assert!(query(&value, ".a.b.c").is_err());
+        assert!(query(&value, ".a[").is_err());
+        assert!(query(&value, ".a[x]").is_err());
+    }
+}
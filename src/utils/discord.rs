@@ -1,12 +1,27 @@
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
 use once_cell::sync::Lazy;
 use serde_json::{Number, Value};
 use serenity::{
-    model::prelude::{GuildId, Member, Permissions, Role, User, UserId},
+    model::prelude::{
+        ChannelId, GuildChannel, GuildId, Member, Message, PermissionOverwriteType, Permissions, Role, RoleId, User,
+        UserId,
+    },
     prelude::Context,
     utils::Colour,
     Result,
 };
 
+use crate::models::commands::{CachedPermissions, PermissionCache};
+
+const PERMISSION_CACHE_TTL: Duration = Duration::from_secs(300);
+
+// Largest attachment commands will download, 8 MiB
+const MAX_ATTACHMENT_SIZE: u64 = 8 * 1024 * 1024;
+
 pub async fn get_member(ctx: &Context, gid: GuildId, uid: UserId) -> Result<Member> {
     let mut value = reqwest::Client::new()
         .get(&format!("https://discord.com/api/v8/guilds/{}/members/{}", gid.0, uid.0))
@@ -52,9 +67,10 @@ pub async fn get_permissions(
 ) -> Permissions {
     let member = if let Some(member) = member {
         member.clone()
+    } else if let Ok(member) = get_member(ctx, gid, ctx.cache.current_user_id().await).await {
+        member
     } else {
-        let user = ctx.http.get_current_user().await.unwrap();
-        ctx.http.get_member(gid.0, user.id.0).await.unwrap()
+        return Permissions::empty();
     };
 
     let roles = if let Some(roles) = roles {
@@ -84,6 +100,114 @@ pub async fn get_permissions(
     }
 }
 
+pub async fn get_channel_permissions(ctx: &Context, channel: &GuildChannel) -> Permissions {
+    let user_id = ctx.cache.current_user_id().await;
+
+    let guild = match ctx.http.get_guild(channel.guild_id.0).await {
+        Ok(guild) => guild,
+        Err(_) => return Permissions::empty(),
+    };
+
+    if guild.owner_id == user_id {
+        return Permissions::all();
+    }
+
+    let member = match get_member(ctx, guild.id, user_id).await {
+        Ok(member) => member,
+        Err(_) => return Permissions::empty(),
+    };
+
+    // The @everyone role shares its ID with the guild
+    let mut perms = guild
+        .roles
+        .get(&RoleId(guild.id.0))
+        .map_or_else(Permissions::empty, |role| role.permissions);
+
+    for role in &member.roles {
+        if let Some(role) = guild.roles.get(role) {
+            perms |= role.permissions;
+        }
+    }
+
+    if perms.administrator() {
+        return Permissions::all();
+    }
+
+    let mut everyone = None;
+    let mut member_overwrite = None;
+    let mut role_allow = Permissions::empty();
+    let mut role_deny = Permissions::empty();
+
+    for overwrite in &channel.permission_overwrites {
+        match overwrite.kind {
+            PermissionOverwriteType::Role(id) if id.0 == guild.id.0 => everyone = Some(overwrite),
+            PermissionOverwriteType::Role(id) if member.roles.contains(&id) => {
+                role_allow |= overwrite.allow;
+                role_deny |= overwrite.deny;
+            },
+            PermissionOverwriteType::Member(id) if id == user_id => member_overwrite = Some(overwrite),
+            _ => {},
+        }
+    }
+
+    // Overwrites apply in order of @everyone, roles then the member
+    if let Some(overwrite) = everyone {
+        perms = (perms & !overwrite.deny) | overwrite.allow;
+    }
+
+    perms = (perms & !role_deny) | role_allow;
+
+    if let Some(overwrite) = member_overwrite {
+        perms = (perms & !overwrite.deny) | overwrite.allow;
+    }
+
+    perms
+}
+
+pub async fn get_cached_permissions(ctx: &Context, channel_id: ChannelId) -> CachedPermissions {
+    let cache = {
+        let data = ctx.data.read().await;
+
+        Arc::clone(data.get::<PermissionCache>().expect("Expected PermissionCache in TypeMap."))
+    };
+
+    if let Some(cached) = cache.read().await.get(&channel_id.0) {
+        if cached.fetched.elapsed() < PERMISSION_CACHE_TTL {
+            return cached.clone();
+        }
+    }
+
+    let cached = match ctx.http.get_channel(channel_id.0).await {
+        Ok(channel) => {
+            if let Some(channel) = channel.guild() {
+                CachedPermissions {
+                    guild_id: Some(channel.guild_id.0),
+                    perms:    get_channel_permissions(ctx, &channel).await,
+                    fetched:  Instant::now(),
+                }
+            } else {
+                CachedPermissions {
+                    guild_id: None,
+                    perms:    DM_PERMISSIONS.unwrap(),
+                    fetched:  Instant::now(),
+                }
+            }
+        },
+        // Don't cache failed lookups so they get retried
+        Err(_) => {
+            return CachedPermissions {
+                guild_id: None,
+                perms:    DM_PERMISSIONS.unwrap(),
+                fetched:  Instant::now(),
+            };
+        },
+    };
+
+    cache.write().await.insert(channel_id.0, cached.clone());
+
+    cached
+}
+
 pub static DM_PERMISSIONS: Lazy<Option<Permissions>> = Lazy::new(|| {
     Some(Permissions::from_bits(0b000_0010_0011_0101_1100_1100_0100_0000).unwrap_or_else(Permissions::empty))
 });
@@ -94,3 +218,20 @@ pub fn get_avatar(user: &User) -> String {
         None => user.default_avatar_url(),
     }
 }
+
+// Downloads the first attachment on a message along with its file name
+pub async fn download_attachment(msg: &Message) -> Option<std::result::Result<(String, Vec<u8>), String>> {
+    let attachment = msg.attachments.first()?;
+
+    if attachment.size > MAX_ATTACHMENT_SIZE {
+        return Some(Err("Attachments can be at most 8 MiB".to_string()));
+    }
+
+    Some(
+        attachment
+            .download()
+            .await
+            .map(|data| (attachment.filename.clone(), data))
+            .map_err(|why| format!("Unable to download the attachment: {}", why)),
+    )
+}
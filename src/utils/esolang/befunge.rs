@@ -0,0 +1,233 @@
+use rand::Rng;
+
+use super::{Halt, Interpreter, Runtime};
+
+const WIDTH: usize = 80;
+const HEIGHT: usize = 25;
+
+struct Machine {
+    grid:        [[i64; WIDTH]; HEIGHT],
+    stack:       Vec<i64>,
+    x:           usize,
+    y:           usize,
+    dx:          isize,
+    dy:          isize,
+    string_mode: bool,
+}
+
+impl Machine {
+    fn new(code: &str) -> Result<Machine, String> {
+        let mut grid = [[b' ' as i64; WIDTH]; HEIGHT];
+
+        for (y, line) in code.lines().enumerate() {
+            if y >= HEIGHT {
+                return Err(format!("Programs can be at most {} lines", HEIGHT));
+            }
+
+            for (x, c) in line.chars().enumerate() {
+                if x >= WIDTH {
+                    return Err(format!("Lines can be at most {} characters", WIDTH));
+                }
+
+                grid[y][x] = c as i64;
+            }
+        }
+
+        Ok(Machine {
+            grid,
+            stack: Vec::new(),
+            x: 0,
+            y: 0,
+            dx: 1,
+            dy: 0,
+            string_mode: false,
+        })
+    }
+
+    // Popping an empty stack gives 0
+    fn pop(&mut self) -> i64 {
+        self.stack.pop().unwrap_or(0)
+    }
+
+    fn advance(&mut self) {
+        self.x = (self.x as isize + self.dx).rem_euclid(WIDTH as isize) as usize;
+        self.y = (self.y as isize + self.dy).rem_euclid(HEIGHT as isize) as usize;
+    }
+
+    fn cell(&self, x: i64, y: i64) -> Option<(usize, usize)> {
+        if x >= 0 && (x as usize) < WIDTH && y >= 0 && (y as usize) < HEIGHT {
+            Some((x as usize, y as usize))
+        } else {
+            None
+        }
+    }
+}
+
+pub struct Befunge;
+
+impl Interpreter for Befunge {
+    fn name(&self) -> &'static str {
+        "befunge"
+    }
+
+    fn aliases(&self) -> &'static [&'static str] {
+        &["befunge93", "bf93"]
+    }
+
+    // Befunge-93 with an 80x25 torus, division by zero gives 0 and
+    // reading past the end of the input gives -1
+    fn run(&self, code: &str, runtime: &mut Runtime) -> Result<(), Halt> {
+        let mut machine = Machine::new(code).map_err(Halt::Error)?;
+
+        loop {
+            runtime.tick()?;
+
+            let instruction = machine.grid[machine.y][machine.x];
+
+            if machine.string_mode {
+                if instruction == b'"' as i64 {
+                    machine.string_mode = false;
+                } else {
+                    machine.stack.push(instruction);
+                }
+
+                machine.advance();
+                continue;
+            }
+
+            match std::char::from_u32(instruction as u32).unwrap_or(' ') {
+                '0'..='9' => machine.stack.push(instruction - b'0' as i64),
+                '+' => {
+                    let (b, a) = (machine.pop(), machine.pop());
+                    machine.stack.push(a.wrapping_add(b));
+                },
+                '-' => {
+                    let (b, a) = (machine.pop(), machine.pop());
+                    machine.stack.push(a.wrapping_sub(b));
+                },
+                '*' => {
+                    let (b, a) = (machine.pop(), machine.pop());
+                    machine.stack.push(a.wrapping_mul(b));
+                },
+                '/' => {
+                    let (b, a) = (machine.pop(), machine.pop());
+                    machine.stack.push(a.checked_div(b).unwrap_or(0));
+                },
+                '%' => {
+                    let (b, a) = (machine.pop(), machine.pop());
+                    machine.stack.push(a.checked_rem(b).unwrap_or(0));
+                },
+                '!' => {
+                    let value = machine.pop();
+                    machine.stack.push((value == 0) as i64);
+                },
+                '`' => {
+                    let (b, a) = (machine.pop(), machine.pop());
+                    machine.stack.push((a > b) as i64);
+                },
+                '>' => {
+                    machine.dx = 1;
+                    machine.dy = 0;
+                },
+                '<' => {
+                    machine.dx = -1;
+                    machine.dy = 0;
+                },
+                '^' => {
+                    machine.dx = 0;
+                    machine.dy = -1;
+                },
+                'v' => {
+                    machine.dx = 0;
+                    machine.dy = 1;
+                },
+                '?' => {
+                    let (dx, dy) = [(1, 0), (-1, 0), (0, 1), (0, -1)][rand::thread_rng().gen_range(0..4)];
+                    machine.dx = dx;
+                    machine.dy = dy;
+                },
+                '_' => {
+                    machine.dx = if machine.pop() == 0 { 1 } else { -1 };
+                    machine.dy = 0;
+                },
+                '|' => {
+                    machine.dx = 0;
+                    machine.dy = if machine.pop() == 0 { 1 } else { -1 };
+                },
+                '"' => machine.string_mode = true,
+                ':' => {
+                    let value = machine.pop();
+                    machine.stack.push(value);
+                    machine.stack.push(value);
+                },
+                '\\' => {
+                    let (b, a) = (machine.pop(), machine.pop());
+                    machine.stack.push(b);
+                    machine.stack.push(a);
+                },
+                '$' => {
+                    machine.pop();
+                },
+                '.' => {
+                    let value = machine.pop();
+                    runtime.write(&format!("{} ", value))?;
+                },
+                ',' => {
+                    let value = machine.pop();
+                    runtime.write_char(std::char::from_u32(value as u32).unwrap_or('\u{fffd}'))?;
+                },
+                '#' => machine.advance(),
+                'g' => {
+                    let (y, x) = (machine.pop(), machine.pop());
+                    let value = machine.cell(x, y).map_or(0, |(x, y)| machine.grid[y][x]);
+                    machine.stack.push(value);
+                },
+                'p' => {
+                    let (y, x, value) = (machine.pop(), machine.pop(), machine.pop());
+
+                    if let Some((x, y)) = machine.cell(x, y) {
+                        machine.grid[y][x] = value;
+                    }
+                },
+                '&' => {
+                    let value = runtime.read_number().unwrap_or(-1);
+                    machine.stack.push(value);
+                },
+                '~' => {
+                    let value = runtime.read_char().map_or(-1, |c| c as i64);
+                    machine.stack.push(value);
+                },
+                '@' => return Ok(()),
+                ' ' => {},
+                c => {
+                    return Err(Halt::Error(format!(
+                        "Unknown instruction `{}` at {}, {}",
+                        c, machine.x, machine.y
+                    )));
+                },
+            }
+
+            machine.advance();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::esolang::{execute, Limits};
+
+    fn run(code: &str, input: &str) -> String {
+        execute(&Befunge, code, input, Limits::default()).output
+    }
+
+    #[test]
+    fn test_befunge() {
+        assert_eq!(run("v\n>25*3-.@", ""), "7 ");
+        assert_eq!(run("&&+.@", "20 22"), "42 ");
+        assert_eq!(run("~:1+!#@_,", "abc"), "abc");
+        assert_eq!(run("5 0/.@", ""), "0 ");
+        // Self modifying code, `p` writes an `@` over the `x`
+        assert_eq!(run("88*90p1. x", ""), "1 ");
+    }
+}
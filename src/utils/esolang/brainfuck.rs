@@ -0,0 +1,287 @@
+use super::{Halt, Interpreter, Runtime};
+
+const TAPE_SIZE: usize = 30_000;
+// Cells shown around the data pointer
+const WINDOW_SIZE: usize = 9;
+const OUTPUT_LIMIT: usize = 2000;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Snapshot {
+    pub step:         usize,
+    // Instructions run since the last snapshot, runs of the same `+`, `-`,
+    // `<` or `>` are merged. None for the initial state
+    pub span:         Option<(usize, usize)>,
+    pub pointer:      usize,
+    pub window_start: usize,
+    pub window:       Vec<u8>,
+    pub output:       String,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Outcome {
+    Finished,
+    StepLimit,
+    OutputLimit,
+    Error(String),
+}
+
+pub struct Trace {
+    // The last snapshot is always the final state, including all output
+    pub snapshots: Vec<Snapshot>,
+    pub steps:     usize,
+    pub outcome:   Outcome,
+}
+
+pub struct Program {
+    code:  Vec<char>,
+    // Index of the matching bracket for every `[` and `]`
+    jumps: Vec<usize>,
+}
+
+struct Machine {
+    tape:    Vec<u8>,
+    pointer: usize,
+    ip:      usize,
+}
+
+impl Machine {
+    fn new() -> Machine {
+        Machine {
+            tape:    vec![0; TAPE_SIZE],
+            pointer: 0,
+            ip:      0,
+        }
+    }
+}
+
+// Input and output are left to whoever runs the program
+enum Io {
+    Read,
+    Write(u8),
+}
+
+fn bytes_to_string(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| *byte as char).collect()
+}
+
+impl Program {
+    // Any character that isn't an instruction is treated as a comment
+    pub fn parse(code: &str) -> Result<Program, String> {
+        let code = code.chars().filter(|c| "+-<>.,[]".contains(*c)).collect::<Vec<char>>();
+        let mut jumps = vec![0; code.len()];
+        let mut open = Vec::new();
+
+        for (idx, c) in code.iter().enumerate() {
+            match c {
+                '[' => open.push(idx),
+                ']' => {
+                    let start = open.pop().ok_or_else(|| format!("Unmatched `]` at instruction {}", idx + 1))?;
+
+                    jumps[start] = idx;
+                    jumps[idx] = start;
+                },
+                _ => {},
+            }
+        }
+
+        if let Some(idx) = open.pop() {
+            return Err(format!("Unmatched `[` at instruction {}", idx + 1));
+        }
+
+        Ok(Program {
+            code,
+            jumps,
+        })
+    }
+
+    pub fn code(&self) -> String {
+        self.code.iter().collect()
+    }
+
+    // Runs the instruction at the instruction pointer and moves past it
+    fn step(&self, machine: &mut Machine) -> Result<Option<Io>, String> {
+        let Machine {
+            tape,
+            pointer,
+            ip,
+        } = machine;
+        let instruction = self.code[*ip];
+
+        match instruction {
+            '+' => tape[*pointer] = tape[*pointer].wrapping_add(1),
+            '-' => tape[*pointer] = tape[*pointer].wrapping_sub(1),
+            '>' if *pointer + 1 >= TAPE_SIZE => {
+                return Err(format!("Moved past the last cell at instruction {}", *ip + 1));
+            },
+            '>' => *pointer += 1,
+            '<' if *pointer == 0 => {
+                return Err(format!("Moved before the first cell at instruction {}", *ip + 1));
+            },
+            '<' => *pointer -= 1,
+            '[' if tape[*pointer] == 0 => *ip = self.jumps[*ip],
+            ']' if tape[*pointer] != 0 => *ip = self.jumps[*ip],
+            _ => {},
+        }
+
+        let io = match instruction {
+            '.' => Some(Io::Write(tape[*pointer])),
+            ',' => Some(Io::Read),
+            _ => None,
+        };
+
+        *ip += 1;
+
+        Ok(io)
+    }
+
+    fn snapshot(step: usize, span: Option<(usize, usize)>, machine: &Machine, output: &[u8]) -> Snapshot {
+        let window_start = machine.pointer.saturating_sub(WINDOW_SIZE / 2).min(TAPE_SIZE - WINDOW_SIZE);
+
+        Snapshot {
+            step,
+            span,
+            pointer: machine.pointer,
+            window_start,
+            window: machine.tape[window_start..window_start + WINDOW_SIZE].to_vec(),
+            output: bytes_to_string(output),
+        }
+    }
+
+    // Runs until the program ends or `step_limit` instructions have run,
+    // snapshots stop being recorded after `snapshot_limit` but the final
+    // state is always included
+    pub fn trace(&self, input: &[u8], step_limit: usize, snapshot_limit: usize) -> Trace {
+        let mut machine = Machine::new();
+        let mut input = input.iter();
+        let mut output = Vec::new();
+        let mut steps = 0;
+        let mut snapshots = vec![Program::snapshot(0, None, &machine, &output)];
+
+        let outcome = loop {
+            if machine.ip >= self.code.len() {
+                break Outcome::Finished;
+            }
+
+            if steps >= step_limit {
+                break Outcome::StepLimit;
+            }
+
+            let executed = machine.ip;
+            match self.step(&mut machine) {
+                Err(why) => break Outcome::Error(why),
+                Ok(Some(Io::Write(_))) if output.len() >= OUTPUT_LIMIT => break Outcome::OutputLimit,
+                Ok(Some(Io::Write(byte))) => output.push(byte),
+                Ok(Some(Io::Read)) => machine.tape[machine.pointer] = input.next().copied().unwrap_or(0),
+                Ok(None) => {},
+            }
+
+            steps += 1;
+
+            // Only merge into the snapshot from the previous step, otherwise
+            // a skipped snapshot would be hidden
+            let merge_start = snapshots
+                .last()
+                .filter(|last| last.step + 1 == steps)
+                .and_then(|last| last.span)
+                .filter(|(start, end)| {
+                    end + 1 == executed
+                        && self.code[*start] == self.code[executed]
+                        && "+-<>".contains(self.code[executed])
+                })
+                .map(|(start, _)| start);
+
+            if let Some(start) = merge_start {
+                let last = snapshots.len() - 1;
+
+                snapshots[last] = Program::snapshot(steps, Some((start, executed)), &machine, &output);
+            } else if snapshots.len() < snapshot_limit {
+                snapshots.push(Program::snapshot(steps, Some((executed, executed)), &machine, &output));
+            }
+        };
+
+        if snapshots.last().map_or(true, |last| last.step != steps) {
+            snapshots.push(Program::snapshot(steps, None, &machine, &output));
+        }
+
+        Trace {
+            snapshots,
+            steps,
+            outcome,
+        }
+    }
+}
+
+pub struct Brainfuck;
+
+impl Interpreter for Brainfuck {
+    fn name(&self) -> &'static str {
+        "brainfuck"
+    }
+
+    fn aliases(&self) -> &'static [&'static str] {
+        &["bf"]
+    }
+
+    // Cells are bytes, reading past the end of the input gives 0
+    fn run(&self, code: &str, runtime: &mut Runtime) -> Result<(), Halt> {
+        let program = Program::parse(code).map_err(Halt::Error)?;
+        let mut machine = Machine::new();
+
+        while machine.ip < program.code.len() {
+            runtime.tick()?;
+
+            match program.step(&mut machine).map_err(Halt::Error)? {
+                Some(Io::Write(byte)) => runtime.write_char(byte as char)?,
+                Some(Io::Read) => machine.tape[machine.pointer] = runtime.read_char().map_or(0, |c| c as u8),
+                None => {},
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        assert!(Program::parse("+[->+<]").is_ok());
+        assert_eq!(Program::parse("a+b").unwrap().code(), "+");
+        assert_eq!(Program::parse("+]").err().unwrap(), "Unmatched `]` at instruction 2");
+        assert_eq!(Program::parse("[[]").err().unwrap(), "Unmatched `[` at instruction 1");
+    }
+
+    #[test]
+    fn test_trace() {
+        let trace = Program::parse("++++++++[>++++++++<-]>+.").unwrap().trace(&[], 10_000, 100);
+        assert_eq!(trace.outcome, Outcome::Finished);
+        assert_eq!(trace.snapshots.last().unwrap().output, "A");
+
+        let trace = Program::parse(",[.,]").unwrap().trace(b"hi", 10_000, 100);
+        assert_eq!(trace.snapshots.last().unwrap().output, "hi");
+
+        let trace = Program::parse("+[]").unwrap().trace(&[], 1000, 10);
+        assert_eq!(trace.outcome, Outcome::StepLimit);
+        assert_eq!(trace.steps, 1000);
+        assert_eq!(trace.snapshots.len(), 11);
+        assert_eq!(trace.snapshots.last().unwrap().step, 1000);
+
+        let trace = Program::parse("<").unwrap().trace(&[], 1000, 10);
+        assert!(matches!(trace.outcome, Outcome::Error(_)));
+    }
+
+    #[test]
+    fn test_snapshots() {
+        let trace = Program::parse("+++>-.").unwrap().trace(&[], 1000, 100);
+        let spans = trace.snapshots.iter().map(|snapshot| snapshot.span).collect::<Vec<_>>();
+
+        assert_eq!(spans, vec![None, Some((0, 2)), Some((3, 3)), Some((4, 4)), Some((5, 5))]);
+        assert_eq!(trace.snapshots[1].step, 3);
+        assert_eq!(trace.snapshots[1].window[0], 3);
+        assert_eq!(trace.snapshots[3].pointer, 1);
+        assert_eq!(trace.snapshots[3].window[1], 255);
+        assert_eq!(trace.snapshots[4].output, "\u{ff}");
+    }
+}
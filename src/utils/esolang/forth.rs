@@ -0,0 +1,611 @@
+use std::collections::HashMap;
+
+use super::{Halt, Interpreter, Runtime};
+
+const STACK_LIMIT: usize = 10_000;
+const RETURN_STACK_LIMIT: usize = 1024;
+
+type Builtin = fn(&mut Machine, &mut Runtime) -> Result<(), Halt>;
+
+#[derive(Clone)]
+enum Op {
+    Push(i64),
+    Builtin(Builtin),
+    Call(usize),
+    Print(String),
+    Jump(usize),
+    JumpIfZero(usize),
+    Do,
+    Loop(usize),
+    PlusLoop(usize),
+    // Index of the innermost loop, or the one around it
+    Index(usize),
+    Exit,
+}
+
+enum Token {
+    Word(String),
+    Text(String),
+}
+
+// Only used while compiling to match up control flow words
+enum Control {
+    If(usize),
+    Else(usize),
+    Begin(usize),
+    While(usize, usize),
+    Do(usize),
+}
+
+struct Machine {
+    stack:  Vec<i64>,
+    memory: Vec<i64>,
+    // Index and limit of every running `do` loop
+    loops:  Vec<(i64, i64)>,
+}
+
+fn flag(value: bool) -> i64 {
+    if value {
+        -1
+    } else {
+        0
+    }
+}
+
+impl Machine {
+    fn push(&mut self, value: i64) -> Result<(), Halt> {
+        if self.stack.len() >= STACK_LIMIT {
+            return Err(Halt::error("Stack overflow"));
+        }
+
+        self.stack.push(value);
+
+        Ok(())
+    }
+
+    fn pop(&mut self) -> Result<i64, Halt> {
+        self.stack.pop().ok_or_else(|| Halt::error("Stack underflow"))
+    }
+
+    fn unary(&mut self, f: fn(i64) -> i64) -> Result<(), Halt> {
+        let value = self.pop()?;
+
+        self.push(f(value))
+    }
+
+    fn binary(&mut self, f: fn(i64, i64) -> Option<i64>) -> Result<(), Halt> {
+        let (b, a) = (self.pop()?, self.pop()?);
+        let value = f(a, b).ok_or_else(|| Halt::error("Division by zero"))?;
+
+        self.push(value)
+    }
+
+    // Pushes the values at the given depths from the top, in order
+    fn copy(&mut self, depths: &[usize]) -> Result<(), Halt> {
+        for depth in depths {
+            let value = *self
+                .stack
+                .iter()
+                .rev()
+                .nth(*depth)
+                .ok_or_else(|| Halt::error("Stack underflow"))?;
+
+            self.push(value)?;
+        }
+
+        Ok(())
+    }
+
+    fn address(&mut self) -> Result<usize, Halt> {
+        let address = self.pop()?;
+
+        if address >= 0 && (address as usize) < self.memory.len() {
+            Ok(address as usize)
+        } else {
+            Err(Halt::Error(format!("Invalid address {}", address)))
+        }
+    }
+}
+
+static BUILTINS: [(&str, Builtin); 49] = [
+    ("+", |m, _| m.binary(|a, b| Some(a.wrapping_add(b)))),
+    ("-", |m, _| m.binary(|a, b| Some(a.wrapping_sub(b)))),
+    ("*", |m, _| m.binary(|a, b| Some(a.wrapping_mul(b)))),
+    ("/", |m, _| m.binary(i64::checked_div)),
+    ("mod", |m, _| m.binary(i64::checked_rem)),
+    ("/mod", |m, _| {
+        let (b, a) = (m.pop()?, m.pop()?);
+        let (quotient, remainder) = a
+            .checked_div(b)
+            .zip(a.checked_rem(b))
+            .ok_or_else(|| Halt::error("Division by zero"))?;
+
+        m.push(remainder)?;
+        m.push(quotient)
+    }),
+    ("negate", |m, _| m.unary(i64::wrapping_neg)),
+    ("abs", |m, _| m.unary(i64::wrapping_abs)),
+    ("min", |m, _| m.binary(|a, b| Some(a.min(b)))),
+    ("max", |m, _| m.binary(|a, b| Some(a.max(b)))),
+    ("1+", |m, _| m.unary(|a| a.wrapping_add(1))),
+    ("1-", |m, _| m.unary(|a| a.wrapping_sub(1))),
+    ("2*", |m, _| m.unary(|a| a.wrapping_mul(2))),
+    ("2/", |m, _| m.unary(|a| a >> 1)),
+    ("=", |m, _| m.binary(|a, b| Some(flag(a == b)))),
+    ("<>", |m, _| m.binary(|a, b| Some(flag(a != b)))),
+    ("<", |m, _| m.binary(|a, b| Some(flag(a < b)))),
+    (">", |m, _| m.binary(|a, b| Some(flag(a > b)))),
+    ("<=", |m, _| m.binary(|a, b| Some(flag(a <= b)))),
+    (">=", |m, _| m.binary(|a, b| Some(flag(a >= b)))),
+    ("0=", |m, _| m.unary(|a| flag(a == 0))),
+    ("0<", |m, _| m.unary(|a| flag(a < 0))),
+    ("0>", |m, _| m.unary(|a| flag(a > 0))),
+    ("and", |m, _| m.binary(|a, b| Some(a & b))),
+    ("or", |m, _| m.binary(|a, b| Some(a | b))),
+    ("xor", |m, _| m.binary(|a, b| Some(a ^ b))),
+    ("invert", |m, _| m.unary(|a| !a)),
+    ("dup", |m, _| m.copy(&[0])),
+    ("over", |m, _| m.copy(&[1])),
+    ("2dup", |m, _| m.copy(&[1, 1])),
+    ("?dup", |m, _| {
+        let value = *m.stack.last().ok_or_else(|| Halt::error("Stack underflow"))?;
+
+        if value != 0 {
+            m.push(value)?;
+        }
+
+        Ok(())
+    }),
+    ("drop", |m, _| m.pop().map(|_| ())),
+    ("2drop", |m, _| m.pop().and_then(|_| m.pop()).map(|_| ())),
+    ("nip", |m, _| {
+        let value = m.pop()?;
+        m.pop()?;
+
+        m.push(value)
+    }),
+    ("swap", |m, _| {
+        let (b, a) = (m.pop()?, m.pop()?);
+        m.push(b)?;
+
+        m.push(a)
+    }),
+    ("tuck", |m, _| {
+        let (b, a) = (m.pop()?, m.pop()?);
+        m.push(b)?;
+        m.push(a)?;
+
+        m.push(b)
+    }),
+    ("rot", |m, _| {
+        let (c, b, a) = (m.pop()?, m.pop()?, m.pop()?);
+        m.push(b)?;
+        m.push(c)?;
+
+        m.push(a)
+    }),
+    ("pick", |m, _| {
+        let depth = m.pop()?;
+
+        m.copy(&[depth.max(0) as usize])
+    }),
+    ("depth", |m, _| m.push(m.stack.len() as i64)),
+    (".", |m, rt| {
+        let value = m.pop()?;
+
+        rt.write(&format!("{} ", value))
+    }),
+    (".s", |m, rt| {
+        let values = m.stack.iter().map(|value| format!("{} ", value)).collect::<String>();
+
+        rt.write(&format!("<{}> {}", m.stack.len(), values))
+    }),
+    ("emit", |m, rt| {
+        let value = m.pop()?;
+
+        rt.write_char(std::char::from_u32(value as u32).unwrap_or('\u{fffd}'))
+    }),
+    ("cr", |_, rt| rt.write("\n")),
+    ("space", |_, rt| rt.write(" ")),
+    ("spaces", |m, rt| {
+        let amount = m.pop()?;
+
+        for _ in 0..amount {
+            rt.tick()?;
+            rt.write(" ")?;
+        }
+
+        Ok(())
+    }),
+    ("key", |m, rt| {
+        let value = rt.read_char().map_or(-1, |c| c as i64);
+
+        m.push(value)
+    }),
+    ("@", |m, _| {
+        let address = m.address()?;
+
+        m.push(m.memory[address])
+    }),
+    ("!", |m, _| {
+        let address = m.address()?;
+        m.memory[address] = m.pop()?;
+
+        Ok(())
+    }),
+    ("+!", |m, _| {
+        let address = m.address()?;
+        m.memory[address] = m.memory[address].wrapping_add(m.pop()?);
+
+        Ok(())
+    }),
+];
+
+// `( comments )`, `\ comments` and `." strings"` are handled here since they
+// aren't separated by whitespace like everything else
+fn tokenize(code: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = Vec::new();
+    let mut chars = code.chars().peekable();
+
+    loop {
+        while chars.peek().map_or(false, |c| c.is_whitespace()) {
+            chars.next();
+        }
+
+        let mut word = String::new();
+        while let Some(c) = chars.peek().copied().filter(|c| !c.is_whitespace()) {
+            word.push(c);
+            chars.next();
+        }
+
+        match word.as_str() {
+            "" => return Ok(tokens),
+            "\\" => while chars.next().map_or(false, |c| c != '\n') {},
+            "(" => {
+                if !chars.any(|c| c == ')') {
+                    return Err("Unclosed `(` comment".to_string());
+                }
+            },
+            ".\"" => {
+                chars.next();
+
+                let mut text = String::new();
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some(c) => text.push(c),
+                        None => return Err("Unclosed `.\"` string".to_string()),
+                    }
+                }
+
+                tokens.push(Token::Text(text));
+            },
+            _ => tokens.push(Token::Word(word.to_lowercase())),
+        }
+    }
+}
+
+// Points the forward jump at `idx` to the end of `code`
+fn patch(code: &mut [Op], idx: usize) {
+    let target = code.len();
+
+    match &mut code[idx] {
+        Op::Jump(jump) | Op::JumpIfZero(jump) => *jump = target,
+        _ => {},
+    }
+}
+
+struct Compiler {
+    words:      Vec<Vec<Op>>,
+    dictionary: HashMap<String, usize>,
+    memory:     usize,
+}
+
+impl Compiler {
+    fn compile_word(
+        &mut self,
+        word: &str,
+        code: &mut Vec<Op>,
+        control: &mut Vec<Control>,
+        current: Option<usize>,
+    ) -> Result<(), String> {
+        let unexpected = || format!("Unexpected `{}`", word);
+
+        match word {
+            "if" => {
+                control.push(Control::If(code.len()));
+                code.push(Op::JumpIfZero(0));
+            },
+            "else" => match control.pop() {
+                Some(Control::If(idx)) => {
+                    control.push(Control::Else(code.len()));
+                    code.push(Op::Jump(0));
+                    patch(code, idx);
+                },
+                _ => return Err(unexpected()),
+            },
+            "then" => match control.pop() {
+                Some(Control::If(idx)) | Some(Control::Else(idx)) => patch(code, idx),
+                _ => return Err(unexpected()),
+            },
+            "begin" => control.push(Control::Begin(code.len())),
+            "until" | "again" | "while" => match control.pop() {
+                Some(Control::Begin(target)) if word == "until" => code.push(Op::JumpIfZero(target)),
+                Some(Control::Begin(target)) if word == "again" => code.push(Op::Jump(target)),
+                Some(Control::Begin(target)) => {
+                    control.push(Control::While(target, code.len()));
+                    code.push(Op::JumpIfZero(0));
+                },
+                _ => return Err(unexpected()),
+            },
+            "repeat" => match control.pop() {
+                Some(Control::While(target, idx)) => {
+                    code.push(Op::Jump(target));
+                    patch(code, idx);
+                },
+                _ => return Err(unexpected()),
+            },
+            "do" => {
+                code.push(Op::Do);
+                control.push(Control::Do(code.len()));
+            },
+            "loop" | "+loop" => match control.pop() {
+                Some(Control::Do(target)) if word == "loop" => code.push(Op::Loop(target)),
+                Some(Control::Do(target)) => code.push(Op::PlusLoop(target)),
+                _ => return Err(unexpected()),
+            },
+            "i" => code.push(Op::Index(0)),
+            "j" => code.push(Op::Index(1)),
+            "exit" => code.push(Op::Exit),
+            "recurse" => code.push(Op::Call(current.ok_or_else(unexpected)?)),
+            _ => {
+                if let Some(idx) = self.dictionary.get(word) {
+                    code.push(Op::Call(*idx));
+                } else if let Some((_, builtin)) = BUILTINS.iter().find(|(name, _)| *name == word) {
+                    code.push(Op::Builtin(*builtin));
+                } else if let Ok(number) = word.parse::<i64>() {
+                    code.push(Op::Push(number));
+                } else {
+                    return Err(format!("Unknown word `{}`", word));
+                }
+            },
+        }
+
+        Ok(())
+    }
+
+    fn define(&mut self, name: String, code: Vec<Op>) {
+        self.dictionary.insert(name, self.words.len());
+        self.words.push(code);
+    }
+
+    // Returns the index of the word holding the top level code
+    fn compile(&mut self, tokens: Vec<Token>) -> Result<usize, String> {
+        let mut tokens = tokens.into_iter();
+        let mut main = Vec::new();
+        let mut main_control = Vec::new();
+        // Name, index, code and control flow of the word being defined
+        let mut definition: Option<(String, usize, Vec<Op>, Vec<Control>)> = None;
+
+        while let Some(token) = tokens.next() {
+            let (code, control, current) = match &mut definition {
+                Some((_, idx, code, control)) => (code, control, Some(*idx)),
+                None => (&mut main, &mut main_control, None),
+            };
+
+            let word = match token {
+                Token::Text(text) => {
+                    code.push(Op::Print(text));
+                    continue;
+                },
+                Token::Word(word) => word,
+            };
+
+            let mut name = || match tokens.next() {
+                Some(Token::Word(name)) => Ok(name),
+                _ => Err(format!("Expected a name after `{}`", word)),
+            };
+
+            match word.as_str() {
+                ":" if current.is_none() => {
+                    let name = name()?;
+
+                    // Reserved now so `recurse` knows where the word will be
+                    self.words.push(Vec::new());
+                    definition = Some((name, self.words.len() - 1, Vec::new(), Vec::new()));
+                },
+                ";" if current.is_some() => {
+                    let (name, idx, code, control) = definition.take().unwrap_or_else(|| unreachable!());
+
+                    if !control.is_empty() {
+                        return Err(format!("Unfinished control flow in `{}`", name));
+                    }
+
+                    self.words[idx] = code;
+                    self.dictionary.insert(name, idx);
+                },
+                "variable" => {
+                    let name = name()?;
+                    let address = self.memory as i64;
+
+                    self.memory += 1;
+                    self.define(name, vec![Op::Push(address)]);
+                },
+                "constant" => {
+                    let name = name()?;
+                    let address = self.memory as i64;
+                    let fetch = BUILTINS.iter().find(|(name, _)| *name == "@").map(|(_, builtin)| *builtin);
+                    let store = BUILTINS.iter().find(|(name, _)| *name == "!").map(|(_, builtin)| *builtin);
+
+                    // Stored when the code runs and read back by the new word
+                    self.memory += 1;
+                    code.push(Op::Push(address));
+                    code.extend(store.map(Op::Builtin));
+                    self.define(
+                        name,
+                        vec![Op::Push(address)].into_iter().chain(fetch.map(Op::Builtin)).collect(),
+                    );
+                },
+                word => self.compile_word(word, code, control, current)?,
+            }
+        }
+
+        if let Some((name, ..)) = definition {
+            return Err(format!("Missing `;` after the definition of `{}`", name));
+        }
+
+        if !main_control.is_empty() {
+            return Err("Unfinished control flow".to_string());
+        }
+
+        self.words.push(main);
+
+        Ok(self.words.len() - 1)
+    }
+}
+
+pub struct Forth;
+
+impl Interpreter for Forth {
+    fn name(&self) -> &'static str {
+        "forth"
+    }
+
+    fn aliases(&self) -> &'static [&'static str] {
+        &["fs"]
+    }
+
+    fn run(&self, code: &str, runtime: &mut Runtime) -> Result<(), Halt> {
+        let mut compiler = Compiler {
+            words:      Vec::new(),
+            dictionary: HashMap::new(),
+            memory:     0,
+        };
+        let main = tokenize(code)
+            .and_then(|tokens| compiler.compile(tokens))
+            .map_err(Halt::Error)?;
+        let words = compiler.words;
+
+        let mut machine = Machine {
+            stack:  Vec::new(),
+            memory: vec![0; compiler.memory],
+            loops:  Vec::new(),
+        };
+        // Word and position of every call in progress
+        let mut frames = vec![(main, 0)];
+
+        while let Some((word, pc)) = frames.last_mut() {
+            let op = match words[*word].get(*pc) {
+                Some(op) => op,
+                None => {
+                    frames.pop();
+                    continue;
+                },
+            };
+
+            runtime.tick()?;
+            *pc += 1;
+
+            match op {
+                Op::Push(value) => machine.push(*value)?,
+                Op::Builtin(builtin) => builtin(&mut machine, runtime)?,
+                Op::Call(idx) => {
+                    if frames.len() >= RETURN_STACK_LIMIT {
+                        return Err(Halt::error("Return stack overflow"));
+                    }
+
+                    frames.push((*idx, 0));
+                },
+                Op::Print(text) => runtime.write(text)?,
+                Op::Jump(target) => *pc = *target,
+                Op::JumpIfZero(target) => {
+                    if machine.pop()? == 0 {
+                        *pc = *target;
+                    }
+                },
+                Op::Do => {
+                    let (start, limit) = (machine.pop()?, machine.pop()?);
+                    machine.loops.push((start, limit));
+                },
+                Op::Loop(target) | Op::PlusLoop(target) => {
+                    let step = if let Op::PlusLoop(_) = op { machine.pop()? } else { 1 };
+                    let (index, limit) = machine
+                        .loops
+                        .last_mut()
+                        .ok_or_else(|| Halt::error("`loop` outside of a `do` loop"))?;
+                    *index = index.wrapping_add(step);
+
+                    if (step >= 0 && *index < *limit) || (step < 0 && *index >= *limit) {
+                        *pc = *target;
+                    } else {
+                        machine.loops.pop();
+                    }
+                },
+                Op::Index(depth) => {
+                    let (index, _) = *machine
+                        .loops
+                        .iter()
+                        .rev()
+                        .nth(*depth)
+                        .ok_or_else(|| Halt::error("Loop index used outside of a `do` loop"))?;
+                    machine.push(index)?;
+                },
+                Op::Exit => {
+                    frames.pop();
+                },
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::esolang::{execute, Limits};
+
+    fn run(code: &str) -> (String, Result<(), Halt>) {
+        let execution = execute(&Forth, code, "", Limits::default());
+
+        (execution.output, execution.result)
+    }
+
+    #[test]
+    fn test_forth() {
+        assert_eq!(run("1 2 + . 7 2 /mod . ."), ("3 3 1 ".to_string(), Ok(())));
+        assert_eq!(run(": fizz? 3 mod 0= ; 9 fizz? . 10 fizz? ."), ("-1 0 ".to_string(), Ok(())));
+        assert_eq!(
+            run(": fact dup 1 > if dup 1- recurse * then ; 10 fact ."),
+            ("3628800 ".to_string(), Ok(()))
+        );
+        assert_eq!(run("5 0 do i . loop"), ("0 1 2 3 4 ".to_string(), Ok(())));
+        assert_eq!(run("3 0 do 2 0 do j i + . loop loop"), ("0 1 1 2 2 3 ".to_string(), Ok(())));
+        assert_eq!(run("0 10 0 do i + 2 +loop ."), ("20 ".to_string(), Ok(())));
+        assert_eq!(
+            run("variable x 5 x ! 3 x +! x @ . 42 constant answer answer ."),
+            ("8 42 ".to_string(), Ok(()))
+        );
+        assert_eq!(
+            run(": countdown begin dup . 1- dup 0= until drop ; 3 countdown"),
+            ("3 2 1 ".to_string(), Ok(()))
+        );
+        assert_eq!(
+            run("( comment ) .\" Hello, World!\" cr \\ another comment\n1 2 3 .s"),
+            ("Hello, World!\n<3> 1 2 3 ".to_string(), Ok(()))
+        );
+        assert_eq!(run(": x 1 if 2 else 3 then ; x ."), ("2 ".to_string(), Ok(())));
+        assert_eq!(run(": x begin dup while 1- repeat ; 5 x ."), ("0 ".to_string(), Ok(())));
+    }
+
+    #[test]
+    fn test_errors() {
+        assert_eq!(run("1 0 /").1, Err(Halt::error("Division by zero")));
+        assert_eq!(run("drop").1, Err(Halt::error("Stack underflow")));
+        assert_eq!(run("foo").1, Err(Halt::error("Unknown word `foo`")));
+        assert_eq!(run(": x if ;").1, Err(Halt::error("Unfinished control flow in `x`")));
+        assert_eq!(run("then").1, Err(Halt::error("Unexpected `then`")));
+        assert_eq!(run(": x").1, Err(Halt::error("Missing `;` after the definition of `x`")));
+        assert_eq!(run("begin 1 again").1, Err(Halt::error("Stack overflow")));
+    }
+}
@@ -0,0 +1,807 @@
+use std::collections::HashMap;
+
+use super::{Halt, Interpreter, Runtime};
+
+// Nested calls recurse on the native stack, calls in tail position don't
+const DEPTH_LIMIT: usize = 256;
+const PRINT_LIMIT: usize = 4096;
+
+static BUILTINS: [&str; 27] = [
+    "+", "-", "*", "/", "modulo", "=", "<", ">", "<=", ">=", "abs", "min", "max", "not", "cons", "car", "cdr", "list",
+    "append", "length", "null?", "pair?", "number?", "eq?", "display", "newline", "print",
+];
+
+// Everything lives in arenas owned by the machine, so values are just
+// indices. This avoids reference cycles between closures and scopes and
+// stops long lists from overflowing the stack when dropped.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Value {
+    Nil,
+    Void,
+    Bool(bool),
+    Int(i64),
+    Str(usize),
+    Symbol(usize),
+    Pair(usize),
+    Lambda(usize),
+    Builtin(&'static str),
+}
+
+struct Lambda {
+    params: Vec<usize>,
+    // `(lambda args ...)` collects every argument into a list
+    rest:   Option<usize>,
+    body:   Vec<Value>,
+    scope:  usize,
+}
+
+struct Scope {
+    vars:   HashMap<usize, Value>,
+    parent: Option<usize>,
+}
+
+enum Token {
+    Open,
+    Close,
+    Quote,
+    Str(String),
+    Atom(String),
+}
+
+fn tokenize(code: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = Vec::new();
+    let mut chars = code.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '(' | '[' => tokens.push(Token::Open),
+            ')' | ']' => tokens.push(Token::Close),
+            '\'' => tokens.push(Token::Quote),
+            ';' => while chars.next().map_or(false, |c| c != '\n') {},
+            '"' => {
+                let mut text = String::new();
+
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => match chars.next() {
+                            Some('n') => text.push('\n'),
+                            Some('t') => text.push('\t'),
+                            Some(c) => text.push(c),
+                            None => return Err("Unclosed string".to_string()),
+                        },
+                        Some(c) => text.push(c),
+                        None => return Err("Unclosed string".to_string()),
+                    }
+                }
+
+                tokens.push(Token::Str(text));
+            },
+            c if c.is_whitespace() => {},
+            c => {
+                let mut atom = c.to_string();
+
+                while let Some(c) = chars.peek().copied().filter(|c| !c.is_whitespace() && !"()[]'\";".contains(*c)) {
+                    atom.push(c);
+                    chars.next();
+                }
+
+                tokens.push(Token::Atom(atom));
+            },
+        }
+    }
+
+    Ok(tokens)
+}
+
+struct Machine<'a> {
+    runtime:    &'a mut Runtime,
+    pairs:      Vec<(Value, Value)>,
+    lambdas:    Vec<Lambda>,
+    scopes:     Vec<Scope>,
+    strings:    Vec<String>,
+    symbols:    Vec<String>,
+    symbol_ids: HashMap<String, usize>,
+}
+
+impl<'a> Machine<'a> {
+    fn new(runtime: &'a mut Runtime) -> Machine<'a> {
+        let mut machine = Machine {
+            runtime,
+            pairs: Vec::new(),
+            lambdas: Vec::new(),
+            scopes: Vec::new(),
+            strings: Vec::new(),
+            symbols: Vec::new(),
+            symbol_ids: HashMap::new(),
+        };
+
+        let mut globals = HashMap::new();
+        for name in BUILTINS.iter() {
+            globals.insert(machine.symbol(name), Value::Builtin(name));
+        }
+
+        machine.scopes.push(Scope {
+            vars:   globals,
+            parent: None,
+        });
+
+        machine
+    }
+
+    fn symbol(&mut self, name: &str) -> usize {
+        if let Some(id) = self.symbol_ids.get(name) {
+            return *id;
+        }
+
+        self.symbols.push(name.to_string());
+        self.symbol_ids.insert(name.to_string(), self.symbols.len() - 1);
+
+        self.symbols.len() - 1
+    }
+
+    // Allocations use fuel so a program can't fill memory faster than it
+    // runs out
+    fn cons(&mut self, car: Value, cdr: Value) -> Result<Value, Halt> {
+        self.runtime.tick()?;
+        self.pairs.push((car, cdr));
+
+        Ok(Value::Pair(self.pairs.len() - 1))
+    }
+
+    fn new_scope(&mut self, parent: usize) -> Result<usize, Halt> {
+        self.runtime.tick()?;
+        self.scopes.push(Scope {
+            vars:   HashMap::new(),
+            parent: Some(parent),
+        });
+
+        Ok(self.scopes.len() - 1)
+    }
+
+    fn list_from(&mut self, values: &[Value], tail: Value) -> Result<Value, Halt> {
+        let mut list = tail;
+
+        for value in values.iter().rev() {
+            list = self.cons(*value, list)?;
+        }
+
+        Ok(list)
+    }
+
+    fn list_to_vec(&self, mut list: Value) -> Result<Vec<Value>, Halt> {
+        let mut values = Vec::new();
+
+        loop {
+            match list {
+                Value::Nil => return Ok(values),
+                Value::Pair(idx) => {
+                    values.push(self.pairs[idx].0);
+                    list = self.pairs[idx].1;
+                },
+                _ => return Err(Halt::error("Expected a list")),
+            }
+        }
+    }
+
+    fn parse(&mut self, tokens: &[Token], position: &mut usize, depth: usize) -> Result<Value, Halt> {
+        if depth > DEPTH_LIMIT {
+            return Err(Halt::error("The code is nested too deeply"));
+        }
+
+        let token = tokens.get(*position).ok_or_else(|| Halt::error("Missing `)`"))?;
+        *position += 1;
+
+        match token {
+            Token::Open => {
+                let mut values = Vec::new();
+
+                loop {
+                    match tokens.get(*position) {
+                        Some(Token::Close) => {
+                            *position += 1;
+                            break;
+                        },
+                        Some(_) => values.push(self.parse(tokens, position, depth + 1)?),
+                        None => return Err(Halt::error("Missing `)`")),
+                    }
+                }
+
+                self.list_from(&values, Value::Nil)
+            },
+            Token::Close => Err(Halt::error("Unexpected `)`")),
+            Token::Quote => {
+                let quoted = self.parse(tokens, position, depth + 1)?;
+                let quote = Value::Symbol(self.symbol("quote"));
+
+                self.list_from(&[quote, quoted], Value::Nil)
+            },
+            Token::Str(text) => {
+                self.strings.push(text.clone());
+
+                Ok(Value::Str(self.strings.len() - 1))
+            },
+            Token::Atom(atom) => Ok(match atom.as_str() {
+                "#t" | "true" => Value::Bool(true),
+                "#f" | "false" => Value::Bool(false),
+                "nil" => Value::Nil,
+                atom => match atom.parse::<i64>() {
+                    Ok(number) => Value::Int(number),
+                    Err(_) => Value::Symbol(self.symbol(&atom.to_lowercase())),
+                },
+            }),
+        }
+    }
+
+    fn lookup(&self, mut scope: usize, symbol: usize) -> Result<Value, Halt> {
+        loop {
+            if let Some(value) = self.scopes[scope].vars.get(&symbol) {
+                return Ok(*value);
+            }
+
+            scope = self.scopes[scope]
+                .parent
+                .ok_or_else(|| Halt::Error(format!("Unbound variable `{}`", self.symbols[symbol])))?;
+        }
+    }
+
+    fn assign(&mut self, mut scope: usize, symbol: usize, value: Value) -> Result<(), Halt> {
+        loop {
+            if let Some(var) = self.scopes[scope].vars.get_mut(&symbol) {
+                *var = value;
+
+                return Ok(());
+            }
+
+            scope = self.scopes[scope]
+                .parent
+                .ok_or_else(|| Halt::Error(format!("Unbound variable `{}`", self.symbols[symbol])))?;
+        }
+    }
+
+    fn expect_symbol(&self, value: Value) -> Result<usize, Halt> {
+        match value {
+            Value::Symbol(symbol) => Ok(symbol),
+            value => Err(Halt::Error(format!("Expected a name, got `{}`", self.show(value, true)))),
+        }
+    }
+
+    fn lambda(&mut self, params: Value, body: &[Value], scope: usize) -> Result<Value, Halt> {
+        if body.is_empty() {
+            return Err(Halt::error("A function needs a body"));
+        }
+
+        let (params, rest) = match params {
+            Value::Symbol(rest) => (Vec::new(), Some(rest)),
+            params => (
+                self.list_to_vec(params)?
+                    .into_iter()
+                    .map(|param| self.expect_symbol(param))
+                    .collect::<Result<Vec<usize>, Halt>>()?,
+                None,
+            ),
+        };
+
+        self.lambdas.push(Lambda {
+            params,
+            rest,
+            body: body.to_vec(),
+            scope,
+        });
+
+        Ok(Value::Lambda(self.lambdas.len() - 1))
+    }
+
+    fn eval(&mut self, mut expr: Value, mut scope: usize, depth: usize) -> Result<Value, Halt> {
+        if depth > DEPTH_LIMIT {
+            return Err(Halt::error("Recursion is too deep"));
+        }
+
+        // Tail calls and the last expression of a body loop instead of
+        // recursing
+        loop {
+            self.runtime.tick()?;
+
+            let (head, rest) = match expr {
+                Value::Symbol(symbol) => return self.lookup(scope, symbol),
+                Value::Pair(idx) => self.pairs[idx],
+                expr => return Ok(expr),
+            };
+            let args = self.list_to_vec(rest)?;
+
+            let form = match head {
+                Value::Symbol(symbol) => self.symbols[symbol].clone(),
+                _ => String::new(),
+            };
+            let arity = |min: usize, max: usize| {
+                if args.len() < min || args.len() > max {
+                    Err(Halt::Error(format!("Wrong number of arguments to `{}`", form)))
+                } else {
+                    Ok(())
+                }
+            };
+
+            match form.as_str() {
+                "quote" => {
+                    arity(1, 1)?;
+
+                    return Ok(args[0]);
+                },
+                "if" => {
+                    arity(2, 3)?;
+
+                    let condition = self.eval(args[0], scope, depth + 1)?;
+                    expr = if condition != Value::Bool(false) {
+                        args[1]
+                    } else {
+                        args.get(2).copied().unwrap_or(Value::Void)
+                    };
+                },
+                "define" => {
+                    arity(1, usize::MAX)?;
+
+                    // `(define (name params...) body...)` is shorthand for a
+                    // lambda
+                    let (symbol, value) = match args[0] {
+                        Value::Pair(idx) => {
+                            let (name, params) = self.pairs[idx];
+                            let symbol = self.expect_symbol(name)?;
+
+                            (symbol, self.lambda(params, &args[1..], scope)?)
+                        },
+                        name => {
+                            arity(2, 2)?;
+                            let symbol = self.expect_symbol(name)?;
+
+                            (symbol, self.eval(args[1], scope, depth + 1)?)
+                        },
+                    };
+
+                    self.scopes[scope].vars.insert(symbol, value);
+
+                    return Ok(Value::Void);
+                },
+                "set!" => {
+                    arity(2, 2)?;
+
+                    let symbol = self.expect_symbol(args[0])?;
+                    let value = self.eval(args[1], scope, depth + 1)?;
+                    self.assign(scope, symbol, value)?;
+
+                    return Ok(Value::Void);
+                },
+                "lambda" => {
+                    arity(2, usize::MAX)?;
+
+                    return self.lambda(args[0], &args[1..], scope);
+                },
+                "let" => {
+                    arity(2, usize::MAX)?;
+
+                    let bindings = self.list_to_vec(args[0])?;
+                    let inner = self.new_scope(scope)?;
+
+                    for binding in bindings {
+                        let binding = self.list_to_vec(binding)?;
+                        if binding.len() != 2 {
+                            return Err(Halt::error("`let` bindings should look like `(name value)`"));
+                        }
+
+                        let symbol = self.expect_symbol(binding[0])?;
+                        let value = self.eval(binding[1], scope, depth + 1)?;
+                        self.scopes[inner].vars.insert(symbol, value);
+                    }
+
+                    for body in &args[1..args.len() - 1] {
+                        self.eval(*body, inner, depth + 1)?;
+                    }
+
+                    expr = args[args.len() - 1];
+                    scope = inner;
+                },
+                "begin" => {
+                    arity(1, usize::MAX)?;
+
+                    for body in &args[..args.len() - 1] {
+                        self.eval(*body, scope, depth + 1)?;
+                    }
+
+                    expr = args[args.len() - 1];
+                },
+                "cond" => {
+                    expr = Value::Void;
+
+                    for clause in args {
+                        let clause = self.list_to_vec(clause)?;
+                        if clause.len() != 2 {
+                            return Err(Halt::error("`cond` clauses should look like `(test value)`"));
+                        }
+
+                        let matched = match clause[0] {
+                            Value::Symbol(symbol) if self.symbols[symbol] == "else" => true,
+                            test => self.eval(test, scope, depth + 1)? != Value::Bool(false),
+                        };
+
+                        if matched {
+                            expr = clause[1];
+                            break;
+                        }
+                    }
+                },
+                "and" | "or" => {
+                    let mut result = Value::Bool(form == "and");
+
+                    for arg in args {
+                        result = self.eval(arg, scope, depth + 1)?;
+
+                        if (result == Value::Bool(false)) == (form == "and") {
+                            break;
+                        }
+                    }
+
+                    return Ok(result);
+                },
+                _ => {
+                    let function = self.eval(head, scope, depth + 1)?;
+                    let values = args
+                        .iter()
+                        .map(|arg| self.eval(*arg, scope, depth + 1))
+                        .collect::<Result<Vec<Value>, Halt>>()?;
+
+                    let idx = match function {
+                        Value::Builtin(name) => return self.builtin(name, &values),
+                        Value::Lambda(idx) => idx,
+                        function => {
+                            return Err(Halt::Error(format!("`{}` is not a function", self.show(function, true))));
+                        },
+                    };
+
+                    let (params, rest, parent) = {
+                        let lambda = &self.lambdas[idx];
+
+                        (lambda.params.clone(), lambda.rest, lambda.scope)
+                    };
+
+                    if values.len() < params.len() || (rest.is_none() && values.len() > params.len()) {
+                        return Err(Halt::Error(format!(
+                            "Expected {} arguments but got {}",
+                            params.len(),
+                            values.len()
+                        )));
+                    }
+
+                    let inner = self.new_scope(parent)?;
+                    for (param, value) in params.iter().zip(values.iter()) {
+                        self.scopes[inner].vars.insert(*param, *value);
+                    }
+
+                    if let Some(rest) = rest {
+                        let list = self.list_from(&values[params.len()..], Value::Nil)?;
+                        self.scopes[inner].vars.insert(rest, list);
+                    }
+
+                    let body = self.lambdas[idx].body.clone();
+                    for body in &body[..body.len() - 1] {
+                        self.eval(*body, inner, depth + 1)?;
+                    }
+
+                    expr = body[body.len() - 1];
+                    scope = inner;
+                },
+            }
+        }
+    }
+
+    fn int(&self, value: Value) -> Result<i64, Halt> {
+        match value {
+            Value::Int(int) => Ok(int),
+            value => Err(Halt::Error(format!("Expected a number, got `{}`", self.show(value, true)))),
+        }
+    }
+
+    fn builtin(&mut self, name: &str, args: &[Value]) -> Result<Value, Halt> {
+        let overflow = || Halt::error("Integer overflow");
+        let arity = |count: usize| {
+            if args.len() == count {
+                Ok(())
+            } else {
+                Err(Halt::Error(format!("`{}` takes {} arguments", name, count)))
+            }
+        };
+
+        match name {
+            "+" | "*" => {
+                let mut total = if name == "+" { 0i64 } else { 1 };
+
+                for arg in args {
+                    let int = self.int(*arg)?;
+                    total = if name == "+" {
+                        total.checked_add(int)
+                    } else {
+                        total.checked_mul(int)
+                    }
+                    .ok_or_else(overflow)?;
+                }
+
+                Ok(Value::Int(total))
+            },
+            "-" | "/" | "modulo" => {
+                let ints = args.iter().map(|arg| self.int(*arg)).collect::<Result<Vec<i64>, Halt>>()?;
+                let (first, rest) = ints
+                    .split_first()
+                    .ok_or_else(|| Halt::Error(format!("`{}` needs at least 1 argument", name)))?;
+
+                if name == "-" && rest.is_empty() {
+                    return first.checked_neg().map(Value::Int).ok_or_else(overflow);
+                }
+
+                let mut total = *first;
+                for int in rest {
+                    total = match name {
+                        "-" => total.checked_sub(*int).ok_or_else(overflow)?,
+                        _ if *int == 0 => return Err(Halt::error("Division by zero")),
+                        "/" => total.checked_div(*int).ok_or_else(overflow)?,
+                        _ => total.checked_rem(*int).ok_or_else(overflow)?,
+                    };
+                }
+
+                Ok(Value::Int(total))
+            },
+            "=" | "<" | ">" | "<=" | ">=" => {
+                let ints = args.iter().map(|arg| self.int(*arg)).collect::<Result<Vec<i64>, Halt>>()?;
+
+                Ok(Value::Bool(ints.windows(2).all(|pair| match name {
+                    "=" => pair[0] == pair[1],
+                    "<" => pair[0] < pair[1],
+                    ">" => pair[0] > pair[1],
+                    "<=" => pair[0] <= pair[1],
+                    _ => pair[0] >= pair[1],
+                })))
+            },
+            "abs" => {
+                arity(1)?;
+
+                self.int(args[0])?.checked_abs().map(Value::Int).ok_or_else(overflow)
+            },
+            "min" | "max" => {
+                let ints = args.iter().map(|arg| self.int(*arg)).collect::<Result<Vec<i64>, Halt>>()?;
+                let result = if name == "min" {
+                    ints.iter().min()
+                } else {
+                    ints.iter().max()
+                };
+
+                result
+                    .map(|int| Value::Int(*int))
+                    .ok_or_else(|| Halt::Error(format!("`{}` needs at least 1 argument", name)))
+            },
+            "not" => {
+                arity(1)?;
+
+                Ok(Value::Bool(args[0] == Value::Bool(false)))
+            },
+            "cons" => {
+                arity(2)?;
+
+                self.cons(args[0], args[1])
+            },
+            "car" | "cdr" => {
+                arity(1)?;
+
+                match args[0] {
+                    Value::Pair(idx) if name == "car" => Ok(self.pairs[idx].0),
+                    Value::Pair(idx) => Ok(self.pairs[idx].1),
+                    value => Err(Halt::Error(format!(
+                        "`{}` needs a pair, got `{}`",
+                        name,
+                        self.show(value, true)
+                    ))),
+                }
+            },
+            "list" => self.list_from(args, Value::Nil),
+            "append" => {
+                let (last, lists) = match args.split_last() {
+                    Some(split) => split,
+                    None => return Ok(Value::Nil),
+                };
+
+                let mut values = Vec::new();
+                for list in lists {
+                    values.extend(self.list_to_vec(*list)?);
+                }
+
+                self.list_from(&values, *last)
+            },
+            "length" => {
+                arity(1)?;
+
+                Ok(Value::Int(self.list_to_vec(args[0])?.len() as i64))
+            },
+            "null?" => {
+                arity(1)?;
+
+                Ok(Value::Bool(args[0] == Value::Nil))
+            },
+            "pair?" => {
+                arity(1)?;
+
+                Ok(Value::Bool(matches!(args[0], Value::Pair(_))))
+            },
+            "number?" => {
+                arity(1)?;
+
+                Ok(Value::Bool(matches!(args[0], Value::Int(_))))
+            },
+            "eq?" => {
+                arity(2)?;
+
+                let equal = match (args[0], args[1]) {
+                    (Value::Str(a), Value::Str(b)) => self.strings[a] == self.strings[b],
+                    (a, b) => a == b,
+                };
+
+                Ok(Value::Bool(equal))
+            },
+            // `display` shows strings as they are, `print` shows them quoted
+            "display" | "print" => {
+                arity(1)?;
+
+                let text = self.show(args[0], name == "print");
+                self.runtime.write(&text)?;
+
+                Ok(Value::Void)
+            },
+            "newline" => {
+                arity(0)?;
+                self.runtime.write("\n")?;
+
+                Ok(Value::Void)
+            },
+            name => Err(Halt::Error(format!("Unknown builtin `{}`", name))),
+        }
+    }
+
+    fn show(&self, value: Value, quoted: bool) -> String {
+        let mut out = String::new();
+        self.write_value(value, quoted, &mut out);
+
+        out
+    }
+
+    // Stops once the limit is reached, so deeply nested values can't
+    // recurse forever
+    fn write_value(&self, value: Value, quoted: bool, out: &mut String) {
+        if out.len() > PRINT_LIMIT {
+            return;
+        }
+
+        match value {
+            Value::Nil => out.push_str("()"),
+            Value::Void => {},
+            Value::Bool(boolean) => out.push_str(if boolean { "#t" } else { "#f" }),
+            Value::Int(int) => out.push_str(&int.to_string()),
+            Value::Str(idx) if quoted => out.push_str(&format!("{:?}", self.strings[idx])),
+            Value::Str(idx) => out.push_str(&self.strings[idx]),
+            Value::Symbol(symbol) => out.push_str(&self.symbols[symbol]),
+            Value::Lambda(_) => out.push_str("#<procedure>"),
+            Value::Builtin(name) => out.push_str(&format!("#<procedure {}>", name)),
+            Value::Pair(mut idx) => {
+                out.push('(');
+
+                loop {
+                    let (car, cdr) = self.pairs[idx];
+                    self.write_value(car, quoted, out);
+
+                    match cdr {
+                        Value::Nil => break,
+                        Value::Pair(next) if out.len() <= PRINT_LIMIT => {
+                            out.push(' ');
+                            idx = next;
+                        },
+                        Value::Pair(_) => break,
+                        cdr => {
+                            out.push_str(" . ");
+                            self.write_value(cdr, quoted, out);
+                            break;
+                        },
+                    }
+                }
+
+                out.push(')');
+            },
+        }
+    }
+}
+
+pub struct Lisp;
+
+impl Interpreter for Lisp {
+    fn name(&self) -> &'static str {
+        "lisp"
+    }
+
+    fn aliases(&self) -> &'static [&'static str] {
+        &["scheme"]
+    }
+
+    // The value of the last expression is shown when it has one
+    fn run(&self, code: &str, runtime: &mut Runtime) -> Result<(), Halt> {
+        let tokens = tokenize(code).map_err(Halt::Error)?;
+        let mut machine = Machine::new(runtime);
+
+        let mut exprs = Vec::new();
+        let mut position = 0;
+        while position < tokens.len() {
+            exprs.push(machine.parse(&tokens, &mut position, 0)?);
+        }
+
+        let mut result = Value::Void;
+        for expr in exprs {
+            result = machine.eval(expr, 0, 0)?;
+        }
+
+        if result != Value::Void {
+            let text = machine.show(result, true);
+            machine.runtime.write(&text)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::esolang::{execute, Limits};
+
+    fn run(code: &str) -> (String, Result<(), Halt>) {
+        let execution = execute(&Lisp, code, "", Limits::default());
+
+        (execution.output, execution.result)
+    }
+
+    #[test]
+    fn test_lisp() {
+        assert_eq!(run("(* 2 (+ 1 2) (- 10 3))"), ("42".to_string(), Ok(())));
+        assert_eq!(
+            run("(define (fact n) (if (<= n 1) 1 (* n (fact (- n 1))))) (fact 20)"),
+            ("2432902008176640000".to_string(), Ok(()))
+        );
+        assert_eq!(run("'(1 2 . 3)"), ("(1 2 . 3)".to_string(), Ok(())));
+        assert_eq!(run("(cons 1 (list 2 \"three\"))"), ("(1 2 \"three\")".to_string(), Ok(())));
+        assert_eq!(run("(display \"hi\") (newline)"), ("hi\n".to_string(), Ok(())));
+        assert_eq!(
+            run("(let ((x 2) (y 3)) (cond ((> x y) 'x) (else 'y)))"),
+            ("y".to_string(), Ok(()))
+        );
+        assert_eq!(
+            run("(define counter 0) (define (inc!) (set! counter (+ counter 1))) (inc!) (inc!) counter"),
+            ("2".to_string(), Ok(()))
+        );
+        assert_eq!(
+            run("(define (make-adder n) (lambda (x) (+ x n))) ((make-adder 5) 10)"),
+            ("15".to_string(), Ok(()))
+        );
+        assert_eq!(run("((lambda args (length args)) 1 2 3)"), ("3".to_string(), Ok(())));
+        assert_eq!(run("(and 1 #f 2) (or #f 3)"), ("3".to_string(), Ok(())));
+        assert_eq!(run("(append '(1 2) '(3) '())"), ("(1 2 3)".to_string(), Ok(())));
+
+        // Tail calls don't count towards the recursion limit
+        assert_eq!(
+            run("(define (loop n acc) (if (= n 0) acc (loop (- n 1) (+ acc 1)))) (loop 10000 0)"),
+            ("10000".to_string(), Ok(()))
+        );
+    }
+
+    #[test]
+    fn test_errors() {
+        assert_eq!(run("(/ 1 0)").1, Err(Halt::error("Division by zero")));
+        assert_eq!(run("(foo)").1, Err(Halt::error("Unbound variable `foo`")));
+        assert_eq!(run("(1 2)").1, Err(Halt::error("`1` is not a function")));
+        assert_eq!(run("(car '())").1, Err(Halt::error("`car` needs a pair, got `()`")));
+        assert_eq!(run("(+ 1").1, Err(Halt::error("Missing `)`")));
+        assert_eq!(run(")").1, Err(Halt::error("Unexpected `)`")));
+        assert_eq!(run("(* 9223372036854775807 2)").1, Err(Halt::error("Integer overflow")));
+        assert_eq!(run(&"(".repeat(1000)).1, Err(Halt::error("The code is nested too deeply")));
+    }
+}
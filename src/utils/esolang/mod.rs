@@ -0,0 +1,268 @@
+pub mod befunge;
+pub mod brainfuck;
+pub mod forth;
+pub mod lisp;
+pub mod ook;
+pub mod whitespace;
+
+use std::time::{Duration, Instant};
+
+use self::{befunge::Befunge, brainfuck::Brainfuck, forth::Forth, lisp::Lisp, ook::Ook, whitespace::Whitespace};
+
+// How often the clock is checked, in units of fuel
+const TIME_CHECK_INTERVAL: u64 = 1024;
+const STACK_SIZE: usize = 32 * 1024 * 1024;
+
+#[derive(Clone, Copy, Debug)]
+pub struct Limits {
+    // Roughly one unit per instruction
+    pub fuel:   u64,
+    pub time:   Duration,
+    // In characters
+    pub output: usize,
+}
+
+impl Default for Limits {
+    fn default() -> Limits {
+        Limits {
+            fuel:   1_000_000,
+            time:   Duration::from_secs(3),
+            output: 4000,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Halt {
+    OutOfFuel,
+    Timeout,
+    OutputLimit,
+    Error(String),
+}
+
+impl Halt {
+    pub fn error<D: ToString>(message: D) -> Halt {
+        Halt::Error(message.to_string())
+    }
+}
+
+impl std::fmt::Display for Halt {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Halt::OutOfFuel => write!(f, "Ran out of fuel, the program might loop forever"),
+            Halt::Timeout => write!(f, "Took too long to run"),
+            Halt::OutputLimit => write!(f, "The output is too long"),
+            Halt::Error(why) => write!(f, "{}", why),
+        }
+    }
+}
+
+// Shared state handed to interpreters, every limit is enforced here so an
+// interpreter only has to call `tick` once per instruction
+pub struct Runtime {
+    limits:  Limits,
+    fuel:    u64,
+    started: Instant,
+    input:   Vec<char>,
+    read:    usize,
+    output:  String,
+    written: usize,
+}
+
+impl Runtime {
+    pub fn new(input: &str, limits: Limits) -> Runtime {
+        Runtime {
+            limits,
+            fuel: 0,
+            started: Instant::now(),
+            input: input.chars().collect(),
+            read: 0,
+            output: String::new(),
+            written: 0,
+        }
+    }
+
+    pub fn tick(&mut self) -> Result<(), Halt> {
+        self.fuel += 1;
+
+        if self.fuel > self.limits.fuel {
+            return Err(Halt::OutOfFuel);
+        }
+
+        if self.fuel % TIME_CHECK_INTERVAL == 0 && self.started.elapsed() > self.limits.time {
+            return Err(Halt::Timeout);
+        }
+
+        Ok(())
+    }
+
+    // Whatever fits is still written when the limit is hit
+    pub fn write(&mut self, text: &str) -> Result<(), Halt> {
+        for c in text.chars() {
+            if self.written >= self.limits.output {
+                return Err(Halt::OutputLimit);
+            }
+
+            self.output.push(c);
+            self.written += 1;
+        }
+
+        Ok(())
+    }
+
+    pub fn write_char(&mut self, c: char) -> Result<(), Halt> {
+        self.write(c.encode_utf8(&mut [0; 4]))
+    }
+
+    // None once the input has run out
+    pub fn read_char(&mut self) -> Option<char> {
+        let c = self.input.get(self.read).copied()?;
+        self.read += 1;
+
+        Some(c)
+    }
+
+    // Skips leading whitespace and reads an optionally signed integer
+    pub fn read_number(&mut self) -> Option<i64> {
+        while self.input.get(self.read).map_or(false, |c| c.is_whitespace()) {
+            self.read += 1;
+        }
+
+        let start = self.read;
+        if matches!(self.input.get(self.read), Some('-') | Some('+')) {
+            self.read += 1;
+        }
+
+        while self.input.get(self.read).map_or(false, |c| c.is_ascii_digit()) {
+            self.read += 1;
+        }
+
+        let number = self.input[start..self.read].iter().collect::<String>().parse::<i64>().ok();
+        if number.is_none() {
+            self.read = start;
+        }
+
+        number
+    }
+}
+
+pub trait Interpreter: Send + Sync {
+    fn name(&self) -> &'static str;
+
+    fn aliases(&self) -> &'static [&'static str];
+
+    fn run(&self, code: &str, runtime: &mut Runtime) -> Result<(), Halt>;
+}
+
+pub static INTERPRETERS: [&dyn Interpreter; 6] = [&Befunge, &Brainfuck, &Forth, &Lisp, &Ook, &Whitespace];
+
+pub fn interpreter_by_name(name: &str) -> Option<&'static dyn Interpreter> {
+    let name = name.to_lowercase();
+
+    INTERPRETERS
+        .iter()
+        .find(|interpreter| interpreter.name() == name || interpreter.aliases().contains(&name.as_str()))
+        .copied()
+}
+
+pub struct Execution {
+    pub output:  String,
+    pub result:  Result<(), Halt>,
+    pub fuel:    u64,
+    pub elapsed: Duration,
+}
+
+// Blocks until the program finishes or hits a limit, so should be run off
+// the async runtime. Interpreters get their own thread since recursive ones
+// need more stack than the default
+pub fn execute(interpreter: &'static dyn Interpreter, code: &str, input: &str, limits: Limits) -> Execution {
+    let code = code.to_string();
+    let mut runtime = Runtime::new(input, limits);
+
+    let thread = std::thread::Builder::new()
+        .name(format!("esolang-{}", interpreter.name()))
+        .stack_size(STACK_SIZE)
+        .spawn(move || {
+            let result = interpreter.run(&code, &mut runtime);
+
+            (runtime, result)
+        });
+
+    let (runtime, result) = match thread.map(|thread| thread.join()) {
+        Ok(Ok(finished)) => finished,
+        _ => {
+            return Execution {
+                output:  String::new(),
+                result:  Err(Halt::error("The interpreter crashed")),
+                fuel:    0,
+                elapsed: Duration::default(),
+            };
+        },
+    };
+
+    Execution {
+        output: runtime.output,
+        result,
+        fuel: runtime.fuel.min(limits.fuel),
+        elapsed: runtime.started.elapsed(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(name: &str, code: &str, input: &str) -> (String, Result<(), Halt>) {
+        let execution = execute(interpreter_by_name(name).unwrap(), code, input, Limits::default());
+
+        (execution.output, execution.result)
+    }
+
+    #[test]
+    fn test_runtime() {
+        let mut runtime = Runtime::new(" -12x", Limits {
+            fuel:   2,
+            time:   Duration::from_secs(1),
+            output: 3,
+        });
+
+        assert_eq!(runtime.read_number(), Some(-12));
+        assert_eq!(runtime.read_number(), None);
+        assert_eq!(runtime.read_char(), Some('x'));
+        assert_eq!(runtime.read_char(), None);
+
+        assert_eq!(runtime.write("abcd"), Err(Halt::OutputLimit));
+        assert_eq!(runtime.output, "abc");
+
+        assert!(runtime.tick().is_ok());
+        assert!(runtime.tick().is_ok());
+        assert_eq!(runtime.tick(), Err(Halt::OutOfFuel));
+    }
+
+    #[test]
+    fn test_interpreters() {
+        assert_eq!(run("bf", "++++++++[>++++++++<-]>+.,.", "b"), ("Ab".to_string(), Ok(())));
+        assert_eq!(run("befunge", "\"olleh\">:#,_@", ""), ("hello".to_string(), Ok(())));
+        assert_eq!(
+            run("ook", "Ook. Ook. Ook. Ook. Ook! Ook. Ook. Ook! Ook! Ook.", "A"),
+            ("\u{2}A".to_string(), Ok(()))
+        );
+        assert_eq!(run("forth", ": sq dup * ; 3 sq .", ""), ("9 ".to_string(), Ok(())));
+        assert_eq!(run("lisp", "(+ 1 2)", ""), ("3".to_string(), Ok(())));
+        assert_eq!(run("ws", "   \t\n\t\n \t\n\n\n", ""), ("1".to_string(), Ok(())));
+    }
+
+    #[test]
+    fn test_limits() {
+        assert_eq!(run("bf", "+[]", "").1, Err(Halt::OutOfFuel));
+        assert_eq!(run("befunge", ">", "").1, Err(Halt::OutOfFuel));
+        assert_eq!(run("forth", ": f begin again ; f", "").1, Err(Halt::OutOfFuel));
+        assert_eq!(run("lisp", "(define (f) (f)) (f)", "").1, Err(Halt::OutOfFuel));
+        assert_eq!(run("bf", "+[.]", "").1, Err(Halt::OutputLimit));
+        assert!(matches!(
+            run("lisp", "(define (f n) (+ 1 (f n))) (f 1)", "").1,
+            Err(Halt::Error(_))
+        ));
+        assert!(matches!(run("forth", ": f recurse 1 ; f", "").1, Err(Halt::Error(_))));
+    }
+}
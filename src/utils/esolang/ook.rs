@@ -0,0 +1,64 @@
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+use super::{brainfuck::Brainfuck, Halt, Interpreter, Runtime};
+
+static OOK_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r"Ook([.?!])").unwrap());
+
+// Every pair of words is one Brainfuck instruction
+fn translate(code: &str) -> Result<String, String> {
+    let words = OOK_REGEX
+        .captures_iter(code)
+        .map(|captures| captures[1].chars().next().unwrap_or_default())
+        .collect::<Vec<char>>();
+
+    if words.len() % 2 != 0 {
+        return Err("Ook! code needs an even number of words".to_string());
+    }
+
+    Ok(words
+        .chunks(2)
+        .map(|pair| match (pair[0], pair[1]) {
+            ('.', '?') => '>',
+            ('?', '.') => '<',
+            ('.', '.') => '+',
+            ('!', '!') => '-',
+            ('!', '.') => '.',
+            ('.', '!') => ',',
+            ('!', '?') => '[',
+            ('?', '!') => ']',
+            // `Ook? Ook?` isn't an instruction
+            _ => ' ',
+        })
+        .collect())
+}
+
+pub struct Ook;
+
+impl Interpreter for Ook {
+    fn name(&self) -> &'static str {
+        "ook"
+    }
+
+    fn aliases(&self) -> &'static [&'static str] {
+        &["ook!"]
+    }
+
+    fn run(&self, code: &str, runtime: &mut Runtime) -> Result<(), Halt> {
+        let code = translate(code).map_err(Halt::Error)?;
+
+        Brainfuck.run(&code, runtime)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_translate() {
+        assert_eq!(translate("Ook. Ook? Ook! Ook? Ook? Ook!").unwrap(), ">[]");
+        assert_eq!(translate("Ook.Ook.\nOok! Ook.").unwrap(), "+.");
+        assert!(translate("Ook. Ook. Ook.").is_err());
+    }
+}
@@ -0,0 +1,376 @@
+use std::collections::HashMap;
+
+use super::{Halt, Interpreter, Runtime};
+
+const CALL_DEPTH_LIMIT: usize = 1024;
+
+#[derive(Clone, Debug, PartialEq)]
+enum Instruction {
+    Push(i64),
+    Duplicate,
+    Copy(i64),
+    Swap,
+    Discard,
+    Slide(i64),
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+    Modulo,
+    Store,
+    Retrieve,
+    Mark(String),
+    Call(String),
+    Jump(String),
+    JumpZero(String),
+    JumpNegative(String),
+    Return,
+    End,
+    PrintChar,
+    PrintNumber,
+    ReadChar,
+    ReadNumber,
+}
+
+// Space, tab and line feed are written as S, T and L, everything else is a
+// comment
+struct Parser {
+    tokens:   Vec<char>,
+    position: usize,
+}
+
+impl Parser {
+    fn new(code: &str) -> Parser {
+        let tokens = code
+            .chars()
+            .filter_map(|c| match c {
+                ' ' => Some('S'),
+                '\t' => Some('T'),
+                '\n' => Some('L'),
+                _ => None,
+            })
+            .collect();
+
+        Parser {
+            tokens,
+            position: 0,
+        }
+    }
+
+    fn next(&mut self) -> Result<char, String> {
+        let token = self
+            .tokens
+            .get(self.position)
+            .copied()
+            .ok_or("The program ends in the middle of an instruction")?;
+        self.position += 1;
+
+        Ok(token)
+    }
+
+    fn number(&mut self) -> Result<i64, String> {
+        let negative = self.next()? == 'T';
+        let mut number: i64 = 0;
+
+        loop {
+            match self.next()? {
+                'L' => break,
+                bit => {
+                    number = number
+                        .checked_mul(2)
+                        .and_then(|number| number.checked_add((bit == 'T') as i64))
+                        .ok_or("Number literal is too large")?;
+                },
+            }
+        }
+
+        Ok(if negative { -number } else { number })
+    }
+
+    fn label(&mut self) -> Result<String, String> {
+        let mut label = String::new();
+
+        loop {
+            match self.next()? {
+                'L' => return Ok(label),
+                token => label.push(token),
+            }
+        }
+    }
+
+    fn instruction(&mut self) -> Result<Instruction, String> {
+        let start = self.position;
+        let instruction = match (self.next()?, self.next()?) {
+            ('S', 'S') => Instruction::Push(self.number()?),
+            ('S', 'L') => match self.next()? {
+                'S' => Instruction::Duplicate,
+                'T' => Instruction::Swap,
+                _ => Instruction::Discard,
+            },
+            ('S', 'T') => match self.next()? {
+                'S' => Instruction::Copy(self.number()?),
+                'L' => Instruction::Slide(self.number()?),
+                _ => return Err(format!("Unknown stack instruction at token {}", start + 1)),
+            },
+            ('T', 'S') => match (self.next()?, self.next()?) {
+                ('S', 'S') => Instruction::Add,
+                ('S', 'T') => Instruction::Subtract,
+                ('S', 'L') => Instruction::Multiply,
+                ('T', 'S') => Instruction::Divide,
+                ('T', 'T') => Instruction::Modulo,
+                _ => return Err(format!("Unknown arithmetic instruction at token {}", start + 1)),
+            },
+            ('T', 'T') => match self.next()? {
+                'S' => Instruction::Store,
+                'T' => Instruction::Retrieve,
+                _ => return Err(format!("Unknown heap instruction at token {}", start + 1)),
+            },
+            ('T', 'L') => match (self.next()?, self.next()?) {
+                ('S', 'S') => Instruction::PrintChar,
+                ('S', 'T') => Instruction::PrintNumber,
+                ('T', 'S') => Instruction::ReadChar,
+                ('T', 'T') => Instruction::ReadNumber,
+                _ => return Err(format!("Unknown I/O instruction at token {}", start + 1)),
+            },
+            ('L', 'S') => match self.next()? {
+                'S' => Instruction::Mark(self.label()?),
+                'T' => Instruction::Call(self.label()?),
+                _ => Instruction::Jump(self.label()?),
+            },
+            ('L', 'T') => match self.next()? {
+                'S' => Instruction::JumpZero(self.label()?),
+                'T' => Instruction::JumpNegative(self.label()?),
+                _ => Instruction::Return,
+            },
+            ('L', 'L') => match self.next()? {
+                'L' => Instruction::End,
+                _ => return Err(format!("Unknown flow instruction at token {}", start + 1)),
+            },
+            _ => return Err(format!("Unknown instruction at token {}", start + 1)),
+        };
+
+        Ok(instruction)
+    }
+}
+
+fn parse(code: &str) -> Result<(Vec<Instruction>, HashMap<String, usize>), String> {
+    let mut parser = Parser::new(code);
+    let mut instructions = Vec::new();
+    let mut labels = HashMap::new();
+
+    while parser.position < parser.tokens.len() {
+        let instruction = parser.instruction()?;
+
+        if let Instruction::Mark(label) = &instruction {
+            if labels.insert(label.clone(), instructions.len()).is_some() {
+                return Err(format!("Label `{}` is defined more than once", label));
+            }
+        }
+
+        instructions.push(instruction);
+    }
+
+    Ok((instructions, labels))
+}
+
+struct Machine {
+    stack: Vec<i64>,
+    heap:  HashMap<i64, i64>,
+}
+
+impl Machine {
+    fn pop(&mut self) -> Result<i64, Halt> {
+        self.stack.pop().ok_or_else(|| Halt::error("Stack underflow"))
+    }
+
+    fn binary(&mut self, f: fn(i64, i64) -> Option<i64>) -> Result<(), Halt> {
+        let (b, a) = (self.pop()?, self.pop()?);
+        let value = f(a, b).ok_or_else(|| Halt::error("Arithmetic overflow or division by zero"))?;
+
+        self.stack.push(value);
+
+        Ok(())
+    }
+}
+
+pub struct Whitespace;
+
+impl Interpreter for Whitespace {
+    fn name(&self) -> &'static str {
+        "whitespace"
+    }
+
+    fn aliases(&self) -> &'static [&'static str] {
+        &["ws"]
+    }
+
+    fn run(&self, code: &str, runtime: &mut Runtime) -> Result<(), Halt> {
+        let (instructions, labels) = parse(code).map_err(Halt::Error)?;
+        let mut machine = Machine {
+            stack: Vec::new(),
+            heap:  HashMap::new(),
+        };
+        let mut calls = Vec::new();
+        let mut ip = 0;
+
+        let jump = |label: &str| {
+            labels
+                .get(label)
+                .copied()
+                .ok_or_else(|| Halt::Error(format!("Unknown label `{}`", label)))
+        };
+
+        while ip < instructions.len() {
+            runtime.tick()?;
+
+            let mut next = ip + 1;
+            match &instructions[ip] {
+                Instruction::Push(value) => machine.stack.push(*value),
+                Instruction::Duplicate => {
+                    let value = *machine.stack.last().ok_or_else(|| Halt::error("Stack underflow"))?;
+                    machine.stack.push(value);
+                },
+                Instruction::Copy(n) => {
+                    let idx = (machine.stack.len() as i64 - 1 - n) as usize;
+                    let value = *machine
+                        .stack
+                        .get(idx)
+                        .filter(|_| *n >= 0)
+                        .ok_or_else(|| Halt::error("Stack underflow"))?;
+                    machine.stack.push(value);
+                },
+                Instruction::Swap => {
+                    let (b, a) = (machine.pop()?, machine.pop()?);
+                    machine.stack.push(b);
+                    machine.stack.push(a);
+                },
+                Instruction::Discard => {
+                    machine.pop()?;
+                },
+                Instruction::Slide(n) => {
+                    let top = machine.pop()?;
+                    let keep = machine.stack.len().saturating_sub((*n).max(0) as usize);
+                    machine.stack.truncate(keep);
+                    machine.stack.push(top);
+                },
+                Instruction::Add => machine.binary(i64::checked_add)?,
+                Instruction::Subtract => machine.binary(i64::checked_sub)?,
+                Instruction::Multiply => machine.binary(i64::checked_mul)?,
+                Instruction::Divide => machine.binary(i64::checked_div)?,
+                Instruction::Modulo => machine.binary(i64::checked_rem)?,
+                Instruction::Store => {
+                    let (value, address) = (machine.pop()?, machine.pop()?);
+                    machine.heap.insert(address, value);
+                },
+                Instruction::Retrieve => {
+                    let address = machine.pop()?;
+                    machine.stack.push(machine.heap.get(&address).copied().unwrap_or(0));
+                },
+                Instruction::Mark(_) => {},
+                Instruction::Call(label) => {
+                    if calls.len() >= CALL_DEPTH_LIMIT {
+                        return Err(Halt::error("Call stack overflow"));
+                    }
+
+                    calls.push(next);
+                    next = jump(label)?;
+                },
+                Instruction::Jump(label) => next = jump(label)?,
+                Instruction::JumpZero(label) => {
+                    if machine.pop()? == 0 {
+                        next = jump(label)?;
+                    }
+                },
+                Instruction::JumpNegative(label) => {
+                    if machine.pop()? < 0 {
+                        next = jump(label)?;
+                    }
+                },
+                Instruction::Return => {
+                    next = calls.pop().ok_or_else(|| Halt::error("Returned outside of a subroutine"))?;
+                },
+                Instruction::End => return Ok(()),
+                Instruction::PrintChar => {
+                    let value = machine.pop()?;
+                    runtime.write_char(std::char::from_u32(value as u32).unwrap_or('\u{fffd}'))?;
+                },
+                Instruction::PrintNumber => {
+                    let value = machine.pop()?;
+                    runtime.write(&value.to_string())?;
+                },
+                Instruction::ReadChar => {
+                    let address = machine.pop()?;
+                    machine.heap.insert(address, runtime.read_char().map_or(-1, |c| c as i64));
+                },
+                Instruction::ReadNumber => {
+                    let address = machine.pop()?;
+                    let value = runtime
+                        .read_number()
+                        .ok_or_else(|| Halt::error("Expected a number in the input"))?;
+                    machine.heap.insert(address, value);
+                },
+            }
+
+            ip = next;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::esolang::{execute, Limits};
+
+    // Lets tests be written with S, T and L instead of invisible characters,
+    // spaces in between are only for readability
+    fn run(code: &str, input: &str) -> (String, Result<(), Halt>) {
+        let code = code
+            .chars()
+            .filter_map(|c| match c {
+                'S' => Some(' '),
+                'T' => Some('\t'),
+                'L' => Some('\n'),
+                _ => None,
+            })
+            .collect::<String>();
+        let execution = execute(&Whitespace, &code, input, Limits::default());
+
+        (execution.output, execution.result)
+    }
+
+    #[test]
+    fn test_parse() {
+        let code = "SSSTSTLSLSTSSSLSSTLLLL".replace('S', " ").replace('T', "\t").replace('L', "\n");
+        let (instructions, labels) = parse(&code).unwrap();
+        assert_eq!(instructions, vec![
+            Instruction::Push(5),
+            Instruction::Duplicate,
+            Instruction::Add,
+            Instruction::Mark("T".to_string()),
+            Instruction::End
+        ]);
+        assert_eq!(labels.get("T"), Some(&3));
+
+        assert!(parse(" ").is_err());
+    }
+
+    #[test]
+    fn test_whitespace() {
+        // Counts down from 3 using a loop
+        assert_eq!(
+            run("SSSTTL LSSSL SLSTLST SSSTL TSST SLS LTSTL LSLSL LSSTL LLL", ""),
+            ("321".to_string(), Ok(()))
+        );
+
+        // Reads a number, calls a subroutine that doubles it
+        assert_eq!(
+            run("SSSL TLTT SSSL TTT LSTSL TLST LLL LSSSL SLS TSSS LTL", "21"),
+            ("42".to_string(), Ok(()))
+        );
+
+        assert_eq!(run("SLL", "").1, Err(Halt::error("Stack underflow")));
+        assert_eq!(run("LSLSL LSSSL LSLSL", "").1, Err(Halt::OutOfFuel));
+    }
+}
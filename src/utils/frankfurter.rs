@@ -0,0 +1,18 @@
+use std::time::Duration;
+
+use crate::{
+    models::{commands::FrankFurterResponse, currency::RateTable},
+    utils::cache::get_cached,
+};
+
+static API_URL: &str = "https://api.frankfurter.app/latest";
+
+// Always fetches the full table against the default base so a single cached
+// response covers every conversion
+pub async fn latest(ttl: u64) -> Result<RateTable, String> {
+    let res = get_cached(API_URL, Duration::from_secs(ttl)).await?;
+    let res = serde_json::from_str::<FrankFurterResponse>(&res)
+        .map_err(|_| "Unable to parse the response from frankfurter".to_string())?;
+
+    Ok(RateTable::new(&res.base, &res.date, res.rates))
+}
@@ -0,0 +1,368 @@
+use image::{
+    imageops::{self, FilterType},
+    png::PngEncoder,
+    ColorType, Rgba, RgbaImage,
+};
+use once_cell::sync::Lazy;
+use rusttype::{point, Font, Scale};
+
+// Fonts are bundled so rendering works the same on every machine
+static REGULAR: Lazy<Font<'static>> = Lazy::new(|| {
+    Font::try_from_bytes(include_bytes!("../../../assets/fonts/DejaVuSans.ttf") as &[u8]).expect("Invalid bundled font")
+});
+static BOLD: Lazy<Font<'static>> = Lazy::new(|| {
+    Font::try_from_bytes(include_bytes!("../../../assets/fonts/DejaVuSans-Bold.ttf") as &[u8])
+        .expect("Invalid bundled font")
+});
+
+pub const LINE_SPACING: f32 = 1.25;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Weight {
+    Regular,
+    Bold,
+}
+
+impl Weight {
+    fn font(self) -> &'static Font<'static> {
+        match self {
+            Weight::Regular => &REGULAR,
+            Weight::Bold => &BOLD,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Align {
+    Left,
+    Centre,
+}
+
+pub fn rgb(hex: u32) -> Rgba<u8> {
+    rgba(hex, 255)
+}
+
+pub fn rgba(hex: u32, alpha: u8) -> Rgba<u8> {
+    Rgba([(hex >> 16) as u8, (hex >> 8) as u8, hex as u8, alpha])
+}
+
+pub fn text_width(text: &str, weight: Weight, size: f32) -> f32 {
+    weight
+        .font()
+        .layout(text, Scale::uniform(size), point(0.0, 0.0))
+        .last()
+        .map_or(0.0, |glyph| glyph.position().x + glyph.unpositioned().h_metrics().advance_width)
+}
+
+// Splits words wider than the line so nothing is drawn off the canvas
+fn split_word(word: &str, weight: Weight, size: f32, max_width: f32) -> Vec<String> {
+    let mut parts = vec![String::new()];
+
+    for c in word.chars() {
+        let part = parts.last_mut().unwrap();
+        part.push(c);
+
+        if part.chars().count() > 1 && text_width(part, weight, size) > max_width {
+            part.pop();
+            parts.push(c.to_string());
+        }
+    }
+
+    parts
+}
+
+pub fn wrap_text(text: &str, weight: Weight, size: f32, max_width: f32) -> Vec<String> {
+    let mut lines = Vec::new();
+
+    for paragraph in text.lines() {
+        let mut line = String::new();
+
+        for word in paragraph.split_whitespace() {
+            let joined = if line.is_empty() {
+                word.to_string()
+            } else {
+                format!("{} {}", line, word)
+            };
+
+            if text_width(&joined, weight, size) <= max_width {
+                line = joined;
+                continue;
+            }
+
+            if !line.is_empty() {
+                lines.push(line);
+            }
+
+            let mut parts = split_word(word, weight, size, max_width);
+            line = parts.pop().unwrap_or_default();
+            lines.extend(parts);
+        }
+
+        lines.push(line);
+    }
+
+    lines
+}
+
+// Shrinks the text until it fits the box, anything still too long at the
+// smallest size is cut short
+pub fn fit_text(
+    text: &str,
+    weight: Weight,
+    max_width: f32,
+    max_height: f32,
+    max_size: f32,
+    min_size: f32,
+) -> (f32, Vec<String>) {
+    let mut size = max_size;
+
+    loop {
+        let lines = wrap_text(text, weight, size, max_width);
+
+        if lines.len() as f32 * size * LINE_SPACING <= max_height {
+            return (size, lines);
+        }
+
+        if size <= min_size {
+            break;
+        }
+
+        size = (size - 2.0).max(min_size);
+    }
+
+    let mut lines = wrap_text(text, weight, size, max_width);
+    let max_lines = ((max_height / (size * LINE_SPACING)) as usize).max(1);
+    lines.truncate(max_lines);
+
+    if let Some(last) = lines.last_mut() {
+        while !last.is_empty() && text_width(&format!("{}…", last), weight, size) > max_width {
+            last.pop();
+        }
+
+        last.push('…');
+    }
+
+    (size, lines)
+}
+
+fn blend(bottom: Rgba<u8>, top: Rgba<u8>, coverage: f32) -> Rgba<u8> {
+    let top_alpha = top[3] as f32 / 255.0 * coverage;
+    let bottom_alpha = bottom[3] as f32 / 255.0;
+    let alpha = top_alpha + bottom_alpha * (1.0 - top_alpha);
+
+    if alpha <= 0.0 {
+        return Rgba([0, 0, 0, 0]);
+    }
+
+    let channel = |idx: usize| {
+        let value = top[idx] as f32 * top_alpha + bottom[idx] as f32 * bottom_alpha * (1.0 - top_alpha);
+
+        (value / alpha).round() as u8
+    };
+
+    Rgba([channel(0), channel(1), channel(2), (alpha * 255.0).round() as u8])
+}
+
+// Signed distance from a point to a rounded rectangle, negative inside
+fn rounded_rect_distance(px: f32, py: f32, x: f32, y: f32, width: f32, height: f32, radius: f32) -> f32 {
+    let radius = radius.min(width / 2.0).min(height / 2.0);
+    let qx = (px - (x + width / 2.0)).abs() - (width / 2.0 - radius);
+    let qy = (py - (y + height / 2.0)).abs() - (height / 2.0 - radius);
+
+    (qx.max(0.0).powi(2) + qy.max(0.0).powi(2)).sqrt() + qx.max(qy).min(0.0) - radius
+}
+
+fn point_in_polygon(px: f32, py: f32, points: &[(f32, f32)]) -> bool {
+    let mut inside = false;
+    let mut prev = points[points.len() - 1];
+
+    for &(x, y) in points {
+        if (y > py) != (prev.1 > py) && px < (prev.0 - x) * (py - y) / (prev.1 - y) + x {
+            inside = !inside;
+        }
+
+        prev = (x, y);
+    }
+
+    inside
+}
+
+pub struct Canvas {
+    pub image: RgbaImage,
+}
+
+impl Canvas {
+    pub fn new(width: u32, height: u32, background: Rgba<u8>) -> Canvas {
+        Canvas {
+            image: RgbaImage::from_pixel(width, height, background),
+        }
+    }
+
+    fn blend_pixel(&mut self, x: i64, y: i64, colour: Rgba<u8>, coverage: f32) {
+        if coverage <= 0.0 || x < 0 || y < 0 || x >= self.image.width() as i64 || y >= self.image.height() as i64 {
+            return;
+        }
+
+        let pixel = self.image.get_pixel_mut(x as u32, y as u32);
+        *pixel = blend(*pixel, colour, coverage.min(1.0));
+    }
+
+    pub fn fill_rect(&mut self, x: i64, y: i64, width: u32, height: u32, colour: Rgba<u8>) {
+        for py in y..y + height as i64 {
+            for px in x..x + width as i64 {
+                self.blend_pixel(px, py, colour, 1.0);
+            }
+        }
+    }
+
+    pub fn fill_rounded_rect(&mut self, x: i64, y: i64, width: u32, height: u32, radius: f32, colour: Rgba<u8>) {
+        let (fx, fy, fw, fh) = (x as f32, y as f32, width as f32, height as f32);
+
+        for py in y..y + height as i64 {
+            for px in x..x + width as i64 {
+                let distance = rounded_rect_distance(px as f32 + 0.5, py as f32 + 0.5, fx, fy, fw, fh, radius);
+
+                self.blend_pixel(px, py, colour, 0.5 - distance);
+            }
+        }
+    }
+
+    pub fn fill_circle(&mut self, cx: f32, cy: f32, radius: f32, colour: Rgba<u8>) {
+        let size = (radius * 2.0).ceil() as u32;
+
+        self.fill_rounded_rect((cx - radius) as i64, (cy - radius) as i64, size, size, radius, colour);
+    }
+
+    // Anti-aliased by sampling each pixel four times
+    pub fn fill_polygon(&mut self, points: &[(f32, f32)], colour: Rgba<u8>) {
+        if points.len() < 3 {
+            return;
+        }
+
+        let min_x = points.iter().map(|p| p.0).fold(f32::MAX, f32::min).floor() as i64;
+        let max_x = points.iter().map(|p| p.0).fold(f32::MIN, f32::max).ceil() as i64;
+        let min_y = points.iter().map(|p| p.1).fold(f32::MAX, f32::min).floor() as i64;
+        let max_y = points.iter().map(|p| p.1).fold(f32::MIN, f32::max).ceil() as i64;
+
+        for py in min_y..=max_y {
+            for px in min_x..=max_x {
+                let hits = [(0.25, 0.25), (0.75, 0.25), (0.25, 0.75), (0.75, 0.75)]
+                    .iter()
+                    .filter(|(ox, oy)| point_in_polygon(px as f32 + ox, py as f32 + oy, points))
+                    .count();
+
+                self.blend_pixel(px, py, colour, hits as f32 / 4.0);
+            }
+        }
+    }
+
+    pub fn draw_image(&mut self, image: &RgbaImage, x: i64, y: i64) {
+        for (ix, iy, pixel) in image.enumerate_pixels() {
+            self.blend_pixel(x + ix as i64, y + iy as i64, *pixel, 1.0);
+        }
+    }
+
+    // Scales the image to a square with rounded corners, a radius of half
+    // the size gives a circle
+    pub fn draw_avatar(&mut self, avatar: &RgbaImage, x: i64, y: i64, size: u32, radius: f32) {
+        let mut avatar = imageops::resize(avatar, size, size, FilterType::Triangle);
+        let size = size as f32;
+
+        for (px, py, pixel) in avatar.enumerate_pixels_mut() {
+            let distance = rounded_rect_distance(px as f32 + 0.5, py as f32 + 0.5, 0.0, 0.0, size, size, radius);
+            pixel[3] = (pixel[3] as f32 * (0.5 - distance).clamp(0.0, 1.0)) as u8;
+        }
+
+        self.draw_image(&avatar, x, y);
+    }
+
+    // Draws a single line with its top at `y`, returning its width
+    pub fn draw_text(&mut self, text: &str, weight: Weight, size: f32, x: f32, y: f32, colour: Rgba<u8>) -> f32 {
+        let font = weight.font();
+        let scale = Scale::uniform(size);
+        let ascent = font.v_metrics(scale).ascent;
+
+        for glyph in font.layout(text, scale, point(x, y + ascent)) {
+            if let Some(bounds) = glyph.pixel_bounding_box() {
+                glyph.draw(|gx, gy, coverage| {
+                    self.blend_pixel(
+                        bounds.min.x as i64 + gx as i64,
+                        bounds.min.y as i64 + gy as i64,
+                        colour,
+                        coverage,
+                    )
+                });
+            }
+        }
+
+        text_width(text, weight, size)
+    }
+
+    // Draws wrapped lines, `x` is the left edge or the centre depending on
+    // the alignment. Returns the height used
+    pub fn draw_lines(
+        &mut self,
+        lines: &[String],
+        weight: Weight,
+        size: f32,
+        (x, y): (f32, f32),
+        align: Align,
+        colour: Rgba<u8>,
+    ) -> f32 {
+        let line_height = size * LINE_SPACING;
+
+        for (idx, line) in lines.iter().enumerate() {
+            let line_x = match align {
+                Align::Left => x,
+                Align::Centre => x - text_width(line, weight, size) / 2.0,
+            };
+
+            self.draw_text(line, weight, size, line_x, y + idx as f32 * line_height, colour);
+        }
+
+        lines.len() as f32 * line_height
+    }
+}
+
+pub fn encode_png(image: &RgbaImage) -> Result<Vec<u8>, String> {
+    let mut data = Vec::new();
+
+    PngEncoder::new(&mut data)
+        .encode(image, image.width(), image.height(), ColorType::Rgba8)
+        .map_err(|why| format!("Unable to encode the image: {}", why))?;
+
+    Ok(data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wrap_text() {
+        let lines = wrap_text(
+            "one two three\nfour",
+            Weight::Regular,
+            20.0,
+            text_width("one two", Weight::Regular, 20.0),
+        );
+        assert_eq!(lines, vec!["one two", "three", "four"]);
+
+        let long = "a".repeat(50);
+        let lines = wrap_text(&long, Weight::Regular, 20.0, 100.0);
+        assert!(lines.len() > 1);
+        assert_eq!(lines.concat(), long);
+        assert!(lines.iter().all(|line| text_width(line, Weight::Regular, 20.0) <= 100.0));
+    }
+
+    #[test]
+    fn test_fit_text() {
+        let (size, lines) = fit_text("Short", Weight::Bold, 400.0, 100.0, 40.0, 20.0);
+        assert_eq!((size, lines.len()), (40.0, 1));
+
+        let (size, lines) = fit_text(&"word ".repeat(500), Weight::Regular, 300.0, 100.0, 40.0, 20.0);
+        assert_eq!(size, 20.0);
+        assert!(lines.len() as f32 * size * LINE_SPACING <= 100.0);
+        assert!(lines.last().unwrap().ends_with('…'));
+    }
+}
@@ -0,0 +1,222 @@
+pub mod canvas;
+pub mod templates;
+
+use canvas::encode_png;
+use image::RgbaImage;
+use urlencoding::encode;
+
+use crate::{
+    models::{commands::NekoBotResponse, settings::ImageGenConfig},
+    utils::cache::fetch_text,
+};
+
+// Discord serves avatars up to 8 MiB, anything larger isn't one
+const MAX_AVATAR_SIZE: usize = 8 * 1024 * 1024;
+
+#[derive(Clone, Debug)]
+pub enum Template {
+    Clyde(String),
+    Kanna(String),
+    ChangeMyMind(String),
+    TrumpTweet(String),
+    PhComment {
+        text:     String,
+        username: String,
+        avatar:   String,
+    },
+    Lolice(String),
+    Captcha {
+        caption: String,
+        avatar:  String,
+    },
+}
+
+pub enum Rendered {
+    // PNG data rendered locally
+    File(Vec<u8>),
+    // Image hosted by nekobot
+    Url(String),
+}
+
+impl Template {
+    pub fn file_name(&self) -> &'static str {
+        match self {
+            Template::Clyde(_) => "clyde.png",
+            Template::Kanna(_) => "kannagen.png",
+            Template::ChangeMyMind(_) => "changemymind.png",
+            Template::TrumpTweet(_) => "trumptweet.png",
+            Template::PhComment {
+                ..
+            } => "phcomment.png",
+            Template::Lolice(_) => "lolice.png",
+            Template::Captcha {
+                ..
+            } => "captcha.png",
+        }
+    }
+
+    fn avatar(&self) -> Option<&str> {
+        match self {
+            Template::PhComment {
+                avatar,
+                ..
+            }
+            | Template::Captcha {
+                avatar,
+                ..
+            }
+            | Template::Lolice(avatar) => Some(avatar),
+            _ => None,
+        }
+    }
+
+    fn draw(&self, avatar: Option<&RgbaImage>) -> RgbaImage {
+        match self {
+            Template::Clyde(text) => templates::clyde(text),
+            Template::Kanna(text) => templates::kanna(text),
+            Template::ChangeMyMind(text) => templates::change_my_mind(text),
+            Template::TrumpTweet(text) => templates::trump_tweet(text),
+            Template::PhComment {
+                text,
+                username,
+                ..
+            } => templates::ph_comment(text, username, avatar),
+            Template::Lolice(_) => templates::lolice(avatar),
+            Template::Captcha {
+                caption,
+                ..
+            } => templates::captcha(caption, avatar),
+        }
+    }
+
+    fn nekobot_url(&self) -> String {
+        let query = match self {
+            Template::Clyde(text) => format!("type=clyde&text={}", encode(text)),
+            Template::Kanna(text) => format!("type=kannagen&text={}", encode(text)),
+            Template::ChangeMyMind(text) => format!("type=changemymind&text={}", encode(text)),
+            Template::TrumpTweet(text) => format!("type=trumptweet&text={}", encode(text)),
+            Template::PhComment {
+                text,
+                username,
+                avatar,
+            } => {
+                format!(
+                    "type=phcomment&text={}&image={}&username={}",
+                    encode(text),
+                    encode(avatar),
+                    encode(username)
+                )
+            },
+            Template::Lolice(avatar) => format!("type=lolice&url={}", encode(avatar)),
+            Template::Captcha {
+                caption,
+                avatar,
+            } => format!("type=captcha&url={}&username={}", encode(avatar), encode(caption)),
+        };
+
+        format!("https://nekobot.xyz/api/imagegen?{}", query)
+    }
+}
+
+// Discord serves WebP avatars by default which can't always be decoded, and
+// only the first frame of animated ones is used anyway
+fn png_avatar_url(url: &str) -> String {
+    url.replacen(".webp", ".png", 1).replacen(".gif", ".png", 1)
+}
+
+async fn download_avatar(url: &str) -> Result<RgbaImage, String> {
+    let mut res = reqwest::get(&png_avatar_url(url))
+        .await
+        .map_err(|_| "Unable to download the avatar".to_string())?;
+
+    if !res.status().is_success() {
+        return Err(format!("Unable to download the avatar ({})", res.status().as_str()));
+    }
+
+    // The length isn't always sent, so the body is also read in chunks and
+    // stopped once it gets too large
+    if matches!(res.content_length(), Some(len) if len > MAX_AVATAR_SIZE as u64) {
+        return Err("The avatar is too large".to_string());
+    }
+
+    let mut data = Vec::new();
+    while let Some(chunk) = res.chunk().await.map_err(|_| "Unable to download the avatar".to_string())? {
+        if data.len() + chunk.len() > MAX_AVATAR_SIZE {
+            return Err("The avatar is too large".to_string());
+        }
+
+        data.extend_from_slice(&chunk);
+    }
+
+    image::load_from_memory(&data)
+        .map(|image| image.to_rgba8())
+        .map_err(|_| "Unable to read the avatar".to_string())
+}
+
+pub async fn render(template: &Template) -> Result<Vec<u8>, String> {
+    let avatar = match template.avatar() {
+        Some(url) => Some(download_avatar(url).await?),
+        None => None,
+    };
+
+    // Drawing is CPU bound so is kept off the async runtime
+    let template = template.clone();
+    tokio::task::spawn_blocking(move || encode_png(&template.draw(avatar.as_ref())))
+        .await
+        .map_err(|_| "The renderer crashed".to_string())?
+}
+
+pub async fn nekobot(template: &Template) -> Result<String, String> {
+    let res = fetch_text(&template.nekobot_url()).await?;
+
+    serde_json::from_str::<NekoBotResponse>(&res)
+        .map(|res| res.message)
+        .map_err(|_| "Unable to parse the response from nekobot".to_string())
+}
+
+// Renders with the configured renderer, trying the other one if it fails
+// and fallback is enabled
+pub async fn generate(template: &Template, config: &ImageGenConfig) -> Result<Rendered, String> {
+    let local_first = config.renderer != "nekobot";
+
+    let first = if local_first {
+        render(template).await.map(Rendered::File)
+    } else {
+        nekobot(template).await.map(Rendered::Url)
+    };
+
+    let why = match first {
+        Ok(rendered) => return Ok(rendered),
+        Err(why) if !config.fallback => return Err(why),
+        Err(why) => why,
+    };
+
+    let second = if local_first {
+        nekobot(template).await.map(Rendered::Url)
+    } else {
+        render(template).await.map(Rendered::File)
+    };
+
+    second.map_err(|fallback_why| format!("{}, the fallback also failed: {}", why, fallback_why))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_png_avatar_url() {
+        assert_eq!(
+            png_avatar_url("https://cdn.discordapp.com/avatars/1/abc.webp?size=1024"),
+            "https://cdn.discordapp.com/avatars/1/abc.png?size=1024"
+        );
+        assert_eq!(
+            png_avatar_url("https://cdn.discordapp.com/avatars/1/a_abc.gif?size=1024"),
+            "https://cdn.discordapp.com/avatars/1/a_abc.png?size=1024"
+        );
+        assert_eq!(
+            png_avatar_url("https://cdn.discordapp.com/embed/avatars/0.png"),
+            "https://cdn.discordapp.com/embed/avatars/0.png"
+        );
+    }
+}
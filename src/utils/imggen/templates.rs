@@ -0,0 +1,347 @@
+use chrono::Local;
+use image::RgbaImage;
+use rand::Rng;
+
+use super::canvas::{fit_text, rgb, rgba, text_width, wrap_text, Align, Canvas, Weight, LINE_SPACING};
+
+// Shown when there's no avatar to draw, Discord's grey default avatar
+const AVATAR_PLACEHOLDER: u32 = 0x99aab5;
+
+fn draw_avatar(canvas: &mut Canvas, avatar: Option<&RgbaImage>, x: i64, y: i64, size: u32, radius: f32) {
+    match avatar {
+        Some(avatar) => canvas.draw_avatar(avatar, x, y, size, radius),
+        None => canvas.fill_rounded_rect(x, y, size, size, radius, rgb(AVATAR_PLACEHOLDER)),
+    }
+}
+
+// 12345 -> 12.3K
+fn short_count(count: u32) -> String {
+    if count < 1000 {
+        count.to_string()
+    } else {
+        format!("{}.{}K", count / 1000, count % 1000 / 100)
+    }
+}
+
+fn star(cx: f32, cy: f32, outer: f32, inner: f32) -> Vec<(f32, f32)> {
+    (0..10)
+        .map(|idx| {
+            let radius = if idx % 2 == 0 { outer } else { inner };
+            let angle = std::f32::consts::PI / 5.0 * idx as f32 - std::f32::consts::FRAC_PI_2;
+
+            (cx + radius * angle.cos(), cy + radius * angle.sin())
+        })
+        .collect()
+}
+
+// A Discord message from Clyde, the system bot
+pub fn clyde(text: &str) -> RgbaImage {
+    let width = 900;
+    let text_x = 136.0;
+    let (size, lines) = fit_text(text, Weight::Regular, width as f32 - text_x - 32.0, 700.0, 28.0, 20.0);
+    let text_height = lines.len() as f32 * size * LINE_SPACING;
+    let height = (68.0 + text_height + 70.0) as u32;
+
+    let mut canvas = Canvas::new(width, height, rgb(0x36393f));
+
+    // Clyde's avatar, a small robot face on blurple
+    canvas.fill_circle(72.0, 64.0, 40.0, rgb(0x5865f2));
+    canvas.fill_rounded_rect(52, 48, 40, 30, 8.0, rgb(0xffffff));
+    canvas.fill_circle(63.0, 62.0, 4.5, rgb(0x5865f2));
+    canvas.fill_circle(81.0, 62.0, 4.5, rgb(0x5865f2));
+
+    let name_width = canvas.draw_text("Clyde", Weight::Bold, 30.0, text_x, 22.0, rgb(0xffffff));
+    let tag_x = text_x + name_width + 10.0;
+    canvas.fill_rounded_rect(tag_x as i64, 28, 52, 26, 6.0, rgb(0x5865f2));
+    canvas.draw_text("BOT", Weight::Bold, 16.0, tag_x + 8.0, 31.0, rgb(0xffffff));
+
+    let time = format!("Today at {}", Local::now().format("%-I:%M %p"));
+    canvas.draw_text(&time, Weight::Regular, 20.0, tag_x + 64.0, 31.0, rgb(0xa3a6aa));
+
+    canvas.draw_lines(&lines, Weight::Regular, size, (text_x, 68.0), Align::Left, rgb(0xdcddde));
+
+    let footer_y = 68.0 + text_height + 16.0;
+    let footer = "Only you can see this • ";
+    let footer_width = canvas.draw_text(footer, Weight::Regular, 20.0, text_x, footer_y, rgb(0xa3a6aa));
+    canvas.draw_text(
+        "Dismiss message",
+        Weight::Regular,
+        20.0,
+        text_x + footer_width,
+        footer_y,
+        rgb(0x00aff4),
+    );
+
+    canvas.image
+}
+
+// Kanna peeking over a sheet of paper
+pub fn kanna(text: &str) -> RgbaImage {
+    let mut canvas = Canvas::new(800, 800, rgb(0xefe6f7));
+
+    // Her head behind the paper, with horns and hair beads
+    canvas.fill_circle(400.0, 190.0, 150.0, rgb(0xe3d5f2));
+    canvas.fill_polygon(&[(300.0, 95.0), (278.0, 20.0), (328.0, 78.0)], rgb(0x3b3b5c));
+    canvas.fill_polygon(&[(500.0, 95.0), (522.0, 20.0), (472.0, 78.0)], rgb(0x3b3b5c));
+    canvas.fill_circle(268.0, 200.0, 14.0, rgb(0xd23c3c));
+    canvas.fill_circle(532.0, 200.0, 14.0, rgb(0xd23c3c));
+    canvas.fill_circle(350.0, 160.0, 16.0, rgb(0x6a4c93));
+    canvas.fill_circle(450.0, 160.0, 16.0, rgb(0x6a4c93));
+
+    canvas.fill_rounded_rect(158, 218, 500, 520, 6.0, rgba(0x000000, 48));
+    canvas.fill_rounded_rect(150, 210, 500, 520, 6.0, rgb(0xffffff));
+
+    let (size, lines) = fit_text(text, Weight::Regular, 420.0, 440.0, 56.0, 20.0);
+    let text_height = lines.len() as f32 * size * LINE_SPACING;
+    canvas.draw_lines(
+        &lines,
+        Weight::Regular,
+        size,
+        (400.0, 470.0 - text_height / 2.0),
+        Align::Centre,
+        rgb(0x202020),
+    );
+
+    for x in &[160.0, 640.0] {
+        canvas.fill_circle(*x, 700.0, 44.0, rgb(0xe8c4b0));
+        canvas.fill_circle(*x, 700.0, 40.0, rgb(0xffe3d3));
+    }
+
+    canvas.image
+}
+
+// A sign on a table reading "Change my mind"
+pub fn change_my_mind(text: &str) -> RgbaImage {
+    let mut canvas = Canvas::new(1000, 650, rgb(0xc9dbe6));
+
+    canvas.fill_rect(0, 360, 1000, 290, rgb(0x7a9a5a));
+    canvas.fill_rect(160, 400, 20, 240, rgb(0x6e4521));
+    canvas.fill_rect(820, 400, 20, 240, rgb(0x6e4521));
+    canvas.fill_rect(120, 380, 760, 30, rgb(0x8b5a2b));
+
+    // Coffee mug
+    canvas.fill_rounded_rect(790, 318, 52, 62, 6.0, rgb(0xe9e4dc));
+    canvas.fill_circle(848.0, 346.0, 14.0, rgb(0xe9e4dc));
+    canvas.fill_circle(848.0, 346.0, 7.0, rgb(0xc9dbe6));
+
+    canvas.fill_rounded_rect(226, 156, 548, 428, 12.0, rgb(0xc8c8c8));
+    canvas.fill_rounded_rect(230, 160, 540, 420, 10.0, rgb(0xfafafa));
+
+    let (size, lines) = fit_text(text, Weight::Regular, 480.0, 290.0, 48.0, 18.0);
+    let text_height = lines.len() as f32 * size * LINE_SPACING;
+    canvas.draw_lines(
+        &lines,
+        Weight::Regular,
+        size,
+        (500.0, 320.0 - text_height / 2.0),
+        Align::Centre,
+        rgb(0x222222),
+    );
+
+    canvas.fill_rect(260, 482, 480, 2, rgb(0xd0d0d0));
+    canvas.draw_lines(
+        &["CHANGE MY MIND".to_string()],
+        Weight::Bold,
+        44.0,
+        (500.0, 504.0),
+        Align::Centre,
+        rgb(0x111111),
+    );
+
+    canvas.image
+}
+
+pub fn trump_tweet(text: &str) -> RgbaImage {
+    let width = 1000;
+    let (size, lines) = fit_text(text, Weight::Regular, 920.0, 560.0, 40.0, 24.0);
+    let text_height = lines.len() as f32 * size * LINE_SPACING;
+    let height = (170.0 + text_height + 180.0) as u32;
+
+    let mut canvas = Canvas::new(width, height, rgb(0xffffff));
+
+    canvas.fill_circle(88.0, 88.0, 48.0, rgb(0x2b4c7e));
+    canvas.draw_lines(
+        &["DJT".to_string()],
+        Weight::Bold,
+        30.0,
+        (88.0, 70.0),
+        Align::Centre,
+        rgb(0xffffff),
+    );
+
+    let name_width = canvas.draw_text("Donald J. Trump", Weight::Bold, 32.0, 156.0, 46.0, rgb(0x14171a));
+    let badge_x = 156.0 + name_width + 24.0;
+    canvas.fill_circle(badge_x, 66.0, 15.0, rgb(0x1da1f2));
+    canvas.draw_lines(
+        &["✓".to_string()],
+        Weight::Bold,
+        20.0,
+        (badge_x, 54.0),
+        Align::Centre,
+        rgb(0xffffff),
+    );
+    canvas.draw_text("@realDonaldTrump", Weight::Regular, 26.0, 156.0, 92.0, rgb(0x657786));
+
+    canvas.draw_lines(&lines, Weight::Regular, size, (40.0, 170.0), Align::Left, rgb(0x14171a));
+
+    let mut y = 170.0 + text_height + 24.0;
+    let date = Local::now().format("%-I:%M %p · %b %-d, %Y").to_string();
+    canvas.draw_text(&date, Weight::Regular, 24.0, 40.0, y, rgb(0x657786));
+
+    y += 52.0;
+    canvas.fill_rect(40, y as i64, width - 80, 1, rgb(0xe1e8ed));
+
+    y += 22.0;
+    let mut rng = rand::thread_rng();
+    let mut x = 40.0;
+    for (count, label) in &[
+        (rng.gen_range(5000..60000), "Retweets"),
+        (rng.gen_range(20000..250000), "Likes"),
+    ] {
+        x += canvas.draw_text(&short_count(*count), Weight::Bold, 24.0, x, y, rgb(0x14171a));
+        x += canvas.draw_text(&format!(" {}", label), Weight::Regular, 24.0, x, y, rgb(0x657786)) + 32.0;
+    }
+
+    y += 52.0;
+    canvas.fill_rect(40, y as i64, width - 80, 1, rgb(0xe1e8ed));
+
+    canvas.image
+}
+
+pub fn ph_comment(text: &str, username: &str, avatar: Option<&RgbaImage>) -> RgbaImage {
+    let width = 1000;
+    let text_x = 160.0;
+    let (size, lines) = fit_text(text, Weight::Regular, width as f32 - text_x - 30.0, 400.0, 30.0, 20.0);
+    let text_height = lines.len() as f32 * size * LINE_SPACING;
+    let footer_y = 86.0 + text_height + 18.0;
+    let height = ((footer_y + 60.0) as u32).max(160);
+
+    let mut canvas = Canvas::new(width, height, rgb(0x1b1b1b));
+    draw_avatar(&mut canvas, avatar, 30, 30, 100, 8.0);
+
+    let name_width = canvas.draw_text(username, Weight::Bold, 30.0, text_x, 32.0, rgb(0xffffff));
+    canvas.draw_text(" · just now", Weight::Regular, 22.0, text_x + name_width, 38.0, rgb(0x8d8d8d));
+
+    canvas.draw_lines(&lines, Weight::Regular, size, (text_x, 86.0), Align::Left, rgb(0xc6c6c6));
+
+    let mut rng = rand::thread_rng();
+    let mut x = text_x;
+    x += canvas.draw_text("▲ ", Weight::Regular, 22.0, x, footer_y, rgb(0xff9000));
+    x += canvas.draw_text(
+        &rng.gen_range(10..2000).to_string(),
+        Weight::Bold,
+        22.0,
+        x,
+        footer_y,
+        rgb(0xc6c6c6),
+    );
+    x += canvas.draw_text("   ▼ ", Weight::Regular, 22.0, x, footer_y, rgb(0x8d8d8d));
+    x += canvas.draw_text(
+        &rng.gen_range(0..50).to_string(),
+        Weight::Bold,
+        22.0,
+        x,
+        footer_y,
+        rgb(0xc6c6c6),
+    );
+    canvas.draw_text("   Reply", Weight::Regular, 22.0, x, footer_y, rgb(0x8d8d8d));
+
+    canvas.image
+}
+
+// The avatar promoted to Lolice Chief
+pub fn lolice(avatar: Option<&RgbaImage>) -> RgbaImage {
+    let mut canvas = Canvas::new(512, 512, rgb(0x1c2a5a));
+    draw_avatar(&mut canvas, avatar, 0, 0, 512, 0.0);
+
+    // Police cap along the top
+    canvas.fill_polygon(&[(96.0, 96.0), (130.0, 18.0), (382.0, 18.0), (416.0, 96.0)], rgb(0x1c2a5a));
+    canvas.fill_rounded_rect(70, 90, 372, 26, 12.0, rgb(0x0f1733));
+    canvas.fill_polygon(&star(256.0, 56.0, 30.0, 13.0), rgb(0xf4c542));
+
+    canvas.fill_rect(0, 408, 512, 104, rgba(0x1c2a5a, 230));
+    canvas.fill_rect(0, 408, 512, 4, rgb(0xf4c542));
+
+    let title = "LOLICE CHIEF";
+    let size = (52.0 * 460.0 / text_width(title, Weight::Bold, 52.0)).min(52.0);
+    canvas.draw_lines(
+        &[title.to_string()],
+        Weight::Bold,
+        size,
+        (256.0, 460.0 - size * LINE_SPACING / 2.0),
+        Align::Centre,
+        rgb(0xf4c542),
+    );
+
+    canvas.image
+}
+
+// A reCAPTCHA image challenge made of the avatar
+pub fn captcha(caption: &str, avatar: Option<&RgbaImage>) -> RgbaImage {
+    let mut canvas = Canvas::new(600, 846, rgb(0xd3d3d3));
+    canvas.fill_rect(1, 1, 598, 844, rgb(0xffffff));
+    canvas.fill_rect(8, 8, 584, 164, rgb(0x4a90e2));
+
+    canvas.draw_text("Select all squares with", Weight::Regular, 22.0, 32.0, 26.0, rgb(0xffffff));
+    let caption_lines = wrap_text(caption, Weight::Bold, 34.0, 536.0);
+    let caption = caption_lines.first().cloned().unwrap_or_default();
+    canvas.draw_lines(&[caption], Weight::Bold, 34.0, (32.0, 62.0), Align::Left, rgb(0xffffff));
+    canvas.draw_text(
+        "If there are none, click skip",
+        Weight::Regular,
+        20.0,
+        32.0,
+        122.0,
+        rgb(0xffffff),
+    );
+
+    // The avatar split into a 3x3 grid
+    let mut grid = Canvas::new(576, 576, rgb(AVATAR_PLACEHOLDER));
+    draw_avatar(&mut grid, avatar, 0, 0, 576, 0.0);
+
+    for row in 0..3 {
+        for col in 0..3 {
+            let tile = image::imageops::crop_imm(&grid.image, col * 192, row * 192, 192, 192).to_image();
+            canvas.draw_image(&tile, 8 + col as i64 * 196, 180 + row as i64 * 196);
+        }
+    }
+
+    canvas.fill_rect(0, 774, 600, 1, rgb(0xdfdfdf));
+    canvas.fill_rounded_rect(470, 786, 120, 48, 4.0, rgb(0x4a90e2));
+    canvas.draw_lines(
+        &["VERIFY".to_string()],
+        Weight::Bold,
+        20.0,
+        (530.0, 797.0),
+        Align::Centre,
+        rgb(0xffffff),
+    );
+
+    canvas.image
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_templates() {
+        let avatar = RgbaImage::from_pixel(64, 64, rgb(0xff0000));
+
+        assert_eq!(kanna("Hello").dimensions(), (800, 800));
+        assert_eq!(change_my_mind("Hello").dimensions(), (1000, 650));
+        assert_eq!(trump_tweet("Hello").width(), 1000);
+        assert_eq!(ph_comment("Hello", "Inori", Some(&avatar)).width(), 1000);
+        assert_eq!(lolice(None).dimensions(), (512, 512));
+
+        let captcha = captcha("a cutie in them", Some(&avatar));
+        assert_eq!(captcha.dimensions(), (600, 846));
+        // Top left tile of the grid is the avatar
+        assert_eq!(*captcha.get_pixel(100, 250), rgb(0xff0000));
+
+        // Long messages make the image taller, up to a point
+        let short = clyde("Hello").height();
+        let long = clyde(&"Lorem ipsum dolor sit amet ".repeat(100)).height();
+        assert!(short < long && long <= 68 + 700 + 70);
+    }
+}
@@ -0,0 +1,307 @@
+use std::time::Duration;
+
+use async_trait::async_trait;
+use serde::de::DeserializeOwned;
+use urlencoding::encode;
+
+use crate::{
+    models::{
+        anime::{
+            AnimeProvider, Appearance, Character, CharacterRole, Link, Media, MediaKind, Person, Relation, VoiceRole,
+        },
+        commands::{
+            MALAnime, MALAppearance, MALCharacter, MALCharacterRole, MALEntry, MALManga, MALPerson, MALRelation,
+            MALResponse,
+        },
+    },
+    utils::cache::get_cached,
+};
+
+static BASE_URL: &str = "https://api.jikan.moe/v4/";
+
+// How long responses are cached for, in seconds
+const SEARCH_TTL: u64 = 60 * 60;
+const LIST_TTL: u64 = 6 * 60 * 60;
+const DETAILS_TTL: u64 = 24 * 60 * 60;
+
+async fn jikan<T: DeserializeOwned>(path: &str, ttl: u64) -> Result<T, String> {
+    let res = get_cached(&format!("{}{}", BASE_URL, path), Duration::from_secs(ttl)).await?;
+
+    serde_json::from_str::<MALResponse<T>>(&res)
+        .map(|res| res.data)
+        .map_err(|_| "Unable to parse the response from jikan".to_string())
+}
+
+fn links(entries: &[MALEntry]) -> Vec<Link> {
+    entries.iter().map(|entry| Link::new(&entry.name, &entry.url)).collect()
+}
+
+fn relations(relations: &[MALRelation]) -> Vec<Relation> {
+    relations
+        .iter()
+        .map(|relation| Relation {
+            relation: relation.relation.clone(),
+            entries:  relation
+                .entry
+                .iter()
+                .map(|entry| Link::new(format!("{} ({})", entry.name, entry.kind), &entry.url))
+                .collect(),
+        })
+        .collect()
+}
+
+fn appearances(entries: &[MALAppearance]) -> Vec<Appearance> {
+    entries
+        .iter()
+        .map(|entry| Appearance {
+            media: Link::new(&entry.anime.name, &entry.anime.url),
+            role:  entry.role.clone(),
+        })
+        .collect()
+}
+
+fn character_roles(characters: Vec<MALCharacterRole>) -> Vec<CharacterRole> {
+    characters
+        .into_iter()
+        .map(|role| {
+            // Prefer the Japanese cast, falling back to whoever is listed
+            // first
+            let actor = role
+                .voice_actors
+                .iter()
+                .find(|actor| actor.language == "Japanese")
+                .or_else(|| role.voice_actors.first())
+                .map(|actor| Link::new(&actor.person.name, &actor.person.url));
+
+            CharacterRole {
+                character: Link::new(&role.character.name, &role.character.url),
+                role: role.role,
+                actor,
+            }
+        })
+        .collect()
+}
+
+impl From<MALAnime> for Media {
+    fn from(anime: MALAnime) -> Media {
+        Media {
+            source: "MyAnimeList",
+            kind: MediaKind::Anime,
+            mal_id: Some(anime.mal_id),
+            image: anime.images.url().cloned(),
+            url: anime.url,
+            title: anime.title,
+            title_alt: anime.title_english,
+            format: anime.kind,
+            status: anime.status,
+            dates: anime.aired.string,
+            episodes: anime.episodes,
+            season: match (anime.season, anime.year) {
+                (Some(season), Some(year)) => Some(format!("{} {}", season, year)),
+                _ => None,
+            },
+            rating: anime.rating,
+            score: anime.score,
+            rank: anime.rank,
+            members: anime.members,
+            synopsis: anime.synopsis,
+            genres: links(&anime.genres),
+            themes: links(&anime.themes),
+            creators: links(&anime.studios),
+            streaming: anime
+                .streaming
+                .iter()
+                .map(|stream| Link::new(&stream.name, &stream.url))
+                .collect(),
+            relations: relations(&anime.relations),
+            ..Media::default()
+        }
+    }
+}
+
+impl From<MALManga> for Media {
+    fn from(manga: MALManga) -> Media {
+        Media {
+            source: "MyAnimeList",
+            kind: MediaKind::Manga,
+            mal_id: Some(manga.mal_id),
+            image: manga.images.url().cloned(),
+            url: manga.url,
+            title: manga.title,
+            title_alt: manga.title_english,
+            format: manga.kind,
+            status: manga.status,
+            dates: manga.published.string,
+            volumes: manga.volumes,
+            chapters: manga.chapters,
+            score: manga.score,
+            rank: manga.rank,
+            members: manga.members,
+            synopsis: manga.synopsis,
+            genres: links(&manga.genres),
+            themes: links(&manga.themes),
+            creators: links(&manga.authors),
+            publishers: links(&manga.serializations),
+            relations: relations(&manga.relations),
+            ..Media::default()
+        }
+    }
+}
+
+impl From<MALCharacter> for Character {
+    fn from(character: MALCharacter) -> Character {
+        Character {
+            source: "MyAnimeList",
+            id: character.mal_id,
+            image: character.images.url().cloned(),
+            anime: appearances(&character.anime),
+            manga: appearances(&character.manga),
+            actors: character
+                .voices
+                .iter()
+                .map(|voice| Appearance {
+                    media: Link::new(&voice.person.name, &voice.person.url),
+                    role:  voice.language.clone(),
+                })
+                .collect(),
+            url: character.url,
+            name: character.name,
+            name_native: character.name_kanji,
+            nicknames: character.nicknames,
+            favorites: character.favorites,
+            about: character.about,
+        }
+    }
+}
+
+impl From<MALPerson> for Person {
+    fn from(person: MALPerson) -> Person {
+        Person {
+            source: "MyAnimeList",
+            id: person.mal_id,
+            image: person.images.url().cloned(),
+            roles: person
+                .voices
+                .iter()
+                .map(|voice| VoiceRole {
+                    character: Link::new(&voice.character.name, &voice.character.url),
+                    media:     Link::new(&voice.anime.name, &voice.anime.url),
+                    role:      voice.role.clone(),
+                })
+                .collect(),
+            url: person.url,
+            name: person.name,
+            alternate_names: person.alternate_names,
+            birthday: person.birthday.map(|birthday| birthday.chars().take(10).collect()),
+            favorites: person.favorites,
+            about: person.about,
+        }
+    }
+}
+
+pub struct Jikan;
+
+impl Jikan {
+    async fn search<T: DeserializeOwned>(kind: &str, query: &str) -> Result<Vec<T>, String> {
+        jikan(&format!("{}?q={}&limit=10", kind, encode(query)), SEARCH_TTL).await
+    }
+
+    // Anime airing in a season, the current one if None
+    pub async fn seasonal(&self, season: Option<(u64, &str)>) -> Result<Vec<Media>, String> {
+        let path = match season {
+            Some((year, season)) => format!("seasons/{}/{}", year, season),
+            None => "seasons/now".to_string(),
+        };
+
+        jikan::<Vec<MALAnime>>(&path, LIST_TTL)
+            .await
+            .map(|results| results.into_iter().map(Media::from).collect())
+    }
+
+    pub async fn top(&self, kind: MediaKind, filter: Option<&str>) -> Result<Vec<Media>, String> {
+        let path = match filter {
+            Some(filter) => format!("top/{}?filter={}", kind.name(), filter),
+            None => format!("top/{}", kind.name()),
+        };
+
+        match kind {
+            MediaKind::Anime => jikan::<Vec<MALAnime>>(&path, LIST_TTL)
+                .await
+                .map(|results| results.into_iter().map(Media::from).collect()),
+            MediaKind::Manga => jikan::<Vec<MALManga>>(&path, LIST_TTL)
+                .await
+                .map(|results| results.into_iter().map(Media::from).collect()),
+        }
+    }
+
+    // Random results are never cached
+    pub async fn random_media(&self, kind: MediaKind) -> Result<Media, String> {
+        match kind {
+            MediaKind::Anime => jikan::<MALAnime>("random/anime", 0).await.map(Media::from),
+            MediaKind::Manga => jikan::<MALManga>("random/manga", 0).await.map(Media::from),
+        }
+    }
+
+    pub async fn random_character(&self) -> Result<Character, String> {
+        jikan::<MALCharacter>("random/characters", 0).await.map(Character::from)
+    }
+}
+
+#[async_trait]
+impl AnimeProvider for Jikan {
+    fn name(&self) -> &'static str {
+        "jikan"
+    }
+
+    async fn search_media(&self, kind: MediaKind, query: &str) -> Result<Vec<Media>, String> {
+        match kind {
+            MediaKind::Anime => Jikan::search::<MALAnime>("anime", query)
+                .await
+                .map(|results| results.into_iter().map(Media::from).collect()),
+            MediaKind::Manga => Jikan::search::<MALManga>("manga", query)
+                .await
+                .map(|results| results.into_iter().map(Media::from).collect()),
+        }
+    }
+
+    async fn media(&self, kind: MediaKind, mal_id: u64) -> Result<Media, String> {
+        let mut media = match kind {
+            MediaKind::Anime => Media::from(jikan::<MALAnime>(&format!("anime/{}/full", mal_id), DETAILS_TTL).await?),
+            MediaKind::Manga => Media::from(jikan::<MALManga>(&format!("manga/{}/full", mal_id), DETAILS_TTL).await?),
+        };
+
+        // Characters are extra information, the details are still useful
+        // without them
+        media.characters =
+            jikan::<Vec<MALCharacterRole>>(&format!("{}/{}/characters", kind.name(), mal_id), DETAILS_TTL)
+                .await
+                .map(character_roles)
+                .unwrap_or_default();
+
+        Ok(media)
+    }
+
+    async fn search_characters(&self, query: &str) -> Result<Vec<Character>, String> {
+        Jikan::search::<MALCharacter>("characters", query)
+            .await
+            .map(|results| results.into_iter().map(Character::from).collect())
+    }
+
+    async fn character(&self, id: u64) -> Result<Character, String> {
+        jikan::<MALCharacter>(&format!("characters/{}/full", id), DETAILS_TTL)
+            .await
+            .map(Character::from)
+    }
+
+    async fn search_people(&self, query: &str) -> Result<Vec<Person>, String> {
+        Jikan::search::<MALPerson>("people", query)
+            .await
+            .map(|results| results.into_iter().map(Person::from).collect())
+    }
+
+    async fn person(&self, id: u64) -> Result<Person, String> {
+        jikan::<MALPerson>(&format!("people/{}/full", id), DETAILS_TTL)
+            .await
+            .map(Person::from)
+    }
+}
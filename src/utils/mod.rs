@@ -1,7 +1,23 @@
+pub mod anilist;
+pub mod backup;
+pub mod cache;
+pub mod calc;
 pub mod chat;
 pub mod checks;
+pub mod codec;
+pub mod coingecko;
 pub mod consts;
+pub mod crates_io;
+pub mod data_format;
 pub mod discord;
 pub mod emotes;
+pub mod esolang;
+pub mod frankfurter;
+pub mod imggen;
+pub mod jikan;
 pub mod logging;
+pub mod purge_filter;
+pub mod regex_tester;
+pub mod rust_eval;
+pub mod rustdoc;
 pub mod version;
@@ -0,0 +1,282 @@
+use chrono::{DateTime, NaiveDate};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serenity::model::channel::Message;
+
+use crate::utils::chat::has_emotes;
+
+static LINK_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r"https?://\S+").unwrap());
+
+// Milliseconds between the Unix epoch and the Discord epoch
+const DISCORD_EPOCH: u64 = 1420070400000;
+
+pub fn snowflake_to_millis(id: u64) -> u64 {
+    (id >> 22) + DISCORD_EPOCH
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum HasKind {
+    Attachment,
+    Image,
+    Link,
+    Embed,
+    Reaction,
+    Mention,
+    Emote,
+}
+
+impl HasKind {
+    fn parse(kind: &str) -> Option<HasKind> {
+        match kind.to_lowercase().as_str() {
+            "attachment" | "file" => Some(HasKind::Attachment),
+            "image" | "img" => Some(HasKind::Image),
+            "link" | "url" => Some(HasKind::Link),
+            "embed" => Some(HasKind::Embed),
+            "reaction" => Some(HasKind::Reaction),
+            "mention" => Some(HasKind::Mention),
+            "emote" | "emoji" => Some(HasKind::Emote),
+            _ => None,
+        }
+    }
+
+    fn matches(&self, msg: &Message) -> bool {
+        match self {
+            HasKind::Attachment => !msg.attachments.is_empty(),
+            HasKind::Image => {
+                msg.attachments.iter().any(|a| a.width.is_some()) || msg.embeds.iter().any(|e| e.image.is_some())
+            },
+            HasKind::Link => LINK_REGEX.is_match(&msg.content),
+            HasKind::Embed => !msg.embeds.is_empty(),
+            HasKind::Reaction => !msg.reactions.is_empty(),
+            HasKind::Mention => !msg.mentions.is_empty() || !msg.mention_roles.is_empty() || msg.mention_everyone,
+            HasKind::Emote => has_emotes(&msg.content),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub enum Condition {
+    Before(u64),
+    After(u64),
+    Has(HasKind),
+    Contains(String),
+    Regex(Regex),
+    Pinned,
+}
+
+impl Condition {
+    fn matches(&self, msg: &Message) -> bool {
+        match self {
+            Condition::Before(time) => snowflake_to_millis(msg.id.0) < *time,
+            Condition::After(time) => snowflake_to_millis(msg.id.0) > *time,
+            Condition::Has(kind) => kind.matches(msg),
+            Condition::Contains(text) => msg.content.to_lowercase().contains(text),
+            Condition::Regex(regex) => regex.is_match(&msg.content),
+            Condition::Pinned => msg.pinned,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct PurgeFilter {
+    conditions: Vec<(bool, Condition)>,
+}
+
+// Accepts a message ID, a date (2021-01-31) or an RFC 3339 timestamp
+fn parse_time(value: &str) -> Result<u64, String> {
+    if let Ok(id) = value.parse::<u64>() {
+        return Ok(snowflake_to_millis(id));
+    }
+
+    if let Ok(date) = NaiveDate::parse_from_str(value, "%Y-%m-%d") {
+        return Ok(date.and_hms(0, 0, 0).timestamp_millis() as u64);
+    }
+
+    if let Ok(time) = DateTime::parse_from_rfc3339(value) {
+        return Ok(time.timestamp_millis() as u64);
+    }
+
+    Err(format!("Invalid date or message ID: `{}`", value))
+}
+
+// Splits on whitespace while keeping quoted text together
+fn tokenize(input: &str) -> Result<Vec<String>, String> {
+    let mut tokens = Vec::new();
+    let mut token = String::new();
+    let mut quoted = false;
+    let mut chars = input.chars();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' if quoted => {
+                if let Some(next) = chars.next() {
+                    token.push(next);
+                }
+            },
+            '"' => quoted = !quoted,
+            c if c.is_whitespace() && !quoted => {
+                if !token.is_empty() {
+                    tokens.push(token);
+                    token = String::new();
+                }
+            },
+            c => token.push(c),
+        }
+    }
+
+    if quoted {
+        return Err("Unclosed quote in filter".to_string());
+    }
+
+    if !token.is_empty() {
+        tokens.push(token);
+    }
+
+    Ok(tokens)
+}
+
+// Whether a token is one of the filters below, used to tell filter
+// expressions apart from plain regexes
+fn is_filter(token: &str) -> bool {
+    let token = token.strip_prefix('-').unwrap_or(token);
+
+    match token.split_once(':') {
+        Some((key, value)) => match key.to_lowercase().as_str() {
+            "before" | "after" | "has" | "contains" | "regex" => true,
+            "is" => value.eq_ignore_ascii_case("pinned"),
+            _ => false,
+        },
+        None => false,
+    }
+}
+
+impl PurgeFilter {
+    // Parses filters such as `before:2021-01-01 has:link -has:reaction
+    // contains:"foo bar"`. Input without any filters is used as a regex
+    // exactly as given so older usages still work, anything else that isn't
+    // a filter is joined back together and used as a regex
+    pub fn parse(input: &str) -> Result<PurgeFilter, String> {
+        let mut filter = PurgeFilter::default();
+        let mut leftover = Vec::new();
+
+        let tokens = match tokenize(input) {
+            Ok(tokens) if tokens.iter().any(|token| is_filter(token)) => tokens,
+            _ if input.is_empty() => return Ok(filter),
+            _ => {
+                return match Regex::new(input) {
+                    Ok(regex) => {
+                        filter.conditions.push((false, Condition::Regex(regex)));
+
+                        Ok(filter)
+                    },
+                    Err(_) => Err(format!("Unable to parse regex: `{}`", input)),
+                };
+            },
+        };
+
+        for token in tokens {
+            let (negated, stripped) = match token.strip_prefix('-') {
+                Some(stripped) => (true, stripped),
+                None => (false, token.as_str()),
+            };
+
+            let (key, value) = match stripped.split_once(':') {
+                Some((key, value)) => (key.to_lowercase(), value),
+                None => {
+                    leftover.push(token.clone());
+                    continue;
+                },
+            };
+
+            let condition = match key.as_str() {
+                "before" => Condition::Before(parse_time(value)?),
+                "after" => Condition::After(parse_time(value)?),
+                "has" => match HasKind::parse(value) {
+                    Some(kind) => Condition::Has(kind),
+                    None => return Err(format!("Unknown `has` type: `{}`", value)),
+                },
+                "contains" => Condition::Contains(value.to_lowercase()),
+                "regex" => match Regex::new(value) {
+                    Ok(regex) => Condition::Regex(regex),
+                    Err(_) => return Err(format!("Unable to parse regex: `{}`", value)),
+                },
+                "is" if value.eq_ignore_ascii_case("pinned") => Condition::Pinned,
+                _ => {
+                    leftover.push(token.clone());
+                    continue;
+                },
+            };
+
+            filter.conditions.push((negated, condition));
+        }
+
+        if !leftover.is_empty() {
+            let pattern = leftover.join(" ");
+
+            match Regex::new(&pattern) {
+                Ok(regex) => filter.conditions.push((false, Condition::Regex(regex))),
+                Err(_) => return Err(format!("Unable to parse regex: `{}`", pattern)),
+            }
+        }
+
+        Ok(filter)
+    }
+
+    pub fn matches(&self, msg: &Message) -> bool {
+        self.conditions
+            .iter()
+            .all(|(negated, condition)| condition.matches(msg) != *negated)
+    }
+
+    // Messages are scanned newest first, so once a message is older than an
+    // `after` filter nothing else can match
+    pub fn is_exhausted(&self, msg: &Message) -> bool {
+        let time = snowflake_to_millis(msg.id.0);
+
+        self.conditions.iter().any(|(negated, condition)| match condition {
+            Condition::After(after) => !negated && time <= *after,
+            _ => false,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tokenize() {
+        assert_eq!(
+            tokenize(r#"has:link contains:"foo bar" -has:reaction"#).unwrap(),
+            vec!["has:link", "contains:foo bar", "-has:reaction"]
+        );
+
+        assert!(tokenize(r#"contains:"foo"#).is_err());
+    }
+
+    #[test]
+    fn test_parse() {
+        let filter = PurgeFilter::parse("before:2021-01-01 after:801105575038041266 has:attachment -has:reaction").unwrap();
+        assert_eq!(filter.conditions.len(), 4);
+        assert!(filter.conditions[3].0);
+
+        // Old style regex, kept exactly as typed
+        let filter = PurgeFilter::parse(r"\[[a-zA-Z]*]").unwrap();
+        assert_eq!(filter.conditions.len(), 1);
+
+        let filter = PurgeFilter::parse(r#"a  "b""#).unwrap();
+        match &filter.conditions[0].1 {
+            Condition::Regex(regex) => assert_eq!(regex.as_str(), r#"a  "b""#),
+            _ => panic!("Expected a regex"),
+        }
+
+        // Errors
+        assert!(PurgeFilter::parse("has:nothing").is_err());
+        assert!(PurgeFilter::parse("before:yesterday").is_err());
+    }
+
+    #[test]
+    fn test_snowflake_to_millis() {
+        assert_eq!(snowflake_to_millis(175928847299117063), 1462015105796);
+    }
+}
@@ -0,0 +1,185 @@
+use regex::{Regex, RegexBuilder};
+
+// Stops patterns like `a{1000}{1000}` from using a huge amount of memory
+const SIZE_LIMIT: usize = 1 << 20;
+
+#[derive(Debug)]
+pub struct CompileError {
+    pub message:  String,
+    // Character offsets into the pattern
+    pub position: Option<(usize, usize)>,
+}
+
+impl CompileError {
+    // Shows the message with the offending part of the pattern underlined
+    pub fn render(&self, pattern: &str) -> String {
+        match self.position {
+            Some((start, end)) => format!(
+                "Error at position {}: {}\n```\n{}\n{}{}\n```",
+                start + 1,
+                self.message,
+                pattern,
+                " ".repeat(start),
+                "^".repeat(end.saturating_sub(start).max(1))
+            ),
+            None => self.message.clone(),
+        }
+    }
+}
+
+fn char_offset(text: &str, byte_offset: usize) -> usize {
+    text[..byte_offset.min(text.len())].chars().count()
+}
+
+// The regex crate only gives back a preformatted message, parsing the pattern
+// with regex-syntax first gives the error position as well
+pub fn compile(pattern: &str) -> Result<Regex, CompileError> {
+    if let Err(why) = regex_syntax::Parser::new().parse(pattern) {
+        let (message, span) = match &why {
+            regex_syntax::Error::Parse(err) => (err.kind().to_string(), Some(*err.span())),
+            regex_syntax::Error::Translate(err) => (err.kind().to_string(), Some(*err.span())),
+            _ => (why.to_string(), None),
+        };
+
+        return Err(CompileError {
+            message,
+            position: span.map(|span| (char_offset(pattern, span.start.offset), char_offset(pattern, span.end.offset))),
+        });
+    }
+
+    RegexBuilder::new(pattern)
+        .size_limit(SIZE_LIMIT)
+        .build()
+        .map_err(|why| CompileError {
+            message:  match why {
+                regex::Error::CompiledTooBig(_) => "The pattern is too big".to_string(),
+                why => why.to_string(),
+            },
+            position: None,
+        })
+}
+
+pub struct MatchInfo {
+    // Character offsets into the text
+    pub start:  usize,
+    pub end:    usize,
+    pub text:   String,
+    // Named groups use their name, otherwise their number
+    pub groups: Vec<(String, Option<String>)>,
+}
+
+// Returns up to `limit` matches along with the total amount of matches
+pub fn find_matches(regex: &Regex, text: &str, limit: usize) -> (Vec<MatchInfo>, usize) {
+    let names = regex.capture_names().collect::<Vec<Option<&str>>>();
+    let mut matches = Vec::new();
+    let mut total = 0;
+
+    for captures in regex.captures_iter(text) {
+        total += 1;
+
+        if matches.len() >= limit {
+            continue;
+        }
+
+        let whole = match captures.get(0) {
+            Some(whole) => whole,
+            None => continue,
+        };
+
+        let groups = (1..captures.len())
+            .map(|idx| {
+                let name = names[idx].map_or_else(|| idx.to_string(), str::to_string);
+
+                (name, captures.get(idx).map(|group| group.as_str().to_string()))
+            })
+            .collect();
+
+        matches.push(MatchInfo {
+            start: char_offset(text, whole.start()),
+            end: char_offset(text, whole.end()),
+            text: whole.as_str().to_string(),
+            groups,
+        });
+    }
+
+    (matches, total)
+}
+
+fn escape_markdown(text: &str) -> String {
+    let mut escaped = String::new();
+
+    for c in text.chars() {
+        if matches!(c, '\\' | '*' | '_' | '~' | '`' | '|' | '>') {
+            escaped.push('\\');
+        }
+
+        escaped.push(c);
+    }
+
+    escaped
+}
+
+// Bolds and underlines every match, empty matches can't be shown so they're
+// skipped
+pub fn highlight(regex: &Regex, text: &str) -> String {
+    let mut highlighted = String::new();
+    let mut last = 0;
+
+    for found in regex.find_iter(text) {
+        if found.start() == found.end() {
+            continue;
+        }
+
+        highlighted.push_str(&escape_markdown(&text[last..found.start()]));
+        highlighted.push_str(&format!("**__{}__**", escape_markdown(found.as_str())));
+        last = found.end();
+    }
+
+    highlighted.push_str(&escape_markdown(&text[last..]));
+
+    highlighted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compile_errors() {
+        let err = compile("ab(cd").err().unwrap();
+        assert_eq!(err.position, Some((2, 3)));
+        assert!(err.render("ab(cd").starts_with("Error at position 3: "));
+        assert!(err.render("ab(cd").ends_with("ab(cd\n  ^\n```"));
+
+        let err = compile("é[z-a]").err().unwrap();
+        assert_eq!(err.position, Some((2, 5)));
+
+        let err = compile(r"\p{Nope}").err().unwrap();
+        assert!(err.position.is_some());
+
+        assert!(compile("a{1000}{1000}").is_err());
+        assert!(compile(r"(?P<word>\w+)").is_ok());
+    }
+
+    #[test]
+    fn test_matches() {
+        let regex = compile(r"(?P<key>\w+)=(\d+)?").unwrap();
+        let (matches, total) = find_matches(&regex, "é=1 b= c=3", 2);
+
+        assert_eq!(total, 3);
+        assert_eq!(matches.len(), 2);
+        assert_eq!((matches[0].start, matches[0].end), (0, 3));
+        assert_eq!(matches[0].groups, vec![
+            ("key".to_string(), Some("é".to_string())),
+            ("2".to_string(), Some("1".to_string()))
+        ]);
+        assert_eq!(matches[1].groups[1], ("2".to_string(), None));
+    }
+
+    #[test]
+    fn test_highlight() {
+        let regex = compile(r"\d+|x*").unwrap();
+
+        assert_eq!(highlight(&regex, "a_1 b 22"), r"a\_**__1__** b **__22__**");
+    }
+}
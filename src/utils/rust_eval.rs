@@ -0,0 +1,566 @@
+use std::{
+    ffi::CString,
+    fs,
+    io::Read,
+    path::{Path, PathBuf},
+    process::{Command, ExitStatus, Stdio},
+    thread,
+    time::{Duration, Instant},
+};
+
+use uuid::Uuid;
+
+use crate::models::settings::RustEvalConfig;
+
+// rustc and the linker need far more address space than the snippet does
+const COMPILE_MEMORY: u64 = 4096;
+// RLIMIT_NPROC counts every process and thread the user owns, so this is
+// only a rough cap to stop fork bombs
+const PROCESS_LIMIT: u64 = 256;
+// MiB, stops the program filling the disk
+const FILE_SIZE_LIMIT: u64 = 16;
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+// What the toolchain needs to compile and link, bound read-only into an
+// otherwise empty root. Anything else, including the bot's directory and
+// config, can't be seen from inside the sandbox
+const SYSTEM_PATHS: [&str; 9] = [
+    "/usr",
+    "/bin",
+    "/sbin",
+    "/lib",
+    "/lib64",
+    "/etc/alternatives",
+    "/etc/ld.so.cache",
+    "/etc/ld.so.conf",
+    "/etc/ld.so.conf.d",
+];
+const DEVICES: [&str; 4] = ["/dev/null", "/dev/zero", "/dev/random", "/dev/urandom"];
+// Where the snippet is compiled and run inside the sandbox
+const WORK_DIR: &str = "/sandbox";
+// IDs the sandbox runs as, anything but 0 so no capabilities survive exec
+const SANDBOX_ID: u32 = 1000;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Stage {
+    Compile,
+    Run,
+}
+
+pub struct Evaluation {
+    pub stage:     Stage,
+    pub success:   bool,
+    pub status:    String,
+    pub stdout:    String,
+    pub stderr:    String,
+    pub elapsed:   Duration,
+    pub timed_out: bool,
+    pub truncated: bool,
+}
+
+struct Limits {
+    time:      Duration,
+    // MiB
+    memory:    u64,
+    processes: Option<u64>,
+    // Bytes for each of stdout and stderr
+    output:    usize,
+}
+
+#[derive(Clone)]
+struct BindMount {
+    source: CString,
+    target: CString,
+    // Flags which have to be kept when remounting read-only
+    flags:  libc::c_ulong,
+}
+
+// A skeleton root filesystem, built before forking so nothing needs to
+// allocate between fork and exec
+#[derive(Clone)]
+struct Jail {
+    root:     CString,
+    work_dir: CString,
+    mounts:   Vec<BindMount>,
+    uid_map:  String,
+    gid_map:  String,
+}
+
+// Removes the working directory however the evaluation ends
+struct TempDir(PathBuf);
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}
+
+// Snippets without a main function are treated as its body
+pub fn wrap_snippet(code: &str) -> String {
+    if code.contains("fn main") {
+        code.to_string()
+    } else {
+        format!("fn main() {{\n{}\n}}\n", code)
+    }
+}
+
+// Mount flags which are locked inside a user namespace, remounting a bind
+// read-only fails unless they're kept
+#[cfg(target_os = "linux")]
+fn locked_flags(path: &CString) -> libc::c_ulong {
+    let mut stat = unsafe { std::mem::zeroed::<libc::statvfs>() };
+    if unsafe { libc::statvfs(path.as_ptr(), &mut stat) } != 0 {
+        return 0;
+    }
+
+    [
+        (libc::ST_NOSUID, libc::MS_NOSUID),
+        (libc::ST_NODEV, libc::MS_NODEV),
+        (libc::ST_NOEXEC, libc::MS_NOEXEC),
+        (libc::ST_NOATIME, libc::MS_NOATIME),
+        (libc::ST_NODIRATIME, libc::MS_NODIRATIME),
+        (libc::ST_RELATIME, libc::MS_RELATIME),
+    ]
+    .iter()
+    .filter(|(st, _)| stat.f_flag & st != 0)
+    .fold(0, |flags, (_, ms)| flags | ms)
+}
+
+#[cfg(target_os = "linux")]
+fn c_path(path: &Path) -> Result<CString, String> {
+    use std::os::unix::ffi::OsStrExt;
+
+    CString::new(path.as_os_str().as_bytes()).map_err(|_| format!("Invalid path: {}", path.display()))
+}
+
+#[cfg(target_os = "linux")]
+fn build_jail(dir: &Path, sysroot: &Path) -> Result<Jail, String> {
+    let io = |why: std::io::Error| format!("Unable to set up the sandbox: {}", why);
+    let root = dir.join("root");
+    let bot_dir = std::env::current_dir().and_then(fs::canonicalize).map_err(io)?;
+    let mut mounts = Vec::new();
+
+    let sources = SYSTEM_PATHS
+        .iter()
+        .chain(DEVICES.iter())
+        .map(Path::new)
+        .chain(std::iter::once(sysroot));
+
+    for source in sources {
+        let metadata = match fs::symlink_metadata(source) {
+            Ok(metadata) => metadata,
+            Err(_) => continue,
+        };
+
+        let target = root.join(source.strip_prefix("/").unwrap_or(source));
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent).map_err(io)?;
+        }
+
+        // Merged /usr systems link /bin and friends into /usr, which is
+        // bound already
+        if metadata.file_type().is_symlink() {
+            std::os::unix::fs::symlink(fs::read_link(source).map_err(io)?, &target).map_err(io)?;
+
+            continue;
+        }
+
+        if bot_dir.starts_with(source) {
+            return Err(format!(
+                "The bot is running from inside `{}` which the sandbox needs to see, move it elsewhere to use rust \
+                 eval",
+                source.display()
+            ));
+        }
+
+        if metadata.is_dir() {
+            fs::create_dir_all(&target).map_err(io)?;
+        } else {
+            fs::File::create(&target).map_err(io)?;
+        }
+
+        let source = c_path(source)?;
+        mounts.push(BindMount {
+            flags: locked_flags(&source),
+            source,
+            target: c_path(&target)?,
+        });
+    }
+
+    fs::create_dir_all(root.join("tmp")).map_err(io)?;
+    fs::create_dir_all(root.join(WORK_DIR.trim_start_matches('/'))).map_err(io)?;
+
+    let (uid, gid) = unsafe { (libc::getuid(), libc::getgid()) };
+
+    Ok(Jail {
+        root: c_path(&root)?,
+        work_dir: c_path(Path::new(WORK_DIR))?,
+        mounts,
+        uid_map: format!("{} {} 1", SANDBOX_ID, uid),
+        gid_map: format!("{} {} 1", SANDBOX_ID, gid),
+    })
+}
+
+#[cfg(not(target_os = "linux"))]
+fn build_jail(_dir: &Path, _sysroot: &Path) -> Result<Jail, String> {
+    Err("Rust eval is only supported on Linux".to_string())
+}
+
+#[cfg(target_os = "linux")]
+fn sandbox(command: &mut Command, limits: &Limits, jail: &Jail) {
+    use std::{io, os::unix::process::CommandExt, ptr};
+
+    // The resource type differs between libcs, so this can't be a function
+    macro_rules! set_limit {
+        ($resource:expr, $value:expr) => {
+            let limit = libc::rlimit {
+                rlim_cur: $value as libc::rlim_t,
+                rlim_max: $value as libc::rlim_t,
+            };
+
+            if libc::setrlimit($resource, &limit) != 0 {
+                return Err(io::Error::last_os_error());
+            }
+        };
+    }
+
+    macro_rules! check {
+        ($call:expr) => {
+            if $call < 0 {
+                return Err(io::Error::last_os_error());
+            }
+        };
+    }
+
+    // Paths have to be null terminated for libc
+    unsafe fn write_file(path: &[u8], content: &str) -> io::Result<()> {
+        let fd = libc::open(path.as_ptr() as *const libc::c_char, libc::O_WRONLY);
+        check!(fd);
+
+        let written = libc::write(fd, content.as_ptr() as *const libc::c_void, content.len());
+        libc::close(fd);
+        check!(written);
+
+        Ok(())
+    }
+
+    let memory = limits.memory * 1024 * 1024;
+    // CPU time is a backstop for the wall clock timeout, rounded up so it
+    // never fires first
+    let cpu = limits.time.as_secs() + 1;
+    let processes = limits.processes;
+    let jail = jail.clone();
+
+    // Only async signal safe calls are allowed between fork and exec
+    unsafe {
+        command.pre_exec(move || {
+            // A process group of its own lets the timeout kill any children
+            check!(libc::setpgid(0, 0));
+
+            // New network namespaces start with only a downed loopback
+            // device, a user namespace lets this work without root
+            check!(libc::unshare(libc::CLONE_NEWUSER | libc::CLONE_NEWNS | libc::CLONE_NEWNET));
+            write_file(b"/proc/self/setgroups\0", "deny")?;
+            write_file(b"/proc/self/uid_map\0", &jail.uid_map)?;
+            write_file(b"/proc/self/gid_map\0", &jail.gid_map)?;
+
+            // Keep the mounts below from showing up outside the sandbox
+            check!(libc::mount(
+                ptr::null(),
+                b"/\0".as_ptr() as *const libc::c_char,
+                ptr::null(),
+                libc::MS_REC | libc::MS_PRIVATE,
+                ptr::null()
+            ));
+
+            // pivot_root needs the new root to be a mount point
+            check!(libc::mount(
+                jail.root.as_ptr(),
+                jail.root.as_ptr(),
+                ptr::null(),
+                libc::MS_BIND,
+                ptr::null()
+            ));
+
+            for mount in &jail.mounts {
+                check!(libc::mount(
+                    mount.source.as_ptr(),
+                    mount.target.as_ptr(),
+                    ptr::null(),
+                    libc::MS_BIND | libc::MS_REC,
+                    ptr::null()
+                ));
+                check!(libc::mount(
+                    ptr::null(),
+                    mount.target.as_ptr(),
+                    ptr::null(),
+                    libc::MS_BIND | libc::MS_REMOUNT | libc::MS_RDONLY | mount.flags,
+                    ptr::null()
+                ));
+            }
+
+            // Swap to the new root and detach the old one so nothing outside
+            // the sandbox can be reached, chroot alone can be escaped
+            let dot = b".\0".as_ptr() as *const libc::c_char;
+            check!(libc::chdir(jail.root.as_ptr()));
+            check!(libc::syscall(libc::SYS_pivot_root, dot, dot));
+            check!(libc::umount2(dot, libc::MNT_DETACH));
+            check!(libc::chdir(jail.work_dir.as_ptr()));
+
+            set_limit!(libc::RLIMIT_AS, memory);
+            set_limit!(libc::RLIMIT_CPU, cpu);
+            set_limit!(libc::RLIMIT_FSIZE, FILE_SIZE_LIMIT * 1024 * 1024);
+            set_limit!(libc::RLIMIT_CORE, 0);
+
+            if let Some(processes) = processes {
+                set_limit!(libc::RLIMIT_NPROC, processes);
+            }
+
+            Ok(())
+        });
+    }
+}
+
+// Reads up to the limit then drops the pipe, so a program which keeps
+// writing gets killed by SIGPIPE instead of blocking forever
+fn read_limited<R: Read + Send + 'static>(reader: Option<R>, limit: usize) -> thread::JoinHandle<(String, bool)> {
+    thread::spawn(move || {
+        let mut buffer = Vec::new();
+
+        if let Some(reader) = reader {
+            let _ = reader.take(limit as u64 + 1).read_to_end(&mut buffer);
+        }
+
+        let truncated = buffer.len() > limit;
+        buffer.truncate(limit);
+
+        (String::from_utf8_lossy(&buffer).to_string(), truncated)
+    })
+}
+
+fn describe_status(status: &ExitStatus) -> String {
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::ExitStatusExt;
+
+        match status.signal() {
+            Some(libc::SIGKILL) => return "Killed".to_string(),
+            Some(libc::SIGXCPU) => return "Used too much CPU time".to_string(),
+            Some(libc::SIGXFSZ) => return "Wrote too large of a file".to_string(),
+            Some(libc::SIGSEGV) => return "Segmentation fault, possibly a stack overflow".to_string(),
+            Some(libc::SIGABRT) => return "Aborted, possibly out of memory".to_string(),
+            Some(libc::SIGPIPE) => return "Stopped after printing too much".to_string(),
+            Some(signal) => return format!("Killed by signal {}", signal),
+            None => {},
+        }
+    }
+
+    match status.code() {
+        Some(code) => format!("Exited with code {}", code),
+        None => "Exited".to_string(),
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn run_limited(stage: Stage, mut command: Command, limits: Limits, jail: &Jail) -> Result<Evaluation, String> {
+    sandbox(&mut command, &limits, jail);
+
+    let started = Instant::now();
+    let mut child = command
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|why| format!("Unable to start the sandbox, user namespaces might be disabled: {}", why))?;
+
+    let stdout = read_limited(child.stdout.take(), limits.output);
+    let stderr = read_limited(child.stderr.take(), limits.output);
+    let group = child.id() as libc::pid_t;
+
+    let mut timed_out = false;
+    let status = loop {
+        if let Some(status) = child.try_wait().map_err(|why| why.to_string())? {
+            break status;
+        }
+
+        if started.elapsed() > limits.time {
+            timed_out = true;
+            unsafe { libc::kill(-group, libc::SIGKILL) };
+
+            break child.wait().map_err(|why| why.to_string())?;
+        }
+
+        thread::sleep(POLL_INTERVAL);
+    };
+    let elapsed = started.elapsed();
+
+    // Anything left in the group could keep the pipes open
+    unsafe { libc::kill(-group, libc::SIGKILL) };
+
+    let (stdout, stdout_truncated) = stdout.join().unwrap_or_default();
+    let (stderr, stderr_truncated) = stderr.join().unwrap_or_default();
+
+    Ok(Evaluation {
+        stage,
+        success: status.success() && !timed_out,
+        status: if timed_out {
+            format!("Timed out after {}s", limits.time.as_secs())
+        } else {
+            describe_status(&status)
+        },
+        stdout,
+        stderr,
+        elapsed,
+        timed_out,
+        truncated: stdout_truncated || stderr_truncated,
+    })
+}
+
+#[cfg(not(target_os = "linux"))]
+fn run_limited(_stage: Stage, _command: Command, _limits: Limits, _jail: &Jail) -> Result<Evaluation, String> {
+    Err("Rust eval is only supported on Linux".to_string())
+}
+
+struct Toolchain {
+    sysroot: PathBuf,
+    lld:     bool,
+}
+
+// Since 1.90 x86_64 Linux links with a bundled lld, whose wrapper finds
+// itself through /proc. Takes the output of `rustc -vV`
+fn uses_lld(version: &str) -> bool {
+    let field = |name: &str| {
+        version
+            .lines()
+            .find_map(|line| line.strip_prefix(name))
+            .map(str::trim)
+            .unwrap_or_default()
+    };
+
+    let release = field("release:")
+        .split(|c: char| !c.is_ascii_digit())
+        .take(2)
+        .map(|part| part.parse::<u32>().unwrap_or_default())
+        .collect::<Vec<u32>>();
+
+    field("host:") == "x86_64-unknown-linux-gnu" && release >= vec![1, 90]
+}
+
+// The rustup proxy needs the user's environment and home directory to pick a
+// toolchain, so the real compiler is found before entering the sandbox
+fn find_toolchain(rustc: &str) -> Result<Toolchain, String> {
+    let run = |args: &[&str]| {
+        let output = Command::new(rustc)
+            .args(args)
+            .output()
+            .map_err(|why| format!("Unable to run rustc: {}", why))?;
+
+        if output.status.success() {
+            Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+        } else {
+            Err("Unable to find the Rust toolchain".to_string())
+        }
+    };
+
+    let sysroot = fs::canonicalize(run(&["--print", "sysroot"])?)
+        .map_err(|why| format!("Unable to find the Rust toolchain: {}", why))?;
+
+    Ok(Toolchain {
+        sysroot,
+        lld: uses_lld(&run(&["-vV"])?),
+    })
+}
+
+// Blocks until the snippet has compiled and finished running, so should be
+// run off the async runtime
+pub fn evaluate(code: &str, config: &RustEvalConfig) -> Result<Evaluation, String> {
+    let dir = TempDir(std::env::temp_dir().join(format!("inori-rust-{}", Uuid::new_v4())));
+    fs::create_dir(&dir.0).map_err(|why| format!("Unable to create a working directory: {}", why))?;
+
+    let toolchain = find_toolchain(&config.rustc)?;
+    let sysroot = &toolchain.sysroot;
+    let jail = build_jail(&dir.0, sysroot)?;
+
+    let work_dir = dir.0.join("root").join(WORK_DIR.trim_start_matches('/'));
+    fs::write(work_dir.join("main.rs"), wrap_snippet(code))
+        .map_err(|why| format!("Unable to save the code: {}", why))?;
+
+    let binary = Path::new(WORK_DIR).join("snippet");
+
+    // There's no /proc in the sandbox, so rustc can't find its own libraries
+    // or sysroot from where it's installed and has to be told
+    let mut rustc = Command::new(sysroot.join("bin").join("rustc"));
+    rustc
+        .env_clear()
+        .env("PATH", "/usr/local/bin:/usr/bin:/bin")
+        .env("TMPDIR", "/tmp")
+        .env("LD_LIBRARY_PATH", sysroot.join("lib"))
+        .arg("--sysroot")
+        .arg(sysroot)
+        .args(["--edition", &config.edition, "--color", "never"])
+        .args(["--crate-name", "snippet", "-o"])
+        .arg(&binary)
+        .arg("main.rs");
+
+    if toolchain.lld {
+        rustc.args(["-C", "linker-features=-lld"]);
+    }
+
+    let compiled = run_limited(
+        Stage::Compile,
+        rustc,
+        Limits {
+            time:      Duration::from_secs(config.compile_timeout),
+            memory:    COMPILE_MEMORY,
+            processes: None,
+            output:    config.output,
+        },
+        &jail,
+    )?;
+
+    if !compiled.success {
+        return Ok(compiled);
+    }
+
+    let mut program = Command::new(&binary);
+    program.env_clear();
+
+    run_limited(
+        Stage::Run,
+        program,
+        Limits {
+            time:      Duration::from_secs(config.timeout),
+            memory:    config.memory,
+            processes: Some(PROCESS_LIMIT),
+            output:    config.output,
+        },
+        &jail,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wrap_snippet() {
+        assert_eq!(
+            wrap_snippet("println!(\"{}\", 1 + 1);"),
+            "fn main() {\nprintln!(\"{}\", 1 + 1);\n}\n"
+        );
+
+        let program = "fn main() {\n    println!(\"Hi\");\n}";
+        assert_eq!(wrap_snippet(program), program);
+    }
+
+    #[test]
+    fn test_uses_lld() {
+        let version = |release: &str, host: &str| {
+            format!("rustc {}\nbinary: rustc\nhost: {}\nrelease: {}\n", release, host, release)
+        };
+
+        assert!(uses_lld(&version("1.95.0", "x86_64-unknown-linux-gnu")));
+        assert!(uses_lld(&version("1.90.0-nightly", "x86_64-unknown-linux-gnu")));
+        assert!(!uses_lld(&version("1.89.0", "x86_64-unknown-linux-gnu")));
+        assert!(!uses_lld(&version("1.95.0", "aarch64-unknown-linux-gnu")));
+    }
+}
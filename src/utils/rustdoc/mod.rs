@@ -0,0 +1,796 @@
+mod stringdex;
+
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    process::Command,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use once_cell::sync::Lazy;
+use regex::Regex;
+use reqwest::Url;
+use serde_json::Value;
+
+use self::stringdex::Index;
+use crate::utils::cache::get_cached;
+
+// Crates documented at doc.rust-lang.org rather than docs.rs
+const STD_CRATES: [&str; 5] = ["std", "core", "alloc", "proc_macro", "test"];
+const INDEX_TTL: Duration = Duration::from_secs(24 * 60 * 60);
+const DESCRIPTION_LIMIT: usize = 200;
+
+// Indexed by rustdoc's `ItemType`, which is what the search index stores
+const ITEM_TYPES: [&str; 26] = [
+    "mod",
+    "externcrate",
+    "import",
+    "struct",
+    "enum",
+    "fn",
+    "type",
+    "static",
+    "trait",
+    "impl",
+    "tymethod",
+    "method",
+    "structfield",
+    "variant",
+    "macro",
+    "primitive",
+    "associatedtype",
+    "constant",
+    "associatedconstant",
+    "union",
+    "foreigntype",
+    "keyword",
+    "opaque",
+    "attr",
+    "derive",
+    "traitalias",
+];
+
+// The sharded index numbers them differently, `existential` is renamed to
+// match `opaque` above
+const SHARDED_ITEM_TYPES: [&str; 28] = [
+    "keyword",
+    "primitive",
+    "mod",
+    "externcrate",
+    "import",
+    "struct",
+    "enum",
+    "fn",
+    "type",
+    "static",
+    "trait",
+    "impl",
+    "tymethod",
+    "method",
+    "structfield",
+    "variant",
+    "macro",
+    "associatedtype",
+    "constant",
+    "associatedconstant",
+    "union",
+    "foreigntype",
+    "opaque",
+    "attr",
+    "derive",
+    "traitalias",
+    "generic",
+    "attribute",
+];
+
+// Columns of the sharded index which items are built from
+const SHARDED_COLUMNS: [&str; 4] = ["name", "path", "entry", "desc"];
+
+static SEARCH_INDEX_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r#"[^"'\s]*search-index[^"'\s]*\.js"#).unwrap());
+static ROOT_PATH_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r#"data-root-path="([^"]*)""#).unwrap());
+static RESOURCE_SUFFIX_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r#"data-resource-suffix="([^"]*)""#).unwrap());
+static TAG_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r"</?[a-zA-Z][^>]*>").unwrap());
+
+// Parsing the std index takes a while so it's only done once
+static STD_INDEXES: Lazy<Mutex<HashMap<String, Arc<Vec<Item>>>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Item {
+    pub kind:        u8,
+    pub name:        String,
+    // Module the item is in, like `std::vec`
+    pub path:        String,
+    // Kind and name of the type or trait for methods, fields and variants
+    pub parent:      Option<(u8, String)>,
+    pub description: String,
+    pub signature:   Option<String>,
+}
+
+impl Item {
+    pub fn kind_name(&self) -> &'static str {
+        match ITEM_TYPES.get(self.kind as usize).copied().unwrap_or_default() {
+            "mod" => "module",
+            "externcrate" => "extern crate",
+            "fn" => "function",
+            "type" => "type alias",
+            "tymethod" | "method" => "method",
+            "structfield" => "field",
+            "associatedtype" => "associated type",
+            "associatedconstant" => "associated constant",
+            "foreigntype" => "foreign type",
+            "opaque" => "opaque type",
+            "attr" => "attribute macro",
+            "derive" => "derive macro",
+            "traitalias" => "trait alias",
+            kind => kind,
+        }
+    }
+
+    pub fn full_path(&self) -> String {
+        match &self.parent {
+            Some((_, parent)) => format!("{}::{}::{}", self.path, parent, self.name),
+            None => format!("{}::{}", self.path, self.name),
+        }
+    }
+
+    // Relative to the root of the docs, mirroring how rustdoc's search
+    // builds links
+    pub fn doc_path(&self) -> String {
+        let dir = self.path.replace("::", "/");
+        let kind = ITEM_TYPES.get(self.kind as usize).copied().unwrap_or_default();
+
+        match &self.parent {
+            Some((parent_kind, parent)) => {
+                let parent_kind = ITEM_TYPES.get(*parent_kind as usize).copied().unwrap_or_default();
+
+                format!("{}/{}.{}.html#{}.{}", dir, parent_kind, parent, kind, self.name)
+            },
+            None if kind == "mod" => format!("{}/{}/index.html", dir, self.name),
+            None => format!("{}/{}.{}.html", dir, kind, self.name),
+        }
+    }
+
+    pub fn url(&self) -> String {
+        let krate = self.path.split("::").next().unwrap_or_default();
+
+        if is_std(krate) {
+            format!("https://doc.rust-lang.org/{}", self.doc_path())
+        } else {
+            format!("https://docs.rs/{}/latest/{}", krate, self.doc_path())
+        }
+    }
+}
+
+pub fn is_std(krate: &str) -> bool {
+    STD_CRATES.contains(&krate)
+}
+
+fn item_type(value: &Value) -> u8 {
+    value.as_u64().unwrap_or(u64::MAX) as u8
+}
+
+// Descriptions are HTML in newer indexes
+fn clean_description(description: &str) -> String {
+    let description = description.replace("<code>", "`").replace("</code>", "`");
+    let description = TAG_REGEX
+        .replace_all(&description, "")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&amp;", "&");
+
+    if description.chars().count() > DESCRIPTION_LIMIT {
+        format!("{}...", description.chars().take(DESCRIPTION_LIMIT).collect::<String>())
+    } else {
+        description
+    }
+}
+
+// Types are stored as a name or `[name, [generics]]`, optionally paired
+// with their kind
+fn render_type(value: &Value) -> Option<String> {
+    match value {
+        Value::String(name) => Some(name.clone()),
+        Value::Array(parts) => match (parts.first()?, parts.get(1)) {
+            (Value::String(name), Some(Value::Array(generics))) if !generics.is_empty() => {
+                let generics = generics.iter().filter_map(render_type).collect::<Vec<String>>();
+
+                Some(format!("{}<{}>", name, generics.join(", ")))
+            },
+            (first, _) => render_type(first),
+        },
+        _ => None,
+    }
+}
+
+// Only older indexes store function types as JSON, newer ones use a
+// compact string encoding which isn't worth decoding for a preview
+fn render_signature(name: &str, value: &Value) -> Option<String> {
+    let parts = value.as_array()?;
+    let inputs = parts
+        .first()?
+        .as_array()?
+        .iter()
+        .filter_map(render_type)
+        .collect::<Vec<String>>();
+    let output = match parts.get(1) {
+        Some(Value::Array(outputs)) if outputs.iter().all(Value::is_array) => {
+            let outputs = outputs.iter().filter_map(render_type).collect::<Vec<String>>();
+
+            format!(" -> ({})", outputs.join(", "))
+        },
+        Some(output) => render_type(output).map(|output| format!(" -> {}", output)).unwrap_or_default(),
+        None => String::new(),
+    };
+
+    Some(format!("fn {}({}){}", name, inputs.join(", "), output))
+}
+
+// Indexes before Rust 1.52 store a row per item
+fn parse_rows(data: &Value) -> Option<Vec<Item>> {
+    let parents = data.get("p").and_then(Value::as_array);
+    let mut path = String::new();
+
+    data.get("i")?
+        .as_array()?
+        .iter()
+        .map(|row| {
+            let row = row.as_array()?;
+            let name = row.get(1)?.as_str()?.to_string();
+
+            if let Some(row_path) = row.get(2).and_then(Value::as_str).filter(|path| !path.is_empty()) {
+                path = row_path.to_string();
+            }
+
+            Some(Item {
+                kind: item_type(row.first()?),
+                signature: row.get(5).and_then(|value| render_signature(&name, value)),
+                name,
+                path: path.clone(),
+                parent: row
+                    .get(4)
+                    .and_then(Value::as_u64)
+                    .and_then(|idx| parents?.get(idx as usize))
+                    .and_then(parent_of),
+                description: clean_description(row.get(3).and_then(Value::as_str).unwrap_or_default()),
+            })
+        })
+        .collect()
+}
+
+fn parent_of(value: &Value) -> Option<(u8, String)> {
+    let parent = value.as_array()?;
+
+    Some((item_type(parent.first()?), parent.get(1)?.as_str()?.to_string()))
+}
+
+// Later indexes store a column per field, paths are only written when they
+// change
+fn parse_columns(data: &Value) -> Option<Vec<Item>> {
+    let names = data.get("n")?.as_array()?;
+    let kinds = match data.get("t")? {
+        // Newer indexes write one letter per item, starting from `A`
+        Value::String(kinds) => kinds
+            .chars()
+            .map(|c| (c as u32).saturating_sub('A' as u32) as u8)
+            .collect::<Vec<u8>>(),
+        Value::Array(kinds) => kinds.iter().map(item_type).collect::<Vec<u8>>(),
+        _ => return None,
+    };
+
+    let mut paths = vec![None; names.len()];
+    match data.get("q") {
+        Some(Value::Array(columns)) if columns.iter().all(Value::is_string) => {
+            for (idx, path) in columns.iter().enumerate() {
+                if let Some(path) = path.as_str().filter(|path| !path.is_empty()) {
+                    *paths.get_mut(idx)? = Some(path.to_string());
+                }
+            }
+        },
+        // Or as `[index, path]` pairs
+        Some(Value::Array(pairs)) => {
+            for pair in pairs {
+                let idx = pair.get(0)?.as_u64()? as usize;
+                *paths.get_mut(idx)? = Some(pair.get(1)?.as_str()?.to_string());
+            }
+        },
+        _ => {},
+    }
+
+    let descriptions = data.get("d").and_then(Value::as_array);
+    let parent_ids = data.get("i").and_then(Value::as_array);
+    let functions = data.get("f").and_then(Value::as_array);
+    let parents = data.get("p").and_then(Value::as_array);
+
+    let mut path = String::new();
+    let mut items = Vec::with_capacity(names.len());
+
+    for (idx, name) in names.iter().enumerate() {
+        let name = name.as_str()?.to_string();
+        if let Some(new_path) = &paths[idx] {
+            path = new_path.clone();
+        }
+
+        // Parents are 1 based so 0 can mean none
+        let parent = parent_ids
+            .and_then(|ids| ids.get(idx)?.as_u64())
+            .filter(|id| *id > 0)
+            .and_then(|id| parents?.get(id as usize - 1))
+            .and_then(parent_of);
+
+        items.push(Item {
+            kind: *kinds.get(idx)?,
+            signature: functions
+                .and_then(|functions| functions.get(idx))
+                .and_then(|value| render_signature(&name, value)),
+            name,
+            path: path.clone(),
+            parent,
+            description: clean_description(
+                descriptions
+                    .and_then(|descriptions| descriptions.get(idx)?.as_str())
+                    .unwrap_or_default(),
+            ),
+        });
+    }
+
+    Some(items)
+}
+
+// The index is JSON inside a quoted JavaScript string, `\x` escapes are
+// bytes in the sharded index
+fn unescape_js(text: &str) -> Vec<u8> {
+    let mut unescaped = Vec::with_capacity(text.len());
+    let mut chars = text.chars();
+
+    while let Some(c) = chars.next() {
+        let c = match c {
+            '\\' => match chars.next() {
+                Some('x') => {
+                    let hex = chars.by_ref().take(2).collect::<String>();
+                    if let Ok(byte) = u8::from_str_radix(&hex, 16) {
+                        unescaped.push(byte);
+                    }
+
+                    continue;
+                },
+                // Line continuation
+                Some('\n') | None => continue,
+                Some(c) => c,
+            },
+            c => c,
+        };
+
+        unescaped.extend_from_slice(c.encode_utf8(&mut [0; 4]).as_bytes());
+    }
+
+    unescaped
+}
+
+// Returns the items of every crate in a `search-index.js`
+pub fn parse_index(js: &str) -> Result<HashMap<String, Vec<Item>>, String> {
+    let start = js.find("JSON.parse('").ok_or("Unsupported search index format")? + 12;
+    let end = js
+        .rfind("')")
+        .filter(|end| *end >= start)
+        .ok_or("Unsupported search index format")?;
+
+    let index = serde_json::from_slice::<Value>(&unescape_js(&js[start..end]))
+        .map_err(|why| format!("Unable to parse the search index: {}", why))?;
+
+    // Older indexes are an object of crates, newer ones are `[name, data]`
+    // pairs passed to a Map
+    let crates = match index {
+        Value::Object(crates) => crates.into_iter().collect::<Vec<(String, Value)>>(),
+        Value::Array(pairs) => pairs
+            .into_iter()
+            .filter_map(|pair| match pair {
+                Value::Array(mut pair) if pair.len() == 2 => {
+                    let data = pair.pop()?;
+
+                    Some((pair.pop()?.as_str()?.to_string(), data))
+                },
+                _ => None,
+            })
+            .collect(),
+        _ => return Err("Unsupported search index format".to_string()),
+    };
+
+    crates
+        .into_iter()
+        .map(|(name, data)| {
+            let has_rows = data
+                .get("i")
+                .and_then(Value::as_array)
+                .map_or(false, |rows| rows.first().map_or(false, Value::is_array));
+
+            let items = if has_rows {
+                parse_rows(&data)
+            } else {
+                parse_columns(&data)
+            };
+
+            items
+                .map(|items| (name.clone(), items))
+                .ok_or_else(|| format!("Unable to read the search index for `{}`", name))
+        })
+        .collect()
+}
+
+fn sharded_item_type(value: &Value) -> Option<u8> {
+    let kind = SHARDED_ITEM_TYPES.get(value.as_u64()? as usize)?;
+
+    ITEM_TYPES.iter().position(|item_type| item_type == kind).map(|idx| idx as u8)
+}
+
+// Returns the items of every crate in a sharded index, `read` is given the
+// path of each shard relative to the `search.index` directory
+pub fn parse_sharded_index(
+    index: &Index,
+    mut read: impl FnMut(&str) -> Result<String, String>,
+) -> Result<HashMap<String, Vec<Item>>, String> {
+    let mut columns = Vec::with_capacity(SHARDED_COLUMNS.len());
+    for column in SHARDED_COLUMNS.iter() {
+        let shards = index
+            .shards(column)?
+            .iter()
+            .map(|path| read(path))
+            .collect::<Result<Vec<String>, String>>()?;
+
+        columns.push(index.rows(column, &shards)?);
+    }
+
+    let (names, paths, entries, descriptions) = (&columns[0], &columns[1], &columns[2], &columns[3]);
+    let text = |rows: &[Vec<u8>], id: usize| {
+        rows.get(id)
+            .map(|row| String::from_utf8_lossy(row).into_owned())
+            .unwrap_or_default()
+    };
+    let json = |rows: &[Vec<u8>], id: usize| serde_json::from_slice::<Value>(rows.get(id)?).ok();
+    // Links to other rows are 1 based so 0 can mean none
+    let link = |value: Option<&Value>| value?.as_u64()?.checked_sub(1).map(|id| id as usize);
+
+    let mut crates = HashMap::<String, Vec<Item>>::new();
+    for id in 0..entries.len() {
+        // Entries are `[crate, kind, module, exact module, parent, ...]`,
+        // rows without one are only referenced by other items
+        let entry = match json(entries, id) {
+            Some(Value::Array(entry)) => entry,
+            _ => continue,
+        };
+
+        // Crate roots have no module
+        let (kind, module) = match (entry.get(1).and_then(sharded_item_type), link(entry.get(2))) {
+            (Some(kind), Some(module)) => (kind, module),
+            _ => continue,
+        };
+
+        // Modules store the path of the module they're in
+        let path = match json(paths, module).as_ref().and_then(|path| path.get(1)?.as_str()) {
+            Some(path) if !path.is_empty() => format!("{}::{}", path, text(names, module)),
+            _ => text(names, module),
+        };
+
+        let parent = link(entry.get(4)).and_then(|parent| {
+            let kind = sharded_item_type(json(paths, parent)?.get(0)?)?;
+
+            Some((kind, text(names, parent)))
+        });
+
+        let krate = entry
+            .first()
+            .and_then(Value::as_u64)
+            .map(|krate| text(names, krate as usize))
+            .unwrap_or_default();
+
+        crates.entry(krate).or_default().push(Item {
+            kind,
+            name: text(names, id),
+            path,
+            parent,
+            description: clean_description(&text(descriptions, id)),
+            signature: None,
+        });
+    }
+
+    if crates.is_empty() {
+        return Err("The search index has no items".to_string());
+    }
+
+    Ok(crates)
+}
+
+// Searches by name, `Vec::push` or `vec push` narrows it down to items
+// whose path contains every other part
+pub fn search<'a>(items: &'a [Item], query: &str, limit: usize) -> Vec<&'a Item> {
+    let query = query.to_lowercase();
+    let mut parts = query
+        .split(|c: char| c == ':' || c.is_whitespace())
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<&str>>();
+
+    let name = match parts.pop() {
+        Some(name) => name,
+        None => return Vec::new(),
+    };
+
+    let mut matches = items
+        .iter()
+        .filter(|item| item.kind != 2 && item.kind != 9)
+        .filter_map(|item| {
+            let item_name = item.name.to_lowercase();
+            let score = if item_name == name {
+                0
+            } else if item_name.starts_with(name) {
+                1
+            } else if item_name.contains(name) {
+                2
+            } else {
+                return None;
+            };
+
+            let full_path = item.full_path().to_lowercase();
+            if !parts.iter().all(|part| full_path.contains(part)) {
+                return None;
+            }
+
+            // Items on a type rank below the type itself
+            Some(((score, item.parent.is_some(), full_path.len()), item))
+        })
+        .collect::<Vec<_>>();
+
+    matches.sort_by(|(a, a_item), (b, b_item)| a.cmp(b).then_with(|| a_item.name.cmp(&b_item.name)));
+    matches.into_iter().take(limit).map(|(_, item)| item).collect()
+}
+
+fn find_file(dir: &Path, prefix: &str) -> Option<PathBuf> {
+    std::fs::read_dir(dir)
+        .ok()?
+        .filter_map(Result::ok)
+        .map(|entry| entry.path())
+        .find(|path| {
+            path.file_name()
+                .and_then(|name| name.to_str())
+                .map_or(false, |name| name.starts_with(prefix) && name.ends_with(".js"))
+        })
+}
+
+// `rustup doc --path` gives the location of the installed docs
+fn local_index() -> Option<HashMap<String, Vec<Item>>> {
+    let output = Command::new("rustup").args(["doc", "--path"]).output().ok()?;
+    let index = PathBuf::from(String::from_utf8(output.stdout).ok()?.trim());
+    let docs = index.parent()?;
+
+    if let Some(file) = find_file(docs, "search-index") {
+        return parse_index(&std::fs::read_to_string(file).ok()?).ok();
+    }
+
+    let dir = docs.join("search.index");
+    let index = Index::parse(&std::fs::read_to_string(find_file(&dir, "root")?).ok()?).ok()?;
+
+    parse_sharded_index(&index, |path| {
+        std::fs::read_to_string(dir.join(path)).map_err(|why| why.to_string())
+    })
+    .ok()
+}
+
+// Finds the search index linked from a docs page and downloads it
+async fn remote_index(page: &str) -> Result<HashMap<String, Vec<Item>>, String> {
+    let html = get_cached(page, INDEX_TTL).await?;
+    let page = Url::parse(page).map_err(|_| "Invalid docs URL".to_string())?;
+    let join = |base: &Url, path: &str| base.join(path).map_err(|_| "Invalid search index URL".to_string());
+
+    if let Some(path) = SEARCH_INDEX_REGEX.find(&html) {
+        return parse_index(&get_cached(join(&page, path.as_str())?.as_str(), INDEX_TTL).await?);
+    }
+
+    // Newer docs load `search.index/root<suffix>.js` from the docs root
+    // instead of linking it
+    let root_path = ROOT_PATH_REGEX
+        .captures(&html)
+        .and_then(|captures| captures.get(1))
+        .ok_or("Couldn't find a search index for these docs")?
+        .as_str();
+    let suffix = RESOURCE_SUFFIX_REGEX
+        .captures(&html)
+        .and_then(|captures| captures.get(1))
+        .map_or("", |suffix| suffix.as_str());
+
+    let dir = join(&page, &format!("{}search.index/", root_path))?;
+    let root = get_cached(join(&dir, &format!("root{}.js", suffix))?.as_str(), INDEX_TTL).await?;
+    let index = Index::parse(&root)?;
+
+    // Shards are named by their hash so they're safe to cache
+    let mut shards = HashMap::new();
+    for column in SHARDED_COLUMNS.iter() {
+        for path in index.shards(column)? {
+            let shard = get_cached(join(&dir, &path)?.as_str(), INDEX_TTL).await?;
+            shards.insert(path, shard);
+        }
+    }
+
+    parse_sharded_index(&index, |path| {
+        shards
+            .remove(path)
+            .ok_or_else(|| format!("Missing search index shard `{}`", path))
+    })
+}
+
+pub async fn load_index(krate: &str) -> Result<Arc<Vec<Item>>, String> {
+    let krate = krate.replace('-', "_");
+
+    if !is_std(&krate) {
+        return remote_index(&format!("https://docs.rs/{}/latest/{}/", krate, krate))
+            .await?
+            .remove(&krate)
+            .map(Arc::new)
+            .ok_or_else(|| format!("`{}` isn't in its own search index", krate));
+    }
+
+    let cached = STD_INDEXES.lock().unwrap().get(&krate).cloned();
+    if let Some(items) = cached {
+        return Ok(items);
+    }
+
+    let parsed = match tokio::task::spawn_blocking(local_index).await.ok().flatten() {
+        Some(parsed) => parsed,
+        None => remote_index("https://doc.rust-lang.org/stable/std/").await?,
+    };
+
+    let mut indexes = STD_INDEXES.lock().unwrap();
+    for (name, items) in parsed {
+        indexes.insert(name, Arc::new(items));
+    }
+
+    indexes
+        .get(&krate)
+        .cloned()
+        .ok_or_else(|| format!("`{}` isn't in the search index", krate))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ROWS_INDEX: &str = "var searchIndex = JSON.parse('{\\\n\"std\":{\"doc\":\"The Rust Standard \
+                              Library\",\"i\":[[3,\"Vec\",\"std::vec\",\"A contiguous growable array type.\",null,\
+                              null],[11,\"push\",\"\",\"Appends an element to the back.\",0,[[[\"vec\",3],[\"t\",\
+                              15]],null]],[5,\"swap\",\"std::mem\",\"Swaps two values, it\\'s \
+                              <code>unsafe</code>-free.\",null,[[[\"t\",15],[\"t\",15]]]]],\"p\":[[3,\"Vec\"]]}\\\n}');\
+                              \ninitSearch(searchIndex);";
+
+    const COLUMNS_INDEX: &str =
+        "var searchIndex = new Map(JSON.parse('[[\"serde\",{\"t\":\"IKIA\",\"n\":[\"Serialize\",\
+                                 \"serialize\",\"Serializer\",\"ser\"],\"q\":[[0,\"serde\"],[3,\"serde\"]],\"i\":[0,1,\
+                                 0,0],\"p\":[[8,\"Serialize\"]]}]]'));";
+
+    // Generated by rustdoc 1.95 for a crate with a `Stack` struct, a `Show`
+    // trait and a `util` module, trimmed to the columns which are read
+    const SHARDED_ROOT: &str = "rr_('{\"name\":{\"N\":\"B`\",\"E\":\"OjAAAAAAAAA=\",\"H\":\"GNCP9uLI\"},\"path\":{\"N\
+                               \":\"Ad\",\"E\":\"OjAAAAEAAAAAAAsAEAAAAAgACQAKAAsADAAQABIAFwAaABsAHAAfAA==\",\"H\":\"\
+                               Jan2ZqpL\"},\"entry\":{\"N\":\"o\",\"E\":\"OzAAAAEAABAABQAAAAYADgABABMAAwAYAAEAHQABAA\
+                               ==\",\"H\":\"cSzIzRml\"},\"desc\":{\"N\":\"i\",\"E\":\"OzAAAAEAABYAAwAAAAYADgACABMADA\
+                               A=\",\"H\":\"ibyu+y8v\"}}')";
+
+    const SHARDED_SHARDS: [(&str, &str); 4] = [
+        (
+            "name/18d08ff6e2c8.js",
+            "rd_(\"b()bu8cAnycVeccmutdFromdIntodShowdfromdintodpushdshowdswapdtinydunitdutil0eStackeitemsfBorrowfResu\
+            ltfStringfTypeIdfborrowgTryFromgTryIntogtype_idhtry_fromhtry_intoiBorrowMutireferencejborrow_mut\")",
+        ),
+        (
+            "path/25a9f666aa4b.js",
+            "rd_(\"f[1,\\\"\\\"]0A`[10,\\\"core::any\\\"]Am[5,\\\"alloc::vec\\\",\\\"alloc::vec\\\"]f[0,\\\"\\\"]Ad[1\
+            0,\\\"core::convert\\\"]0Ab[10,\\\"tiny\\\",\\\"tiny\\\"]f[3,\\\"\\\"]6j[2,\\\"tiny\\\"]Aa[5,\\\"tiny\\\
+            \",\\\"tiny\\\"]Ac[10,\\\"core::borrow\\\"]Ba[6,\\\"core::result\\\",\\\"core::result\\\"]Bc[5,\\\"alloc\
+            ::string\\\",\\\"alloc::string\\\"]Ak[5,\\\"core::any\\\",\\\"core::any\\\"]883<\")",
+        ),
+        (
+            "entry/712cc8cd19a5.js",
+            "rd_(\"Ad[13,10,14,0,0,0,0,0]Af[13,13,14,14,18,6,0,0]Af[13,13,14,14,18,7,0,0]Af[13,13,14,14,18,0,0,0]Ae[1\
+            3,12,14,14,8,0,0,0]Ac[13,7,16,0,0,0,0,0]Ab[13,3,0,0,0,0,0,0]Ac[13,2,14,0,0,0,0,0]Ac[13,5,14,0,0,0,0,0]Af\
+            [13,14,14,14,18,0,0,0]Ag[13,13,14,14,18,20,0,0]Af[13,13,14,14,18,3,0,0]Ag[13,13,14,14,18,25,0,0]Ag[13,13\
+            ,14,14,18,26,0,0]Ag[13,13,14,14,18,30,0,0]\")",
+        ),
+        (
+            "desc/89bcaefb2f2f.js",
+            "rd_(\"AjThings which can be shown.AoReturns the argument unchanged.BaCalls <code>U::from(self)</code>.An\
+            Pushes an item onto the stack.A`Shows the value.AaSwaps two values.mA tiny crate.ClA contiguous growable s\
+            tack, it\\xe2\\x80\\x99s <code>Vec</code> backed.AgThe items on the stack.\")",
+        ),
+    ];
+
+    #[test]
+    fn test_parse_rows() {
+        let index = parse_index(ROWS_INDEX).unwrap();
+        let items = &index["std"];
+
+        assert_eq!(items.len(), 3);
+        assert_eq!(items[1], Item {
+            kind:        11,
+            name:        "push".to_string(),
+            path:        "std::vec".to_string(),
+            parent:      Some((3, "Vec".to_string())),
+            description: "Appends an element to the back.".to_string(),
+            signature:   Some("fn push(vec, t)".to_string()),
+        });
+        assert_eq!(items[2].description, "Swaps two values, it's `unsafe`-free.");
+        assert_eq!(items[2].signature, Some("fn swap(t, t)".to_string()));
+
+        assert_eq!(items[0].url(), "https://doc.rust-lang.org/std/vec/struct.Vec.html");
+        assert_eq!(items[1].url(), "https://doc.rust-lang.org/std/vec/struct.Vec.html#method.push");
+        assert_eq!(items[1].full_path(), "std::vec::Vec::push");
+    }
+
+    #[test]
+    fn test_parse_columns() {
+        let index = parse_index(COLUMNS_INDEX).unwrap();
+        let items = &index["serde"];
+
+        assert_eq!(items[1].kind_name(), "method");
+        assert_eq!(items[1].full_path(), "serde::Serialize::serialize");
+        assert_eq!(
+            items[1].url(),
+            "https://docs.rs/serde/latest/serde/trait.Serialize.html#tymethod.serialize"
+        );
+        assert_eq!(items[3].url(), "https://docs.rs/serde/latest/serde/ser/index.html");
+
+        assert!(parse_index("var searchIndex = {};").is_err());
+    }
+
+    #[test]
+    fn test_parse_sharded_index() {
+        let shards = SHARDED_SHARDS.iter().copied().collect::<HashMap<&str, &str>>();
+        let index = Index::parse(SHARDED_ROOT).unwrap();
+        let parsed = parse_sharded_index(&index, |path| Ok(shards[path].to_string())).unwrap();
+        let items = &parsed["tiny"];
+
+        let push = items.iter().find(|item| item.name == "push").unwrap();
+        assert_eq!(push, &Item {
+            kind:        11,
+            name:        "push".to_string(),
+            path:        "tiny".to_string(),
+            parent:      Some((3, "Stack".to_string())),
+            description: "Pushes an item onto the stack.".to_string(),
+            signature:   None,
+        });
+        assert_eq!(push.url(), "https://docs.rs/tiny/latest/tiny/struct.Stack.html#method.push");
+
+        let names = |query: &str| {
+            search(items, query, 5)
+                .iter()
+                .map(|item| item.full_path())
+                .collect::<Vec<String>>()
+        };
+
+        assert_eq!(names("util swap"), vec!["tiny::util::swap"]);
+        assert_eq!(names("stack"), vec!["tiny::Stack"]);
+        assert_eq!(
+            search(items, "Stack", 1)[0].description,
+            "A contiguous growable stack, it\u{2019}s `Vec` backed."
+        );
+
+        assert!(parse_sharded_index(&index, |path| Err(format!("No {}", path))).is_err());
+        assert!(Index::parse("rr_('{}')").unwrap().shards("name").is_err());
+    }
+
+    #[test]
+    fn test_search() {
+        let index = parse_index(ROWS_INDEX).unwrap();
+        let items = &index["std"];
+
+        let names = |query: &str| {
+            search(items, query, 5)
+                .iter()
+                .map(|item| item.full_path())
+                .collect::<Vec<String>>()
+        };
+
+        assert_eq!(names("vec"), vec!["std::vec::Vec"]);
+        assert_eq!(names("Vec::push"), vec!["std::vec::Vec::push"]);
+        assert_eq!(names("mem swap"), vec!["std::mem::swap"]);
+        assert!(names("vec swap").is_empty());
+    }
+}
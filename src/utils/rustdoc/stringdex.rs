@@ -0,0 +1,314 @@
+use std::collections::{HashMap, HashSet};
+
+use serde_json::Value;
+
+use super::unescape_js;
+
+const UNSUPPORTED: &str = "Unsupported search index format";
+
+// Newer rustdoc versions split the search index into a `search.index/root.js`
+// which describes every column, and files of rows per column named by their
+// hash.
+// Rows are shared between columns, so row 5 of `name` and `desc` are the
+// same item
+pub struct Index {
+    columns: HashMap<String, Column>,
+}
+
+struct Column {
+    // Non empty rows in each file
+    counts: Vec<usize>,
+    hashes: Vec<String>,
+    empty:  HashSet<u32>,
+}
+
+impl Index {
+    pub fn parse(js: &str) -> Result<Self, String> {
+        let start = js.find("rr_('").ok_or(UNSUPPORTED)? + 5;
+        let end = js.rfind("')").filter(|end| *end >= start).ok_or(UNSUPPORTED)?;
+
+        let root = serde_json::from_slice::<Value>(&unescape_js(&js[start..end]))
+            .map_err(|why| format!("Unable to parse the search index: {}", why))?;
+
+        let columns = root
+            .as_object()
+            .ok_or(UNSUPPORTED)?
+            .iter()
+            // Columns without counts are search trees rather than data
+            .filter(|(_, column)| column.get("N").is_some())
+            .map(|(name, column)| {
+                Column::parse(column)
+                    .map(|column| (name.clone(), column))
+                    .ok_or_else(|| format!("Unable to read the `{}` column of the search index", name))
+            })
+            .collect::<Result<HashMap<String, Column>, String>>()?;
+
+        Ok(Self {
+            columns,
+        })
+    }
+
+    fn column(&self, name: &str) -> Result<&Column, String> {
+        self.columns
+            .get(name)
+            .ok_or_else(|| format!("The search index has no `{}` column", name))
+    }
+
+    // Files holding a column, relative to the `search.index` directory
+    pub fn shards(&self, column: &str) -> Result<Vec<String>, String> {
+        Ok(self
+            .column(column)?
+            .hashes
+            .iter()
+            .map(|hash| format!("{}/{}.js", column, hash))
+            .collect())
+    }
+
+    // `shards` are the contents of the files from `shards`, in the same order
+    pub fn rows(&self, column: &str, shards: &[String]) -> Result<Vec<Vec<u8>>, String> {
+        let column = self.column(column)?;
+        if shards.len() != column.counts.len() {
+            return Err("Wrong number of search index shards".to_string());
+        }
+
+        let mut rows = Vec::new();
+        for (count, shard) in column.counts.iter().zip(shards) {
+            let mut values = parse_shard(shard).ok_or("Unable to read a search index shard")?.into_iter();
+
+            for _ in 0..*count {
+                while column.empty.contains(&(rows.len() as u32)) {
+                    rows.push(Vec::new());
+                }
+
+                rows.push(values.next().ok_or("Search index shard is missing rows")?);
+            }
+        }
+
+        Ok(rows)
+    }
+}
+
+impl Column {
+    fn parse(column: &Value) -> Option<Self> {
+        let counts = column.get("N")?.as_str()?.as_bytes();
+        let mut idx = 0;
+        let mut parsed_counts = Vec::new();
+        while idx < counts.len() {
+            parsed_counts.push(read_number(counts, &mut idx)?);
+        }
+
+        // Hashes are 6 bytes each, written as hex in file names
+        let hashes = base64::decode(column.get("H")?.as_str()?).ok()?;
+        let hashes = hashes
+            .chunks(6)
+            .map(|hash| hash.iter().map(|byte| format!("{:02x}", byte)).collect::<String>())
+            .collect::<Vec<String>>();
+
+        if hashes.len() != parsed_counts.len() {
+            return None;
+        }
+
+        Some(Self {
+            counts: parsed_counts,
+            hashes,
+            empty: parse_bitmap(&base64::decode(column.get("E")?.as_str()?).ok()?)?,
+        })
+    }
+}
+
+// Numbers are written 4 bits per byte, the last byte is 96 or above
+fn read_number(bytes: &[u8], idx: &mut usize) -> Option<usize> {
+    let mut number = 0usize;
+
+    loop {
+        let byte = *bytes.get(*idx)?;
+        *idx += 1;
+        number = number << 4 | (byte & 0x0F) as usize;
+
+        if byte >= 96 {
+            return Some(number);
+        }
+    }
+}
+
+fn u16_at(bytes: &[u8], idx: usize) -> Option<u32> {
+    Some(*bytes.get(idx)? as u32 | (*bytes.get(idx + 1)? as u32) << 8)
+}
+
+fn u32_at(bytes: &[u8], idx: usize) -> Option<u32> {
+    Some(u16_at(bytes, idx)? | u16_at(bytes, idx + 2)? << 16)
+}
+
+// Sets of rows are roaring bitmaps, with a few shorter forms for small sets
+fn parse_bitmap(bytes: &[u8]) -> Option<HashSet<u32>> {
+    let mut set = HashSet::new();
+    let tag = match bytes.first() {
+        Some(tag) if *tag != 0 => *tag,
+        _ => return Some(set),
+    };
+    let len = (tag & 0x0F) as u32;
+
+    match tag {
+        // A value followed by two or one byte deltas
+        0xF1..=0xFF | 0xD1..=0xE0 => {
+            let mut value = u32_at(bytes, 1)?;
+            set.insert(value);
+
+            let mut idx = 5;
+            for _ in 1..len {
+                if tag > 0xF0 {
+                    value = value.checked_add(u16_at(bytes, idx)?)?;
+                    idx += 2;
+                } else {
+                    value = value.checked_add(*bytes.get(idx)? as u32)?;
+                    idx += 1;
+                }
+
+                set.insert(value);
+            }
+        },
+        // A single run of values
+        0xE1..=0xF0 => {
+            let start = u32_at(bytes, 1)?;
+            set.extend(start..start.checked_add(len)?);
+        },
+        // A list of values
+        0x01..=0x39 => {
+            for idx in 0..tag as usize {
+                set.insert(u32_at(bytes, 1 + idx * 4)?);
+            }
+        },
+        // The standard roaring format, `0x3B` has run containers
+        0x3A | 0x3B => {
+            let has_runs = tag == 0x3B;
+            let (size, mut idx) = if has_runs {
+                (u16_at(bytes, 2)? as usize + 1, 4)
+            } else {
+                (u32_at(bytes, 4)? as usize, 8)
+            };
+
+            let runs = if has_runs {
+                let runs = bytes.get(idx..idx + ((size + 7) >> 3))?;
+                idx += (size + 7) >> 3;
+                runs
+            } else {
+                &[]
+            };
+
+            let headers = bytes.get(idx..idx + size * 4)?;
+            idx += size * 4;
+
+            // Offsets of each container, which aren't needed when reading
+            // them all
+            if !has_runs || size >= 4 {
+                idx += size * 4;
+            }
+
+            for container in 0..size {
+                let key = u16_at(headers, container * 4)? << 16;
+                let cardinality = u16_at(headers, container * 4 + 2)? as usize + 1;
+
+                if matches!(runs.get(container / 8), Some(bits) if bits & (1 << (container % 8)) != 0) {
+                    let count = u16_at(bytes, idx)?;
+                    idx += 2;
+
+                    for _ in 0..count {
+                        let start = key | u16_at(bytes, idx)?;
+                        set.extend(start..=start + u16_at(bytes, idx + 2)?);
+                        idx += 4;
+                    }
+                } else if cardinality >= 4096 {
+                    let bits = bytes.get(idx..idx + 8192)?;
+                    set.extend(
+                        (0..65536u32)
+                            .filter(|value| bits[*value as usize / 8] & (1 << (value % 8)) != 0)
+                            .map(|value| key | value),
+                    );
+                    idx += 8192;
+                } else {
+                    for value in 0..cardinality {
+                        set.insert(key | u16_at(bytes, idx + value * 2)?);
+                    }
+                    idx += cardinality * 2;
+                }
+            }
+        },
+        _ => return None,
+    }
+
+    Some(set)
+}
+
+// Shards are `rd_("...")` with a byte per character, or `rb_("...")` in
+// base64. Values are length prefixed, `0` to `?` repeat one of the last
+// 16 values
+fn parse_shard(js: &str) -> Option<Vec<Vec<u8>>> {
+    let js = js.trim().trim_end_matches(';');
+    let data = js.get(5..)?.strip_suffix("\")")?;
+    let bytes = match js.get(..5)? {
+        "rd_(\"" => unescape_js(data),
+        "rb_(\"" => base64::decode(data).ok()?,
+        _ => return None,
+    };
+
+    let mut values = Vec::new();
+    let mut recent = Vec::<Vec<u8>>::new();
+    let mut idx = 0;
+
+    while idx < bytes.len() {
+        if (b'0'..=b'?').contains(&bytes[idx]) {
+            values.push(recent.get((bytes[idx] - b'0') as usize)?.clone());
+            idx += 1;
+            continue;
+        }
+
+        let len = read_number(&bytes, &mut idx)?;
+        let value = bytes.get(idx..idx.checked_add(len)?)?.to_vec();
+        idx += len;
+
+        recent.insert(0, value.clone());
+        recent.truncate(16);
+        values.push(value);
+    }
+
+    Some(values)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_bitmap() {
+        let sorted = |bytes: &[u8]| {
+            let mut set = parse_bitmap(bytes).unwrap().into_iter().collect::<Vec<u32>>();
+            set.sort_unstable();
+            set
+        };
+
+        assert!(sorted(&base64::decode("OjAAAAAAAAA=").unwrap()).is_empty());
+        assert_eq!(sorted(&[0x02, 5, 0, 0, 0, 1, 0, 1, 0]), vec![5, 65537]);
+        assert_eq!(sorted(&[0xE3, 4, 0, 0, 0]), vec![4, 5, 6]);
+        assert_eq!(sorted(&[0xD3, 1, 0, 0, 0, 2, 3]), vec![1, 3, 6]);
+        assert_eq!(sorted(&[0xF2, 1, 0, 0, 0, 0, 1]), vec![1, 257]);
+        // Standard format with a run container and an array container
+        assert_eq!(
+            sorted(&[0x3B, 0x30, 1, 0, 0b01, 0, 0, 2, 0, 1, 0, 0, 0, 1, 0, 10, 0, 2, 0, 7, 0]),
+            vec![10, 11, 12, 65543]
+        );
+        assert!(parse_bitmap(&[0x50]).is_none());
+    }
+
+    #[test]
+    fn test_parse_shard() {
+        let values = parse_shard(r#"rd_("cfoocbar1ba\x80")"#).unwrap();
+
+        assert_eq!(values, vec![
+            b"foo".to_vec(),
+            b"bar".to_vec(),
+            b"foo".to_vec(),
+            vec![b'a', 0x80]
+        ]);
+        assert!(parse_shard("rd_(\"e\")").is_none());
+    }
+}